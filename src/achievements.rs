@@ -0,0 +1,140 @@
+// Achievements: milestones the player unlocks once and keeps - a hard eight, five
+// points in one hand, twenty rolls without a seven. `Achievements` watches every judged
+// roll and says which ones it unlocked. The achievements plugin feeds it rolls, saves
+// it, and shows the toasts and the list.
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::payout::Cents;
+use crate::rules::RollOutcome;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Achievement {
+    FirstWin,    // Win any bet
+    OnTheBoard,  // Make a point
+    HotHand,     // Make five points before sevening out
+    HardEight,   // Roll a pair of fours
+    HardWays,    // Roll all four hardways, over any number of sessions
+    SevenDodger, // Twenty rolls in a row without a seven
+    BigScore,    // Win $100 or more on one roll
+    HighRoller,  // Hold $5,000 at the table
+}
+
+impl Achievement {
+    // In the order the list shows them - roughly easiest first
+    pub const ALL: [Achievement; 8] = [
+        Achievement::FirstWin,
+        Achievement::OnTheBoard,
+        Achievement::HardEight,
+        Achievement::BigScore,
+        Achievement::HardWays,
+        Achievement::HotHand,
+        Achievement::SevenDodger,
+        Achievement::HighRoller,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Achievement::FirstWin => "Winner Winner",
+            Achievement::OnTheBoard => "On the Board",
+            Achievement::HotHand => "Hot Hand",
+            Achievement::HardEight => "Hard Eight",
+            Achievement::HardWays => "The Hard Way",
+            Achievement::SevenDodger => "Seven Dodger",
+            Achievement::BigScore => "Big Score",
+            Achievement::HighRoller => "High Roller",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Achievement::FirstWin => "Win a bet",
+            Achievement::OnTheBoard => "Make a point",
+            Achievement::HotHand => "Make 5 points in one hand",
+            Achievement::HardEight => "Hit a hard 8",
+            Achievement::HardWays => "Hit every hardway: hard 4, 6, 8 and 10",
+            Achievement::SevenDodger => "Roll 20 times without a seven",
+            Achievement::BigScore => "Win $100 or more on one roll",
+            Achievement::HighRoller => "Have $5,000 at the table",
+        }
+    }
+}
+
+const HOT_HAND_POINTS: u32 = 5;
+const SEVEN_DODGER_ROLLS: u32 = 20;
+const BIG_SCORE: Cents = 10_000; // $100
+const HIGH_ROLLER: Cents = 500_000; // $5,000
+
+// What the player has unlocked, and how far along they are with the ones that take
+// more than one roll. Saved between launches, so `#[serde(default)]` lets an older
+// save load.
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Achievements {
+    pub unlocked: Vec<Achievement>, // In the order they were unlocked
+    rolls_without_seven: u32,
+    points_this_hand: u32,
+    hardways: Vec<u8>, // The hardway totals hit so far
+}
+
+impl Achievements {
+    pub fn has(&self, achievement: Achievement) -> bool {
+        self.unlocked.contains(&achievement)
+    }
+
+    // Count one judged roll - what it paid the player beyond their bets (`won`) and
+    // everything they have afterwards (`holding`) - and unlock anything it earned.
+    // Returns the newly unlocked achievements.
+    pub fn record(
+        &mut self,
+        die_values: [u8; 2],
+        outcome: RollOutcome,
+        won: Cents,
+        holding: Cents,
+    ) -> Vec<Achievement> {
+        let [first, second] = die_values;
+        let total = first + second;
+        let hard = first == second && matches!(total, 4 | 6 | 8 | 10);
+        if hard && !self.hardways.contains(&total) {
+            self.hardways.push(total);
+        }
+        if total == 7 {
+            self.rolls_without_seven = 0;
+        } else {
+            self.rolls_without_seven += 1;
+        }
+        match outcome {
+            RollOutcome::PointMade(_) => self.points_this_hand += 1,
+            RollOutcome::SevenOut => self.points_this_hand = 0, // The dice move on
+            _ => {}
+        }
+
+        let earned = [
+            (Achievement::FirstWin, won > 0),
+            (
+                Achievement::OnTheBoard,
+                matches!(outcome, RollOutcome::PointMade(_)),
+            ),
+            (
+                Achievement::HotHand,
+                self.points_this_hand >= HOT_HAND_POINTS,
+            ),
+            (Achievement::HardEight, hard && total == 8),
+            (Achievement::HardWays, self.hardways.len() == 4),
+            (
+                Achievement::SevenDodger,
+                self.rolls_without_seven >= SEVEN_DODGER_ROLLS,
+            ),
+            (Achievement::BigScore, won >= BIG_SCORE),
+            (Achievement::HighRoller, holding >= HIGH_ROLLER),
+        ];
+        let mut new = Vec::new();
+        for (achievement, done) in earned {
+            if done && !self.has(achievement) {
+                self.unlocked.push(achievement);
+                new.push(achievement);
+            }
+        }
+        new
+    }
+}
@@ -0,0 +1,75 @@
+// What happened at the table, written out in words for players who can't follow it by
+// looking - for a screen reader to read, or the game's own voice (see
+// plugins/announce.rs). Each roll becomes a few short sentences: the dice, what the
+// roll meant, what each bet did, and where the puck went.
+use crate::betting::{Bet, BetResult};
+use crate::locale::Locale;
+use crate::payout::{Cents, format_money};
+use crate::registry::BetRegistry;
+use crate::rules::{RollOutcome, RoundPhase};
+
+// The dice and what they meant: "Rolled 3 and 4, seven. Winner on the come-out."
+pub fn roll_announcement(die_values: [u8; 2], outcome: RollOutcome, locale: &Locale) -> String {
+    let [first, second] = die_values;
+    let total = first + second;
+    let dice = locale.fill(
+        "Rolled {first} and {second}, {total}.",
+        &[("first", &first), ("second", &second), ("total", &total)],
+    );
+    let meaning = match outcome {
+        RollOutcome::Natural(_) => locale.tr("Winner on the come-out.").to_string(),
+        RollOutcome::Craps(_) => locale.tr("Craps.").to_string(),
+        RollOutcome::PointSet(point) => locale.fill("The point is {point}.", &[("point", &point)]),
+        RollOutcome::PointMade(_) => locale.tr("The point is made, winner!").to_string(),
+        RollOutcome::SevenOut => locale.tr("Seven out.").to_string(),
+        RollOutcome::NoDecision(_) => locale.tr("No decision.").to_string(),
+    };
+    // A pair on 4, 6, 8 or 10 decides the hardways, so it's worth saying
+    if first == second && matches!(total, 4 | 6 | 8 | 10) {
+        format!("{dice} {} {meaning}", locale.tr("The hard way."))
+    } else {
+        format!("{dice} {meaning}")
+    }
+}
+
+// What a roll did to one bet, or None if it left the bet alone
+pub fn bet_announcement(
+    bet: &Bet,
+    result: BetResult,
+    returned: Cents,
+    registry: &BetRegistry, // For the side bets' names
+    locale: &Locale,
+) -> Option<String> {
+    let sentence = |english: &str, money: Cents| {
+        locale.fill(
+            english,
+            &[
+                ("bet", &registry.bet_name(bet.kind, locale)),
+                ("money", &format_money(money)),
+            ],
+        )
+    };
+    match result {
+        BetResult::Win(profit) => Some(sentence("{bet} wins {money}.", profit)),
+        BetResult::Lose => Some(sentence("{bet} loses {money}.", bet.amount)),
+        BetResult::Push => Some(sentence("{bet} pushes, {money} back.", returned)),
+        BetResult::Travel(to) => Some(locale.fill(
+            "{bet} moves to {to}.",
+            &[
+                ("bet", &registry.bet_name(bet.kind, locale)),
+                ("to", &registry.bet_name(to, locale)),
+            ],
+        )),
+        BetResult::Stay => None,
+    }
+}
+
+// Where the puck is now the round has moved on
+pub fn phase_announcement(phase: RoundPhase, locale: &Locale) -> String {
+    match phase.point() {
+        Some(point) => locale.fill("The puck is on {point}.", &[("point", &point)]),
+        None => locale
+            .tr("The puck is off. Next roll is a come-out roll.")
+            .to_string(),
+    }
+}
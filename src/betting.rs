@@ -0,0 +1,1163 @@
+// The betting engine - which bets are on the table, when they may be placed, and how
+// each one is decided by a roll. Like the rules module, this is pure game logic with
+// no rendering, so the numbers can be checked without starting the game.
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::locale::Locale;
+use crate::payout::{Bankroll, Cents, commission, format_money, pay_at_odds};
+use crate::props::{CAndEBet, FieldBet, HopBet, HornBet};
+use crate::registry::{BetRegistry, BetRule, MAX_REGISTERED_BETS, SideRoll};
+use crate::rules::{
+    ComeOut, Roll, RollOutcome, RoundPhase, TableRules, Variant, VigPolicy, true_odds,
+};
+use crate::sicbo::{SicBoBet, SicBoDice};
+
+// Every kind of bet the table understands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BetKind {
+    PassLine,          // Bet with the shooter: wins on 7/11 or by making the point
+    DontPass,          // Bet against the shooter: the mirror image of the pass line
+    Come,              // Like a pass line bet, but made after the point is on
+    DontCome,          // Like a don't pass bet, made after the point is on
+    ComePoint(u8),     // A come bet that has travelled to its own number
+    DontComePoint(u8), // A don't come bet that has travelled behind a number
+    PassOdds,          // Free odds behind the pass line
+    DontPassOdds,      // Free (lay) odds behind the don't pass
+    ComeOdds(u8),      // Free odds on a come point
+    DontComeOdds(u8),  // Free (lay) odds on a don't come point
+    Buy(u8),           // The number before a 7, at true odds minus commission
+    Lay(u8),           // A 7 before the number, at true odds minus commission
+    Field,             // One roll: wins on 2, 3, 4, 9, 10, 11 or 12
+    AnySeven,          // One roll: any 7, pays 4:1
+    AnyCraps,          // One roll: any 2, 3 or 12, pays 7:1
+    Horn,              // One roll: split four ways across 2, 3, 11 and 12
+    CAndE,             // One roll: split between any craps and eleven
+    Hop(u8, u8),       // One roll: exactly these two dice (lower die first)
+    SicBo(SicBoBet),   // A sic bo bet, decided by three dice (see sicbo.rs)
+    Side(u8),          // A bet from the BetRegistry, the one in this side bet box (see registry.rs)
+}
+
+const DOLLAR: Cents = 100;
+
+// What a roll did to a bet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum BetResult {
+    Win(Cents),      // The bet won - this is the profit on top of the original bet
+    Lose,            // The bet lost - the house keeps it
+    Push,            // A tie - the bet comes back to the player untouched
+    Stay,            // No decision yet - the bet stays on the table
+    Travel(BetKind), // The bet moves to another spot (a come bet going to its number)
+}
+
+// What the player can do with a bet that has just won, instead of taking it all back:
+// put it straight back up, bigger
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LetItRide {
+    Press,  // Double the bet, the extra coming out of the winnings (all of them, if less)
+    Parlay, // Put the bet back up with every cent it won
+}
+
+impl LetItRide {
+    pub const ALL: [LetItRide; 2] = [LetItRide::Press, LetItRide::Parlay];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LetItRide::Press => "Press {money}",
+            LetItRide::Parlay => "Parlay {money}",
+        }
+    }
+
+    // What goes back on the spot for a bet of `amount` that won `profit`, rounded down
+    // to what the spot takes; the rest of the winnings stay with the player. On an
+    // even-money bet the two come to the same thing.
+    pub fn stake(
+        self,
+        kind: BetKind,
+        amount: Cents,
+        profit: Cents,
+        phase: RoundPhase,
+        rules: &TableRules,
+    ) -> Cents {
+        let stake = match self {
+            // A win smaller than the bet can only raise it by what it won
+            LetItRide::Press => amount + profit.min(amount),
+            LetItRide::Parlay => amount + profit,
+        };
+        if !rules.enforce_increments {
+            return stake;
+        }
+        let step = kind.increment(phase);
+        stake - stake % step
+    }
+}
+
+// Why a bet couldn't be placed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BetError {
+    NotOffered,            // The table's game has no such bet (don't bets at crapless)
+    WrongPhase,            // Not allowed at this point in the round
+    NoLineBet,             // Odds need a line or come bet to sit behind
+    OverOddsLimit,         // More odds than the table allows
+    BelowMinimum(Cents),   // Less on the spot than the table minimum
+    OverMaximum(Cents),    // More on the spot than the table maximum
+    WrongIncrement(Cents), // The spot's total must be a multiple of this to pay out evenly
+    InsufficientFunds,     // The player can't cover it
+    Contract,              // A line or come bet with a point can't be taken back down
+    NoMoreBets,            // The shooter has started a throw, and the roll isn't paid yet
+    NotOnLayout,           // The chips to take back aren't on the spot any more
+    Watching,              // Only watching a networked table, without a seat
+}
+
+impl BetError {
+    // Why the bet was turned down, in the player's language
+    pub fn message(self, locale: &Locale) -> String {
+        let money = |english: &str, amount: Cents| {
+            locale.fill(english, &[("money", &format_money(amount))])
+        };
+        match self {
+            BetError::NotOffered => locale.tr("this table doesn't take that bet").to_string(),
+            BetError::WrongPhase => locale.tr("that bet can't be made right now").to_string(),
+            BetError::NoLineBet => locale.tr("odds need a line bet to go behind").to_string(),
+            BetError::OverOddsLimit => locale
+                .tr("that's more odds than the table allows")
+                .to_string(),
+            BetError::BelowMinimum(min) => money("the table minimum is {money}", min),
+            BetError::OverMaximum(max) => money("the table maximum is {money}", max),
+            BetError::WrongIncrement(step) => {
+                money("this bet goes down in multiples of {money}", step)
+            }
+            BetError::InsufficientFunds => locale.tr("not enough money").to_string(),
+            BetError::Contract => locale
+                .tr("pass and come bets stay up once they have a point")
+                .to_string(),
+            BetError::NoMoreBets => locale
+                .tr("no more bets until the dice are read")
+                .to_string(),
+            BetError::NotOnLayout => locale
+                .tr("those chips aren't on the layout any more")
+                .to_string(),
+            BetError::Watching => locale
+                .tr("you're watching - take a seat to bet")
+                .to_string(),
+        }
+    }
+}
+
+// In English, for the log and the debug console
+impl std::fmt::Display for BetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.message(&Locale::default()))
+    }
+}
+
+impl BetKind {
+    // Is this bet allowed to be put down right now?
+    pub fn can_place(self, phase: RoundPhase) -> bool {
+        match self {
+            // Line bets are only taken before the point is set
+            BetKind::PassLine | BetKind::DontPass => phase.is_come_out(),
+            // Come bets and line odds only make sense once a point is on
+            BetKind::Come | BetKind::DontCome | BetKind::PassOdds | BetKind::DontPassOdds => {
+                !phase.is_come_out()
+            }
+            // Odds on a come point can go down whenever the come bet is there
+            BetKind::ComeOdds(_) | BetKind::DontComeOdds(_) => true,
+            // Buy and lay bets can go on any point number at any time
+            BetKind::Buy(number) | BetKind::Lay(number) => true_odds(number).is_some(),
+            // One-roll bets are always open
+            BetKind::AnySeven | BetKind::AnyCraps => true,
+            // The props written as rules say for themselves, whatever the table pays
+            BetKind::Field | BetKind::Horn | BetKind::CAndE | BetKind::Hop(_, _) => self
+                .prop(&TableRules::default())
+                .is_some_and(|prop| prop.can_place(phase)),
+            // Sic bo has no rounds, so every bet is open before every throw
+            BetKind::SicBo(bet) => bet.is_valid(),
+            // Side bets go down before any roll; the script only starts counting then
+            BetKind::Side(index) => usize::from(index) < MAX_REGISTERED_BETS,
+            // Nobody bets on these directly - come bets travel there on their own
+            BetKind::ComePoint(_) | BetKind::DontComePoint(_) => false,
+        }
+    }
+
+    // May the bet be taken back off the layout between rolls? Almost everything can:
+    // odds, buy and lay bets, the props, the don't side (the house is glad to see a
+    // don't bet go once it's past its best roll). A pass line bet with a point on, or a
+    // come bet that has moved to its number, is a contract - it has already had its
+    // come-out, where it was most likely to win, so it stays up until it's decided.
+    pub fn can_take_down(self, phase: RoundPhase) -> bool {
+        match self {
+            BetKind::PassLine => phase.is_come_out(),
+            BetKind::ComePoint(_) => false,
+            _ => true,
+        }
+    }
+
+    // Does the table's game have this bet at all? Crapless craps has no don't side,
+    // the numbers only have boxes at the points the variant allows, and a sic bo table
+    // takes only sic bo bets.
+    pub fn is_offered(self, variant: Variant) -> bool {
+        match self {
+            BetKind::SicBo(_) => variant == Variant::SicBo,
+            _ if variant == Variant::SicBo => false,
+            BetKind::DontPass
+            | BetKind::DontCome
+            | BetKind::DontPassOdds
+            | BetKind::DontComePoint(_)
+            | BetKind::DontComeOdds(_) => variant.has_dont_bets(),
+            BetKind::ComePoint(number)
+            | BetKind::ComeOdds(number)
+            | BetKind::Buy(number)
+            | BetKind::Lay(number) => variant.is_point(number),
+            _ => true,
+        }
+    }
+
+    // Decided by the very next roll, win or lose
+    pub fn is_one_roll(self) -> bool {
+        matches!(
+            self,
+            BetKind::Field
+                | BetKind::AnySeven
+                | BetKind::AnyCraps
+                | BetKind::Horn
+                | BetKind::CAndE
+                | BetKind::Hop(_, _)
+                | BetKind::SicBo(_)
+        )
+    }
+
+    // When the bet may go down, in words - `can_place` says whether it may right now
+    pub fn placement_window(self, rules: &TableRules) -> &'static str {
+        match self {
+            BetKind::PassLine | BetKind::DontPass => "On the come-out only",
+            BetKind::Come | BetKind::DontCome => "Only while a point is on",
+            BetKind::PassOdds | BetKind::DontPassOdds => "Once the point is on, behind a line bet",
+            BetKind::ComeOdds(_) | BetKind::DontComeOdds(_) => {
+                "Behind a come bet that has moved to its number"
+            }
+            BetKind::Buy(_) if rules.buy_working => "Any time",
+            BetKind::Buy(_) => "Any time (off on the come-out)",
+            BetKind::ComePoint(_) | BetKind::DontComePoint(_) => {
+                "Not bet directly - come bets move here"
+            }
+            _ => "Any roll",
+        }
+    }
+
+    // What a win pays, as the odds printed on a layout
+    pub fn payout_odds(self, phase: RoundPhase, rules: &TableRules, locale: &Locale) -> String {
+        let odds = |(pays, per): (u64, u64)| format!("{pays}:{per}");
+        // Odds bets pay the true odds of the number they're behind, once it's known
+        if let Some((pays, per)) = self
+            .odds_base(phase)
+            .and_then(|(_, number)| true_odds(number))
+        {
+            let paid = if self.is_lay_odds() {
+                (per, pays)
+            } else {
+                (pays, per)
+            };
+            return locale.fill("{odds} (true odds)", &[("odds", &odds(paid))]);
+        }
+        match self {
+            BetKind::PassLine
+            | BetKind::DontPass
+            | BetKind::Come
+            | BetKind::DontCome
+            | BetKind::ComePoint(_)
+            | BetKind::DontComePoint(_) => "1:1".to_string(),
+            BetKind::PassOdds | BetKind::ComeOdds(_) => match rules.variant {
+                Variant::Standard => locale
+                    .tr("True odds: 2:1 on 4/10, 3:2 on 5/9, 6:5 on 6/8")
+                    .to_string(),
+                Variant::Crapless => locale
+                    .tr("True odds: 6:1 on 2/12, 3:1 on 3/11, 2:1 on 4/10, 3:2 on 5/9, 6:5 on 6/8")
+                    .to_string(),
+                Variant::SicBo => "-".to_string(),
+            },
+            BetKind::DontPassOdds | BetKind::DontComeOdds(_) => locale
+                .tr("True odds: 1:2 on 4/10, 2:3 on 5/9, 5:6 on 6/8")
+                .to_string(),
+            BetKind::Buy(number) => match true_odds(number) {
+                Some(true_pays) => locale.fill(
+                    "{odds} less {percent}% commission",
+                    &[
+                        ("odds", &odds(true_pays)),
+                        ("percent", &rules.commission_percent),
+                    ],
+                ),
+                None => "-".to_string(),
+            },
+            BetKind::Lay(number) => match true_odds(number) {
+                Some((pays, per)) => locale.fill(
+                    "{odds} less {percent}% commission on the win",
+                    &[
+                        ("odds", &odds((per, pays))),
+                        ("percent", &rules.commission_percent),
+                    ],
+                ),
+                None => "-".to_string(),
+            },
+            BetKind::AnySeven => "4:1".to_string(),
+            BetKind::AnyCraps => "7:1".to_string(),
+            BetKind::Field | BetKind::Horn | BetKind::CAndE | BetKind::Hop(_, _) => {
+                match self.prop(rules) {
+                    Some(prop) => prop.payout_odds(locale),
+                    None => "-".to_string(),
+                }
+            }
+            BetKind::SicBo(bet) => bet.payout_odds(locale),
+            BetKind::Side(_) => "-".to_string(), // Whatever the script says it pays
+        }
+    }
+
+    // Whether the table's minimum and maximum apply. Odds are limited by the line bet
+    // they sit behind instead, and the props in the middle take any amount, as do the
+    // side bets. At sic bo the even-money small and big bets have limits, like the field.
+    pub fn has_table_limits(self, phase: RoundPhase) -> bool {
+        let even_money = matches!(
+            self,
+            BetKind::Field | BetKind::SicBo(SicBoBet::Small | SicBoBet::Big)
+        );
+        let side = matches!(self, BetKind::Side(_));
+        self.odds_base(phase).is_none() && !side && (!self.is_one_roll() || even_money)
+    }
+
+    // The amount the bet has to be a multiple of so a win pays out in whole dollars:
+    // $5 on the 6 pays 6:5, so odds on the 6 go down in fives. Bets paid at even money
+    // (or in whole multiples) can be any number of dollars.
+    pub fn increment(self, phase: RoundPhase) -> Cents {
+        let (number, lay) = match self {
+            BetKind::Buy(number) => (number, false),
+            BetKind::Lay(number) => (number, true),
+            _ => match self.odds_base(phase) {
+                Some((_, number)) => (number, self.is_lay_odds()),
+                None => return DOLLAR,
+            },
+        };
+        match true_odds(number) {
+            // Taking 6:5 needs multiples of $5; laying it needs multiples of $6
+            Some((pays, per)) => DOLLAR * if lay { pays } else { per },
+            None => DOLLAR,
+        }
+    }
+
+    // The rule behind a prop that's written as one (see props.rs), with the field paying
+    // what this table pays on the 2 and 12
+    pub fn prop(self, rules: &TableRules) -> Option<Box<dyn BetRule>> {
+        match self {
+            BetKind::Field => Some(Box::new(FieldBet {
+                two_pays: rules.field_two_pays,
+                twelve_pays: rules.field_twelve_pays,
+            })),
+            BetKind::Horn => Some(Box::new(HornBet)),
+            BetKind::CAndE => Some(Box::new(CAndEBet)),
+            BetKind::Hop(low, high) => Some(Box::new(HopBet { low, high })),
+            _ => None,
+        }
+    }
+
+    // For odds bets: the bet they sit behind, and the number they are paid against
+    pub fn odds_base(self, phase: RoundPhase) -> Option<(BetKind, u8)> {
+        match self {
+            BetKind::PassOdds => phase.point().map(|point| (BetKind::PassLine, point)),
+            BetKind::DontPassOdds => phase.point().map(|point| (BetKind::DontPass, point)),
+            BetKind::ComeOdds(number) => Some((BetKind::ComePoint(number), number)),
+            BetKind::DontComeOdds(number) => Some((BetKind::DontComePoint(number), number)),
+            _ => None,
+        }
+    }
+
+    // True for the "don't" odds, which are laid rather than taken
+    fn is_lay_odds(self) -> bool {
+        matches!(self, BetKind::DontPassOdds | BetKind::DontComeOdds(_))
+    }
+
+    // The commission owed on this bet, based on the table's vig rate.
+    // Buy bets pay 5% of the bet, lay bets pay 5% of what they stand to win.
+    pub fn vig(self, amount: Cents, rules: &TableRules) -> Cents {
+        let basis = match self {
+            BetKind::Buy(_) => amount,
+            BetKind::Lay(number) => match true_odds(number) {
+                Some((pays, per)) => pay_at_odds(amount, (per, pays)),
+                None => return 0,
+            },
+            _ => return 0, // No other bet pays a commission
+        };
+        commission(basis, rules.commission_percent)
+    }
+
+    // The commission to collect as soon as the bet is made
+    pub fn vig_at_placement(self, amount: Cents, rules: &TableRules) -> Cents {
+        match rules.vig {
+            VigPolicy::UpFront => self.vig(amount, rules),
+            VigPolicy::OnWin => 0,
+        }
+    }
+
+    // Decide the bet against one roll
+    pub fn resolve(self, amount: Cents, roll: &Roll, rules: &TableRules) -> BetResult {
+        let total = roll.total();
+
+        match self {
+            BetKind::PassLine => match roll.outcome {
+                // Pass line pays even money (1:1)
+                RollOutcome::Natural(_) | RollOutcome::PointMade(_) => BetResult::Win(amount),
+                RollOutcome::Craps(_) | RollOutcome::SevenOut => BetResult::Lose,
+                RollOutcome::PointSet(_) | RollOutcome::NoDecision(_) => BetResult::Stay,
+            },
+            // Exactly the pass line's results flipped, except the barred number ties.
+            // Without this "bar" the don't side would have an edge over the house.
+            BetKind::DontPass => match roll.outcome {
+                RollOutcome::Craps(total) if total == rules.barred_number => BetResult::Push,
+                RollOutcome::Craps(_) | RollOutcome::SevenOut => BetResult::Win(amount),
+                RollOutcome::Natural(_) | RollOutcome::PointMade(_) => BetResult::Lose,
+                RollOutcome::PointSet(_) | RollOutcome::NoDecision(_) => BetResult::Stay,
+            },
+            // A come bet treats the very next roll as its own come-out
+            BetKind::Come => match rules.variant.come_out(total) {
+                ComeOut::Natural => BetResult::Win(amount),
+                ComeOut::Craps => BetResult::Lose,
+                ComeOut::Point => BetResult::Travel(BetKind::ComePoint(total)),
+            },
+            BetKind::DontCome => match rules.variant.come_out(total) {
+                ComeOut::Craps if total == rules.barred_number => BetResult::Push,
+                ComeOut::Craps => BetResult::Win(amount),
+                ComeOut::Natural => BetResult::Lose,
+                ComeOut::Point => BetResult::Travel(BetKind::DontComePoint(total)),
+            },
+            BetKind::ComePoint(number) => match total {
+                7 => BetResult::Lose,
+                total if total == number => BetResult::Win(amount),
+                _ => BetResult::Stay,
+            },
+            BetKind::DontComePoint(number) => match total {
+                7 => BetResult::Win(amount),
+                total if total == number => BetResult::Lose,
+                _ => BetResult::Stay,
+            },
+            // Pass odds win at true odds when the point is made...
+            BetKind::PassOdds => match roll.outcome {
+                RollOutcome::PointMade(point) => take_odds_win(amount, point),
+                RollOutcome::SevenOut => BetResult::Lose,
+                _ => BetResult::Stay,
+            },
+            // ...and don't pass odds win at the inverse when the 7 shows first
+            BetKind::DontPassOdds => match (roll.outcome, roll.previous.point()) {
+                (RollOutcome::SevenOut, Some(point)) => lay_odds_win(amount, point),
+                (RollOutcome::PointMade(_), _) => BetResult::Lose,
+                _ => BetResult::Stay,
+            },
+            // Come odds are "off" on the come-out unless the house says otherwise:
+            // rather than being decided, they are simply handed back if their number
+            // or a 7 shows
+            BetKind::ComeOdds(number)
+                if roll.is_come_out()
+                    && !rules.come_odds_working
+                    && (total == 7 || total == number) =>
+            {
+                BetResult::Push
+            }
+            BetKind::ComeOdds(number) => match total {
+                7 => BetResult::Lose,
+                total if total == number => take_odds_win(amount, number),
+                _ => BetResult::Stay,
+            },
+            BetKind::DontComeOdds(number) => match total {
+                7 => lay_odds_win(amount, number),
+                total if total == number => BetResult::Lose,
+                _ => BetResult::Stay,
+            },
+            // Buy bets are off on the come-out too, unless the house works them
+            BetKind::Buy(_) if roll.is_come_out() && !rules.buy_working => BetResult::Stay,
+            BetKind::Buy(number) => match total {
+                7 => BetResult::Lose,
+                total if total == number => {
+                    self.less_vig(take_odds_win(amount, number), amount, rules)
+                }
+                _ => BetResult::Stay,
+            },
+            BetKind::Lay(number) => match total {
+                7 => self.less_vig(lay_odds_win(amount, number), amount, rules),
+                total if total == number => BetResult::Lose,
+                _ => BetResult::Stay,
+            },
+            // The center-table props - all one-roll bets. Those written as rules are
+            // handed this roll as the first since they went down.
+            BetKind::Field | BetKind::Horn | BetKind::CAndE | BetKind::Hop(_, _) => {
+                let next = SideRoll {
+                    dice: roll.die_values,
+                    point: roll.previous.point(),
+                };
+                match self.prop(rules) {
+                    Some(prop) => prop.resolve(amount, &[next]),
+                    None => BetResult::Stay,
+                }
+            }
+            BetKind::AnySeven => match total {
+                7 => BetResult::Win(amount * 4),
+                _ => BetResult::Lose,
+            },
+            BetKind::AnyCraps => match total {
+                2 | 3 | 12 => BetResult::Win(amount * 7),
+                _ => BetResult::Lose,
+            },
+            // Two dice can't decide a sic bo bet - resolve_sic_bo() does, with three
+            BetKind::SicBo(_) => BetResult::Stay,
+            // Nor a side bet - its script does, over all the rolls since it went down
+            BetKind::Side(_) => BetResult::Stay,
+        }
+    }
+
+    // Take the commission out of a win, unless it was already paid up front
+    fn less_vig(self, result: BetResult, amount: Cents, rules: &TableRules) -> BetResult {
+        match (result, rules.vig) {
+            (BetResult::Win(profit), VigPolicy::OnWin) => {
+                BetResult::Win(profit.saturating_sub(self.vig(amount, rules)))
+            }
+            _ => result,
+        }
+    }
+}
+
+// Taking odds: pays true odds, e.g. 2:1 on the 4
+fn take_odds_win(amount: Cents, point: u8) -> BetResult {
+    match true_odds(point) {
+        Some(odds) => BetResult::Win(pay_at_odds(amount, odds)),
+        None => BetResult::Push, // Can't happen for a real point - hand the money back
+    }
+}
+
+// Laying odds: pays the inverse of true odds, e.g. 1:2 against the 4
+fn lay_odds_win(amount: Cents, point: u8) -> BetResult {
+    match true_odds(point) {
+        Some((pays, per)) => BetResult::Win(pay_at_odds(amount, (per, pays))),
+        None => BetResult::Push,
+    }
+}
+
+// A single stack of money on the layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bet {
+    pub kind: BetKind,
+    pub amount: Cents,
+    // Commission paid up front for the chips on the spot, added up over every time
+    // they went down - what's handed back if the bet is taken down
+    #[serde(default)]
+    pub vig: Cents,
+}
+
+impl Bet {
+    // A bet with no commission paid for it
+    pub fn new(kind: BetKind, amount: Cents) -> Self {
+        Self {
+            kind,
+            amount,
+            vig: 0,
+        }
+    }
+}
+
+// All bets currently on the table
+#[derive(Resource, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct TableBets {
+    pub bets: Vec<Bet>,
+}
+
+impl TableBets {
+    // Total money currently riding on the layout
+    pub fn total_at_risk(&self) -> Cents {
+        self.bets.iter().map(|bet| bet.amount).sum()
+    }
+
+    // How much is on one spot (zero if nothing is there)
+    pub fn amount_on(&self, kind: BetKind) -> Cents {
+        self.bets
+            .iter()
+            .find(|bet| bet.kind == kind)
+            .map_or(0, |bet| bet.amount)
+    }
+
+    // The most odds that may sit behind a bet, or None if `kind` isn't an odds bet
+    // with something to sit behind.
+    //
+    // Taking odds is limited to `multiple` times the line bet. Laying odds is limited
+    // so the lay *wins* no more than the matching maximum take would put down, which is
+    // how casinos quote "3-4-5x" on the don't side - a lay of 6x on every number.
+    pub fn max_odds(&self, kind: BetKind, phase: RoundPhase, rules: &TableRules) -> Option<Cents> {
+        let (base, number) = kind.odds_base(phase)?;
+        let flat = self.amount_on(base);
+        if flat == 0 {
+            return None;
+        }
+        let max_take = flat * rules.odds.multiple(number);
+        if !kind.is_lay_odds() {
+            return Some(max_take);
+        }
+        let (pays, per) = true_odds(number)?;
+        Some(pay_at_odds(max_take, (pays, per)))
+    }
+
+    // How much more odds may go behind a bet right now, rounded down to the amounts
+    // that pay out evenly. None for a bet that isn't odds or has nothing to sit behind.
+    pub fn odds_room(&self, kind: BetKind, phase: RoundPhase, rules: &TableRules) -> Option<Cents> {
+        let max = self.max_odds(kind, phase, rules)?;
+        let room = max.saturating_sub(self.amount_on(kind));
+        if !rules.enforce_increments {
+            return Some(room);
+        }
+        // What's already down is a whole number of steps, so the rest must be too
+        let step = kind.increment(phase);
+        Some(room - room % step)
+    }
+
+    // Check whether the spot takes bets at all right now, whatever the amount: the
+    // table's game has to have the bet, the round has to be at the right point, and
+    // odds need something to sit behind
+    pub fn check_open(
+        &self,
+        kind: BetKind,
+        phase: RoundPhase,
+        rules: &TableRules,
+    ) -> Result<(), BetError> {
+        if !kind.is_offered(rules.variant) {
+            return Err(BetError::NotOffered);
+        }
+        if !kind.can_place(phase) {
+            return Err(BetError::WrongPhase);
+        }
+        if kind.odds_base(phase).is_some() && self.max_odds(kind, phase, rules).is_none() {
+            return Err(BetError::NoLineBet);
+        }
+        Ok(())
+    }
+
+    // Check whether a bet may go down, without changing anything
+    pub fn validate(
+        &self,
+        kind: BetKind,
+        amount: Cents,
+        phase: RoundPhase,
+        rules: &TableRules,
+        balance: Cents,
+    ) -> Result<(), BetError> {
+        self.check_open(kind, phase, rules)?;
+        let total = self.amount_on(kind) + amount;
+        if kind.odds_base(phase).is_some() {
+            // check_open() already found the line bet the odds go behind
+            let max = self.max_odds(kind, phase, rules).unwrap_or(0);
+            if total > max {
+                return Err(BetError::OverOddsLimit);
+            }
+        }
+        if kind.has_table_limits(phase) {
+            if total < rules.min_bet {
+                return Err(BetError::BelowMinimum(rules.min_bet));
+            }
+            if total > rules.max_bet {
+                return Err(BetError::OverMaximum(rules.max_bet));
+            }
+        }
+        let step = kind.increment(phase);
+        if rules.enforce_increments && total % step != 0 {
+            return Err(BetError::WrongIncrement(step));
+        }
+        // Never let the player bet money they don't have (commission included)
+        if amount + kind.vig_at_placement(amount, rules) > balance {
+            return Err(BetError::InsufficientFunds);
+        }
+        Ok(())
+    }
+
+    // Add money to a bet, creating it if it isn't on the table yet. `vig` is the
+    // commission paid up front for the new chips.
+    pub fn place(&mut self, kind: BetKind, amount: Cents, vig: Cents) {
+        match self.bets.iter_mut().find(|bet| bet.kind == kind) {
+            Some(bet) => {
+                bet.amount += amount;
+                bet.vig += vig;
+            }
+            None => self.bets.push(Bet { kind, amount, vig }),
+        }
+    }
+
+    // Put a bet down, paying for it (and any up-front commission) out of the bankroll.
+    // Hands back the commission that was paid.
+    pub fn buy_in(
+        &mut self,
+        kind: BetKind,
+        amount: Cents,
+        phase: RoundPhase,
+        rules: &TableRules,
+        bankroll: &mut Bankroll,
+    ) -> Result<Cents, BetError> {
+        self.validate(kind, amount, phase, rules, bankroll.balance)?;
+        let vig = kind.vig_at_placement(amount, rules);
+        // validate() already checked the bankroll covers this
+        bankroll
+            .debit(amount + vig)
+            .map_err(|_| BetError::InsufficientFunds)?;
+        self.place(kind, amount, vig);
+        Ok(vig)
+    }
+
+    // Put a whole pattern of bets down at once, topping each spot up to its amount in
+    // the pattern. Spots that aren't open right now are passed over (odds behind a
+    // point that has since been made, say), and the bets go down in the pattern's order
+    // so a line bet is there before its odds. If any of them can't go down - the
+    // bankroll won't cover them all, or one breaks the table's limits - nothing does.
+    // Hands back what went on each spot, or the first bet that was refused and why.
+    pub fn buy_in_pattern(
+        &mut self,
+        pattern: &[Bet],
+        phase: RoundPhase,
+        rules: &TableRules,
+        bankroll: &mut Bankroll,
+    ) -> Result<Vec<Bet>, (Bet, BetError)> {
+        let mut table = self.clone();
+        let mut money = bankroll.clone();
+        let mut placed = Vec::new();
+        for bet in pattern {
+            let top_up = Bet::new(
+                bet.kind,
+                bet.amount.saturating_sub(table.amount_on(bet.kind)),
+            );
+            if top_up.amount == 0 || table.check_open(bet.kind, phase, rules).is_err() {
+                continue;
+            }
+            table
+                .buy_in(top_up.kind, top_up.amount, phase, rules, &mut money)
+                .map_err(|error| (top_up, error))?;
+            placed.push(top_up);
+        }
+        *self = table;
+        *bankroll = money;
+        Ok(placed)
+    }
+
+    // Take a bet back off the layout between rolls, with any odds sitting behind it,
+    // and give the money (and the commission paid up front for it, however many times
+    // it was pressed) back to the bankroll. Hands back the bets that came down - none
+    // if nothing was on the spot.
+    pub fn take_down(
+        &mut self,
+        kind: BetKind,
+        phase: RoundPhase,
+        bankroll: &mut Bankroll,
+    ) -> Result<Vec<Bet>, BetError> {
+        if self.amount_on(kind) == 0 {
+            return Ok(Vec::new());
+        }
+        if !kind.can_take_down(phase) {
+            return Err(BetError::Contract);
+        }
+        let mut taken = Vec::new();
+        self.bets.retain(|bet| {
+            let behind = bet.kind.odds_base(phase).map(|(base, _)| base) == Some(kind);
+            if bet.kind != kind && !behind {
+                return true;
+            }
+            taken.push(*bet);
+            false
+        });
+        for bet in &taken {
+            bankroll.credit(bet.amount + bet.vig);
+        }
+        Ok(taken)
+    }
+
+    // What leaving the table now hands back: every bet that could be taken down, with
+    // any commission paid for it, as `take_down` would give it. A contract bet (a pass
+    // line or come bet once its point is on) can't come off, so it's lost with the seat.
+    pub fn cash_out(&self, phase: RoundPhase) -> Cents {
+        let mut left = self.clone();
+        let mut pocket = Bankroll { balance: 0 };
+        for bet in &self.bets {
+            // Odds already taken down with their line bet are simply not found again
+            let _ = left.take_down(bet.kind, phase, &mut pocket);
+        }
+        pocket.balance
+    }
+
+    // Take back chips put down since the last roll: `bet.amount` comes off its spot (the
+    // spot is cleared if that was all of it) and goes back to the bankroll, with any
+    // commission paid for those chips. No roll has played for them yet, so unlike
+    // taking a bet down this is fine for a line bet too.
+    pub fn undo_placement(
+        &mut self,
+        bet: Bet,
+        rules: &TableRules,
+        bankroll: &mut Bankroll,
+    ) -> Result<(), BetError> {
+        let Some(index) = self
+            .bets
+            .iter()
+            .position(|placed| placed.kind == bet.kind && placed.amount >= bet.amount)
+        else {
+            return Err(BetError::NotOnLayout);
+        };
+        let placed = &mut self.bets[index];
+        // What those chips were charged, never more than the spot has paid - or all of
+        // it, when they're the last chips on the spot
+        let vig = if placed.amount == bet.amount {
+            placed.vig
+        } else {
+            bet.kind.vig_at_placement(bet.amount, rules).min(placed.vig)
+        };
+        placed.amount -= bet.amount;
+        placed.vig -= vig;
+        if placed.amount == 0 {
+            self.bets.remove(index);
+        }
+        bankroll.credit(bet.amount + vig);
+        Ok(())
+    }
+
+    // Decide every bet against a roll. Decided bets come off the table, travelling
+    // come bets move to their number, and the decisions are handed back so the
+    // caller can settle up the money.
+    pub fn resolve_roll(&mut self, roll: &Roll, rules: &TableRules) -> Vec<(Bet, BetResult)> {
+        let mut decisions = Vec::new();
+        let mut travelled = Vec::new();
+
+        self.bets.retain(|bet| {
+            let result = bet.kind.resolve(bet.amount, roll, rules);
+            match result {
+                BetResult::Stay => return true,
+                BetResult::Travel(to) => travelled.push((to, bet.amount, bet.vig)),
+                BetResult::Win(_) | BetResult::Lose | BetResult::Push => {}
+            }
+            decisions.push((*bet, result));
+            false
+        });
+
+        for (kind, amount, vig) in travelled {
+            self.place(kind, amount, vig);
+        }
+        decisions
+    }
+
+    // Decide every sic bo bet against a throw of three dice. They're all one-throw
+    // bets, so they all come off the table.
+    pub fn resolve_sic_bo(&mut self, dice: SicBoDice) -> Vec<(Bet, BetResult)> {
+        let mut decisions = Vec::new();
+        self.bets.retain(|bet| {
+            let BetKind::SicBo(sic_bo) = bet.kind else {
+                return true;
+            };
+            decisions.push((*bet, sic_bo.resolve(bet.amount, dice)));
+            false
+        });
+        decisions
+    }
+
+    // Decide every registered bet by its rule, each against the rolls since it went
+    // down (`rolls` finds those). Decided bets come off the table.
+    pub fn resolve_registered<'a>(
+        &mut self,
+        registry: &BetRegistry,
+        rolls: impl Fn(u8) -> &'a [SideRoll],
+    ) -> Vec<(Bet, BetResult)> {
+        let mut decisions = Vec::new();
+        self.bets.retain(|bet| {
+            let BetKind::Side(index) = bet.kind else {
+                return true;
+            };
+            let result = registry.resolve(bet.kind, bet.amount, rolls(index));
+            if result == BetResult::Stay {
+                return true;
+            }
+            decisions.push((*bet, result));
+            false
+        });
+        decisions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::OddsLimit;
+
+    const FLAT: Cents = 1_000; // A $10 line bet
+
+    // $10 on the pass line and on the don't pass
+    fn line_bets() -> TableBets {
+        TableBets {
+            bets: vec![
+                Bet::new(BetKind::PassLine, FLAT),
+                Bet::new(BetKind::DontPass, FLAT),
+            ],
+        }
+    }
+
+    fn rules_with(odds: OddsLimit) -> TableRules {
+        TableRules {
+            odds,
+            ..TableRules::default()
+        }
+    }
+
+    #[test]
+    fn max_odds_at_three_four_five() {
+        let rules = rules_with(OddsLimit::default());
+        let bets = line_bets();
+        // Taking: 3x, 4x and 5x. Laying: 6x on every number, which wins what the take puts down.
+        for (point, take, lay) in [
+            (4, 3, 6),
+            (10, 3, 6),
+            (5, 4, 6),
+            (9, 4, 6),
+            (6, 5, 6),
+            (8, 5, 6),
+        ] {
+            let phase = RoundPhase::PointEstablished(point);
+            assert_eq!(
+                bets.max_odds(BetKind::PassOdds, phase, &rules),
+                Some(FLAT * take),
+                "taking odds on {point}"
+            );
+            assert_eq!(
+                bets.max_odds(BetKind::DontPassOdds, phase, &rules),
+                Some(FLAT * lay),
+                "laying odds on {point}"
+            );
+        }
+    }
+
+    #[test]
+    fn max_odds_at_a_flat_limit() {
+        let rules = rules_with(OddsLimit::flat(10));
+        let bets = line_bets();
+        // A lay at 10x wins 10x: 20x laid on 4/10, 15x on 5/9 and 12x on 6/8
+        for (point, lay) in [(4, 20), (10, 20), (5, 15), (9, 15), (6, 12), (8, 12)] {
+            let phase = RoundPhase::PointEstablished(point);
+            assert_eq!(
+                bets.max_odds(BetKind::PassOdds, phase, &rules),
+                Some(FLAT * 10),
+                "taking odds on {point}"
+            );
+            assert_eq!(
+                bets.max_odds(BetKind::DontPassOdds, phase, &rules),
+                Some(FLAT * lay),
+                "laying odds on {point}"
+            );
+        }
+    }
+
+    #[test]
+    fn max_odds_needs_a_line_bet() {
+        let rules = TableRules::default();
+        let phase = RoundPhase::PointEstablished(6);
+        let bets = TableBets::default();
+        assert_eq!(bets.max_odds(BetKind::PassOdds, phase, &rules), None);
+        assert_eq!(bets.max_odds(BetKind::Field, phase, &rules), None);
+    }
+
+    // A roll of these two dice, thrown in `phase` at a standard table
+    fn roll(die_values: [u8; 2], phase: RoundPhase) -> Roll {
+        let (outcome, _) =
+            crate::rules::resolve_roll(phase, die_values[0] + die_values[1], Variant::Standard);
+        Roll {
+            die_values,
+            previous: phase,
+            outcome,
+        }
+    }
+
+    #[test]
+    fn increment_follows_the_true_odds() {
+        let six = RoundPhase::PointEstablished(6);
+        assert_eq!(BetKind::PassOdds.increment(six), 500);
+        assert_eq!(BetKind::DontPassOdds.increment(six), 600);
+        assert_eq!(BetKind::ComeOdds(5).increment(six), 200);
+        assert_eq!(BetKind::DontComeOdds(5).increment(six), 300);
+        assert_eq!(BetKind::Buy(4).increment(six), 100);
+        assert_eq!(BetKind::Lay(4).increment(six), 200);
+        assert_eq!(BetKind::Field.increment(six), DOLLAR);
+        // No point, so the pass odds have nothing to be paid against
+        assert_eq!(BetKind::PassOdds.increment(RoundPhase::ComeOut), DOLLAR);
+    }
+
+    #[test]
+    fn field_pays_double_on_two_and_triple_on_twelve() {
+        let rules = TableRules::default();
+        let come_out = RoundPhase::ComeOut;
+        let field = |dice| BetKind::Field.resolve(FLAT, &roll(dice, come_out), &rules);
+        assert_eq!(field([1, 1]), BetResult::Win(FLAT * 2));
+        assert_eq!(field([6, 6]), BetResult::Win(FLAT * 3));
+        for dice in [[1, 2], [2, 2], [4, 5], [5, 5], [5, 6]] {
+            assert_eq!(field(dice), BetResult::Win(FLAT), "{dice:?}");
+        }
+        for dice in [[1, 4], [3, 3], [3, 4], [4, 4]] {
+            assert_eq!(field(dice), BetResult::Lose, "{dice:?}");
+        }
+    }
+
+    #[test]
+    fn horn_and_c_and_e_pay_less_the_parts_that_missed() {
+        let rules = TableRules::default();
+        let phase = RoundPhase::PointEstablished(8);
+        let horn = |dice| BetKind::Horn.resolve(400, &roll(dice, phase), &rules);
+        // $4 on the horn is $1 on each number: 30 won on one, 3 lost on the others
+        assert_eq!(horn([1, 1]), BetResult::Win(2_700));
+        assert_eq!(horn([6, 6]), BetResult::Win(2_700));
+        assert_eq!(horn([1, 2]), BetResult::Win(1_200));
+        assert_eq!(horn([5, 6]), BetResult::Win(1_200));
+        assert_eq!(horn([3, 4]), BetResult::Lose);
+
+        // $2 on C&E is $1 on any craps and $1 on eleven
+        let c_and_e = |dice| BetKind::CAndE.resolve(200, &roll(dice, phase), &rules);
+        assert_eq!(c_and_e([1, 1]), BetResult::Win(600));
+        assert_eq!(c_and_e([1, 2]), BetResult::Win(600));
+        assert_eq!(c_and_e([6, 6]), BetResult::Win(600));
+        assert_eq!(c_and_e([5, 6]), BetResult::Win(1_400));
+        assert_eq!(c_and_e([2, 2]), BetResult::Lose);
+    }
+
+    #[test]
+    fn hops_need_the_exact_dice() {
+        let rules = TableRules::default();
+        let phase = RoundPhase::ComeOut;
+        let hop = |kind: BetKind, dice| kind.resolve(FLAT, &roll(dice, phase), &rules);
+        assert_eq!(hop(BetKind::Hop(2, 5), [2, 5]), BetResult::Win(FLAT * 15));
+        assert_eq!(hop(BetKind::Hop(2, 5), [5, 2]), BetResult::Win(FLAT * 15));
+        assert_eq!(hop(BetKind::Hop(2, 5), [3, 4]), BetResult::Lose);
+        assert_eq!(hop(BetKind::Hop(3, 3), [3, 3]), BetResult::Win(FLAT * 30));
+        assert_eq!(hop(BetKind::Hop(3, 3), [2, 4]), BetResult::Lose);
+    }
+
+    #[test]
+    fn a_press_comes_out_of_the_winnings() {
+        let rules = TableRules::default();
+        let stake = |ride: LetItRide, profit| {
+            ride.stake(BetKind::Field, FLAT, profit, RoundPhase::ComeOut, &rules)
+        };
+        assert_eq!(stake(LetItRide::Press, FLAT * 2), FLAT * 2);
+        assert_eq!(stake(LetItRide::Parlay, FLAT * 2), FLAT * 3);
+        // A $4 win on a $10 bet can only press it up to $14
+        assert_eq!(stake(LetItRide::Press, 400), FLAT + 400);
+    }
+
+    #[test]
+    fn take_down_refunds_the_bet_and_the_odds_behind_it() {
+        let mut bankroll = Bankroll { balance: 0 };
+        let mut bets = TableBets {
+            bets: vec![
+                Bet::new(BetKind::DontPass, FLAT),
+                Bet::new(BetKind::DontPassOdds, 1_200),
+                Bet::new(BetKind::Field, FLAT),
+            ],
+        };
+        let phase = RoundPhase::PointEstablished(6);
+        let taken = bets
+            .take_down(BetKind::DontPass, phase, &mut bankroll)
+            .unwrap();
+        assert_eq!(taken.len(), 2);
+        assert_eq!(bankroll.balance, FLAT + 1_200);
+        assert_eq!(bets.amount_on(BetKind::Field), FLAT);
+        // A spot with nothing on it refunds nothing
+        assert_eq!(
+            bets.take_down(BetKind::Buy(6), phase, &mut bankroll),
+            Ok(Vec::new())
+        );
+    }
+
+    #[test]
+    fn take_down_leaves_contract_bets_alone() {
+        let mut bankroll = Bankroll { balance: 0 };
+        let mut bets = line_bets();
+        let phase = RoundPhase::PointEstablished(4);
+        assert_eq!(
+            bets.take_down(BetKind::PassLine, phase, &mut bankroll),
+            Err(BetError::Contract)
+        );
+        assert_eq!(bets.amount_on(BetKind::PassLine), FLAT);
+        assert_eq!(bankroll.balance, 0);
+        // Before the point is set the pass line can still come down
+        assert!(
+            bets.take_down(BetKind::PassLine, RoundPhase::ComeOut, &mut bankroll)
+                .is_ok()
+        );
+        assert_eq!(bankroll.balance, FLAT);
+    }
+
+    #[test]
+    fn take_down_hands_back_the_commission_actually_paid() {
+        let rules = TableRules {
+            vig: VigPolicy::UpFront,
+            ..TableRules::default()
+        };
+        let mut bankroll = Bankroll { balance: 10_000 };
+        let mut bets = TableBets::default();
+        let phase = RoundPhase::ComeOut;
+        // $10 on the 4 costs the $1 minimum commission, and so does pressing it to $20 -
+        // $2 paid, where $20 bought at once would have cost only $1
+        for _ in 0..2 {
+            let vig = bets
+                .buy_in(BetKind::Buy(4), FLAT, phase, &rules, &mut bankroll)
+                .unwrap();
+            assert_eq!(vig, 100);
+        }
+        assert_eq!(bankroll.balance, 10_000 - 2 * (FLAT + 100));
+        bets.take_down(BetKind::Buy(4), phase, &mut bankroll)
+            .unwrap();
+        assert_eq!(bankroll.balance, 10_000);
+    }
+
+    #[test]
+    fn undo_placement_hands_back_what_those_chips_paid() {
+        let rules = TableRules {
+            vig: VigPolicy::UpFront,
+            ..TableRules::default()
+        };
+        let mut bankroll = Bankroll { balance: 10_000 };
+        let mut bets = TableBets::default();
+        let phase = RoundPhase::ComeOut;
+        for _ in 0..2 {
+            bets.buy_in(BetKind::Buy(4), FLAT, phase, &rules, &mut bankroll)
+                .unwrap();
+        }
+        let press = Bet::new(BetKind::Buy(4), FLAT);
+        bets.undo_placement(press, &rules, &mut bankroll).unwrap();
+        assert_eq!(bankroll.balance, 10_000 - FLAT - 100);
+        assert_eq!(bets.bets[0].vig, 100);
+        bets.undo_placement(press, &rules, &mut bankroll).unwrap();
+        assert_eq!(bankroll.balance, 10_000);
+        assert!(bets.bets.is_empty());
+    }
+
+    #[test]
+    fn undo_placement_takes_back_only_the_new_chips() {
+        let rules = TableRules::default();
+        let mut bankroll = Bankroll { balance: 0 };
+        let mut bets = line_bets();
+        let press = Bet::new(BetKind::PassLine, 500);
+        bets.bets[0].amount += press.amount;
+        bets.undo_placement(press, &rules, &mut bankroll).unwrap();
+        assert_eq!(bets.amount_on(BetKind::PassLine), FLAT);
+        assert_eq!(bankroll.balance, 500);
+        // Undoing the rest clears the spot
+        bets.undo_placement(bets.bets[0], &rules, &mut bankroll)
+            .unwrap();
+        assert_eq!(bets.amount_on(BetKind::PassLine), 0);
+        assert_eq!(bankroll.balance, 500 + FLAT);
+        assert_eq!(
+            bets.undo_placement(press, &rules, &mut bankroll),
+            Err(BetError::NotOnLayout)
+        );
+    }
+
+    #[test]
+    fn cash_out_forfeits_contract_bets() {
+        let mut bets = line_bets();
+        bets.bets.push(Bet::new(BetKind::PassOdds, 3_000));
+        bets.bets.push(Bet::new(BetKind::ComePoint(5), FLAT));
+        // The pass line and the come bet on 5 stay; the don't pass and the odds come back
+        let phase = RoundPhase::PointEstablished(8);
+        assert_eq!(bets.cash_out(phase), FLAT + 3_000);
+        // Nothing is a contract before the point, but a come bet on its number still is
+        assert_eq!(bets.cash_out(RoundPhase::ComeOut), FLAT * 2 + 3_000);
+    }
+}
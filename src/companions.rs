@@ -0,0 +1,129 @@
+// The computer players who keep a solo player company at the table. Each one has a
+// bankroll of their own and a simple betting personality, and bets through the same
+// `TableState` code the hotseat and networked tables use for their other players, so
+// their money always follows the real rules. The companions plugin seats them and
+// draws their chips.
+use bevy::prelude::*;
+
+use crate::betting::{BetKind, BetResult, TableBets};
+use crate::payout::{Bankroll, Cents};
+use crate::rules::{RoundPhase, TableRules};
+use crate::session::TableState;
+
+// The most companions a table seats
+pub const MAX_COMPANIONS: usize = 3;
+
+// How a companion likes to bet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Personality {
+    Conservative,   // The pass line with odds behind it, and nothing else
+    FieldBettor,    // A few dollars on the field, every single roll
+    PropDegenerate, // The one-roll bets in the middle, hoping for a big hit
+}
+
+impl Personality {
+    // In the order they sit down, so one companion is always the careful one
+    pub const ALL: [Personality; MAX_COMPANIONS] = [
+        Personality::Conservative,
+        Personality::FieldBettor,
+        Personality::PropDegenerate,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Personality::Conservative => "Walt",
+            Personality::FieldBettor => "Dot",
+            Personality::PropDegenerate => "Lucky Lou",
+        }
+    }
+
+    // The bets they want to add before the next roll, given what's already down
+    pub fn next_bets(self, phase: RoundPhase, bets: &TableBets) -> Vec<(BetKind, Cents)> {
+        let mut wanted = Vec::new();
+        match self {
+            Personality::Conservative => match phase.point() {
+                None if bets.amount_on(BetKind::PassLine) == 0 => {
+                    wanted.push((BetKind::PassLine, 1_000)); // $10 on the line
+                }
+                // Double odds once there's a point - the best bet on the table
+                Some(_) if bets.amount_on(BetKind::PassOdds) == 0 => {
+                    let line = bets.amount_on(BetKind::PassLine);
+                    if line > 0 {
+                        wanted.push((BetKind::PassOdds, line * 2));
+                    }
+                }
+                _ => {}
+            },
+            // The field is decided every roll, so it goes back down every roll
+            Personality::FieldBettor => wanted.push((BetKind::Field, 500)),
+            Personality::PropDegenerate => {
+                wanted.push((BetKind::Horn, 400)); // $1 on each of 2, 3, 11 and 12
+                if phase.point().is_none() {
+                    wanted.push((BetKind::CAndE, 200));
+                } else {
+                    wanted.push((BetKind::AnyCraps, 100));
+                }
+            }
+        }
+        wanted
+    }
+}
+
+// How a companion takes a roll
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reaction {
+    Cheer, // Came out ahead on it
+    Groan, // Lost money on it
+}
+
+// One computer player and their money
+#[derive(Component, Debug, Clone)]
+pub struct Companion {
+    pub personality: Personality,
+    pub table: TableState,
+}
+
+impl Companion {
+    // Sitting down with the standard stack, partway through the round if need be
+    pub fn new(personality: Personality, phase: RoundPhase) -> Self {
+        Self {
+            personality,
+            table: TableState {
+                phase,
+                bets: TableBets::default(),
+                bankroll: Bankroll::default(),
+            },
+        }
+    }
+
+    // Put down whatever their personality wants before the next roll. A bet they
+    // can't afford (or that the odds limit turns down) simply isn't made.
+    pub fn place_bets(&mut self, rules: &TableRules) {
+        for (kind, amount) in self
+            .personality
+            .next_bets(self.table.phase, &self.table.bets)
+        {
+            let _ = self.table.place_bet(kind, amount, rules);
+        }
+    }
+
+    // Settle their bets against a roll. Hands back how they took it, or None if the
+    // roll didn't decide anything of theirs (or they broke even).
+    pub fn roll(&mut self, die_values: [u8; 2], rules: &TableRules) -> Option<Reaction> {
+        let (_, decisions) = self.table.roll(die_values, rules);
+        let mut won: Cents = 0;
+        let mut lost: Cents = 0;
+        for (bet, result) in decisions {
+            match result {
+                BetResult::Win(profit) => won += profit,
+                BetResult::Lose => lost += bet.amount,
+                _ => {}
+            }
+        }
+        match won.cmp(&lost) {
+            std::cmp::Ordering::Greater => Some(Reaction::Cheer),
+            std::cmp::Ordering::Less => Some(Reaction::Groan),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+}
@@ -0,0 +1,84 @@
+// The debug console's commands, and reading them from a typed line. The console
+// itself - the text box, and carrying each command out at the table - is in
+// plugins/console.rs.
+use bevy::prelude::*;
+use thiserror::Error;
+
+use crate::payout::{Cents, parse_money};
+use crate::rules::{RoundPhase, true_odds};
+
+// Something the console can make happen at the table straight away, to set up a
+// situation without throwing the dice until it comes up
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsoleCommand {
+    Roll([u8; 2]),     // Settle a roll showing these faces, as if the dice had just stopped
+    Bankroll(Cents),   // Set the bankroll
+    State(RoundPhase), // Move the round to the come-out or a point
+    Seed(u64),         // Start the throws' randomness again from this seed
+    Help,              // List the commands
+}
+
+// Why a line couldn't be read as a command. Shown in the console.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CommandError {
+    #[error("unknown command '{0}' - try 'help'")]
+    Unknown(String),
+    #[error("usage: {0}")]
+    Usage(&'static str),
+    #[error("{0} isn't a die face - dice show 1 to 6")]
+    BadFace(u8),
+    #[error("{0} can't be a point - only a number from 2 to 12 other than 7")]
+    BadPoint(u8),
+}
+
+pub const HELP: &str =
+    "Commands: roll A B | bankroll DOLLARS | state comeout | state point N | seed N | help";
+
+const ROLL_USAGE: &str = "roll A B, like roll 6 1";
+const BANKROLL_USAGE: &str = "bankroll DOLLARS, like bankroll 500 or bankroll 12.50";
+const STATE_USAGE: &str = "state comeout, or state point N";
+const SEED_USAGE: &str = "seed N, like seed 12345";
+
+impl ConsoleCommand {
+    // Read one typed line. Upper or lower case, any spacing.
+    pub fn parse(line: &str) -> Result<Self, CommandError> {
+        let line = line.to_lowercase();
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["roll", first, second] => Ok(Self::Roll([face(first)?, face(second)?])),
+            ["roll", ..] => Err(CommandError::Usage(ROLL_USAGE)),
+            ["bankroll", amount] => parse_money(amount)
+                .map(Self::Bankroll)
+                .ok_or(CommandError::Usage(BANKROLL_USAGE)),
+            ["bankroll", ..] => Err(CommandError::Usage(BANKROLL_USAGE)),
+            ["state", "comeout" | "come-out"] => Ok(Self::State(RoundPhase::ComeOut)),
+            ["state", "point", point] => {
+                let point: u8 = point
+                    .parse()
+                    .map_err(|_| CommandError::Usage(STATE_USAGE))?;
+                if true_odds(point).is_none() {
+                    return Err(CommandError::BadPoint(point));
+                }
+                Ok(Self::State(RoundPhase::PointEstablished(point)))
+            }
+            ["state", ..] => Err(CommandError::Usage(STATE_USAGE)),
+            ["seed", seed] => seed
+                .parse()
+                .map(Self::Seed)
+                .map_err(|_| CommandError::Usage(SEED_USAGE)),
+            ["seed", ..] => Err(CommandError::Usage(SEED_USAGE)),
+            ["help"] | [] => Ok(Self::Help),
+            [other, ..] => Err(CommandError::Unknown(other.to_string())),
+        }
+    }
+}
+
+// One die face, 1 to 6
+fn face(word: &str) -> Result<u8, CommandError> {
+    let value: u8 = word.parse().map_err(|_| CommandError::Usage(ROLL_USAGE))?;
+    if (1..=6).contains(&value) {
+        Ok(value)
+    } else {
+        Err(CommandError::BadFace(value))
+    }
+}
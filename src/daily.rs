@@ -0,0 +1,253 @@
+// The daily challenge: one table a day, the same for every player. The day's date (in
+// UTC, so it changes at the same moment everywhere) is turned into the seed for the
+// throws, the dice are decided before they land instead of by the physics
+// (`RollMode::RngAuthoritative`), and everyone sits down with the same stack. The
+// result of each roll comes from the seed and the roll's number alone (`dice`), so a
+// throw that doesn't count can't shift the ones after it. On the same day everyone
+// gets the same rolls in the same order, however they throw - the only difference
+// between players is how they bet. After `DAILY_ROLLS` rolls the challenge is over,
+// and what the player holds is their score for the day. The daily plugin runs the
+// challenge, keeps the scores with storage.rs and shows them.
+use std::cmp::Reverse;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::payout::Cents;
+
+pub const DAILY_ROLLS: u32 = 40; // How many rolls the challenge lasts
+pub const DAILY_BANKROLL: Cents = 100_000; // $1000, what everyone starts with
+
+// Today's challenge, and how far into it the player is
+#[derive(Resource, Debug, Clone)]
+pub struct DailyChallenge {
+    pub date: String, // "2026-10-14"
+    pub seed: u64,    // The ThrowRng seed every player gets today
+    pub rolls: u32,   // Rolls judged so far
+    pub finished: bool,
+}
+
+impl DailyChallenge {
+    pub fn today() -> Self {
+        Self::for_day(days_since_epoch())
+    }
+
+    // The challenge for a day, counted from 1 January 1970
+    pub fn for_day(days: i64) -> Self {
+        let date = date_of(days);
+        Self {
+            seed: seed_for(&date),
+            date,
+            rolls: 0,
+            finished: false,
+        }
+    }
+
+    pub fn rolls_left(&self) -> u32 {
+        DAILY_ROLLS.saturating_sub(self.rolls)
+    }
+
+    // What the dice will show on the roll the challenge is up to: `count` of them, 1 to
+    // 6. Worked out from the day's seed and the rolls counted so far, so a throw that
+    // doesn't count (a no roll) is given the same dice again, and nothing else drawn
+    // during the day - a throw's wobble, a cocked die's hop - can move them on.
+    pub fn dice(&self, count: usize) -> Vec<u8> {
+        let mut state = self.seed ^ u64::from(self.rolls).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        (0..count)
+            .map(|_| (split_mix(&mut state) % 6) as u8 + 1)
+            .collect()
+    }
+
+    // Count one judged roll. Returns true if that was the last one.
+    pub fn record_roll(&mut self) -> bool {
+        self.rolls += 1;
+        self.rolls >= DAILY_ROLLS
+    }
+}
+
+// Today's date in UTC, like "2026-10-14" - the leaderboard dates its sessions with it too
+pub fn todays_date() -> String {
+    date_of(days_since_epoch())
+}
+
+// The date of a day counted from 1 January 1970
+fn date_of(days: i64) -> String {
+    let (year, month, day) = civil_date(days);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+// Turn the date into a seed. This has to come out the same on every machine and with
+// every version of Rust, so it's a simple hash written out here (FNV-1a) rather than
+// the standard library's, which is free to change.
+fn seed_for(date: &str) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in format!("bevy_craps daily {date}").bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+// The next number of a simple random sequence (SplitMix64), written out here for the
+// same reason as `seed_for`: every machine must draw the same dice from it
+fn split_mix(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut mixed = *state;
+    mixed = (mixed ^ (mixed >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    mixed = (mixed ^ (mixed >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    mixed ^ (mixed >> 31)
+}
+
+// The year, month and day a day number falls on (Howard Hinnant's `civil_from_days`,
+// which counts in 400-year cycles starting in March so leap days fall at the end)
+fn civil_date(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468; // Count from 1 March of the year 0 instead
+    let era = days.div_euclid(146_097); // 400-year cycles
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn days_since_epoch() -> i64 {
+    let seconds = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    (seconds / 86_400) as i64
+}
+
+// The browser has no system clock for Rust to read, so ask the page for the time
+#[cfg(target_arch = "wasm32")]
+fn days_since_epoch() -> i64 {
+    (js_sys::Date::now() / 86_400_000.0).floor() as i64 // Milliseconds in a day
+}
+
+// One finished challenge
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DailyScore {
+    pub date: String,
+    pub balance: Cents, // What the player held when the last roll was paid
+}
+
+// Every day's score, kept between launches. `#[serde(default)]` lets an older save load.
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DailyScores {
+    pub days: Vec<DailyScore>, // Oldest first
+}
+
+impl DailyScores {
+    // The score for a day, if the player has finished it
+    pub fn on(&self, date: &str) -> Option<Cents> {
+        self.days
+            .iter()
+            .find(|score| score.date == date)
+            .map(|score| score.balance)
+    }
+
+    // The best day so far
+    pub fn best(&self) -> Option<&DailyScore> {
+        self.days.iter().max_by_key(|score| score.balance)
+    }
+
+    // The best `count` days, best first
+    pub fn top(&self, count: usize) -> Vec<&DailyScore> {
+        let mut days: Vec<&DailyScore> = self.days.iter().collect();
+        days.sort_by_key(|score| Reverse(score.balance)); // Stable, so an earlier day wins a tie
+        days.truncate(count);
+        days
+    }
+
+    // Keep the score of a finished challenge. Only the first go of a day counts - after
+    // that the player knows what the dice are going to do. Returns whether it counted.
+    pub fn record(&mut self, date: &str, balance: Cents) -> bool {
+        if self.on(date).is_some() {
+            return false;
+        }
+        self.days.push(DailyScore {
+            date: date.to_string(),
+            balance,
+        });
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_throw_that_doesnt_count_leaves_the_rolls_alone() {
+        let mut straight = DailyChallenge::for_day(20_740);
+        let mut rethrown = straight.clone();
+        let mut expected = Vec::new();
+        for _ in 0..DAILY_ROLLS {
+            expected.push(straight.dice(2));
+            straight.record_roll();
+        }
+        let mut rolled = Vec::new();
+        for roll in 0..DAILY_ROLLS {
+            // Every third throw is a no roll and has to be thrown again
+            if roll % 3 == 0 {
+                rethrown.dice(2);
+            }
+            rolled.push(rethrown.dice(2));
+            rethrown.record_roll();
+        }
+        assert_eq!(rolled, expected);
+        assert!(expected.iter().flatten().all(|die| (1..=6).contains(die)));
+    }
+
+    #[test]
+    fn each_day_rolls_its_own_dice() {
+        let day = |days| {
+            let mut daily = DailyChallenge::for_day(days);
+            (0..DAILY_ROLLS)
+                .map(|_| {
+                    let dice = daily.dice(2);
+                    daily.record_roll();
+                    dice
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(day(20_740), day(20_740));
+        assert_ne!(day(20_740), day(20_741));
+    }
+
+    #[test]
+    fn civil_date_counts_from_1970() {
+        assert_eq!(civil_date(0), (1970, 1, 1));
+        assert_eq!(civil_date(-1), (1969, 12, 31));
+        // 2000 was a leap year (divisible by 400), 2100 won't be
+        assert_eq!(civil_date(11_016), (2000, 2, 29));
+        assert_eq!(civil_date(47_541), (2100, 3, 1));
+        assert_eq!(date_of(20_740), "2026-10-14");
+    }
+
+    #[test]
+    fn only_the_first_go_of_a_day_is_kept() {
+        let mut scores = DailyScores::default();
+        assert!(scores.record("2026-10-13", 80_000));
+        assert!(scores.record("2026-10-14", 150_000));
+        assert!(!scores.record("2026-10-14", 900_000));
+        assert_eq!(scores.on("2026-10-14"), Some(150_000));
+        assert_eq!(scores.on("2026-10-15"), None);
+        assert_eq!(scores.best().map(|score| score.balance), Some(150_000));
+        let top: Vec<&str> = scores
+            .top(5)
+            .iter()
+            .map(|score| score.date.as_str())
+            .collect();
+        assert_eq!(top, ["2026-10-14", "2026-10-13"]);
+    }
+}
@@ -0,0 +1,87 @@
+// Setting the dice: picking them up a particular way round before the throw, the way
+// a "controlled shooter" does. Each set puts chosen faces on top and towards the
+// shooter, and the dice are thrown with backspin about the set's axis and less of
+// the random wobble, so the faces that come round as a die tumbles are more likely to
+// be the ones it lands on. Whether that beats the house in a real casino is argued
+// about; here it nudges the physics, and in RollMode::RngAuthoritative it changes
+// nothing at all. The dice plugin turns a set into the dice's starting rotation.
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// The sets the shooter can pick up the dice in. The chosen one is kept as a resource
+// and sent with each throw.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DiceSet {
+    #[default]
+    Random, // Picked up any way up, the way most players throw
+    Hardway,   // Both dice the same way round, so every face rolling up is a pair
+    ThreeV,    // Threes on top, with two sevens on the way round - for the come-out
+    AllSevens, // Every face rolling up adds to seven - for a player betting the don't
+}
+
+impl DiceSet {
+    pub const ALL: [DiceSet; 4] = [
+        DiceSet::Random,
+        DiceSet::Hardway,
+        DiceSet::ThreeV,
+        DiceSet::AllSevens,
+    ];
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&set| set == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DiceSet::Random => "Any way up",
+            DiceSet::Hardway => "Hardway set",
+            DiceSet::ThreeV => "3-V set",
+            DiceSet::AllSevens => "All-sevens set",
+        }
+    }
+
+    // The face on top of each die and the face looking back at the shooter, die #1
+    // (on the shooter's right) first. None for dice picked up any way up.
+    pub fn faces(self) -> Option<[SetFaces; 2]> {
+        let faces = |top, towards_shooter| SetFaces {
+            top,
+            towards_shooter,
+        };
+        match self {
+            DiceSet::Random => None,
+            DiceSet::Hardway => Some([faces(3, 2), faces(3, 2)]),
+            DiceSet::ThreeV => Some([faces(3, 1), faces(3, 6)]),
+            DiceSet::AllSevens => Some([faces(3, 2), faces(4, 5)]),
+        }
+    }
+
+    // The totals showing on top as the dice tumble forward a quarter turn at a time
+    // together - what the set is aiming for
+    pub fn primary_totals(self) -> Option<[u8; 4]> {
+        let [first, second] = self.faces()?;
+        let (first, second) = (first.rolling(), second.rolling());
+        Some(std::array::from_fn(|turn| first[turn] + second[turn]))
+    }
+}
+
+// How one die is set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetFaces {
+    pub top: u8,
+    pub towards_shooter: u8, // Must be a side next to `top`, not `top` or its opposite
+}
+
+impl SetFaces {
+    // The faces that come up on top as the die tumbles forward, starting with `top`.
+    // Rolling away from the shooter, the face looking back at them comes up next,
+    // and opposite faces always add up to 7.
+    pub fn rolling(self) -> [u8; 4] {
+        [
+            self.top,
+            self.towards_shooter,
+            7 - self.top,
+            7 - self.towards_shooter,
+        ]
+    }
+}
@@ -0,0 +1,72 @@
+// Keyframe animation: a clip is a list of poses at moments in time, and whatever plays
+// it is moved smoothly from each pose to the next. The shooter's dice cup (see
+// plugins/shooter.rs) is animated this way, from clips written in
+// assets/shooter.clips.ron. Like the rules module, this is pure logic with no
+// rendering - hand a clip a time and it gives back a pose.
+use bevy::prelude::*;
+use serde::Deserialize;
+
+// One pose in a clip. The pose is measured from where the clip is being played, looking
+// the way it's played, so the same clip works whichever way the shooter faces.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Keyframe {
+    pub time: f32,    // Seconds from the start of the clip
+    pub offset: Vec3, // How far it has moved: x to the right, y up, z ahead
+    // Degrees tipped forward (about x), turned left (about y) and leant right (about z)
+    pub turn: Vec3,
+}
+
+impl Keyframe {
+    // The pose as a transform. Bevy looks along -z, so "ahead" is turned round.
+    pub fn pose(&self) -> Transform {
+        let turn = self.turn * Vec3::new(-1.0, 1.0, -1.0);
+        Transform::from_translation(self.offset * Vec3::new(1.0, 1.0, -1.0)).with_rotation(
+            Quat::from_euler(
+                EulerRot::XYZ,
+                turn.x.to_radians(),
+                turn.y.to_radians(),
+                turn.z.to_radians(),
+            ),
+        )
+    }
+}
+
+// A clip is written in a file as just its list of keyframes
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(transparent)]
+pub struct Clip {
+    pub keys: Vec<Keyframe>, // In time order
+}
+
+impl Clip {
+    // How long the clip runs: up to its last keyframe
+    pub fn duration(&self) -> f32 {
+        self.keys.last().map_or(0.0, |key| key.time)
+    }
+
+    // The pose `time` seconds in, eased between the keyframes either side. Before the
+    // first keyframe it's at the first, and after the last it stays at the last.
+    pub fn sample(&self, time: f32) -> Transform {
+        match self.keys.iter().position(|key| key.time > time) {
+            None => self.keys.last().map_or(Transform::IDENTITY, Keyframe::pose),
+            Some(0) => self.keys[0].pose(),
+            Some(next) => {
+                let (from, to) = (self.keys[next - 1].pose(), self.keys[next].pose());
+                let (start, end) = (self.keys[next - 1].time, self.keys[next].time);
+                let progress = (time - start) / (end - start);
+                let eased = progress * progress * (3.0 - 2.0 * progress); // Slow out, slow in
+                Transform::from_translation(from.translation.lerp(to.translation, eased))
+                    .with_rotation(from.rotation.slerp(to.rotation, eased))
+            }
+        }
+    }
+
+    // The same, with the clip going round and round
+    pub fn sample_looped(&self, time: f32) -> Transform {
+        let duration = self.duration();
+        if duration <= 0.0 {
+            return self.sample(0.0);
+        }
+        self.sample(time.rem_euclid(duration))
+    }
+}
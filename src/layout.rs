@@ -0,0 +1,381 @@
+// Where everything sits on the felt. Positions are (x, z) on the table top, centred on
+// the origin, with the shooter throwing from -x towards +x and the player's side of
+// the table at -z.
+//
+// The layout is drawn once for a reference 8 x 4 table and stretched to fit whatever
+// size the table actually is, so a bigger or smaller table gets the same markings.
+// The row of number boxes holds one box for each point the table's variant has, and a
+// sic bo table has a layout of its own. Side bets from scripts (see side_bets.rs) get a
+// row of boxes along the front of the pass line, the way casinos print a fire bet.
+use bevy::prelude::*;
+
+use crate::betting::{BetKind, TableBets};
+use crate::registry::MAX_REGISTERED_BETS;
+use crate::rules::{RoundPhase, Variant};
+use crate::sicbo::SicBoBet;
+
+// The table size the zone coordinates below are written for
+pub const REFERENCE_SIZE: Vec2 = Vec2::new(8.0, 4.0);
+
+// The row of number boxes, shared out between however many points there are
+const NUMBER_BOX_LEFT: f32 = -2.6; // Left edge of the first box
+const NUMBER_BOX_RIGHT: f32 = 1.9; // Right edge of the last one
+
+// A clickable area of the layout. Some spots take different bets depending on the
+// state of the game - clicking the pass line after the point is set adds odds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BetSpot {
+    PassLine,
+    DontPass,
+    Come,
+    DontCome,
+    Field,
+    NumberBox(u8),   // The lower part of a number box
+    NumberStrip(u8), // The thin "don't" strip along the top of a number box
+    AnySeven,
+    Horn,
+    CAndE,
+    AnyCraps,
+    Hop(u8, u8),
+    SicBo(SicBoBet), // Every spot on a sic bo layout takes just its own bet
+    Side(u8),        // The box of one of the scripted side bets
+}
+
+impl BetSpot {
+    // The bet a click on this spot makes right now
+    pub fn bet_kind(self, phase: RoundPhase, bets: &TableBets) -> BetKind {
+        match self {
+            // Once the point is on, the line bets can only be backed with odds
+            BetSpot::PassLine if !phase.is_come_out() => BetKind::PassOdds,
+            BetSpot::PassLine => BetKind::PassLine,
+            BetSpot::DontPass if !phase.is_come_out() => BetKind::DontPassOdds,
+            BetSpot::DontPass => BetKind::DontPass,
+            BetSpot::Come => BetKind::Come,
+            BetSpot::DontCome => BetKind::DontCome,
+            BetSpot::Field => BetKind::Field,
+            // A come bet sitting in the box is backed with odds, otherwise it's a buy
+            BetSpot::NumberBox(number) if bets.amount_on(BetKind::ComePoint(number)) > 0 => {
+                BetKind::ComeOdds(number)
+            }
+            BetSpot::NumberBox(number) => BetKind::Buy(number),
+            BetSpot::NumberStrip(number) if bets.amount_on(BetKind::DontComePoint(number)) > 0 => {
+                BetKind::DontComeOdds(number)
+            }
+            BetSpot::NumberStrip(number) => BetKind::Lay(number),
+            BetSpot::AnySeven => BetKind::AnySeven,
+            BetSpot::Horn => BetKind::Horn,
+            BetSpot::CAndE => BetKind::CAndE,
+            BetSpot::AnyCraps => BetKind::AnyCraps,
+            BetSpot::Hop(low, high) => BetKind::Hop(low, high),
+            BetSpot::SicBo(bet) => BetKind::SicBo(bet),
+            BetSpot::Side(index) => BetKind::Side(index),
+        }
+    }
+
+    // The lettering printed on the spot, in English whatever the player's language -
+    // the pixel font it's drawn in (see palette.rs) only has plain capitals. Six and
+    // nine are spelled out, the way a casino prints them so they can't be read upside
+    // down as each other. The side bets' boxes carry their rule's name instead, which
+    // the layout doesn't know.
+    pub fn marking(self) -> Option<String> {
+        let text = match self {
+            BetSpot::PassLine => "PASS LINE".to_string(),
+            BetSpot::DontPass => "DON'T PASS BAR".to_string(),
+            BetSpot::Come => "COME".to_string(),
+            BetSpot::DontCome => "DON'T COME".to_string(),
+            BetSpot::Field => "FIELD  2 3 4 9 10 11 12".to_string(),
+            BetSpot::NumberBox(6) => "SIX".to_string(),
+            BetSpot::NumberBox(9) => "NINE".to_string(),
+            BetSpot::NumberBox(number) => number.to_string(),
+            BetSpot::NumberStrip(_) => return None, // Left plain, as on a real table
+            BetSpot::AnySeven => "ANY SEVEN".to_string(),
+            BetSpot::Horn => "HORN".to_string(),
+            BetSpot::CAndE => "C & E".to_string(),
+            BetSpot::AnyCraps => "ANY CRAPS".to_string(),
+            BetSpot::Hop(low, high) => format!("{low}-{high}"),
+            BetSpot::SicBo(bet) => match bet {
+                SicBoBet::Small => "SMALL".to_string(),
+                SicBoBet::Big => "BIG".to_string(),
+                SicBoBet::Total(total) => total.to_string(),
+                SicBoBet::Single(face) => face.to_string(),
+                SicBoBet::Double(face) => format!("{face}-{face}"),
+                SicBoBet::Triple(face) => format!("{face}-{face}-{face}"),
+                SicBoBet::AnyTriple => "ANY TRIPLE".to_string(),
+                SicBoBet::Combination(low, high) => format!("{low}-{high}"),
+            },
+            BetSpot::Side(_) => return None,
+        };
+        Some(text)
+    }
+
+    // The bet a right-click on this spot takes down: whatever a click would add to, if
+    // there's anything on it, otherwise the bet its odds would sit behind (the line bet,
+    // or the come bet in the box)
+    pub fn take_down_kind(self, phase: RoundPhase, bets: &TableBets) -> BetKind {
+        let kind = self.bet_kind(phase, bets);
+        match kind.odds_base(phase) {
+            Some((base, _)) if bets.amount_on(kind) == 0 => base,
+            _ => kind,
+        }
+    }
+}
+
+// A rectangle on the felt that belongs to one spot
+#[derive(Debug, Clone, Copy)]
+pub struct BetZone {
+    pub spot: BetSpot,
+    pub min: Vec2, // Corner with the smallest x and z
+    pub max: Vec2, // Corner with the largest x and z
+}
+
+impl BetZone {
+    fn new(spot: BetSpot, min: (f32, f32), max: (f32, f32)) -> Self {
+        Self {
+            spot,
+            min: Vec2::new(min.0, min.1),
+            max: Vec2::new(max.0, max.1),
+        }
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        point.x >= self.min.x
+            && point.x <= self.max.x
+            && point.y >= self.min.y
+            && point.y <= self.max.y
+    }
+
+    pub fn center(&self) -> Vec2 {
+        (self.min + self.max) / 2.0
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.max - self.min
+    }
+}
+
+// Every zone of the layout, in reference-table coordinates
+fn reference_zones(variant: Variant, side_bets: usize) -> Vec<BetZone> {
+    if variant == Variant::SicBo {
+        return sic_bo_zones();
+    }
+    let main_left = -3.9; // The main layout runs from here...
+    let main_right = 1.9; // ...to the start of the props box
+    // The side bets' boxes take the front of the pass line, if there are any
+    let side_bets = side_bets.min(MAX_REGISTERED_BETS);
+    let pass_front = if side_bets > 0 { -1.6 } else { -1.9 };
+    let mut zones = vec![
+        BetZone::new(
+            BetSpot::PassLine,
+            (main_left, pass_front),
+            (main_right, -1.1),
+        ),
+        BetZone::new(BetSpot::DontPass, (main_left, -1.1), (main_right, -0.6)),
+        BetZone::new(BetSpot::Field, (main_left, -0.6), (main_right, 0.2)),
+        BetZone::new(BetSpot::Come, (main_left, 0.2), (main_right, 1.0)),
+        BetZone::new(BetSpot::DontCome, (main_left, 1.0), (NUMBER_BOX_LEFT, 1.9)),
+        // The proposition bets live in their own box at the far end of the table
+        BetZone::new(BetSpot::AnySeven, (2.0, 1.4), (3.9, 1.9)),
+        BetZone::new(BetSpot::Horn, (2.0, 0.9), (3.9, 1.4)),
+        BetZone::new(BetSpot::CAndE, (2.0, 0.4), (3.9, 0.9)),
+        BetZone::new(BetSpot::AnyCraps, (2.0, -0.1), (3.9, 0.4)),
+    ];
+
+    let numbers = variant.point_numbers();
+    let box_width = (NUMBER_BOX_RIGHT - NUMBER_BOX_LEFT) / numbers.len() as f32;
+    for (index, &number) in numbers.iter().enumerate() {
+        let left = NUMBER_BOX_LEFT + box_width * index as f32;
+        let right = left + box_width;
+        zones.push(BetZone::new(
+            BetSpot::NumberBox(number),
+            (left, 1.0),
+            (right, 1.6),
+        ));
+        zones.push(BetZone::new(
+            BetSpot::NumberStrip(number),
+            (left, 1.6),
+            (right, 1.9),
+        ));
+    }
+
+    let side_width = (main_right - main_left) / side_bets.max(1) as f32;
+    for index in 0..side_bets {
+        let left = main_left + side_width * index as f32;
+        zones.push(BetZone::new(
+            BetSpot::Side(index as u8),
+            (left, -1.9),
+            (left + side_width, pass_front),
+        ));
+    }
+
+    // The 21 possible hops sit in a 7 x 3 grid below the other props
+    for (index, (low, high)) in all_hops().into_iter().enumerate() {
+        let left = 2.0 + 0.27 * (index % 7) as f32;
+        let top = -0.1 - 0.6 * (index / 7) as f32;
+        zones.push(BetZone::new(
+            BetSpot::Hop(low, high),
+            (left, top - 0.6),
+            (left + 0.27, top),
+        ));
+    }
+
+    zones
+}
+
+// The sic bo layout, in rows across the table from the player's side: small, the
+// triples and big along the front, then the totals, the doubles, the two-number
+// combinations, and the single numbers along the back
+fn sic_bo_zones() -> Vec<BetZone> {
+    let (left, right) = (-3.9, 3.9);
+    let mut zones = Vec::new();
+    // Lay a row of spots out side by side, each as wide as the others
+    let mut row = |spots: Vec<SicBoBet>, front: f32, back: f32| {
+        let width = (right - left) / spots.len() as f32;
+        for (index, bet) in spots.into_iter().enumerate() {
+            let start = left + width * index as f32;
+            zones.push(BetZone::new(
+                BetSpot::SicBo(bet),
+                (start, front),
+                (start + width, back),
+            ));
+        }
+    };
+    let mut front_row = vec![SicBoBet::Small];
+    front_row.extend((1..=6).map(SicBoBet::Triple));
+    front_row.extend([SicBoBet::AnyTriple, SicBoBet::Big]);
+    row(front_row, -1.9, -1.2);
+    row((4..=17).map(SicBoBet::Total).collect(), -1.2, -0.5);
+    row((1..=6).map(SicBoBet::Double).collect(), -0.5, 0.2);
+    let combinations = SicBoBet::all()
+        .into_iter()
+        .filter(|bet| matches!(bet, SicBoBet::Combination(_, _)))
+        .collect();
+    row(combinations, 0.2, 1.2);
+    row((1..=6).map(SicBoBet::Single).collect(), 1.2, 1.9);
+    zones
+}
+
+// Every distinct pair of dice, lower die first: (1,1), (1,2) ... (6,6)
+pub fn all_hops() -> Vec<(u8, u8)> {
+    (1..=6)
+        .flat_map(|low| (low..=6).map(move |high| (low, high)))
+        .collect()
+}
+
+// The layout of the table that is actually in play
+#[derive(Resource, Debug, Clone)]
+pub struct TableLayout {
+    pub size: Vec2,       // Width (x) and depth (z) of the playing surface
+    pub variant: Variant, // The game it's printed for, which decides the number boxes
+    pub side_bets: usize, // How many side bet boxes it has
+    pub zones: Vec<BetZone>,
+}
+
+impl Default for TableLayout {
+    fn default() -> Self {
+        Self::new(REFERENCE_SIZE, Variant::default(), 0)
+    }
+}
+
+impl TableLayout {
+    // Stretch the reference layout to fit a table of the given size
+    pub fn new(size: Vec2, variant: Variant, side_bets: usize) -> Self {
+        let scale = size / REFERENCE_SIZE;
+        let zones = reference_zones(variant, side_bets)
+            .into_iter()
+            .map(|zone| BetZone {
+                spot: zone.spot,
+                min: zone.min * scale,
+                max: zone.max * scale,
+            })
+            .collect();
+        Self {
+            size,
+            variant,
+            side_bets,
+            zones,
+        }
+    }
+
+    // How much this table is stretched compared to the reference table
+    fn scale(&self) -> Vec2 {
+        self.size / REFERENCE_SIZE
+    }
+
+    pub fn zone(&self, spot: BetSpot) -> Option<&BetZone> {
+        self.zones.iter().find(|zone| zone.spot == spot)
+    }
+
+    // Which spot (if any) a point on the table top falls in
+    pub fn spot_at(&self, point: Vec2) -> Option<BetSpot> {
+        self.zones
+            .iter()
+            .find(|zone| zone.contains(point))
+            .map(|zone| zone.spot)
+    }
+
+    // Where the chips for a bet are stacked: the middle of the spot it sits on, nudged
+    // aside for bets that share a spot with another bet
+    pub fn chip_anchor(&self, kind: BetKind) -> Vec2 {
+        let (spot, offset) = match kind {
+            BetKind::PassLine => (BetSpot::PassLine, Vec2::ZERO),
+            BetKind::PassOdds => (BetSpot::PassLine, Vec2::new(0.0, -0.28)), // Behind the line
+            BetKind::DontPass => (BetSpot::DontPass, Vec2::ZERO),
+            BetKind::DontPassOdds => (BetSpot::DontPass, Vec2::new(0.3, 0.0)),
+            BetKind::Come => (BetSpot::Come, Vec2::ZERO),
+            BetKind::DontCome => (BetSpot::DontCome, Vec2::ZERO),
+            BetKind::Field => (BetSpot::Field, Vec2::ZERO),
+            // Number boxes: "do" bets in the lower part, "don't" bets in the strip above
+            BetKind::ComePoint(number) => (BetSpot::NumberBox(number), Vec2::new(0.0, -0.05)),
+            BetKind::ComeOdds(number) => (BetSpot::NumberBox(number), Vec2::new(0.22, -0.05)),
+            BetKind::Buy(number) => (BetSpot::NumberBox(number), Vec2::new(-0.22, -0.05)),
+            BetKind::DontComePoint(number) => (BetSpot::NumberStrip(number), Vec2::ZERO),
+            BetKind::DontComeOdds(number) => (BetSpot::NumberStrip(number), Vec2::new(0.22, 0.0)),
+            BetKind::Lay(number) => (BetSpot::NumberStrip(number), Vec2::new(-0.22, 0.0)),
+            BetKind::AnySeven => (BetSpot::AnySeven, Vec2::ZERO),
+            BetKind::Horn => (BetSpot::Horn, Vec2::ZERO),
+            BetKind::CAndE => (BetSpot::CAndE, Vec2::ZERO),
+            BetKind::AnyCraps => (BetSpot::AnyCraps, Vec2::ZERO),
+            BetKind::Hop(low, high) => (BetSpot::Hop(low, high), Vec2::ZERO),
+            BetKind::SicBo(bet) => (BetSpot::SicBo(bet), Vec2::ZERO),
+            BetKind::Side(index) => (BetSpot::Side(index), Vec2::ZERO),
+        };
+        let mut offset = offset * self.scale();
+        // The offsets are for a standard table's six boxes; a crapless table fits ten
+        // narrower boxes in the same row, so the chips bunch up to stay inside them
+        if let BetSpot::NumberBox(_) | BetSpot::NumberStrip(_) = spot {
+            let boxes = self.variant.point_numbers().len() as f32;
+            offset.x *= 6.0 / boxes;
+        }
+        // A number without a box of its own falls back to the come area
+        let center = self
+            .zone(spot)
+            .or_else(|| self.zone(BetSpot::Come))
+            .map_or(Vec2::ZERO, BetZone::center);
+        center + offset
+    }
+
+    // Where the player in a seat stands, just inside the rail. The seats run clockwise
+    // (seen from above) round the shooter's end of the table: from the middle of the
+    // far side, past the -x end, to the middle of the player's side.
+    pub fn seat_position(&self, seat: usize, seats: usize) -> Vec2 {
+        let inset = self.scale() * 0.3;
+        let reach = self.size / 2.0 - inset;
+        // Evenly spaced round half an ellipse, with half a gap at each end
+        let along = (seat as f32 + 0.5) / seats.max(1) as f32;
+        let angle = std::f32::consts::FRAC_PI_2 + along * std::f32::consts::PI;
+        Vec2::new(angle.cos(), angle.sin()) * reach
+    }
+
+    // Where the dealer's puck sits: on the point's number box when a point is on,
+    // otherwise parked "OFF" in the corner of the don't come box
+    pub fn puck_position(&self, point: Option<u8>) -> Vec2 {
+        if let Some(zone) = point.and_then(|number| self.zone(BetSpot::NumberBox(number))) {
+            // Above the come point chips, near the top of the box
+            return zone.center() + Vec2::new(0.0, 0.18) * self.scale();
+        }
+        match self.zone(BetSpot::DontCome) {
+            Some(zone) => Vec2::new(zone.min.x, zone.max.y) + Vec2::new(0.2, -0.2) * self.scale(),
+            None => Vec2::ZERO,
+        }
+    }
+}
@@ -0,0 +1,207 @@
+// The player's best sessions, kept between launches: the top few for the biggest
+// bankroll, the longest hand and the most points made in one hand. Each session's
+// `SessionStats` are boiled down to a `SessionRecord` when the player leaves the table,
+// and only records that make at least one of the boards are kept. The leaderboard
+// plugin records the sessions, saves them and shows the boards from the main menu.
+use std::cmp::Reverse;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::locale::Locale;
+use crate::payout::{Cents, format_money};
+use crate::stats::SessionStats;
+
+pub const LEADERBOARD_PLACES: usize = 5; // How many sessions each board shows
+
+// What one session is remembered by
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub date: String,        // The day it was played, like "2026-10-14"
+    pub best_balance: Cents, // The most the player held at once, chips on the layout included
+    pub longest_hand: u32,   // The most rolls one shooter threw
+    pub most_points: u32,    // The most points one shooter made
+    pub rolls: u32,
+}
+
+impl SessionRecord {
+    pub fn from_stats(stats: &SessionStats, date: String) -> Self {
+        Self {
+            date,
+            best_balance: stats.peak_holding,
+            longest_hand: stats.longest_hand(),
+            most_points: stats.most_points_in_hand(),
+            rolls: stats.rolls,
+        }
+    }
+}
+
+// The boards a session can make
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Board {
+    BiggestBankroll,
+    LongestHand,
+    MostPoints,
+}
+
+impl Board {
+    pub const ALL: [Board; 3] = [
+        Board::BiggestBankroll,
+        Board::LongestHand,
+        Board::MostPoints,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Board::BiggestBankroll => "Biggest bankroll",
+            Board::LongestHand => "Longest hand",
+            Board::MostPoints => "Most points in one hand",
+        }
+    }
+
+    // What the board ranks sessions by - bigger is better
+    pub fn score(self, session: &SessionRecord) -> u64 {
+        match self {
+            Board::BiggestBankroll => session.best_balance,
+            Board::LongestHand => u64::from(session.longest_hand),
+            Board::MostPoints => u64::from(session.most_points),
+        }
+    }
+
+    // One line of the board: "1. $2,350 - 2026-10-14"
+    pub fn entry(self, place: usize, session: &SessionRecord, locale: &Locale) -> String {
+        match self {
+            Board::BiggestBankroll => locale.fill(
+                "{place}. {money} - {date}",
+                &[
+                    ("place", &place),
+                    ("money", &format_money(session.best_balance)),
+                    ("date", &session.date),
+                ],
+            ),
+            Board::LongestHand => locale.fill(
+                "{place}. {count} rolls - {date}",
+                &[
+                    ("place", &place),
+                    ("count", &session.longest_hand),
+                    ("date", &session.date),
+                ],
+            ),
+            Board::MostPoints => locale.fill(
+                "{place}. {count} points - {date}",
+                &[
+                    ("place", &place),
+                    ("count", &session.most_points),
+                    ("date", &session.date),
+                ],
+            ),
+        }
+    }
+}
+
+// Every session that's on at least one board. `#[serde(default)]` lets an older save load.
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Leaderboard {
+    pub sessions: Vec<SessionRecord>, // Oldest first
+}
+
+impl Leaderboard {
+    // A board's sessions, best first
+    pub fn top(&self, board: Board) -> Vec<&SessionRecord> {
+        self.top_places(board)
+            .into_iter()
+            .map(|index| &self.sessions[index])
+            .collect()
+    }
+
+    // Where in `sessions` a board's places are. A session that scored nothing (no
+    // points made, say) doesn't take a place, and on a tie the earlier session stays
+    // ahead.
+    fn top_places(&self, board: Board) -> Vec<usize> {
+        let mut places: Vec<usize> = (0..self.sessions.len())
+            .filter(|&index| board.score(&self.sessions[index]) > 0)
+            .collect();
+        places.sort_by_key(|&index| Reverse(board.score(&self.sessions[index])));
+        places.truncate(LEADERBOARD_PLACES);
+        places
+    }
+
+    // Add a finished session, and let go of any that have dropped off every board.
+    // Returns whether the new one made a board.
+    pub fn record(&mut self, session: SessionRecord) -> bool {
+        self.sessions.push(session);
+        let newest = self.sessions.len() - 1;
+        let mut kept: Vec<usize> = Board::ALL
+            .into_iter()
+            .flat_map(|board| self.top_places(board))
+            .collect();
+        let placed = kept.contains(&newest);
+        kept.sort_unstable();
+        kept.dedup();
+        self.sessions = kept
+            .into_iter()
+            .map(|index| self.sessions[index].clone())
+            .collect();
+        placed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(
+        date: &str,
+        best_balance: Cents,
+        longest_hand: u32,
+        most_points: u32,
+    ) -> SessionRecord {
+        SessionRecord {
+            date: date.to_string(),
+            best_balance,
+            longest_hand,
+            most_points,
+            rolls: longest_hand,
+        }
+    }
+
+    #[test]
+    fn boards_rank_best_first_and_ties_keep_the_earlier_session() {
+        let mut board = Leaderboard::default();
+        board.record(session("2026-10-01", 120_000, 8, 0));
+        board.record(session("2026-10-02", 300_000, 8, 2));
+        board.record(session("2026-10-03", 120_000, 20, 1));
+        let dates = |which| -> Vec<&str> {
+            board
+                .top(which)
+                .iter()
+                .map(|session| session.date.as_str())
+                .collect()
+        };
+        assert_eq!(
+            dates(Board::BiggestBankroll),
+            ["2026-10-02", "2026-10-01", "2026-10-03"]
+        );
+        assert_eq!(
+            dates(Board::LongestHand),
+            ["2026-10-03", "2026-10-01", "2026-10-02"]
+        );
+        // A session that made no points isn't on the points board at all
+        assert_eq!(dates(Board::MostPoints), ["2026-10-02", "2026-10-03"]);
+    }
+
+    #[test]
+    fn a_session_off_every_board_is_let_go() {
+        let mut board = Leaderboard::default();
+        for day in 0..LEADERBOARD_PLACES as u32 {
+            assert!(board.record(session("good", 200_000, 10 + day, 3)));
+        }
+        assert!(!board.record(session("poor", 100_000, 2, 1)));
+        assert_eq!(board.sessions.len(), LEADERBOARD_PLACES);
+        assert!(board.sessions.iter().all(|session| session.date == "good"));
+        // Beating one board is enough to be kept
+        assert!(board.record(session("lucky", 100_000, 2, 9)));
+        assert_eq!(board.top(Board::MostPoints)[0].date, "lucky");
+    }
+}
@@ -0,0 +1,288 @@
+// The session's ledger: every roll (or sic bo throw) written down with the bets that
+// went on and off before it, what it did to each bet it decided, and what it did to the
+// player's money. The stats panel counts its numbers from each new line (see
+// plugins/stats.rs), and the same lines can be exported for a spreadsheet - as CSV, one
+// row per bet or roll, or as JSON, one object per roll.
+use bevy::prelude::*;
+use serde::Serialize;
+
+use crate::betting::{Bet, BetKind, BetResult};
+use crate::payout::Cents;
+use crate::rules::RollOutcome;
+
+// Every roll so far, oldest first. Amounts are in cents.
+#[derive(Resource, Debug, Clone, Default, Serialize)]
+pub struct SessionLedger {
+    pub starting_holding: Cents, // What the player sat down with
+    pub rolls: Vec<LedgerRoll>,
+    #[serde(skip)]
+    moves: Vec<ChipMove>, // Chips that went on or off since the last roll
+}
+
+// One roll and everything that came with it
+#[derive(Debug, Clone, Serialize)]
+pub struct LedgerRoll {
+    pub number: u32,          // Counted from 1
+    pub moves: Vec<ChipMove>, // Chips that went on or off since the roll before
+    pub dice: Vec<u8>,        // Two dice at craps, three at sic bo
+    pub total: u8,
+    pub outcome: Option<RollOutcome>, // What it meant for the round - none at sic bo
+    pub decided: Vec<Decision>,       // Each bet the roll settled or moved
+    pub net: i64,                     // Won (or, when negative, lost) on the roll
+    pub holding: Cents,               // Everything the player had afterwards, bets included
+}
+
+// Chips put down on a spot or taken back off it
+#[derive(Debug, Clone, Copy, Serialize)]
+pub enum ChipMove {
+    Placed(Bet),
+    TakenDown(Bet), // A bet taken down between rolls
+    Undone(Bet),    // Chips taken back straight after they went down
+}
+
+// What a roll did to one bet
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Decision {
+    pub bet: Bet,
+    pub result: BetResult,
+    pub returned: Cents, // What went back into the bankroll
+}
+
+// The kinds of file the ledger can be exported as
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub const ALL: [Self; 2] = [Self::Csv, Self::Json];
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+        }
+    }
+
+    // What its button says
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Csv => "Export CSV",
+            Self::Json => "Export JSON",
+        }
+    }
+}
+
+impl SessionLedger {
+    pub fn new(starting_holding: Cents) -> Self {
+        Self {
+            starting_holding,
+            ..Self::default()
+        }
+    }
+
+    // Note chips going on or off, to go in with the next roll
+    pub fn note_move(&mut self, chips: ChipMove) {
+        self.moves.push(chips);
+    }
+
+    // Write a roll down, with the bets it decided and what the player holds now.
+    // Hands back the new line.
+    pub fn record(
+        &mut self,
+        dice: &[u8],
+        outcome: Option<RollOutcome>,
+        decided: Vec<Decision>,
+        holding: Cents,
+    ) -> &LedgerRoll {
+        // A bet that travels (a come bet going to its number) hasn't won or lost yet
+        let net = decided
+            .iter()
+            .filter(|decision| !matches!(decision.result, BetResult::Travel(_)))
+            .map(|decision| decision.returned as i64 - decision.bet.amount as i64)
+            .sum();
+        self.rolls.push(LedgerRoll {
+            number: self.rolls.len() as u32 + 1,
+            moves: std::mem::take(&mut self.moves),
+            dice: dice.to_vec(),
+            total: dice.iter().sum(),
+            outcome,
+            decided,
+            net,
+            holding,
+        });
+        &self.rolls[self.rolls.len() - 1]
+    }
+
+    // The whole ledger as a CSV spreadsheet with a header row. Each roll is a row of
+    // its own, after a row for every bet that went on or off before it and followed by
+    // one for every bet it decided. Money is in dollars, without the $.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "roll,event,die 1,die 2,die 3,total,outcome,bet,amount,result,returned,net,holding\n",
+        );
+        for roll in &self.rolls {
+            for chips in &roll.moves {
+                let (event, bet) = match chips {
+                    ChipMove::Placed(bet) => ("placed", bet),
+                    ChipMove::TakenDown(bet) => ("taken down", bet),
+                    ChipMove::Undone(bet) => ("undone", bet),
+                };
+                csv_row(
+                    &mut csv,
+                    &[
+                        roll.number.to_string(),
+                        event.to_string(),
+                        String::new(),
+                        String::new(),
+                        String::new(),
+                        String::new(),
+                        String::new(),
+                        bet_text(bet.kind),
+                        dollars(bet.amount as i64),
+                    ],
+                );
+            }
+            // A craps roll leaves the third die's column empty
+            let die = |index: usize| roll.dice.get(index).map_or(String::new(), u8::to_string);
+            csv_row(
+                &mut csv,
+                &[
+                    roll.number.to_string(),
+                    "rolled".to_string(),
+                    die(0),
+                    die(1),
+                    die(2),
+                    roll.total.to_string(),
+                    roll.outcome
+                        .map_or(String::new(), |outcome| format!("{outcome:?}")),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                    dollars(roll.net),
+                    dollars(roll.holding as i64),
+                ],
+            );
+            for decision in &roll.decided {
+                let result = match decision.result {
+                    BetResult::Win(_) => "win".to_string(),
+                    BetResult::Lose => "lose".to_string(),
+                    BetResult::Push => "push".to_string(),
+                    BetResult::Stay => "stay".to_string(),
+                    BetResult::Travel(to) => format!("moves to {}", bet_text(to)),
+                };
+                csv_row(
+                    &mut csv,
+                    &[
+                        roll.number.to_string(),
+                        "decided".to_string(),
+                        String::new(),
+                        String::new(),
+                        String::new(),
+                        String::new(),
+                        String::new(),
+                        bet_text(decision.bet.kind),
+                        dollars(decision.bet.amount as i64),
+                        result,
+                        dollars(decision.returned as i64),
+                    ],
+                );
+            }
+        }
+        csv
+    }
+
+    // The ledger written out as a file of this kind
+    pub fn export(&self, format: ExportFormat) -> String {
+        match format {
+            ExportFormat::Csv => self.to_csv(),
+            ExportFormat::Json => self.to_json(),
+        }
+    }
+
+    // The whole ledger as JSON, money in cents
+    pub fn to_json(&self) -> String {
+        // Nothing in the ledger can fail to serialize
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+// One CSV row, the columns it doesn't fill left empty
+fn csv_row(csv: &mut String, fields: &[String]) {
+    const COLUMNS: usize = 13;
+    let mut row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+    row.resize(COLUMNS, String::new());
+    csv.push_str(&row.join(","));
+    csv.push('\n');
+}
+
+// A field quoted if it needs to be - a hop bet's name has a comma in it
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// A bet as the code knows it, like Buy(6) - the same in every language
+fn bet_text(kind: BetKind) -> String {
+    format!("{kind:?}")
+}
+
+// Cents as dollars a spreadsheet reads as a number: 1250 is 12.50, -500 is -5.00
+fn dollars(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    let cents = cents.unsigned_abs();
+    format!("{sign}{}.{:02}", cents / 100, cents % 100)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_field_quotes_only_when_it_has_to() {
+        assert_eq!(csv_field("Field"), "Field");
+        assert_eq!(csv_field("Hop(2, 5)"), "\"Hop(2, 5)\"");
+        assert_eq!(csv_field("say \"seven\""), "\"say \"\"seven\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn csv_keeps_a_hop_in_one_column() {
+        let mut ledger = SessionLedger::new(100_000);
+        let hop = Bet::new(BetKind::Hop(2, 5), 500);
+        ledger.note_move(ChipMove::Placed(hop));
+        ledger.record(
+            &[2, 5],
+            Some(RollOutcome::Natural(7)),
+            vec![Decision {
+                bet: hop,
+                result: BetResult::Win(7_500),
+                returned: 8_000,
+            }],
+            107_500,
+        );
+        let csv = ledger.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[1], "1,placed,,,,,,\"Hop(2, 5)\",5.00,,,,");
+        assert_eq!(lines[2], "1,rolled,2,5,,7,Natural(7),,,,,75.00,1075.00");
+        assert_eq!(lines[3], "1,decided,,,,,,\"Hop(2, 5)\",5.00,win,80.00,,");
+    }
+
+    #[test]
+    fn sic_bo_throw_fills_all_three_dice() {
+        let mut ledger = SessionLedger::new(100_000);
+        let line = ledger.record(&[4, 4, 6], None, Vec::new(), 100_000);
+        assert_eq!(line.total, 14);
+        let csv = ledger.to_csv();
+        assert_eq!(
+            csv.lines().nth(1),
+            Some("1,rolled,4,4,6,14,,,,,,0.00,1000.00")
+        );
+    }
+}
@@ -0,0 +1,190 @@
+// The craps game as a library. `CrapsGamePlugin` adds the whole table - dice,
+// betting, camera and UI - to any Bevy app, so the game can run on its own (see
+// main.rs) or be dropped into another game as a minigame. The components, events
+// and resources other apps are likely to want are gathered in `prelude`.
+//
+// The game opens on its own main menu (see `AppState`). A host game that wants to go
+// straight to the table can set `NextState<AppState>` to `AppState::Loading`.
+
+// Bevy systems ask for everything they use as parameters, so long parameter lists are normal
+#![allow(clippy::too_many_arguments)]
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*; // Physics engine - makes things fall, bounce, and collide realistically
+
+pub mod achievements; // Milestones the player unlocks and keeps
+pub mod announce; // Each roll put into words, for players who can't see the table
+pub mod betting; // Bets on the layout and how each roll decides them
+pub mod companions; // The computer players who bet alongside a solo player
+pub mod console; // The debug console's commands (see plugins/console.rs)
+pub mod daily; // The daily challenge: the same seeded dice for everyone, once a day
+pub mod dice_set; // Setting the dice: which faces the shooter picks them up showing
+pub mod keyframes; // Animation clips: poses at moments in time, eased between
+pub mod layout; // The printed layout: bet zones and where each bet's chips sit
+pub mod leaderboard; // The player's best sessions, kept between launches
+pub mod ledger; // Every roll of the session and what it did to the money, for the stats and exports
+pub mod locale; // The words on screen in the player's language
+pub mod net; // The networked table's messages, and the connection that carries them
+pub mod palette; // The colours of the chips and the layout, colour-blind palettes included
+pub mod payout; // The bankroll and all the money math
+pub mod plugins; // The Bevy side of the game, one plugin per part of the table
+pub mod props; // The field, horn, C&E and hops, each written as a BetRule
+pub mod registry; // Bets added from outside the engine, each a BetRule with a side bet box
+pub mod ron_asset; // Loading game data (dice skins and so on) from RON files
+pub mod rules; // The craps rules: come-out, point, seven-out
+pub mod session; // Recording a whole session so it can be replayed
+pub mod sicbo; // Sic bo: the three-dice game, and how each of its bets is decided
+pub mod side_bets; // Side bets written as scripts by players, read from the save folder
+pub mod stats; // Counting rolls, hands and winnings over a session
+pub mod storage; // Saving small files between sessions - on disk, or in the browser's local storage
+pub mod tutor; // What the bet tutor says about each bet, house edges included
+
+// How many times a second the physics steps. It's a fixed step whatever the frame rate,
+// so the same throw lands the same way on every machine - which replays, a networked
+// table and a shared seed all rely on.
+pub const PHYSICS_HZ: f64 = 60.0;
+
+use plugins::{
+    AchievementsPlugin, AnnouncePlugin, BettingPlugin, CameraPlugin, CelebrationPlugin,
+    CompanionsPlugin, ControlsPlugin, DailyPlugin, DicePlugin, HotseatPlugin, LeaderboardPlugin,
+    LocalePlugin, MenuPlugin, NetPlugin, PressPlugin, QuickBetsPlugin, RailPlugin, ReplayPlugin,
+    RoomPlugin, RulesPlugin, SavePlugin, SessionPlugin, SettingsPlugin, ShooterPlugin, SicBoPlugin,
+    SideBetsPlugin, SoundPlugin, StatsPlugin, StickmanPlugin, StreakPlugin, TablePlugin,
+    TooltipPlugin, TutorPlugin, UiPlugin, UndoPlugin,
+};
+
+// Everything an embedding app needs in one `use bevy_craps::prelude::*;`
+pub mod prelude {
+    pub use crate::CrapsGamePlugin;
+    pub use crate::achievements::{Achievement, Achievements};
+    pub use crate::betting::{Bet, BetKind, BetResult, TableBets};
+    pub use crate::companions::{Companion, Personality, Reaction};
+    pub use crate::daily::{DailyChallenge, DailyScore, DailyScores};
+    pub use crate::dice_set::DiceSet;
+    pub use crate::layout::TableLayout;
+    pub use crate::leaderboard::{Board, Leaderboard, SessionRecord};
+    pub use crate::ledger::{ExportFormat, SessionLedger};
+    pub use crate::locale::Locale;
+    pub use crate::payout::{Bankroll, Cents, format_money};
+    pub use crate::plugins::announce::Announcement;
+    pub use crate::plugins::betting::{
+        BetPlaced, BetRejected, BetResolved, BetTakenDown, BetUndone, ChipStack, RollResolved,
+        SelectedChip, TakeDownRefused,
+    };
+    pub use crate::plugins::camera::{CameraSettings, PlayerCamera};
+    pub use crate::plugins::controls::{Action, Binding, InputMap, PlayerInput};
+    pub use crate::plugins::dice::{
+        Dice, DiceAuthority, DiceConfig, DiceId, DiceSettled, DiceShape, DiceSkin, DiceSkinLibrary,
+        DiceValue, ForcedFace, HitBackWall, NoRoll, NoRollReason, PowerCurve, PowerMode,
+        RollSettled, ThrowConfig, ThrowPower, ThrowRng, ThrowTarget,
+    };
+    pub use crate::plugins::hotseat::{DicePassed, Hotseat, Player, PlayerTable};
+    pub use crate::plugins::lifecycle::{DiceLaunched, PhaseChanged, ThrowStarted};
+    pub use crate::plugins::locale::Localized;
+    pub use crate::plugins::menu::LoadingAssets;
+    pub use crate::plugins::net::{NetClient, NetHost, TableSeats};
+    pub use crate::plugins::replay::{InstantReplay, ReplayRequested};
+    pub use crate::plugins::rules::RulesChoice;
+    pub use crate::plugins::save::LifetimeStats;
+    pub use crate::plugins::session::SessionPlayback;
+    pub use crate::plugins::settings::{GraphicsQuality, Settings, SettingsScreen};
+    pub use crate::plugins::sicbo::SicBoResolved;
+    pub use crate::plugins::side_bets::SideBetRolls;
+    pub use crate::plugins::sound::{AudioSettings, SoundChannel};
+    pub use crate::plugins::stickman::{StickmanCall, StickmanManifest};
+    pub use crate::plugins::table::{
+        BackWall, PointPuck, TableChoice, TableConfig, TableSize, Wall,
+    };
+    pub use crate::plugins::{AppState, InGame, RollStep};
+    pub use crate::registry::{BetRegistry, BetRule, SideRoll};
+    pub use crate::rules::{
+        Heat, OddsLimit, OddsPreset, RollMode, RollOutcome, RoundPhase, ShooterRotation,
+        ShooterStreak, ShortRollPolicy, TableRules, Variant,
+    };
+    pub use crate::session::SessionLog;
+    pub use crate::sicbo::SicBoBet;
+    pub use crate::stats::SessionStats;
+}
+
+// The whole craps table in one plugin
+pub struct CrapsGamePlugin;
+
+impl Plugin for CrapsGamePlugin {
+    fn build(&self, app: &mut App) {
+        // The dice need physics. A host game that already runs Rapier keeps its own setup.
+        if !app.is_plugin_added::<RapierPhysicsPlugin<NoUserData>>() {
+            // Step the physics in FixedPostUpdate, by exactly one PHYSICS_HZ tick each time
+            app.add_plugins(RapierPhysicsPlugin::<NoUserData>::default().in_fixed_schedule())
+                .insert_resource(Time::<Fixed>::from_hz(PHYSICS_HZ))
+                .insert_resource(TimestepMode::Fixed {
+                    dt: (1.0 / PHYSICS_HZ) as f32,
+                    substeps: 1,
+                });
+        }
+        app.add_plugins((
+            // A tuple of plugins can only be so long, so some are grouped
+            (
+                MenuPlugin,     // The main menu, the loading screen and pausing
+                SettingsPlugin, // The player's preferences and the settings screen
+                LocalePlugin,   // Every word on screen in the player's language
+            ),
+            (
+                RulesPlugin, // The house rules, from a file in assets/rules/
+                TablePlugin, // The felt, the printed layout, the walls and the puck
+                RailPlugin,  // The player's rail and the chips in its rack
+                RoomPlugin,  // The casino round the table
+            ),
+            CameraPlugin,   // Our eyes on the table
+            ControlsPlugin, // Keyboard, mouse and gamepad, read in one place
+            (
+                DicePlugin,    // Throwing and reading the dice
+                ShooterPlugin, // The dice cup they're shaken in and thrown from
+            ),
+            (
+                BettingPlugin,   // The round, the bankroll and the bets
+                PressPlugin,     // Letting a winning bet ride
+                QuickBetsPlugin, // The same bets again in one click
+                SicBoPlugin,     // Paying the bets of a sic bo table instead
+                SideBetsPlugin,  // Side bets written as scripts
+                UndoPlugin,      // Taking back a mis-click
+            ),
+            UiPlugin,       // Everything drawn on the screen
+            SoundPlugin,    // The dice knocking about
+            StickmanPlugin, // Calling out each roll
+            ReplayPlugin,   // Slow-motion instant replay of the last roll
+            // Keeping the game between launches
+            (
+                SavePlugin,        // The bankroll and settings
+                SessionPlugin,     // Recording the session, or playing an old one back
+                DailyPlugin,       // The daily challenge and its scores
+                LeaderboardPlugin, // The best sessions
+            ),
+            // Helping the player read the table
+            (
+                StatsPlugin,    // The session statistics panel
+                TutorPlugin,    // Good bets lit up, and every bet explained
+                TooltipPlugin,  // What the bet under the pointer pays
+                AnnouncePlugin, // Every roll in words, for screen readers
+            ),
+            // The table reacting to the big moments
+            (
+                StreakPlugin,       // Glow, crowd noise and a banner for a hot shooter
+                CelebrationPlugin,  // Confetti over the winners when the point is made
+                AchievementsPlugin, // Toasts for milestones, kept between launches
+            ),
+            // Everyone else at the table
+            (
+                NetPlugin,        // Hosting or joining a table over the network
+                HotseatPlugin,    // Several players taking turns at one machine
+                CompanionsPlugin, // Computer players betting beside a solo player
+            ),
+        ));
+        #[cfg(feature = "dev")]
+        app.add_plugins((
+            plugins::DevPlugin,     // The inspector and debug panel
+            plugins::ConsolePlugin, // Backtick opens the debug console
+        ));
+        #[cfg(feature = "vr")]
+        app.add_plugins(plugins::VrPlugin); // Throwing and betting with a VR controller
+    }
+}
@@ -0,0 +1,78 @@
+// The words on the screen in the player's language. The game is written in English,
+// and each other language is a file in assets/lang/ that pairs the English text with
+// its translation:
+//
+//     strings: { "Play": "Jugar", "Bankroll {money}": "Saldo {money}" }
+//
+// The English text is its own key, so anything a file leaves out (or a new line of
+// text nobody has translated yet) is simply shown in English. Text with numbers or
+// money in it has named holes in braces, which `fill` puts the values into after
+// translating - so a translation can move them around the sentence.
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+// The languages the settings screen offers, as (code, name in that language). English
+// is built in; every other code has its file at assets/lang/<code>.lang.ron.
+pub const LANGUAGES: [(&str, &str); 2] = [("en", "English"), ("es", "Español")];
+
+pub const ENGLISH: &str = "en";
+
+// One language file, as it's written in assets/lang/
+#[derive(Asset, TypePath, Debug, Clone, Default, Deserialize)]
+pub struct LanguageFile {
+    // A font with the letters the language needs (a path inside assets/), if the
+    // built-in one doesn't have them
+    #[serde(default)]
+    pub font: Option<String>,
+    pub strings: HashMap<String, String>,
+}
+
+// The language the screen is in right now
+#[derive(Resource, Debug, Clone, Default)]
+pub struct Locale {
+    pub language: String, // One of the codes in LANGUAGES - empty means English
+    strings: HashMap<String, String>,
+}
+
+impl Locale {
+    pub fn new(language: &str, file: &LanguageFile) -> Self {
+        Self {
+            language: language.to_string(),
+            strings: file.strings.clone(),
+        }
+    }
+
+    // The English text in this language, or the English itself if it isn't translated
+    pub fn tr<'a>(&'a self, english: &'a str) -> &'a str {
+        self.strings.get(english).map_or(english, String::as_str)
+    }
+
+    // Translate text with holes in it, then fill each `{name}` hole with its value
+    pub fn fill(&self, english: &str, holes: &[(&str, &dyn Display)]) -> String {
+        let mut text = self.tr(english).to_string();
+        for (name, value) in holes {
+            text = text.replace(&format!("{{{name}}}"), &value.to_string());
+        }
+        text
+    }
+}
+
+// A language's name, for the settings screen
+pub fn language_name(code: &str) -> &'static str {
+    LANGUAGES
+        .iter()
+        .find(|(known, _)| *known == code)
+        .map_or(LANGUAGES[0].1, |(_, name)| name)
+}
+
+// The language after this one in LANGUAGES, going back round to the first
+pub fn next_language(code: &str) -> &'static str {
+    let at = LANGUAGES
+        .iter()
+        .position(|(known, _)| *known == code)
+        .unwrap_or(0);
+    LANGUAGES[(at + 1) % LANGUAGES.len()].0
+}
@@ -0,0 +1,275 @@
+// The networked table's messages and the connection that carries them. One player
+// hosts: their game runs the dice, the rules and everyone's money. Everyone else joins
+// over TCP, sends the host their bets and (on their turn) their throws, and is told
+// what happened. Messages are RON, one per line, so a connection can be watched with
+// any text tool while debugging.
+//
+// Someone can also join just to watch: they're sent everything a player is - the dice,
+// the rolls, everyone's bets - but have no money and no turn with the dice. A watcher
+// can ask for a seat, and waits in line for one if the table is full; a player joining
+// a full table waits in the same line.
+use std::io::{ErrorKind, Read, Write};
+use std::net::TcpStream;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::betting::{Bet, BetKind, TableBets};
+use crate::dice_set::DiceSet;
+use crate::payout::Cents;
+use crate::rules::{RoundPhase, TableRules};
+
+// Which player at the table. The host is always player 0.
+pub type PlayerId = u32;
+pub const HOST_ID: PlayerId = 0;
+
+pub const DEFAULT_PORT: u16 = 7777;
+
+// Sent by a joining player to the host
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ClientMessage {
+    Join {
+        name: String,
+    },
+    // Join to watch rather than to play
+    Watch {
+        name: String,
+    },
+    // A watcher asking to sit down at the next free seat
+    TakeSeat,
+    // ...and changing their mind
+    LeaveQueue,
+    PlaceBet {
+        kind: BetKind,
+        amount: Cents,
+    },
+    // Take this bet (and any odds behind it) back off the layout
+    TakeDownBet {
+        kind: BetKind,
+    },
+    // Take back chips put down since the last roll
+    UndoBet {
+        kind: BetKind,
+        amount: Cents,
+    },
+    // The shooter let go of the throw button: how hard, from where the camera was
+    // (position and rotation) and at which spot on the felt. A VR shooter's throw comes
+    // from their hand instead, with the hand's speed as it let go. A flicked throw
+    // carries how far across the flick swept, and every throw how the dice were set.
+    Throw {
+        power: f32,
+        from: ([f32; 3], [f32; 4]),
+        target: Option<[f32; 2]>,
+        #[serde(default)] // A player on an older version never throws by hand
+        hand_velocity: Option<[f32; 3]>,
+        #[serde(default)] // ...or flicks
+        twist: f32,
+        #[serde(default)] // ...or sets the dice
+        set: DiceSet,
+    },
+}
+
+// Sent by the host to the players who joined
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum HostMessage {
+    // Your seat number, and the table as it stands - with the last few rolls, so
+    // someone who has just sat down can see how the dice are running
+    Welcome {
+        id: PlayerId,
+        rules: TableRules,
+        phase: RoundPhase,
+        #[serde(default)] // A host on an older version doesn't keep them
+        rolls: Vec<[u8; 2]>,
+    },
+    // Everyone at the table in shooting order, whose turn it is to throw, and who's
+    // watching
+    Table {
+        seats: Vec<Seat>,
+        shooter: PlayerId,
+        #[serde(default)]
+        watchers: Vec<Watcher>,
+    },
+    // Where die #1 and die #2 were (position and rotation) at `at` seconds on the host's
+    // clock, sent a few times a second while they roll. `shown` is false once they've
+    // been taken off the table after a no roll.
+    Dice {
+        at: f32,
+        poses: Vec<DicePose>,
+        shown: bool,
+    },
+    // The dice stopped and the roll counts
+    Rolled {
+        die_values: [u8; 2],
+        short_roll: bool,
+    },
+    // The host's record of your money - always right, so replace your own with it
+    Money {
+        balance: Cents,
+        bets: TableBets,
+    },
+    BetRefused {
+        kind: BetKind,
+        reason: String,
+    },
+}
+
+// One player at the table, as everyone else sees them: their money, the bets they
+// have on the layout (drawn in their seat's colour) and what the last roll did for them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Seat {
+    pub id: PlayerId,
+    pub name: String,
+    pub balance: Cents,
+    #[serde(default)] // A host on an older version doesn't say
+    pub bets: Vec<Bet>,
+    #[serde(default)]
+    pub last_roll: i64, // Won (or, when negative, lost) on the last roll
+    #[serde(default)]
+    pub color: usize, // Which of the players' colours is theirs
+}
+
+// Someone watching the table, and whether they're waiting for a seat. The ones
+// waiting come first, in the order they'll sit down.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Watcher {
+    pub id: PlayerId,
+    pub name: String,
+    pub waiting: bool,
+}
+
+// A die's place, squeezed to whole numbers because it's sent so often: the position
+// in millimetres and each part of the rotation in ten-thousandths. Nobody can see the
+// difference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DicePose {
+    pub position: [i16; 3], // Up to 32 metres from the middle of the table - plenty
+    pub rotation: [i16; 4],
+}
+
+const ROTATION_SCALE: f32 = 10_000.0;
+
+impl DicePose {
+    pub fn new(translation: [f32; 3], rotation: [f32; 4]) -> Self {
+        // `as` turns a number too big for an i16 into the biggest one, not garbage
+        Self {
+            position: translation.map(|metres| (metres * 1000.0).round() as i16),
+            rotation: rotation.map(|part| (part * ROTATION_SCALE).round() as i16),
+        }
+    }
+
+    pub fn translation(&self) -> [f32; 3] {
+        self.position
+            .map(|millimetres| f32::from(millimetres) / 1000.0)
+    }
+
+    // Not quite of length one any more, so normalize it before use
+    pub fn rotation(&self) -> [f32; 4] {
+        self.rotation.map(|part| f32::from(part) / ROTATION_SCALE)
+    }
+}
+
+// How many of the last rolls everyone is shown
+pub const RECENT_ROLLS: usize = 12;
+
+#[derive(Debug, Error)]
+pub enum NetError {
+    #[error("connection problem: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("the other side hung up")]
+    Closed,
+    #[error("couldn't understand a message: {0}")]
+    BadMessage(#[from] ron::error::SpannedError),
+    #[error("couldn't write a message: {0}")]
+    Encode(#[from] ron::Error),
+    #[error("a message ran past {} bytes", MAX_LINE)]
+    TooLong,
+}
+
+// The longest line a message may take. The biggest messages (a welcome, or the seats
+// with everyone's bets) are a few kilobytes; a line still going well past that is
+// someone filling our memory, not a message, so the connection is dropped.
+pub const MAX_LINE: usize = 64 * 1024;
+
+// A TCP connection to one other player that never blocks the game: sending queues the
+// message and writes what it can, receiving hands back whatever whole messages have
+// arrived so far. A line that isn't a message we know (a garbled one, or one from
+// another version of the game) is handed back as its error in place of that one
+// message; the lines either side of it still count.
+pub struct Connection {
+    stream: TcpStream,
+    incoming: Vec<u8>, // Bytes read but not yet making up a whole line
+    outgoing: Vec<u8>, // Bytes waiting for room in the socket
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream) -> Result<Self, NetError> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?; // Dice positions are small and late ones are useless
+        Ok(Self {
+            stream,
+            incoming: Vec::new(),
+            outgoing: Vec::new(),
+        })
+    }
+
+    pub fn send<T: Serialize>(&mut self, message: &T) -> Result<(), NetError> {
+        // Compact RON never contains a raw newline, so a newline can end each message
+        let mut line = ron::to_string(message)?;
+        line.push('\n');
+        self.outgoing.extend_from_slice(line.as_bytes());
+        self.flush()
+    }
+
+    pub fn receive<T: DeserializeOwned>(&mut self) -> Result<Vec<Result<T, NetError>>, NetError> {
+        self.flush()?; // Anything that didn't fit last time
+        let mut buffer = [0u8; 4096];
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => return Err(NetError::Closed),
+                Ok(read) => {
+                    self.incoming.extend_from_slice(&buffer[..read]);
+                    // The bytes after the last newline are a line that hasn't ended yet
+                    let line_start = self
+                        .incoming
+                        .iter()
+                        .rposition(|&byte| byte == b'\n')
+                        .map_or(0, |end| end + 1);
+                    if self.incoming.len() - line_start > MAX_LINE {
+                        return Err(NetError::TooLong);
+                    }
+                }
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break, // That's all for now
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error.into()),
+            }
+        }
+
+        let mut messages = Vec::new();
+        while let Some(end) = self.incoming.iter().position(|&byte| byte == b'\n') {
+            if end > MAX_LINE {
+                return Err(NetError::TooLong); // It came in one read, ending and all
+            }
+            let line: Vec<u8> = self.incoming.drain(..=end).collect();
+            let text = String::from_utf8_lossy(&line[..end]);
+            messages.push(ron::from_str(&text).map_err(NetError::from));
+        }
+        Ok(messages)
+    }
+
+    // Write as much of the queue as the socket will take right now
+    fn flush(&mut self) -> Result<(), NetError> {
+        while !self.outgoing.is_empty() {
+            match self.stream.write(&self.outgoing) {
+                Ok(0) => return Err(NetError::Closed),
+                Ok(written) => {
+                    self.outgoing.drain(..written);
+                }
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error.into()),
+            }
+        }
+        Ok(())
+    }
+}
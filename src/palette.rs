@@ -0,0 +1,242 @@
+// The colours the chips, the printed layout and the power meter are drawn in. The
+// standard chips tell their values apart by red, green and orange, which a player with
+// colour blindness can't always do, so the settings screen offers palettes made for the
+// three common kinds. Each one keeps its colours apart by how light or dark they are as
+// well as by hue, using the blues and yellows (or reds and teals, for tritanopia) that
+// player still sees clearly.
+//
+// Chips can also have their value printed round the edge, the way real chips do. The
+// numbers are drawn here with a tiny built-in pixel font, as rows of pixels the chips
+// plugin turns into a texture. The lettering on the layout ("PASS LINE", the number
+// boxes) is drawn in the same font.
+use serde::{Deserialize, Serialize};
+
+use crate::payout::Cents;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Palette {
+    #[default]
+    Standard,
+    Deuteranopia, // Greens are hard to tell from reds (the most common kind)
+    Protanopia,   // Reds are hard to tell from greens, and look darker
+    Tritanopia,   // Blues are hard to tell from greens, and yellows from pinks
+}
+
+impl Palette {
+    pub fn next(self) -> Self {
+        match self {
+            Palette::Standard => Palette::Deuteranopia,
+            Palette::Deuteranopia => Palette::Protanopia,
+            Palette::Protanopia => Palette::Tritanopia,
+            Palette::Tritanopia => Palette::Standard,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Palette::Standard => "Standard",
+            Palette::Deuteranopia => "Deuteranopia (red-green)",
+            Palette::Protanopia => "Protanopia (red-green)",
+            Palette::Tritanopia => "Tritanopia (blue-yellow)",
+        }
+    }
+
+    // Hex colours of the chips, one per entry in CHIP_DENOMINATIONS (biggest first)
+    pub fn chip_colors(self) -> [&'static str; 6] {
+        match self {
+            Palette::Standard => [
+                "#F28C28", // $1000 - orange
+                "#7B3FA0", // $500 - purple
+                "#1A1A1A", // $100 - black
+                "#1E8C3A", // $25 - green
+                "#C8102E", // $5 - red
+                "#F2F2F2", // $1 - white
+            ],
+            Palette::Deuteranopia => [
+                "#E69F00", // $1000 - orange
+                "#0072B2", // $500 - blue
+                "#1A1A1A", // $100 - black
+                "#56B4E9", // $25 - sky blue
+                "#F0E442", // $5 - yellow
+                "#F2F2F2", // $1 - white
+            ],
+            Palette::Protanopia => [
+                "#F0E442", // $1000 - yellow
+                "#004488", // $500 - dark blue
+                "#1A1A1A", // $100 - black
+                "#88CCEE", // $25 - light blue
+                "#997700", // $5 - dark gold
+                "#F2F2F2", // $1 - white
+            ],
+            Palette::Tritanopia => [
+                "#EE99AA", // $1000 - pink
+                "#882255", // $500 - wine
+                "#1A1A1A", // $100 - black
+                "#009988", // $25 - teal
+                "#CC3311", // $5 - red
+                "#F2F2F2", // $1 - white
+            ],
+        }
+    }
+
+    // The outlines printed round the bet zones. None keeps the table file's own colour.
+    pub fn layout_lines(self) -> Option<&'static str> {
+        match self {
+            Palette::Standard => None,
+            Palette::Deuteranopia | Palette::Protanopia => Some("#F0E442"), // Yellow
+            Palette::Tritanopia => Some("#F2F2F2"),                         // White
+        }
+    }
+
+    // The fills inside the bet zones: line bets and come, the field, the number boxes,
+    // the don't strips above them, the props and the hops
+    pub fn layout_fills(self) -> [&'static str; 6] {
+        match self {
+            Palette::Standard => [
+                "#111111", // Line bets and come
+                "#2A1A05", // Field - a dark amber
+                "#161616", // Number boxes
+                "#0E0E0E", // Don't strips above the numbers
+                "#2A0808", // Props - a dark red
+                "#101820", // Hops - a dark blue
+            ],
+            // The field stays warm and the props go blue, so the two never look alike
+            Palette::Deuteranopia | Palette::Protanopia => [
+                "#111111", // Line bets and come
+                "#2E2A05", // Field - a dark yellow
+                "#161616", // Number boxes
+                "#0E0E0E", // Don't strips above the numbers
+                "#05203A", // Props - a dark blue
+                "#262626", // Hops - grey
+            ],
+            Palette::Tritanopia => [
+                "#111111", // Line bets and come
+                "#0A2A28", // Field - a dark teal
+                "#161616", // Number boxes
+                "#0E0E0E", // Don't strips above the numbers
+                "#2A0808", // Props - a dark red
+                "#262626", // Hops - grey
+            ],
+        }
+    }
+
+    // The bar that fills as a throw charges up
+    pub fn meter_color(self) -> &'static str {
+        match self {
+            Palette::Standard | Palette::Tritanopia => "#00CC00", // Bright green
+            Palette::Deuteranopia | Palette::Protanopia => "#56B4E9", // Sky blue
+        }
+    }
+}
+
+// What's printed on a chip's edge: "5" for $5, "1K" for $1000
+pub fn chip_label(value: Cents) -> String {
+    let dollars = value / 100;
+    if dollars >= 1000 && dollars % 1000 == 0 {
+        format!("{}K", dollars / 1000)
+    } else {
+        dollars.to_string()
+    }
+}
+
+// How many pixels tall the edge print is: the letters and a pixel above and below
+pub const EDGE_ROWS: usize = 7;
+
+// The letters of the pixel font, three pixels wide and five tall. Each row is three
+// bits, the leftmost pixel first. It has the digits, the capital letters and the bits
+// of punctuation the layout needs.
+fn glyph(letter: char) -> [u8; 5] {
+    match letter {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b011, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010], // Rounder than a zero
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '\'' => [0b010, 0b010, 0b000, 0b000, 0b000],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '&' => [0b010, 0b101, 0b010, 0b101, 0b011],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        _ => [0; 5], // Anything else (a space, say) is left blank
+    }
+}
+
+// The edge print for a chip: the label over and over, evenly spaced, along a strip
+// `width` pixels long that wraps round the chip. It comes back as RGBA pixels, row by
+// row from the top - white where there's ink and clear everywhere else, so the chip's
+// own colour shows through and the ink can be tinted to stand out against it.
+pub fn edge_pixels(label: &str, width: usize) -> Vec<u8> {
+    let mut pixels = vec![0; width * EDGE_ROWS * 4];
+    let letters: Vec<[u8; 5]> = label.chars().map(glyph).collect();
+    let label_width = letters.len() * 4 - 1; // A pixel between letters
+    let repeats = (width / (label_width + 4)).max(1); // At least four pixels between labels
+    for repeat in 0..repeats {
+        let start = repeat * width / repeats;
+        for (index, rows) in letters.iter().enumerate() {
+            for (row, bits) in rows.iter().enumerate() {
+                for column in 0..3 {
+                    if bits & (0b100 >> column) == 0 {
+                        continue;
+                    }
+                    let x = (start + index * 4 + column) % width;
+                    let at = ((row + 1) * width + x) * 4; // Under the blank top row
+                    pixels[at..at + 4].copy_from_slice(&[255, 255, 255, 255]);
+                }
+            }
+        }
+    }
+    pixels
+}
+
+// The lettering for a bet spot, in the same font: the text along one line with a
+// clear pixel all round it. It comes back as its width and height in pixels and the
+// RGBA pixels row by row from the top, white ink on clear like the edge print.
+pub fn lettering_pixels(text: &str) -> (usize, usize, Vec<u8>) {
+    let letters: Vec<[u8; 5]> = text.chars().map(glyph).collect();
+    let width = (letters.len() * 4).max(1) + 1; // A pixel between letters and at each end
+    let height = 5 + 2;
+    let mut pixels = vec![0; width * height * 4];
+    for (index, rows) in letters.iter().enumerate() {
+        for (row, bits) in rows.iter().enumerate() {
+            for column in 0..3 {
+                if bits & (0b100 >> column) == 0 {
+                    continue;
+                }
+                let x = 1 + index * 4 + column;
+                let at = ((row + 1) * width + x) * 4;
+                pixels[at..at + 4].copy_from_slice(&[255, 255, 255, 255]);
+            }
+        }
+    }
+    (width, height, pixels)
+}
@@ -0,0 +1,183 @@
+// The money side of the table - the player's bankroll and every bit of payout
+// arithmetic. Everything here is plain functions and data with no rendering, so the
+// math can be checked on its own.
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::betting::{Bet, BetResult};
+
+// Money is counted in cents so payouts never lose a fraction to rounding errors
+pub type Cents = u64;
+
+// Turn cents into a readable amount, like 1250 -> "$12.50"
+pub fn format_money(cents: Cents) -> String {
+    format!("${}.{:02}", cents / 100, cents % 100)
+}
+
+// A win or a loss, always with its sign: 1250 -> "+$12.50", -500 -> "-$5.00"
+pub fn format_signed_money(net: i64) -> String {
+    let sign = if net < 0 { "-" } else { "+" };
+    format!("{sign}{}", format_money(net.unsigned_abs()))
+}
+
+// Read an amount of money someone typed, like "500", "$12.50" or "12.5". None if it
+// isn't one (or has fractions of a cent).
+pub fn parse_money(text: &str) -> Option<Cents> {
+    let text = text.strip_prefix('$').unwrap_or(text);
+    let (dollars, fraction) = text.split_once('.').unwrap_or((text, "0"));
+    let cents: Cents = match fraction.len() {
+        1 => fraction.parse::<Cents>().ok()? * 10,
+        2 => fraction.parse().ok()?,
+        _ => return None,
+    };
+    dollars
+        .parse::<Cents>()
+        .ok()?
+        .checked_mul(100)?
+        .checked_add(cents)
+}
+
+// Pay a bet at "pays:for" odds, e.g. 6:5 on $10 is $12 profit.
+// Fractions of a cent are rounded down, the same way a casino pays.
+pub fn pay_at_odds(amount: Cents, (pays, per): (u64, u64)) -> Cents {
+    amount * pays / per
+}
+
+// The commission on a buy or lay bet: `percent` of `basis`, rounded down to whole
+// dollars but never less than $1 - the way a casino collects it in chips. A table
+// that charges no commission (or a bet with nothing to charge it on) owes nothing.
+pub fn commission(basis: Cents, percent: u64) -> Cents {
+    match basis * percent / 100 {
+        0 => 0,
+        exact => (exact / 100 * 100).max(100),
+    }
+}
+
+// Chip values, biggest first: $1000, $500, $100, $25, $5 and $1
+pub const CHIP_DENOMINATIONS: [Cents; 6] = [100_000, 50_000, 10_000, 2_500, 500, 100];
+
+// Break an amount into the fewest chips, biggest first - $35 is a $25 and two $5s.
+// Loose cents that no chip can show are left out.
+pub fn break_into_chips(mut amount: Cents) -> Vec<Cents> {
+    let mut chips = Vec::new();
+    for denomination in CHIP_DENOMINATIONS {
+        while amount >= denomination {
+            chips.push(denomination);
+            amount -= denomination;
+        }
+    }
+    chips
+}
+
+// How many chips a player keeps of each kind before changing the rest up: enough small
+// chips to bet with, the way a player colours up only what they won't be betting
+pub const WORKING_CHIPS: usize = 10;
+
+// The chips in the player's rack for a bankroll, as (chip, how many) biggest first,
+// leaving out any they have none of. The smallest chips are put by first, up to
+// WORKING_CHIPS of each, and whatever is left is broken into the fewest chips - $1000
+// is ten $1s, ten $5s, thirteen $25s and six $100s rather than one $1000 chip.
+pub fn rack_chips(balance: Cents) -> Vec<(Cents, usize)> {
+    let mut counts = [0usize; CHIP_DENOMINATIONS.len()];
+    let mut left = balance;
+    for (count, &value) in counts.iter_mut().zip(&CHIP_DENOMINATIONS).rev() {
+        *count = WORKING_CHIPS.min((left / value) as usize);
+        left -= value * *count as Cents;
+    }
+    for chip in break_into_chips(left) {
+        if let Some(index) = CHIP_DENOMINATIONS.iter().position(|&value| value == chip) {
+            counts[index] += 1;
+        }
+    }
+    CHIP_DENOMINATIONS
+        .into_iter()
+        .zip(counts)
+        .filter(|&(_, count)| count > 0)
+        .collect()
+}
+
+// How much comes back to the player when a bet is decided. Bets are paid for when
+// they are placed, so a win returns the bet plus its profit and a push returns
+// just the bet.
+pub fn returned_to_player(bet: &Bet, result: BetResult) -> Cents {
+    match result {
+        BetResult::Win(profit) => bet.amount + profit,
+        BetResult::Push => bet.amount,
+        BetResult::Lose | BetResult::Stay | BetResult::Travel(_) => 0,
+    }
+}
+
+// What a roll's decisions came to for a player: what came back, less what was riding
+// on the bets. A bet that stays up or travels to its number hasn't been decided yet.
+pub fn roll_net(decisions: &[(Bet, BetResult)]) -> i64 {
+    decisions
+        .iter()
+        .filter(|(_, result)| !matches!(result, BetResult::Stay | BetResult::Travel(_)))
+        .map(|(bet, result)| returned_to_player(bet, *result) as i64 - bet.amount as i64)
+        .sum()
+}
+
+// The player's money that isn't on the table
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+pub struct Bankroll {
+    pub balance: Cents,
+}
+
+impl Default for Bankroll {
+    fn default() -> Self {
+        Self {
+            balance: 100_000, // Everyone sits down with $1000
+        }
+    }
+}
+
+impl Bankroll {
+    // Take money out of the bankroll. Fails (and takes nothing) if it isn't there.
+    pub fn debit(&mut self, amount: Cents) -> Result<(), Cents> {
+        match self.balance.checked_sub(amount) {
+            Some(rest) => {
+                self.balance = rest;
+                Ok(())
+            }
+            None => Err(self.balance),
+        }
+    }
+
+    pub fn credit(&mut self, amount: Cents) {
+        self.balance += amount;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pay_at_odds_rounds_down_to_the_cent() {
+        assert_eq!(pay_at_odds(500, (6, 5)), 600);
+        assert_eq!(pay_at_odds(700, (6, 5)), 840);
+        assert_eq!(pay_at_odds(500, (3, 2)), 750);
+        assert_eq!(pay_at_odds(1_000, (2, 1)), 2_000);
+        // $5.01 at 6:5 is $6.012, and the fraction of a cent is dropped
+        assert_eq!(pay_at_odds(501, (6, 5)), 601);
+        assert_eq!(pay_at_odds(1, (3, 2)), 1);
+    }
+
+    #[test]
+    fn commission_is_whole_dollars_and_at_least_one() {
+        assert_eq!(commission(2_000, 5), 100);
+        assert_eq!(commission(100_000, 5), 5_000);
+        // 5% of $50 is $2.50, collected as $2
+        assert_eq!(commission(5_000, 5), 200);
+        // 5% of $10 is 50 cents, but the smallest chip is $1
+        assert_eq!(commission(1_000, 5), 100);
+        assert_eq!(commission(100, 5), 100);
+    }
+
+    #[test]
+    fn no_commission_is_charged_at_zero_percent() {
+        assert_eq!(commission(2_000, 0), 0);
+        assert_eq!(commission(100_000, 0), 0);
+        assert_eq!(commission(0, 5), 0);
+    }
+}
@@ -0,0 +1,343 @@
+// Achievements (see achievements.rs for what they are and how they're earned). Each
+// judged roll is checked once it has been paid; anything it unlocks pops up as a toast
+// at the top of the screen, is announced for screen readers, and is saved straight
+// away. The main menu's Achievements button lists them all, unlocked or not.
+//
+// A replayed session's rolls were played long ago, so they don't count.
+use bevy::prelude::*;
+
+use super::announce::Announcement;
+use super::betting::{BetResolved, RollResolved};
+use super::locale::Localized;
+use super::session::SessionPlayback;
+use super::{AppState, InGame, RollStep};
+use crate::achievements::{Achievement, Achievements};
+use crate::betting::{BetResult, TableBets};
+use crate::locale::Locale;
+use crate::payout::{Bankroll, Cents};
+use crate::storage;
+
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(load_achievements())
+            .init_resource::<AchievementsScreen>()
+            .add_systems(OnEnter(AppState::MainMenu), setup_achievements_screen)
+            .add_systems(OnEnter(InGame), setup_toasts)
+            .add_systems(
+                Update,
+                (
+                    (
+                        achievements_system
+                            .after(RollStep::Pay) // Once the roll has been paid
+                            .run_if(not(resource_exists::<SessionPlayback>)),
+                        toast_system,
+                    )
+                        .run_if(in_state(AppState::Playing)),
+                    (achievements_screen_system, show_achievements_screen_system)
+                        .run_if(in_state(AppState::MainMenu)),
+                ),
+            );
+    }
+}
+
+// Whether the list of achievements is showing over the main menu
+#[derive(Resource, Debug, Default)]
+pub struct AchievementsScreen {
+    pub open: bool,
+}
+
+#[derive(Component)]
+struct AchievementsPanel;
+
+#[derive(Component)]
+struct AchievementsTitle; // "Achievements - 3 of 8"
+
+#[derive(Component)]
+struct CloseAchievementsButton;
+
+#[derive(Component)]
+struct ToastColumn; // Where the toasts stack up, newest at the bottom
+
+// An unlock being shown, cleared away once it's been up long enough
+#[derive(Component)]
+struct Toast {
+    age: f32,
+}
+
+const ACHIEVEMENTS_KEY: &str = "achievements"; // Where they're saved (see storage.rs)
+const TOAST_SECONDS: f32 = 4.0;
+const UNLOCKED_COLOR: Color = Color::WHITE;
+const LOCKED_COLOR: Color = Color::srgb(0.45, 0.45, 0.45);
+
+fn load_achievements() -> Achievements {
+    let Some(saved) = storage::load(ACHIEVEMENTS_KEY) else {
+        return Achievements::default(); // Nothing unlocked yet
+    };
+    ron::from_str(&saved).unwrap_or_else(|error| {
+        warn!("Couldn't read the saved achievements, starting afresh: {error}");
+        Achievements::default()
+    })
+}
+
+fn save_achievements(achievements: &Achievements) {
+    let saved = ron::ser::to_string_pretty(achievements, ron::ser::PrettyConfig::default())
+        .map_err(|error| error.to_string())
+        .and_then(|text| storage::save(ACHIEVEMENTS_KEY, &text).map_err(|error| error.to_string()));
+    if let Err(error) = saved {
+        warn!("Couldn't save the achievements: {error}");
+    }
+}
+
+fn setup_toasts(mut commands: Commands) {
+    commands.spawn((
+        StateScoped(InGame),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(90.0), // Under the bankroll and the buttons along the top
+            width: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(8.0),
+            ..default()
+        },
+        GlobalZIndex(1), // Over the other readouts
+        ToastColumn,
+    ));
+}
+
+// System that checks each roll for achievements, and pops up a toast for each unlock
+fn achievements_system(
+    mut commands: Commands,
+    mut resolved_events: EventReader<RollResolved>,
+    mut payout_events: EventReader<BetResolved>,
+    bankroll: Res<Bankroll>,
+    bets: Res<TableBets>,
+    locale: Res<Locale>,
+    mut achievements: ResMut<Achievements>,
+    mut announcements: EventWriter<Announcement>,
+    column_q: Query<Entity, With<ToastColumn>>,
+) {
+    let won: Cents = payout_events
+        .read()
+        .map(|payout| match payout.result {
+            BetResult::Win(profit) => profit,
+            _ => 0,
+        })
+        .sum();
+    let holding = bankroll.balance + bets.bets.iter().map(|bet| bet.amount).sum::<Cents>();
+    let mut unlocked = Vec::new();
+    for resolved in resolved_events.read() {
+        unlocked.extend(achievements.record(resolved.die_values, resolved.outcome, won, holding));
+    }
+    // The counts towards the longer ones change every roll, so they're saved too
+    if achievements.is_changed() {
+        save_achievements(&achievements);
+    }
+    for achievement in unlocked {
+        info!("Achievement unlocked: {}", achievement.name());
+        announcements.write(Announcement(locale.fill(
+            "Achievement unlocked: {name}. {description}.",
+            &[
+                ("name", &locale.tr(achievement.name())),
+                ("description", &locale.tr(achievement.description())),
+            ],
+        )));
+        let Ok(column) = column_q.single() else {
+            continue;
+        };
+        commands.entity(column).with_children(|column| {
+            column
+                .spawn((
+                    Node {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        padding: UiRect::axes(Val::Px(16.0), Val::Px(8.0)),
+                        border: UiRect::all(Val::Px(2.0)),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgba(0.05, 0.05, 0.05, 0.9)),
+                    BorderColor(Srgba::hex("#FF5300").unwrap().into()),
+                    BorderRadius::all(Val::Px(6.0)),
+                    Toast { age: 0.0 },
+                ))
+                .with_children(|toast| {
+                    toast.spawn((
+                        Localized("Achievement unlocked"),
+                        TextFont {
+                            font_size: 14.0,
+                            ..default()
+                        },
+                        TextColor(Srgba::hex("#FF5300").unwrap().into()),
+                    ));
+                    toast.spawn((
+                        Localized(achievement.name()),
+                        TextFont {
+                            font_size: 22.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                    ));
+                    toast.spawn((
+                        Localized(achievement.description()),
+                        TextFont {
+                            font_size: 14.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(0.8, 0.8, 0.8)),
+                    ));
+                });
+        });
+    }
+}
+
+// System that clears each toast away once it's been up long enough
+fn toast_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut toasts_q: Query<(Entity, &mut Toast)>,
+) {
+    for (entity, mut toast) in &mut toasts_q {
+        toast.age += time.delta_secs();
+        if toast.age > TOAST_SECONDS {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+// The list, built hidden with the main menu: every achievement, greyed out until it's
+// unlocked
+fn setup_achievements_screen(
+    mut commands: Commands,
+    achievements: Res<Achievements>,
+    mut screen: ResMut<AchievementsScreen>,
+) {
+    screen.open = false; // Coming back to the menu shows the menu
+    commands
+        .spawn((
+            StateScoped(AppState::MainMenu),
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(25.0),
+                top: Val::Percent(10.0),
+                width: Val::Percent(50.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(10.0),
+                padding: UiRect::all(Val::Px(16.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.05, 0.05, 0.05, 0.97)),
+            BorderRadius::all(Val::Px(8.0)),
+            GlobalZIndex(1), // Over the menu's buttons
+            Visibility::Hidden,
+            AchievementsPanel,
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                Text::new(""), // The count, filled in below
+                TextFont {
+                    font_size: 24.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                AchievementsTitle,
+            ));
+            for achievement in Achievement::ALL {
+                let color = if achievements.has(achievement) {
+                    UNLOCKED_COLOR
+                } else {
+                    LOCKED_COLOR
+                };
+                panel
+                    .spawn(Node {
+                        flex_direction: FlexDirection::Column,
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn((
+                            Localized(achievement.name()),
+                            TextFont {
+                                font_size: 18.0,
+                                ..default()
+                            },
+                            TextColor(color),
+                        ));
+                        row.spawn((
+                            Localized(achievement.description()),
+                            TextFont {
+                                font_size: 14.0,
+                                ..default()
+                            },
+                            TextColor(color),
+                        ));
+                    });
+            }
+            panel
+                .spawn((
+                    Button,
+                    Node {
+                        padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                        margin: UiRect::top(Val::Px(6.0)),
+                        align_self: AlignSelf::Start,
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.1, 0.1, 0.1)),
+                    BorderRadius::all(Val::Px(6.0)),
+                    CloseAchievementsButton,
+                ))
+                .with_children(|button| {
+                    button.spawn((
+                        Localized("Back"),
+                        TextFont {
+                            font_size: 16.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                    ));
+                });
+        });
+}
+
+// System that closes the list with its Back button, and keeps its title in the
+// player's language
+fn achievements_screen_system(
+    achievements: Res<Achievements>,
+    locale: Res<Locale>,
+    mut screen: ResMut<AchievementsScreen>,
+    button_q: Query<&Interaction, (Changed<Interaction>, With<CloseAchievementsButton>)>,
+    mut title_q: Query<&mut Text, With<AchievementsTitle>>,
+) {
+    if button_q
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed)
+    {
+        screen.open = false;
+    }
+    for mut title in &mut title_q {
+        if !locale.is_changed() && !title.is_added() {
+            continue;
+        }
+        title.0 = locale.fill(
+            "Achievements - {count} of {total}",
+            &[
+                ("count", &achievements.unlocked.len()),
+                ("total", &Achievement::ALL.len()),
+            ],
+        );
+    }
+}
+
+// System that shows the list while it's open
+fn show_achievements_screen_system(
+    screen: Res<AchievementsScreen>,
+    mut panel_q: Query<&mut Visibility, With<AchievementsPanel>>,
+) {
+    if let Ok(mut visibility) = panel_q.single_mut() {
+        visibility.set_if_neq(if screen.open {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
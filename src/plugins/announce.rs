@@ -0,0 +1,204 @@
+// Every roll told in words, for players who can't follow the game by looking at the
+// table. After each roll the dice, what they meant, what every bet did and where the
+// puck went are put together as one `Announcement` (see announce.rs for the wording),
+// which goes to:
+//
+// - a live region in the accessibility tree, which screen readers read out as it
+//   changes
+// - a caption along the bottom of the screen, with Captions on in the settings
+// - the computer's own voice, with Read aloud on, in a `--features tts` build
+//
+// A host game can listen for the `Announcement` event too.
+use bevy::a11y::AccessibilityNode;
+use bevy::a11y::accesskit::{Live, Node as AccessNode, Role};
+use bevy::prelude::*;
+
+use super::betting::{BetResolved, RollResolved};
+use super::dice::{NoRoll, NoRollReason};
+use super::settings::Settings;
+use super::{AppState, InGame, RollStep};
+use crate::announce::{bet_announcement, phase_announcement, roll_announcement};
+use crate::locale::Locale;
+use crate::registry::BetRegistry;
+use crate::rules::RoundPhase;
+
+pub struct AnnouncePlugin;
+
+impl Plugin for AnnouncePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Announcement>()
+            .add_systems(OnEnter(InGame), setup_caption)
+            .add_systems(
+                Update,
+                (
+                    announce_roll_system.in_set(RollStep::Announce),
+                    caption_system.after(announce_roll_system),
+                    show_caption_system,
+                )
+                    .run_if(in_state(AppState::Playing)),
+            );
+        #[cfg(feature = "tts")]
+        app.insert_non_send_resource(Speech::new())
+            .add_systems(Update, speech_system.after(announce_roll_system));
+    }
+}
+
+// Something that happened at the table, in a sentence or a few
+#[derive(Event, Debug, Clone)]
+pub struct Announcement(pub String);
+
+// The caption along the bottom of the screen, which is also the screen reader's live
+// region
+#[derive(Component)]
+struct Caption;
+
+fn setup_caption(mut commands: Commands, settings: Res<Settings>) {
+    // A status that's read out politely - after whatever the screen reader is saying
+    let mut access = AccessNode::new(Role::Status);
+    access.set_live(Live::Polite);
+    commands.spawn((
+        StateScoped(InGame),
+        Text::new(""),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        TextLayout::new_with_justify(JustifyText::Center),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(70.0), // Above the power meter and the seed
+            left: Val::Percent(20.0),
+            width: Val::Percent(60.0),
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+        // Screen readers still find it while it's hidden
+        if settings.captions {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        },
+        AccessibilityNode(access),
+        Caption,
+    ));
+}
+
+// System that puts each roll into words, once it has been judged and paid
+fn announce_roll_system(
+    mut resolved_events: EventReader<RollResolved>,
+    mut payout_events: EventReader<BetResolved>,
+    mut no_roll_events: EventReader<NoRoll>,
+    phase: Res<RoundPhase>,
+    locale: Res<Locale>,
+    registry: Res<BetRegistry>, // What the side bets are called
+    mut announcements: EventWriter<Announcement>,
+) {
+    for no_roll in no_roll_events.read() {
+        let reason = match no_roll.reason {
+            NoRollReason::CockedDie(die) => {
+                locale.fill("No roll: die {die} is cocked.", &[("die", &die)])
+            }
+            NoRollReason::ShortRoll => locale
+                .tr("No roll: both dice must hit the back wall.")
+                .to_string(),
+            NoRollReason::OffTable(die) => {
+                locale.fill("No roll: die {die} left the table.", &[("die", &die)])
+            }
+        };
+        announcements.write(Announcement(reason));
+    }
+
+    let Some(resolved) = resolved_events.read().last() else {
+        payout_events.clear();
+        return;
+    };
+    let mut sentences = vec![roll_announcement(
+        resolved.die_values,
+        resolved.outcome,
+        &locale,
+    )];
+    sentences.extend(payout_events.read().filter_map(|payout| {
+        bet_announcement(
+            &payout.bet,
+            payout.result,
+            payout.returned,
+            &registry,
+            &locale,
+        )
+    }));
+    // Only when the puck moved - every roll with the point on would say it again
+    if resolved.previous.point() != phase.point() {
+        sentences.push(phase_announcement(*phase, &locale));
+    }
+    announcements.write(Announcement(sentences.join(" ")));
+}
+
+// System that shows the newest announcement in the caption, and hands it to screen readers
+fn caption_system(
+    mut announcements: EventReader<Announcement>,
+    mut caption_q: Query<(&mut Text, &mut AccessibilityNode), With<Caption>>,
+) {
+    let Some(announcement) = announcements.read().last() else {
+        return;
+    };
+    if let Ok((mut text, mut access)) = caption_q.single_mut() {
+        text.0 = announcement.0.clone();
+        access.set_label(announcement.0.as_str());
+    }
+}
+
+// System that shows or hides the caption as the Captions setting changes
+fn show_caption_system(
+    settings: Res<Settings>,
+    mut caption_q: Query<&mut Visibility, With<Caption>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    if let Ok(mut visibility) = caption_q.single_mut() {
+        visibility.set_if_neq(if settings.captions {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
+
+// The computer's text-to-speech voice, if it has one. It's tied to the thread it was
+// made on, so it's kept as a non-send resource.
+#[cfg(feature = "tts")]
+struct Speech(Option<tts::Tts>);
+
+#[cfg(feature = "tts")]
+impl Speech {
+    fn new() -> Self {
+        Self(
+            tts::Tts::default()
+                .map_err(|error| {
+                    warn!("No text-to-speech voice, so nothing is read aloud: {error}")
+                })
+                .ok(),
+        )
+    }
+}
+
+// System that reads each announcement aloud, cutting off the last one if it's still
+// going - the newest roll is the one that matters
+#[cfg(feature = "tts")]
+fn speech_system(
+    settings: Res<Settings>,
+    mut announcements: EventReader<Announcement>,
+    mut speech: NonSendMut<Speech>,
+) {
+    let Some(announcement) = announcements.read().last() else {
+        return;
+    };
+    let Some(voice) = speech.0.as_mut().filter(|_| settings.read_aloud) else {
+        return;
+    };
+    if let Err(error) = voice.speak(announcement.0.as_str(), true) {
+        warn!("Couldn't read the roll aloud: {error}");
+    }
+}
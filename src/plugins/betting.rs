@@ -0,0 +1,1024 @@
+// The game side of the table: the round, the player's bankroll and chips, placing
+// bets by clicking the layout and paying them off when a roll is judged. A bet the
+// table won't take (under the minimum, say) is shaken off the felt, and right-clicking
+// a bet between rolls takes it back down, unless it's a contract. From the moment the
+// shooter starts a throw until the roll is paid it's "no more bets" (`BettingWindow`):
+// nothing goes down or comes off. The dealer pays in chips you can watch: winnings
+// slide over from the bank on the far rail and are pushed to the player's rail with
+// the bet, and lost bets are swept away.
+//
+// The chips are coloured by the palette picked on the settings screen, and can have
+// their value printed round the edge (see palette.rs).
+//
+// The piles of chips on the layout are solid to the dice: a die that runs into one
+// bounces off and shoves it a little way across the felt, and a die can come to rest
+// leaning on a pile - cocked, so it's a no roll like any other (see dice.rs). Once the
+// dice are read, the dealer pushes every pile that was knocked about back onto its spot.
+use std::f32::consts::TAU;
+
+use bevy::asset::RenderAssetUsages;
+use bevy::color::prelude::*; // Color tools - for painting our 3D objects
+use bevy::image::ImageSampler;
+use bevy::pbr::NotShadowCaster;
+use bevy::prelude::*;
+use bevy::render::mesh::Indices;
+use bevy::render::render_resource::{Extent3d, PrimitiveTopology, TextureDimension, TextureFormat};
+use bevy_rapier3d::prelude::*;
+
+use super::controls::PlayerInput;
+use super::dice::{
+    Dice, DiceAuthority, DiceSettled, NoRoll, RollSettled, RollState, ThrowDice, ThrowPower,
+    throw_system,
+};
+use super::lifecycle::{DiceLaunched, PhaseChanged};
+use super::session::SessionPlayback;
+use super::settings::Settings;
+use super::table::{TableChoice, TableConfig};
+use super::{AppState, InGame, RollStep, redraw};
+use crate::betting::{Bet, BetError, BetKind, BetResult, TableBets};
+use crate::layout::TableLayout;
+use crate::palette::{EDGE_ROWS, Palette, chip_label, edge_pixels};
+use crate::payout::{
+    Bankroll, CHIP_DENOMINATIONS, Cents, break_into_chips, format_money, returned_to_player,
+};
+use crate::registry::BetRegistry;
+use crate::rules::{Roll, RollOutcome, RoundPhase, ShooterStreak, TableRules, resolve_roll};
+
+pub struct BettingPlugin;
+
+impl Plugin for BettingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RoundPhase>() // Come-out, point on, or round over
+            .init_resource::<ShooterStreak>() // Points in a row for the shooter with the dice
+            .add_event::<RollResolved>() // What the settled roll meant for the round
+            .add_event::<PhaseChanged>() // The round moved on (see lifecycle.rs)
+            .add_event::<BetResolved>() // One per bet decided by a roll - the UI listens for these
+            .add_event::<BetPlaced>() // The player put chips down
+            .add_event::<BetRejected>() // ...or tried to, and the table said no
+            .add_event::<BetTakenDown>() // The player took chips back off the layout
+            .add_event::<TakeDownRefused>() // ...or tried to, and the bet has to stay
+            .add_event::<BetUndone>() // The player took back chips put down since the last roll
+            .init_resource::<TableBets>() // Every bet currently on the layout
+            .init_resource::<BetRegistry>() // The side bets, added from outside (see registry.rs)
+            .init_resource::<Bankroll>() // The player's money
+            .init_resource::<SelectedChip>() // Which chip a click on the layout puts down
+            .init_resource::<BettingWindow>() // Closed while the dice are out
+            .add_systems(Startup, setup_chips)
+            .add_systems(
+                OnEnter(InGame),
+                (redraw::<TableBets>, setup_dealer_bank), // Lay the chips out again
+            )
+            .add_systems(
+                Update,
+                (
+                    chip_select_system,
+                    // As soon as the throw button goes down, so no click that frame gets in
+                    betting_window_system.after(throw_system),
+                    // Before the roll is judged, so a bet made the frame the dice stop
+                    // plays for that roll - live and when a session is replayed
+                    (bet_click_system, take_down_click_system)
+                        .after(betting_window_system)
+                        .before(RollStep::Judge),
+                    round_system.in_set(RollStep::Judge),
+                    // Bets are settled first, then the bankroll is paid what they're owed
+                    (payout_system, bank_winnings_system)
+                        .chain()
+                        .in_set(RollStep::Pay),
+                    chip_stack_system,
+                    (chip_scatter_system, chip_nudge_system).chain(),
+                    // Once the roll has been paid, so only the piles still up are tidied
+                    tidy_chips_system.after(RollStep::Pay),
+                    restack_system,
+                    (
+                        chip_payout_system.after(RollStep::Pay),
+                        taken_down_chips_system,
+                    ),
+                    chip_slide_system,
+                    (rejected_chips_system, chip_shake_system).chain(),
+                )
+                    .run_if(in_state(AppState::Playing)),
+            )
+            // The palette is picked on the settings screen, while the game is paused
+            .add_systems(Update, chip_palette_system);
+    }
+}
+
+// Whether the table is taking bets. It's "no more bets" from the moment the shooter
+// starts a throw (the throw button goes down) until the dice have been read and the
+// roll paid: no bet goes down, comes off or is taken back in between. Someone only
+// watching a networked table never bets at all - the network plugin shuts the window
+// for them until they're given a seat.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BettingWindow {
+    #[default]
+    Open,
+    NoMoreBets,
+    Watching, // Left alone by betting_window_system
+}
+
+impl BettingWindow {
+    // Refuses with BetError::NoMoreBets while the dice are out
+    pub fn check(self) -> Result<(), BetError> {
+        match self {
+            BettingWindow::Open => Ok(()),
+            BettingWindow::NoMoreBets => Err(BetError::NoMoreBets),
+            BettingWindow::Watching => Err(BetError::Watching),
+        }
+    }
+}
+
+// A pile of chips on the layout showing one bet. We remember the amount it was
+// built for so we can tell when the bet has grown or shrunk.
+#[derive(Component)]
+pub struct ChipStack {
+    kind: BetKind,
+    amount: Cents,
+}
+
+// The mesh and colours shared by every chip, created once at startup
+#[derive(Resource)]
+pub(crate) struct ChipAssets {
+    mesh: Handle<Mesh>,
+    materials: Vec<Handle<StandardMaterial>>, // One per entry in CHIP_DENOMINATIONS
+    edge_mesh: Handle<Mesh>, // A band just outside a chip's edge, for its printed value
+    edge_materials: Vec<Handle<StandardMaterial>>, // Each chip's value, like `materials`
+    palette: Palette,        // What the materials are coloured in right now
+    labels: bool,            // Whether the values are showing
+}
+
+impl ChipAssets {
+    fn edge_visibility(&self) -> Visibility {
+        if self.labels {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        }
+    }
+}
+
+// The band round a chip's edge with its value printed on it
+#[derive(Component)]
+struct ChipEdge;
+
+// A pile of chips being moved by the dealer. It slides through each of its stops in
+// turn, and is cleared away once it reaches the last one.
+#[derive(Component)]
+struct ChipSlide {
+    stops: Vec<Vec3>,
+    leg: usize,    // Sliding from stops[leg] to stops[leg + 1]
+    progress: f32, // 0.0 = at the start of this leg, 1.0 = at its end
+    delay: f32,    // Seconds left to wait before it starts moving
+}
+
+impl ChipSlide {
+    fn new(stops: Vec<Vec3>) -> Self {
+        Self {
+            stops,
+            leg: 0,
+            progress: 0.0,
+            delay: 0.0,
+        }
+    }
+
+    fn after(mut self, delay: f32) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+// A pile a die ran into, sliding to a stop
+#[derive(Component)]
+struct ChipNudge {
+    velocity: Vec3,
+}
+
+// A pile the dealer is pushing back onto its spot, from where it was knocked to
+#[derive(Component)]
+struct Restack {
+    from: Vec3,
+    progress: f32, // 0.0 = where it was knocked to, 1.0 = back on its spot
+}
+
+pub(crate) const CHIP_RADIUS: f32 = 0.1;
+pub(crate) const CHIP_HEIGHT: f32 = 0.025;
+pub(crate) const MAX_CHIPS_SHOWN: usize = 20; // Taller piles than this just stop growing
+const SLIDE_SECONDS: f32 = 0.5; // How long each push of the dealer's hand takes
+const SLIDE_HOP: f32 = 0.15; // Chips are lifted a little as they go, clearing the other piles
+const SHAKE_SECONDS: f32 = 0.5; // How long a refused bet wobbles before it's taken back
+const SHAKE_DISTANCE: f32 = 0.03;
+const SHAKE_SPEED: f32 = 40.0; // Radians a second - a quick side-to-side rattle
+const SCATTER_PER_SPEED: f32 = 0.05; // How fast a pile is shoved for each unit/second of the die
+const MAX_SCATTER_SPEED: f32 = 0.4; // However hard it's hit - a pile is only ever jostled
+const SCATTER_FRICTION: f32 = 6.0; // How quickly the felt stops a shoved pile (per second)
+const TIDY_DISTANCE: f32 = 0.005; // A pile knocked further off its spot than this is pushed back
+
+// The chip the player is holding, as an index into CHIP_DENOMINATIONS
+#[derive(Resource)]
+pub struct SelectedChip(pub usize);
+
+impl Default for SelectedChip {
+    fn default() -> Self {
+        Self(4) // Start with $5 chips
+    }
+}
+
+impl SelectedChip {
+    pub fn value(&self) -> Cents {
+        CHIP_DENOMINATIONS[self.0]
+    }
+}
+
+// Event fired after the rules engine has judged a settled roll
+#[derive(Event, Debug, Clone, Copy)]
+pub struct RollResolved {
+    pub die_values: [u8; 2],
+    pub total: u8,
+    pub outcome: RollOutcome, // What the roll meant (natural, point set, seven out...)
+    pub previous: RoundPhase, // The phase the roll was thrown in
+    pub short_roll: bool,     // Counted, but a die never reached the back wall
+}
+
+// Event fired for every bet a roll decides, so the UI and effects (chip animations,
+// sounds, callouts) can react to wins and losses without re-checking the rules
+#[derive(Event, Debug, Clone, Copy)]
+pub struct BetResolved {
+    pub bet: Bet,
+    pub result: BetResult,
+    pub returned: Cents, // What went back into the bankroll (bet + winnings, or the bet on a push)
+}
+
+// Event fired whenever chips go down on the layout
+#[derive(Event, Debug, Clone, Copy)]
+pub struct BetPlaced {
+    pub kind: BetKind,
+    pub amount: Cents, // What was added to the spot (not counting any commission)
+}
+
+// Event fired when chips are put down on a spot that won't take them
+#[derive(Event, Debug, Clone, Copy)]
+pub struct BetRejected {
+    pub kind: BetKind,
+    pub amount: Cents,
+    pub error: BetError,
+}
+
+// Event fired for every bet taken back off the layout (a bet and its odds come down
+// together, as two events)
+#[derive(Event, Debug, Clone, Copy)]
+pub struct BetTakenDown {
+    pub kind: BetKind,
+    pub amount: Cents, // What went back into the bankroll, not counting any commission
+}
+
+// Event fired when chips put down since the last roll are taken back (see undo.rs)
+#[derive(Event, Debug, Clone, Copy)]
+pub struct BetUndone {
+    pub kind: BetKind,
+    pub amount: Cents, // What came off the spot, not counting any commission
+}
+
+// Event fired when the player tries to take down a bet that can't come down yet
+#[derive(Event, Debug, Clone, Copy)]
+pub struct TakeDownRefused {
+    pub kind: BetKind,
+    pub error: BetError,
+}
+
+// A refused bet's chips, rattling on top of the spot before they disappear
+#[derive(Component)]
+struct ChipShake {
+    home: Vec3,
+    age: f32,
+}
+
+// Every chip shares one cylinder mesh; only the colour changes with the value
+fn setup_chips(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+    settings: Res<Settings>,
+) {
+    let colors = settings.palette.chip_colors();
+    // Long enough that the printed pixels come out square, once wrapped round the edge
+    let edge_width = (TAU * CHIP_RADIUS / CHIP_HEIGHT * EDGE_ROWS as f32).round() as usize;
+    let edge_materials = CHIP_DENOMINATIONS
+        .iter()
+        .zip(colors)
+        .map(|(&value, hex)| {
+            let mut print = Image::new(
+                Extent3d {
+                    width: edge_width as u32,
+                    height: EDGE_ROWS as u32,
+                    depth_or_array_layers: 1,
+                },
+                TextureDimension::D2,
+                edge_pixels(&chip_label(value), edge_width),
+                TextureFormat::Rgba8UnormSrgb,
+                RenderAssetUsages::RENDER_WORLD,
+            );
+            print.sampler = ImageSampler::nearest(); // Crisp pixels, not a blur
+            materials.add(StandardMaterial {
+                base_color: edge_ink(hex),
+                base_color_texture: Some(images.add(print)),
+                alpha_mode: AlphaMode::Mask(0.5), // Only the ink is drawn
+                perceptual_roughness: 0.6,
+                ..default()
+            })
+        })
+        .collect();
+    commands.insert_resource(ChipAssets {
+        mesh: meshes.add(Cylinder::new(CHIP_RADIUS, CHIP_HEIGHT)),
+        materials: colors
+            .into_iter()
+            .map(|hex| {
+                materials.add(StandardMaterial {
+                    base_color: Srgba::hex(hex).unwrap().into(),
+                    perceptual_roughness: 0.6, // Clay chips are a little glossy, not shiny
+                    ..default()
+                })
+            })
+            .collect(),
+        edge_mesh: meshes.add(edge_band(CHIP_RADIUS * 1.01, CHIP_HEIGHT)),
+        edge_materials,
+        palette: settings.palette,
+        labels: settings.chip_labels,
+    });
+}
+
+// The colour a chip's value is printed in: black on a light chip, white on a dark one
+fn edge_ink(chip_hex: &str) -> Color {
+    if Srgba::hex(chip_hex).unwrap().luminance() > 0.18 {
+        Color::BLACK
+    } else {
+        Color::WHITE
+    }
+}
+
+// An open tube - a cylinder with no top or bottom - for the edge print to wrap round
+// once, left to right as it's seen from outside
+fn edge_band(radius: f32, height: f32) -> Mesh {
+    const SEGMENTS: u32 = 32;
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    for step in 0..=SEGMENTS {
+        let along = step as f32 / SEGMENTS as f32;
+        let (sin, cos) = (along * TAU).sin_cos();
+        // A vertex at the top of the edge and one at the bottom, the top of the print
+        // at the top
+        for (y, v) in [(height / 2.0, 0.0), (-height / 2.0, 1.0)] {
+            positions.push([cos * radius, y, sin * radius]);
+            normals.push([cos, 0.0, sin]);
+            // Seen from outside, the tube goes round from right to left, so the print
+            // runs the other way to read the right way round
+            uvs.push([1.0 - along, v]);
+        }
+    }
+    let mut indices = Vec::new();
+    for step in 0..SEGMENTS {
+        // Two triangles between this step's pair of vertices and the next step's
+        let top = step * 2;
+        indices.extend([top, top + 2, top + 1, top + 1, top + 2, top + 3]);
+    }
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+// System that recolours every chip when the player picks another palette, and shows or
+// hides the values printed on their edges
+fn chip_palette_system(
+    settings: Res<Settings>,
+    mut chip_assets: ResMut<ChipAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut edges_q: Query<&mut Visibility, With<ChipEdge>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    if chip_assets.palette != settings.palette {
+        chip_assets.palette = settings.palette;
+        for (index, hex) in settings.palette.chip_colors().into_iter().enumerate() {
+            if let Some(chip) = materials.get_mut(&chip_assets.materials[index]) {
+                chip.base_color = Srgba::hex(hex).unwrap().into();
+            }
+            if let Some(edge) = materials.get_mut(&chip_assets.edge_materials[index]) {
+                edge.base_color = edge_ink(hex);
+            }
+        }
+    }
+    if chip_assets.labels != settings.chip_labels {
+        chip_assets.labels = settings.chip_labels;
+        for mut visibility in &mut edges_q {
+            *visibility = chip_assets.edge_visibility();
+        }
+    }
+}
+
+// System that feeds each settled roll through the rules and advances the round
+fn round_system(
+    mut phase: ResMut<RoundPhase>,
+    mut streak: ResMut<ShooterStreak>,
+    mut settled_events: EventReader<RollSettled>,
+    mut resolved_events: EventWriter<RollResolved>,
+    mut phase_events: EventWriter<PhaseChanged>,
+    rules: Res<TableRules>,
+) {
+    for roll in settled_events.read() {
+        let previous = *phase;
+        let (outcome, next) = resolve_roll(previous, roll.total, rules.variant);
+        *phase = next;
+        if next != previous {
+            phase_events.write(PhaseChanged {
+                from: previous,
+                to: next,
+                jumped: false,
+            });
+        }
+        *streak = streak.after(outcome);
+
+        match outcome {
+            RollOutcome::Natural(total) => info!("{total} - winner on the come-out!"),
+            RollOutcome::Craps(total) => info!("{total} - craps!"),
+            RollOutcome::PointSet(point) => info!("The point is {point}"),
+            RollOutcome::PointMade(point) => info!(
+                "{point} - the point is made, winner! ({} in a row)",
+                streak.points_made
+            ),
+            RollOutcome::SevenOut => info!("Seven out!"),
+            RollOutcome::NoDecision(total) => info!("{total} - no decision"),
+        }
+
+        resolved_events.write(RollResolved {
+            die_values: roll.die_values,
+            total: roll.total,
+            outcome,
+            previous,
+            short_roll: roll.short_roll,
+        });
+    }
+}
+
+// System that lets the scroll wheel (or d-pad) pick a bigger or smaller chip
+fn chip_select_system(input: Res<PlayerInput>, mut selected: ResMut<SelectedChip>) {
+    // CHIP_DENOMINATIONS runs biggest first, so a bigger chip moves towards index 0
+    if input.chip_step > 0 && selected.0 > 0 {
+        selected.0 -= 1;
+    } else if input.chip_step < 0 && selected.0 + 1 < CHIP_DENOMINATIONS.len() {
+        selected.0 += 1;
+    }
+}
+
+// System that puts the selected chip down on whichever bet zone is clicked (or picked
+// with the gamepad pointer)
+fn bet_click_system(
+    input: Res<PlayerInput>,
+    table_layout: Res<TableLayout>,
+    selected: Res<SelectedChip>,
+    phase: Res<RoundPhase>,
+    rules: Res<TableRules>,
+    window: Res<BettingWindow>,
+    registry: Res<BetRegistry>, // When each side bet may go down
+    mut bankroll: ResMut<Bankroll>,
+    mut bets: ResMut<TableBets>,
+    buttons_q: Query<&Interaction>, // Every on-screen button
+    mut placed_events: EventWriter<BetPlaced>,
+    mut rejected_events: EventWriter<BetRejected>,
+    playback: Option<Res<SessionPlayback>>, // A replayed session places its own bets
+) {
+    if !input.place_bet || playback.is_some() {
+        return;
+    }
+    // A click on a button belongs to the button, not to the felt behind it
+    let on_button = buttons_q
+        .iter()
+        .any(|interaction| *interaction != Interaction::None);
+    if input.pointer_is_mouse && on_button {
+        return;
+    }
+    let Some(spot) = input.pointer.and_then(|point| table_layout.spot_at(point)) else {
+        return; // Clicked off the layout
+    };
+
+    let kind = spot.bet_kind(*phase, &bets);
+    let mut amount = selected.value();
+    // A chip too big for the odds still left is cut down to fill them, the way a
+    // dealer makes change rather than turning the bet away
+    if let Some(room) = bets.odds_room(kind, *phase, &rules)
+        && room > 0
+        && amount > room
+    {
+        info!("Capped at {} - full odds", format_money(room));
+        amount = room;
+    }
+    // The money leaves the bankroll the moment the chips hit the felt
+    let bought = window
+        .check()
+        .and_then(|()| registry.check_open(kind, *phase))
+        .and_then(|()| bets.buy_in(kind, amount, *phase, &rules, &mut bankroll));
+    let vig = match bought {
+        Ok(vig) => vig,
+        Err(error) => {
+            info!("Can't bet {} on {kind:?}: {error}", format_money(amount));
+            rejected_events.write(BetRejected {
+                kind,
+                amount,
+                error,
+            });
+            return;
+        }
+    };
+    placed_events.write(BetPlaced { kind, amount });
+    info!("Placed {} on {kind:?}", format_money(amount));
+    if vig > 0 {
+        info!("Paid {} commission", format_money(vig));
+    }
+}
+
+// System that closes the table to bets as a throw begins and opens it again once the
+// dice have been read (or the throw didn't count)
+fn betting_window_system(
+    power: Res<ThrowPower>,
+    roll: Res<RollState>,
+    authority: Res<DiceAuthority>,
+    mut throw_events: EventReader<ThrowDice>,
+    mut launched_events: EventReader<DiceLaunched>,
+    mut dice_events: EventReader<DiceSettled>,
+    mut no_roll_events: EventReader<NoRoll>,
+    mut launching: Local<bool>, // Thrown, but the dice aren't in the air yet
+    mut window: ResMut<BettingWindow>,
+) {
+    // A throw is launched on the physics clock, which may not tick until next frame.
+    // (At someone else's table the host launches it, and keeps the table closed.)
+    if throw_events.read().count() > 0 && authority.simulates {
+        *launching = true;
+    }
+    let over = launched_events.read().count() + dice_events.read().count();
+    if over + no_roll_events.read().count() > 0 {
+        *launching = false;
+    }
+    if *window == BettingWindow::Watching {
+        return;
+    }
+    window.set_if_neq(if power.is_charging() || roll.in_flight() || *launching {
+        BettingWindow::NoMoreBets
+    } else {
+        BettingWindow::Open
+    });
+}
+
+// System that takes the bet on whichever zone is right-clicked back off the table,
+// and its money back into the bankroll. Only between rolls - a bet can't be snatched
+// back once the throw has started.
+fn take_down_click_system(
+    input: Res<PlayerInput>,
+    table_layout: Res<TableLayout>,
+    phase: Res<RoundPhase>,
+    window: Res<BettingWindow>,
+    mut bankroll: ResMut<Bankroll>,
+    mut bets: ResMut<TableBets>,
+    buttons_q: Query<&Interaction>, // Every on-screen button
+    mut taken_events: EventWriter<BetTakenDown>,
+    mut refused_events: EventWriter<TakeDownRefused>,
+    playback: Option<Res<SessionPlayback>>, // A replayed session takes its own bets down
+) {
+    if !input.take_down_bet || playback.is_some() {
+        return;
+    }
+    let on_button = buttons_q
+        .iter()
+        .any(|interaction| *interaction != Interaction::None);
+    if input.pointer_is_mouse && on_button {
+        return;
+    }
+    let Some(spot) = input.pointer.and_then(|point| table_layout.spot_at(point)) else {
+        return;
+    };
+
+    let kind = spot.take_down_kind(*phase, &bets);
+    let taken = match window.check() {
+        Err(error) if bets.amount_on(kind) > 0 => Err(error),
+        _ => bets.take_down(kind, *phase, &mut bankroll),
+    };
+    match taken {
+        Ok(taken) => {
+            for bet in taken {
+                info!("Took {} off {:?}", format_money(bet.amount), bet.kind);
+                taken_events.write(BetTakenDown {
+                    kind: bet.kind,
+                    amount: bet.amount,
+                });
+            }
+        }
+        Err(error) => {
+            info!("Can't take {kind:?} down: {error}");
+            refused_events.write(TakeDownRefused { kind, error });
+        }
+    }
+}
+
+// System that settles every bet on the table against each resolved roll, sending a
+// BetResolved with what each one is owed
+fn payout_system(
+    mut resolved_events: EventReader<RollResolved>,
+    rules: Res<TableRules>,
+    mut bets: ResMut<TableBets>,
+    mut payout_events: EventWriter<BetResolved>,
+) {
+    for resolved in resolved_events.read() {
+        let roll = Roll {
+            die_values: resolved.die_values,
+            previous: resolved.previous,
+            outcome: resolved.outcome,
+        };
+
+        for (bet, result) in bets.resolve_roll(&roll, &rules) {
+            payout_events.write(BetResolved {
+                bet,
+                result,
+                returned: returned_to_player(&bet, result),
+            });
+        }
+    }
+}
+
+// System that pays every decided bet's money back into the bankroll. Whatever settled
+// the bet - this plugin, a side bet's rule or a sic bo throw - only sends a BetResolved
+// saying what it's owed, so this is the one place winnings reach the bankroll.
+pub(crate) fn bank_winnings_system(
+    mut payout_events: EventReader<BetResolved>,
+    mut bankroll: ResMut<Bankroll>,
+) {
+    for payout in payout_events.read() {
+        bankroll.credit(payout.returned);
+    }
+}
+
+// System that keeps a pile of chips on the felt for every bet on the table
+fn chip_stack_system(
+    mut commands: Commands,
+    bets: Res<TableBets>,
+    table_layout: Res<TableLayout>,
+    chip_assets: Res<ChipAssets>,
+    stacks_q: Query<(Entity, &ChipStack)>,
+) {
+    if !bets.is_changed() {
+        return; // Nothing was bet, won or lost - the piles are already right
+    }
+
+    // Clear away piles for bets that were decided or changed size...
+    for (entity, stack) in &stacks_q {
+        if bets.amount_on(stack.kind) != stack.amount {
+            commands.entity(entity).despawn(); // Also despawns the chips inside it
+        }
+    }
+
+    // ...and build fresh ones for anything that doesn't have an up-to-date pile
+    for bet in &bets.bets {
+        let up_to_date = stacks_q
+            .iter()
+            .any(|(_, stack)| stack.kind == bet.kind && stack.amount == bet.amount);
+        if up_to_date {
+            continue;
+        }
+
+        let anchor = table_layout.chip_anchor(bet.kind);
+        let at = Vec3::new(anchor.x, 0.0, anchor.y);
+        let chips = break_into_chips(bet.amount).len().min(MAX_CHIPS_SHOWN);
+        let half_height = CHIP_HEIGHT * chips as f32 / 2.0;
+        commands
+            .entity(spawn_chip_pile(&mut commands, &chip_assets, bet.amount, at))
+            .insert((
+                ChipStack {
+                    kind: bet.kind,
+                    amount: bet.amount,
+                },
+                Name::new(format!("Chips {:?}", bet.kind)),
+                // Kinematic: the dice bounce off it, but only chip_scatter_system moves it
+                RigidBody::KinematicPositionBased,
+                Collider::compound(vec![(
+                    Vec3::Y * half_height, // Standing on the felt, not sunk into it
+                    Quat::IDENTITY,
+                    Collider::cylinder(half_height, CHIP_RADIUS),
+                )]),
+                ActiveEvents::COLLISION_EVENTS, // So chip_scatter_system hears about the dice
+            ));
+    }
+}
+
+// Spawn a pile of chips worth `amount` standing at `at`, biggest chips at the bottom
+// the way a dealer stacks them
+fn spawn_chip_pile(
+    commands: &mut Commands,
+    chip_assets: &ChipAssets,
+    amount: Cents,
+    at: Vec3,
+) -> Entity {
+    spawn_chips(commands, chip_assets, &break_into_chips(amount), at)
+}
+
+// Spawn a pile of exactly these chips standing at `at`, the first at the bottom
+pub(crate) fn spawn_chips(
+    commands: &mut Commands,
+    chip_assets: &ChipAssets,
+    chips: &[Cents],
+    at: Vec3,
+) -> Entity {
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Transform::from_translation(at),
+            Visibility::default(), // Needed so the child chips can be seen
+        ))
+        .with_children(|parent| {
+            for (height, &value) in chips.iter().take(MAX_CHIPS_SHOWN).enumerate() {
+                let color = CHIP_DENOMINATIONS
+                    .iter()
+                    .position(|&d| d == value)
+                    .unwrap_or(0);
+                parent
+                    .spawn((
+                        Mesh3d(chip_assets.mesh.clone()),
+                        MeshMaterial3d(chip_assets.materials[color].clone()),
+                        Transform::from_xyz(0.0, CHIP_HEIGHT * (height as f32 + 0.5), 0.0),
+                    ))
+                    .with_child((
+                        Mesh3d(chip_assets.edge_mesh.clone()),
+                        MeshMaterial3d(chip_assets.edge_materials[color].clone()),
+                        // Turned a little for each chip, so the numbers don't line up
+                        Transform::from_rotation(Quat::from_rotation_y(height as f32 * 0.7)),
+                        chip_assets.edge_visibility(),
+                        NotShadowCaster, // The chip's own shadow is enough
+                        ChipEdge,
+                    ));
+            }
+        })
+        .id()
+}
+
+// The dealer's bank: a row of tall piles, one per chip colour, on the far rail
+fn setup_dealer_bank(
+    mut commands: Commands,
+    chip_assets: Res<ChipAssets>,
+    table_layout: Res<TableLayout>,
+    choice: Res<TableChoice>,
+    configs: Res<Assets<TableConfig>>,
+) {
+    let bank = choice.current(&configs).rail_top(table_layout.size, 1.0);
+    let count = CHIP_DENOMINATIONS.len() as f32;
+    for (index, &value) in CHIP_DENOMINATIONS.iter().enumerate() {
+        let x = (index as f32 - (count - 1.0) / 2.0) * CHIP_RADIUS * 2.2; // Side by side
+        spawn_chips(
+            &mut commands,
+            &chip_assets,
+            &[value; 15], // Fifteen of each
+            bank + Vec3::X * x,
+        );
+    }
+}
+
+// System that has the dealer move chips for every bet a roll decided: a win is paid
+// from the bank next to the bet and pushed to the player with it, a push goes straight
+// back to the player, and a lost bet is swept into the middle and off to the bank
+fn chip_payout_system(
+    mut commands: Commands,
+    mut payout_events: EventReader<BetResolved>,
+    chip_assets: Res<ChipAssets>,
+    table_layout: Res<TableLayout>,
+    choice: Res<TableChoice>,
+    configs: Res<Assets<TableConfig>>,
+) {
+    let config = choice.current(&configs);
+    let bank = config.rail_top(table_layout.size, 1.0);
+    let rail = config.rail_top(table_layout.size, -1.0);
+
+    for payout in payout_events.read() {
+        let anchor = table_layout.chip_anchor(payout.bet.kind);
+        let bet_at = Vec3::new(anchor.x, 0.0, anchor.y);
+        let mut push = |amount: Cents, start: Vec3, path: ChipSlide| {
+            let pile = spawn_chip_pile(&mut commands, &chip_assets, amount, start);
+            commands.entity(pile).insert(path);
+        };
+        match payout.result {
+            BetResult::Win(profit) => {
+                // The winnings are set down beside the bet, then both go to the player
+                let beside = bet_at + Vec3::X * CHIP_RADIUS * 2.2;
+                push(profit, bank, ChipSlide::new(vec![bank, beside, rail]));
+                push(
+                    payout.bet.amount,
+                    bet_at,
+                    ChipSlide::new(vec![bet_at, rail]).after(SLIDE_SECONDS),
+                );
+            }
+            BetResult::Push => push(
+                payout.bet.amount,
+                bet_at,
+                ChipSlide::new(vec![bet_at, rail]),
+            ),
+            BetResult::Lose => {
+                // Swept into the middle of the table, then collected
+                push(
+                    payout.bet.amount,
+                    bet_at,
+                    ChipSlide::new(vec![bet_at, Vec3::ZERO, bank]),
+                );
+            }
+            BetResult::Stay | BetResult::Travel(_) => {} // The chips stay on the layout
+        }
+    }
+}
+
+// System that has the dealer push a bet that was taken down (or chips that were taken
+// back) to the player's rail
+fn taken_down_chips_system(
+    mut commands: Commands,
+    mut taken_events: EventReader<BetTakenDown>,
+    mut undone_events: EventReader<BetUndone>,
+    chip_assets: Res<ChipAssets>,
+    table_layout: Res<TableLayout>,
+    choice: Res<TableChoice>,
+    configs: Res<Assets<TableConfig>>,
+) {
+    let rail = choice.current(&configs).rail_top(table_layout.size, -1.0);
+    let taken = taken_events.read().map(|taken| (taken.kind, taken.amount));
+    let undone = undone_events
+        .read()
+        .map(|undone| (undone.kind, undone.amount));
+    for (kind, amount) in taken.chain(undone) {
+        let anchor = table_layout.chip_anchor(kind);
+        let bet_at = Vec3::new(anchor.x, 0.0, anchor.y);
+        let pile = spawn_chip_pile(&mut commands, &chip_assets, amount, bet_at);
+        commands
+            .entity(pile)
+            .insert(ChipSlide::new(vec![bet_at, rail]));
+    }
+}
+
+// System that moves every sliding pile of chips along, and clears it away at the end
+fn chip_slide_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut slide_q: Query<(Entity, &mut ChipSlide, &mut Transform)>,
+) {
+    for (entity, mut slide, mut transform) in &mut slide_q {
+        if slide.delay > 0.0 {
+            slide.delay -= time.delta_secs();
+            continue;
+        }
+        slide.progress += time.delta_secs() / SLIDE_SECONDS;
+        if slide.progress >= 1.0 {
+            slide.leg += 1;
+            slide.progress = 0.0;
+        }
+        if slide.leg + 1 >= slide.stops.len() {
+            commands.entity(entity).despawn(); // Arrived - into the bank or the player's rack
+            continue;
+        }
+
+        let (from, to) = (slide.stops[slide.leg], slide.stops[slide.leg + 1]);
+        let t = dealer_push(slide.progress);
+        let hop = (slide.progress * std::f32::consts::PI).sin() * SLIDE_HOP;
+        transform.translation = from.lerp(to, t) + Vec3::Y * hop;
+    }
+}
+
+// "Smoothstep" easing, like a dealer's push: starts slow, speeds up, then slows into place
+fn dealer_push(progress: f32) -> f32 {
+    progress * progress * (3.0 - 2.0 * progress)
+}
+
+// System that shoves a pile of chips along the felt when a die runs into it, away from
+// the die and harder the faster it was going. Rapier reports the hit, naming the two
+// colliders in no particular order.
+fn chip_scatter_system(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    dice_q: Query<(&Transform, &Velocity), With<Dice>>,
+    stacks_q: Query<&Transform, (With<ChipStack>, Without<Restack>)>,
+) {
+    for event in collision_events.read() {
+        let CollisionEvent::Started(a, b, _) = *event else {
+            continue; // Only the moment of impact
+        };
+        for (die, pile) in [(a, b), (b, a)] {
+            let (Ok((die_at, velocity)), Ok(pile_at)) = (dice_q.get(die), stacks_q.get(pile))
+            else {
+                continue;
+            };
+            let away = (pile_at.translation - die_at.translation)
+                .with_y(0.0) // Along the felt - a pile never leaves it
+                .normalize_or_zero();
+            let speed = (velocity.linvel.length() * SCATTER_PER_SPEED).min(MAX_SCATTER_SPEED);
+            commands.entity(pile).insert(ChipNudge {
+                velocity: away * speed,
+            });
+        }
+    }
+}
+
+// System that slides each shoved pile along until the felt stops it
+fn chip_nudge_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut nudged_q: Query<(Entity, &mut ChipNudge, &mut Transform)>,
+) {
+    let delta = time.delta_secs();
+    for (entity, mut nudge, mut transform) in &mut nudged_q {
+        transform.translation += nudge.velocity * delta;
+        nudge.velocity *= (1.0 - SCATTER_FRICTION * delta).max(0.0);
+        if nudge.velocity.length() < 0.001 {
+            commands.entity(entity).remove::<ChipNudge>();
+        }
+    }
+}
+
+// System that has the dealer tidy the layout once the dice have been read: every pile
+// still up that was knocked off its spot is pushed back onto it
+fn tidy_chips_system(
+    mut commands: Commands,
+    mut dice_events: EventReader<DiceSettled>,
+    table_layout: Res<TableLayout>,
+    bets: Res<TableBets>,
+    stacks_q: Query<(Entity, &ChipStack, &Transform), Without<Restack>>,
+) {
+    if dice_events.read().count() == 0 {
+        return;
+    }
+    for (entity, stack, transform) in &stacks_q {
+        if bets.amount_on(stack.kind) != stack.amount {
+            continue; // Decided - chip_stack_system is clearing it away
+        }
+        let spot = table_layout.chip_anchor(stack.kind);
+        if transform.translation.xz().distance(spot) > TIDY_DISTANCE {
+            commands
+                .entity(entity)
+                .remove::<ChipNudge>()
+                .insert(Restack {
+                    from: transform.translation,
+                    progress: 0.0,
+                });
+        }
+    }
+}
+
+// System that slides each knocked pile back onto its spot
+fn restack_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    table_layout: Res<TableLayout>,
+    mut restack_q: Query<(Entity, &ChipStack, &mut Restack, &mut Transform)>,
+) {
+    for (entity, stack, mut restack, mut transform) in &mut restack_q {
+        restack.progress = (restack.progress + time.delta_secs() / SLIDE_SECONDS).min(1.0);
+        let spot = table_layout.chip_anchor(stack.kind);
+        let home = Vec3::new(spot.x, 0.0, spot.y);
+        transform.translation = restack.from.lerp(home, dealer_push(restack.progress));
+        if restack.progress >= 1.0 {
+            commands.entity(entity).remove::<Restack>();
+        }
+    }
+}
+
+// System that drops a refused bet's chips on top of the spot, to be shaken off
+fn rejected_chips_system(
+    mut commands: Commands,
+    mut rejected_events: EventReader<BetRejected>,
+    bets: Res<TableBets>,
+    table_layout: Res<TableLayout>,
+    chip_assets: Res<ChipAssets>,
+) {
+    for rejected in rejected_events.read() {
+        let spot = table_layout.chip_anchor(rejected.kind);
+        // On top of whatever is already there
+        let below = break_into_chips(bets.amount_on(rejected.kind))
+            .len()
+            .min(MAX_CHIPS_SHOWN);
+        let home = Vec3::new(spot.x, CHIP_HEIGHT * below as f32, spot.y);
+        let pile = spawn_chip_pile(&mut commands, &chip_assets, rejected.amount, home);
+        commands.entity(pile).insert(ChipShake { home, age: 0.0 });
+    }
+}
+
+// System that rattles refused chips from side to side, dying away, then takes them back
+fn chip_shake_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut shake_q: Query<(Entity, &mut ChipShake, &mut Transform)>,
+) {
+    for (entity, mut shake, mut transform) in &mut shake_q {
+        shake.age += time.delta_secs();
+        if shake.age >= SHAKE_SECONDS {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let left = 1.0 - shake.age / SHAKE_SECONDS;
+        let wobble = (shake.age * SHAKE_SPEED).sin() * SHAKE_DISTANCE * left;
+        transform.translation = shake.home + Vec3::X * wobble;
+    }
+}
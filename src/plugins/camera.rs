@@ -0,0 +1,468 @@
+// The player's point of view: a free-look camera the player turns with the right mouse
+// button or a gamepad's right stick, plus a helper for finding where the mouse points
+// on the table. The number keys fly the camera to preset views (shooter's eye,
+// overhead, low side). The camera draws in HDR with bloom, so the neon trim round the
+// table glows, and a vignette darkens the corners of the screen - both follow the
+// graphics quality (see settings.rs).
+use std::f32::consts::{PI, TAU};
+
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::window::{CursorGrabMode, PrimaryWindow}; // Window control - for hiding/locking the mouse cursor
+
+use super::controls::PlayerInput;
+use super::dice::{Dice, RollState};
+use super::settings::Settings;
+use super::{AppState, InGame};
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraSettings>()
+            .add_systems(OnEnter(InGame), (spawn_camera, spawn_vignette))
+            // The field of view can be changed on the settings screen, over the pause menu
+            .add_systems(Update, camera_fov_system.run_if(in_state(InGame)))
+            .add_systems(
+                Update,
+                (
+                    camera_preset_system,
+                    dice_follow_system,
+                    camera_control_system,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Playing)),
+            );
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(
+            Update,
+            cursor_grab_system.run_if(in_state(AppState::Playing)),
+        )
+        // Pausing mid-look mustn't leave the mouse trapped
+        .add_systems(OnExit(AppState::Playing), release_cursor);
+    }
+}
+
+// #[derive(Component)] is like putting a special sticker on our struct that says
+// "this can be attached to entities in the game world"
+// Without this sticker, Bevy wouldn't know this struct is meant to be a component
+#[derive(Component)]
+pub struct PlayerCamera {
+    yaw: f32, // Horizontal rotation (left/right) - like turning your head side to side
+    // f32 means "32-bit floating point number" - decimals like 3.14
+    pitch: f32, // Vertical rotation (up/down) - like nodding your head
+    // We use radians, where 2π radians = 360 degrees
+    flight: Option<CameraFlight>, // On the way to a preset, if a hotkey was pressed
+    mode: CameraMode,
+}
+
+// What's steering the camera. The player looks around freely until a cinematic (the
+// dice-follow camera, the instant replay) takes over; when it's done, the camera cuts
+// back to where the player was looking.
+#[derive(Debug, Clone, Copy, Default)]
+enum CameraMode {
+    #[default]
+    Free,
+    Directed {
+        return_to: CameraPreset, // The view to cut back to
+    },
+}
+
+impl PlayerCamera {
+    // Hand the camera over to a cinematic, remembering the player's view
+    pub fn take_over(&mut self, transform: &Transform) {
+        if let CameraMode::Free = self.mode {
+            self.flight = None; // The cinematic takes over from any preset flight
+            self.mode = CameraMode::Directed {
+                return_to: CameraPreset {
+                    name: "",
+                    position: transform.translation,
+                    yaw: self.yaw,
+                    pitch: self.pitch,
+                },
+            };
+        }
+    }
+
+    // Give the camera back to the player, cutting straight to the view they had
+    pub fn release(&mut self, transform: &mut Transform) {
+        if let CameraMode::Directed { return_to } = self.mode {
+            transform.translation = return_to.position;
+            self.set_view(transform, return_to.yaw, return_to.pitch);
+            self.mode = CameraMode::Free;
+        }
+    }
+
+    // While a cinematic has the camera, put it at `position` looking at `target`.
+    // Does nothing once the player has taken the camera back with a preset hotkey.
+    pub fn point(&mut self, transform: &mut Transform, position: Vec3, target: Vec3) {
+        if let CameraMode::Directed { .. } = self.mode {
+            transform.translation = position;
+            let view = CameraPreset::looking_at("", position, target);
+            self.set_view(transform, view.yaw, view.pitch);
+        }
+    }
+
+    pub fn is_directed(&self) -> bool {
+        matches!(self.mode, CameraMode::Directed { .. })
+    }
+
+    fn set_view(&mut self, transform: &mut Transform, yaw: f32, pitch: f32) {
+        self.yaw = yaw;
+        self.pitch = pitch;
+        transform.rotation = Quat::from_rotation_y(yaw) * Quat::from_rotation_x(pitch);
+    }
+}
+
+// The player's camera options
+#[derive(Resource, Debug, Default)]
+pub struct CameraSettings {
+    pub follow_dice: bool, // Chase the dice down the table on every throw (C to toggle)
+}
+
+const CHASE_OFFSET: Vec3 = Vec3::new(-2.2, 1.6, 0.0); // Behind (-x) and above the dice
+const CHASE_STIFFNESS: f32 = 6.0; // How quickly the chase camera catches up - higher is tighter
+
+const MAX_PITCH: f32 = 1.54; // ~88 degrees - looking straight up or down flips the view
+
+// A place to stand and a way to look, that the camera can jump to with a hotkey
+#[derive(Debug, Clone, Copy)]
+pub struct CameraPreset {
+    pub name: &'static str,
+    pub position: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+impl CameraPreset {
+    // A preset standing at `position` and looking at `target`
+    fn looking_at(name: &'static str, position: Vec3, target: Vec3) -> Self {
+        // The camera looks down -z before it's turned, so work back from the direction
+        // to the target to the yaw and pitch that point it there
+        let direction = (target - position).normalize();
+        Self {
+            name,
+            position,
+            yaw: (-direction.x).atan2(-direction.z),
+            pitch: direction.y.asin().clamp(-MAX_PITCH, MAX_PITCH),
+        }
+    }
+}
+
+// The camera presets, in hotkey order (1, 2, 3). The shooter stands at the -x end of
+// the table throwing towards +x.
+pub fn camera_presets() -> [CameraPreset; 3] {
+    [
+        CameraPreset::looking_at(
+            "Shooter's view",
+            Vec3::new(-5.5, 2.2, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+        ),
+        CameraPreset::looking_at(
+            "Overhead layout view",
+            Vec3::new(-0.3, 9.0, 0.0),
+            Vec3::ZERO,
+        ),
+        CameraPreset::looking_at(
+            "Low side view",
+            Vec3::new(0.0, 0.8, -5.0),
+            Vec3::new(0.0, 0.2, 0.0),
+        ),
+    ]
+}
+
+// The camera gliding from one view to another
+#[derive(Debug, Clone, Copy)]
+struct CameraFlight {
+    from: CameraPreset, // Where the camera was when the hotkey was pressed
+    to: CameraPreset,
+    progress: f32, // 0.0 = just left, 1.0 = arrived
+}
+
+const CAMERA_FLIGHT_SECONDS: f32 = 0.8;
+
+// Spawn a camera - this is our "eyes" in the 3D world
+fn spawn_camera(mut commands: Commands, settings: Res<Settings>) {
+    commands.spawn((
+        StateScoped(InGame),
+        // spawn() creates a new entity, the double parentheses group components
+        Camera3d::default(), // A standard 3D camera
+        Camera {
+            hdr: settings.graphics.hdr(), // Bloom is added by the graphics quality system
+            ..default()
+        },
+        Projection::from(PerspectiveProjection {
+            fov: settings.fov.to_radians(), // Field of view - 35 degrees unless changed in the settings
+            near: 0.1,  // Closest distance we can see (anything closer is invisible)
+            far: 100.0, // Farthest distance we can see (anything further is invisible)
+            aspect_ratio: 16.0 / 9.0, // Width/height ratio - matches most monitors
+        }),
+        Transform::from_xyz(-10.0, 6.5, -5.0) // Position: 10 units left, 6.5 up, 5 back
+            .looking_at(Vec3::ZERO, Vec3::Y), // Point camera at origin (0,0,0)
+        // Vec3::Y means "up" is the Y direction
+        PlayerCamera {
+            yaw: std::f32::consts::FRAC_PI_4, // Start rotated 45 degrees (π/4 radians)
+            pitch: -0.2,                      // Slightly tilted down
+            flight: None,
+            mode: CameraMode::Free,
+        },
+        SpatialListener::new(0.3), // Our ears, 0.3 units apart - spatial sounds are heard from here
+    ));
+}
+
+// The darkened corners of the screen, drawn as a picture over the table (under the
+// rest of the interface)
+#[derive(Component)]
+pub struct Vignette;
+
+const VIGNETTE_SIZE: u32 = 128; // Pixels across - it's stretched over the whole screen
+const VIGNETTE_DARKNESS: f32 = 0.55; // How dark the very corners get
+
+fn spawn_vignette(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    // Clear in the middle, fading to black towards the edges. A corner is 1.0 away
+    // from the middle and the middle of an edge about 0.7, so the fade starts just
+    // inside the edges and is darkest in the corners.
+    let mut pixels = Vec::with_capacity((VIGNETTE_SIZE * VIGNETTE_SIZE * 4) as usize);
+    let half = VIGNETTE_SIZE as f32 / 2.0;
+    for y in 0..VIGNETTE_SIZE {
+        for x in 0..VIGNETTE_SIZE {
+            let offset = (Vec2::new(x as f32, y as f32) + 0.5 - half) / half;
+            let distance = offset.length() / std::f32::consts::SQRT_2;
+            let fade = ((distance - 0.45) / 0.55).clamp(0.0, 1.0);
+            let alpha = fade * fade * (3.0 - 2.0 * fade) * VIGNETTE_DARKNESS; // Smoothstep
+            pixels.extend_from_slice(&[0, 0, 0, (alpha * 255.0) as u8]);
+        }
+    }
+    let image = Image::new(
+        Extent3d {
+            width: VIGNETTE_SIZE,
+            height: VIGNETTE_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        pixels,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    commands.spawn((
+        StateScoped(InGame),
+        ImageNode::new(images.add(image)),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        GlobalZIndex(-1),   // Under every panel and button
+        Pickable::IGNORE,   // Clicks go straight through to the table
+        Visibility::Hidden, // Shown by the graphics quality system
+        Vignette,
+    ));
+}
+
+// System to control camera rotation (like a first-person game) - with the mouse while
+// the right button is held, or with a gamepad's right stick
+// Systems are functions that run every frame to update the game
+fn camera_control_system(
+    input: Res<PlayerInput>, // What the player did this frame, from any device
+    mut cam_q: Query<(&mut PlayerCamera, &mut Transform)>, // Find entities with both components
+                             // Query is like a database search - "find all things with X and Y"
+) {
+    if input.look == Vec2::ZERO {
+        // No movement? Nothing to do
+        return;
+    }
+
+    // Get the camera entity (should only be one)
+    let Ok((mut cam, mut transform)) = cam_q.single_mut() else {
+        return; // If no camera found or multiple cameras, exit
+    };
+    // This is a "let-else" pattern - like try-catch but cleaner
+    if cam.flight.is_some() || cam.is_directed() {
+        return; // Flying to a preset or in a cinematic - looking around would fight it
+    }
+    cam.yaw -= input.look.x; // Horizontal rotation (negative because mouse right = look right)
+    cam.pitch = (cam.pitch - input.look.y) // Vertical rotation
+        .clamp(-MAX_PITCH, MAX_PITCH); // Limit to ~88 degrees up/down to prevent flipping
+
+    transform.rotation = Quat::from_rotation_y(cam.yaw) * Quat::from_rotation_x(cam.pitch);
+}
+
+// System that hides and locks the cursor only while the mouse is steering, so it can
+// still be used to point at the table the rest of the time. Browsers put up a
+// "press Esc" banner every time a page locks the pointer, so the web build leaves it out.
+#[cfg(not(target_arch = "wasm32"))]
+fn cursor_grab_system(
+    input: Res<PlayerInput>,
+    mut window_q: Query<&mut Window, With<PrimaryWindow>>, // Find the main window
+                                                           // With<T> = "must also have component T"
+) {
+    let Ok(mut window) = window_q.single_mut() else {
+        return;
+    };
+    let grab_mode = if input.mouse_look {
+        CursorGrabMode::Locked
+    } else {
+        CursorGrabMode::None
+    };
+    if window.cursor_options.grab_mode != grab_mode {
+        window.cursor_options.grab_mode = grab_mode;
+        window.cursor_options.visible = !input.mouse_look;
+    }
+}
+
+// Hand the cursor back whenever play stops, e.g. for the pause menu. The grab comes
+// back with the next mouse-look.
+#[cfg(not(target_arch = "wasm32"))]
+fn release_cursor(mut window_q: Query<&mut Window, With<PrimaryWindow>>) {
+    if let Ok(mut window) = window_q.single_mut() {
+        window.cursor_options.grab_mode = CursorGrabMode::None;
+        window.cursor_options.visible = true;
+    }
+}
+
+// System that keeps the camera's field of view matching the settings
+fn camera_fov_system(
+    settings: Res<Settings>,
+    mut projection_q: Query<&mut Projection, With<PlayerCamera>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    for mut projection in &mut projection_q {
+        if let Projection::Perspective(perspective) = &mut *projection {
+            perspective.fov = settings.fov.to_radians();
+        }
+    }
+}
+
+// System that starts a flight to a camera preset when its hotkey is pressed, and
+// moves the camera along any flight in progress
+fn camera_preset_system(
+    input: Res<PlayerInput>,
+    time: Res<Time>,
+    mut cam_q: Query<(&mut PlayerCamera, &mut Transform)>,
+) {
+    let Ok((mut cam, mut transform)) = cam_q.single_mut() else {
+        return;
+    };
+
+    if let Some(preset) = input
+        .camera_preset
+        .and_then(|index| camera_presets().get(index).copied())
+    {
+        info!("Camera: {}", preset.name);
+        cam.mode = CameraMode::Free; // A hotkey takes the camera back from any cinematic
+        cam.flight = Some(CameraFlight {
+            from: CameraPreset {
+                name: "",
+                position: transform.translation,
+                yaw: cam.yaw,
+                pitch: cam.pitch,
+            },
+            to: preset,
+            progress: 0.0,
+        });
+    }
+
+    let Some(flight) = &mut cam.flight else {
+        return;
+    };
+    flight.progress = (flight.progress + time.delta_secs() / CAMERA_FLIGHT_SECONDS).min(1.0);
+    // Ease in and out, so the camera doesn't lurch off or stop dead
+    let t = flight.progress * flight.progress * (3.0 - 2.0 * flight.progress);
+    let (from, to) = (flight.from, flight.to);
+    let done = flight.progress >= 1.0;
+
+    transform.translation = from.position.lerp(to.position, t);
+    // Turn the short way round: a quarter turn left rather than three quarters right
+    let turn = (to.yaw - from.yaw + PI).rem_euclid(TAU) - PI;
+    cam.yaw = from.yaw + turn * t;
+    cam.pitch = from.pitch + (to.pitch - from.pitch) * t;
+    transform.rotation = Quat::from_rotation_y(cam.yaw) * Quat::from_rotation_x(cam.pitch);
+    if done {
+        cam.flight = None;
+    }
+}
+
+// System that runs the follow camera: it starts chasing the dice when they leave the
+// hand, keeps them in view while they roll, and cuts back when they stop
+fn dice_follow_system(
+    input: Res<PlayerInput>,
+    time: Res<Time>,
+    mut settings: ResMut<CameraSettings>,
+    roll: Res<RollState>,
+    dice_q: Query<(&Transform, &Visibility), (With<Dice>, Without<PlayerCamera>)>,
+    mut cam_q: Query<(&mut PlayerCamera, &mut Transform)>,
+    mut following: Local<bool>, // This system has the camera
+) {
+    if input.toggle_follow_camera {
+        settings.follow_dice = !settings.follow_dice;
+        info!(
+            "Follow camera {}",
+            if settings.follow_dice { "on" } else { "off" }
+        );
+    }
+    let Ok((mut cam, mut transform)) = cam_q.single_mut() else {
+        return;
+    };
+
+    if !*following {
+        // Only take the camera if nothing else (the instant replay) has it
+        if settings.follow_dice && roll.in_flight() && !cam.is_directed() {
+            cam.take_over(&transform);
+            *following = true;
+        }
+        return;
+    }
+    if !roll.in_flight() || !settings.follow_dice || !cam.is_directed() {
+        // Settled (or off the table, or follow switched off) - cut straight back
+        cam.release(&mut transform);
+        *following = false;
+        return;
+    }
+
+    // Aim at the middle of the dice still on the table
+    let in_play: Vec<Vec3> = dice_q
+        .iter()
+        .filter(|(_, visibility)| **visibility != Visibility::Hidden)
+        .map(|(die, _)| die.translation)
+        .collect();
+    if in_play.is_empty() {
+        return;
+    }
+    let middle = in_play.iter().sum::<Vec3>() / in_play.len() as f32;
+
+    // Glide towards the chase spot rather than jumping, so bounces don't shake the picture
+    let catch_up = 1.0 - (-CHASE_STIFFNESS * time.delta_secs()).exp();
+    let position = transform.translation.lerp(middle + CHASE_OFFSET, catch_up);
+    cam.point(&mut transform, position, middle);
+}
+
+// Find where the mouse cursor points on the table top, as (x, z)
+pub fn cursor_on_table(
+    window: &Window,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+) -> Option<Vec2> {
+    let cursor = window.cursor_position()?; // None when the cursor is outside the window
+    screen_to_table(camera, camera_transform, cursor)
+}
+
+// Find the spot on the table top under a point on the screen (the cursor, or a
+// finger), as (x, z)
+pub fn screen_to_table(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    screen_position: Vec2,
+) -> Option<Vec2> {
+    // Shoot a ray from the camera through that point into the 3D world...
+    let ray = camera
+        .viewport_to_world(camera_transform, screen_position)
+        .ok()?;
+    // ...and find where it crosses the flat plane of the table (y = 0, facing up)
+    let distance = ray.intersect_plane(Vec3::ZERO, InfinitePlane3d::new(Vec3::Y))?;
+    let hit = ray.get_point(distance);
+    Some(Vec2::new(hit.x, hit.z))
+}
@@ -0,0 +1,230 @@
+// Confetti and sparks for the big moments: when the shooter makes the point, a burst
+// goes up over the dice and over every bet zone that won, and a big prop bet paying
+// out gets a burst of its own. The particles are plain little meshes that fly, fall
+// and fade on their own - no particle plugin needed for a few hundred bits of paper.
+use bevy::color::prelude::*;
+use bevy::prelude::*;
+use rand::Rng;
+
+use super::betting::{BetResolved, RollResolved};
+use super::dice::Dice;
+use super::{AppState, InGame, RollStep};
+use crate::betting::{BetKind, BetResult};
+use crate::layout::TableLayout;
+use crate::rules::RollOutcome;
+
+pub struct CelebrationPlugin;
+
+impl Plugin for CelebrationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_particle_assets).add_systems(
+            Update,
+            (
+                // Once the roll has been judged and every bet paid
+                celebrate_system.after(RollStep::Pay),
+                particle_system,
+            )
+                .run_if(in_state(AppState::Playing)),
+        );
+    }
+}
+
+// One bit of confetti or one spark, flying until it runs out of life
+#[derive(Component)]
+struct Particle {
+    velocity: Vec3,
+    spin: Vec3,    // How fast it tumbles, in radians/second around each axis
+    gravity: f32,  // Confetti flutters down slowly, sparks drop like stones
+    drag: f32,     // How quickly the air slows it down
+    age: f32,      // Seconds since it was spawned
+    lifetime: f32, // Seconds it lasts before it's gone
+}
+
+// The shapes and colours every burst shares
+#[derive(Resource)]
+struct ParticleAssets {
+    confetti: Handle<Mesh>,
+    spark: Handle<Mesh>,
+    confetti_colors: Vec<Handle<StandardMaterial>>,
+    spark_material: Handle<StandardMaterial>,
+}
+
+const CONFETTI_PER_BURST: usize = 40;
+const SPARKS_PER_BURST: usize = 20;
+const FADE_SECONDS: f32 = 0.4; // Particles shrink away over their last moments
+// A one-roll bet winning at least this many times its stake is worth celebrating
+const BIG_PROP_MULTIPLE: u64 = 7;
+
+fn setup_particle_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let confetti_colors = [
+        "#FF5300", // Papaya orange
+        "#FFC72C", // Gold
+        "#C8102E", // Casino red
+        "#F4F1EA", // Ivory
+    ]
+    .into_iter()
+    .map(|hex| {
+        materials.add(StandardMaterial {
+            base_color: Srgba::hex(hex).unwrap().into(),
+            perceptual_roughness: 0.6,
+            double_sided: true, // Paper is seen from both sides as it tumbles
+            cull_mode: None,
+            ..default()
+        })
+    })
+    .collect();
+    commands.insert_resource(ParticleAssets {
+        confetti: meshes.add(Cuboid::new(0.06, 0.004, 0.035)), // A flat scrap of paper
+        spark: meshes.add(Sphere::new(0.018)),
+        confetti_colors,
+        spark_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(1.0, 0.8, 0.4),
+            emissive: LinearRgba::rgb(8.0, 4.0, 1.0), // Glows brighter than anything lit
+            ..default()
+        }),
+    });
+}
+
+// Throw a burst of confetti and sparks up from a spot on (or above) the table
+fn burst(commands: &mut Commands, assets: &ParticleAssets, at: Vec3) {
+    // Not the throw's RNG - confetti mustn't change which dice a seed rolls
+    let mut rng = rand::thread_rng();
+
+    for index in 0..CONFETTI_PER_BURST {
+        let velocity = spray(&mut rng, 4.0, 1.5);
+        let spin = Vec3::new(
+            rng.gen_range(-12.0..=12.0),
+            rng.gen_range(-12.0..=12.0),
+            rng.gen_range(-12.0..=12.0),
+        );
+        let color = assets.confetti_colors[index % assets.confetti_colors.len()].clone();
+        commands.spawn((
+            StateScoped(InGame),
+            Mesh3d(assets.confetti.clone()),
+            MeshMaterial3d(color),
+            Transform::from_translation(at),
+            Particle {
+                velocity,
+                spin,
+                gravity: 4.0,
+                drag: 2.5,
+                age: 0.0,
+                lifetime: 2.2,
+            },
+        ));
+    }
+    for _ in 0..SPARKS_PER_BURST {
+        let velocity = spray(&mut rng, 5.0, 2.5);
+        commands.spawn((
+            StateScoped(InGame),
+            Mesh3d(assets.spark.clone()),
+            MeshMaterial3d(assets.spark_material.clone()),
+            Transform::from_translation(at),
+            Particle {
+                velocity,
+                spin: Vec3::ZERO,
+                gravity: 9.81,
+                drag: 0.8,
+                age: 0.0,
+                lifetime: 0.9,
+            },
+        ));
+    }
+}
+
+// A random launch: upwards by between 60% and all of `up`, and up to `out` to the sides
+fn spray(rng: &mut impl Rng, up: f32, out: f32) -> Vec3 {
+    Vec3::new(
+        rng.gen_range(-out..=out),
+        rng.gen_range(up * 0.6..=up),
+        rng.gen_range(-out..=out),
+    )
+}
+
+// The one-roll bets in the middle of the table, and every bet at sic bo
+fn is_prop(kind: BetKind) -> bool {
+    matches!(
+        kind,
+        BetKind::AnySeven
+            | BetKind::AnyCraps
+            | BetKind::Horn
+            | BetKind::CAndE
+            | BetKind::Hop(_, _)
+            | BetKind::SicBo(_)
+    )
+}
+
+// System that sets off the bursts for a roll worth celebrating
+fn celebrate_system(
+    mut commands: Commands,
+    assets: Res<ParticleAssets>,
+    table_layout: Res<TableLayout>,
+    mut resolved_events: EventReader<RollResolved>,
+    mut payout_events: EventReader<BetResolved>,
+    dice_q: Query<&GlobalTransform, With<Dice>>,
+) {
+    let point_made = resolved_events
+        .read()
+        .any(|resolved| matches!(resolved.outcome, RollOutcome::PointMade(_)));
+
+    // Where the chips sit for every win worth a burst
+    let mut winners = Vec::new();
+    for payout in payout_events.read() {
+        let BetResult::Win(profit) = payout.result else {
+            continue;
+        };
+        let big_prop = is_prop(payout.bet.kind) && profit >= payout.bet.amount * BIG_PROP_MULTIPLE;
+        if point_made || big_prop {
+            let spot = table_layout.chip_anchor(payout.bet.kind);
+            let at = Vec3::new(spot.x, 0.1, spot.y);
+            // Two bets on the same spot share one burst
+            if !winners.contains(&at) {
+                winners.push(at);
+            }
+        }
+    }
+
+    if point_made {
+        for die in &dice_q {
+            burst(&mut commands, &assets, die.translation() + Vec3::Y * 0.2);
+        }
+    }
+    for at in winners {
+        burst(&mut commands, &assets, at);
+    }
+}
+
+// System that flies, tumbles and fades every particle, and clears away the spent ones
+fn particle_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut particle_q: Query<(Entity, &mut Particle, &mut Transform)>,
+) {
+    let dt = time.delta_secs();
+    for (entity, mut particle, mut transform) in &mut particle_q {
+        particle.age += dt;
+        if particle.age >= particle.lifetime {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        // Pulled down, slowed by the air, and never through the felt
+        particle.velocity.y -= particle.gravity * dt;
+        let slowdown = (-particle.drag * dt).exp();
+        particle.velocity *= slowdown;
+        transform.translation += particle.velocity * dt;
+        if transform.translation.y < 0.01 {
+            transform.translation.y = 0.01;
+            particle.velocity = Vec3::ZERO; // Landed - it lies there until it fades
+        }
+
+        let spin = particle.spin * dt;
+        transform.rotate(Quat::from_euler(EulerRot::XYZ, spin.x, spin.y, spin.z));
+        let left = particle.lifetime - particle.age;
+        transform.scale = Vec3::splat((left / FADE_SECONDS).min(1.0));
+    }
+}
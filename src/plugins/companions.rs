@@ -0,0 +1,382 @@
+// Computer players for a solo table (how many is on the settings screen). They stand
+// round the shooter's end of the table, bet the way their personality likes (see
+// src/companions.rs) before every roll, and cheer or groan when a roll pays them or
+// takes their money. Their bets show on the felt as small markers in their own colour,
+// beside the player's chip piles, and a panel on the left keeps track of their money.
+//
+// Hotseat and networked tables already have real people round them, so companions
+// only sit down at a table for one - and only for craps, not sic bo.
+use bevy::audio::{AudioSinkPlayback, Volume};
+use bevy::color::prelude::*;
+use bevy::prelude::*;
+
+use super::dice::RollSettled;
+use super::hotseat::Hotseat;
+use super::lifecycle::PhaseChanged;
+use super::net::{NetClient, NetHost};
+use super::rules::RulesChoice;
+use super::settings::Settings;
+use super::sound::{SoundChannel, table_sound};
+use super::{AppState, InGame, RollStep};
+use crate::companions::{Companion, MAX_COMPANIONS, Personality, Reaction};
+use crate::layout::TableLayout;
+use crate::locale::Locale;
+use crate::payout::{break_into_chips, format_money};
+use crate::rules::{RoundPhase, TableRules, Variant};
+
+pub struct CompanionsPlugin;
+
+impl Plugin for CompanionsPlugin {
+    fn build(&self, app: &mut App) {
+        // main.rs inserts these before the plugins are added. The computer players
+        // only know how to play craps.
+        let world = app.world();
+        let sic_bo = world
+            .get_resource::<RulesChoice>()
+            .is_some_and(|choice| choice.variant == Some(Variant::SicBo));
+        if world.contains_resource::<Hotseat>()
+            || world.contains_resource::<NetHost>()
+            || world.contains_resource::<NetClient>()
+            || sic_bo
+        {
+            return;
+        }
+
+        app.add_systems(
+            OnEnter(InGame),
+            (spawn_companions, setup_companion_panel).chain(),
+        )
+        .add_systems(
+            Update,
+            (
+                companion_count_system,
+                // Once the player's own bets have been paid
+                companion_roll_system.after(RollStep::Pay),
+                companion_phase_system,
+                companion_markers_system,
+                companion_panel_system,
+                reaction_fade_system,
+            )
+                .run_if(in_state(AppState::Playing)),
+        );
+    }
+}
+
+// Each companion's colour, by seat
+const COMPANION_COLORS: [&str; MAX_COMPANIONS] = [
+    "#27AE60", // Green
+    "#8E44AD", // Purple
+    "#E67E22", // Orange
+];
+
+// Where each companion stands, out of five places round the shooter's end: the player
+// has the middle one, and the careful one stands next to them
+const COMPANION_PLACES: [usize; MAX_COMPANIONS] = [1, 3, 0];
+const PLACES: usize = 5;
+
+const MARKER_RADIUS: f32 = 0.045;
+const MARKER_HEIGHT: f32 = 0.02;
+const MARKER_SPREAD: f32 = 0.16; // How far from the bet's usual spot each companion's pile sits
+const MAX_MARKERS_SHOWN: usize = 10;
+const REACTION_VOLUME: f32 = 0.35;
+const REACTION_SECONDS: f32 = 1.2; // A cheer or a groan is a short burst of the crowd sound
+
+// Which companion this is, and how they look on the felt and the panel
+#[derive(Component)]
+struct CompanionSeat {
+    seat: usize,
+    color: Color,
+    material: Handle<StandardMaterial>,
+}
+
+// A pile of markers showing one of a companion's bets
+#[derive(Component)]
+struct CompanionMarker;
+
+#[derive(Component)]
+struct CompanionPanel;
+
+// A cheer or groan playing, fading out as it ends
+#[derive(Component)]
+struct ReactionSound {
+    age: f32,
+}
+
+// Sit one companion down and have them bet on the coming roll
+fn seat_companion(
+    commands: &mut Commands,
+    materials: &mut Assets<StandardMaterial>,
+    seat: usize,
+    phase: RoundPhase,
+    rules: &TableRules,
+) {
+    let personality = Personality::ALL[seat];
+    let color: Color = Srgba::hex(COMPANION_COLORS[seat]).unwrap().into();
+    let mut companion = Companion::new(personality, phase);
+    companion.place_bets(rules);
+    commands.spawn((
+        StateScoped(InGame),
+        companion,
+        CompanionSeat {
+            seat,
+            color,
+            material: materials.add(StandardMaterial {
+                base_color: color,
+                perceptual_roughness: 0.6,
+                ..default()
+            }),
+        },
+        Name::new(personality.name()),
+    ));
+}
+
+// Seat as many companions as the settings ask for as the table is set up
+fn spawn_companions(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    phase: Res<RoundPhase>,
+    rules: Res<TableRules>,
+) {
+    for seat in 0..settings.companions.min(MAX_COMPANIONS) {
+        seat_companion(&mut commands, &mut materials, seat, *phase, &rules);
+    }
+}
+
+// System that seats or sends away companions when the number in the settings changes.
+// The ones already at the table keep their money.
+fn companion_count_system(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    phase: Res<RoundPhase>,
+    rules: Res<TableRules>,
+    companions_q: Query<(Entity, &CompanionSeat)>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    let wanted = settings.companions.min(MAX_COMPANIONS);
+    let mut seated = [false; MAX_COMPANIONS];
+    for (entity, seat) in &companions_q {
+        if seat.seat >= wanted {
+            commands.entity(entity).despawn();
+        } else {
+            seated[seat.seat] = true;
+        }
+    }
+    for seat in (0..wanted).filter(|&seat| !seated[seat]) {
+        seat_companion(&mut commands, &mut materials, seat, *phase, &rules);
+    }
+}
+
+// Where a companion stands, just inside the rail
+fn companion_position(table_layout: &TableLayout, seat: usize) -> Vec3 {
+    let spot = table_layout.seat_position(COMPANION_PLACES[seat], PLACES);
+    Vec3::new(spot.x, 1.2, spot.y) // About head height
+}
+
+// System that settles every companion's bets against the roll, lets the happiest (or
+// unhappiest) of them react out loud, and has them all bet again for the next roll
+fn companion_roll_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
+    rules: Res<TableRules>,
+    table_layout: Res<TableLayout>,
+    mut settled_events: EventReader<RollSettled>,
+    mut companions_q: Query<(&mut Companion, &CompanionSeat)>,
+) {
+    for settled in settled_events.read() {
+        let mut loudest = None;
+        for (mut companion, seat) in &mut companions_q {
+            let reaction = companion.roll(settled.die_values, &rules);
+            companion.place_bets(&rules);
+            // One voice a roll is plenty, and a cheer drowns out a groan
+            match (reaction, loudest) {
+                (Some(Reaction::Cheer), _) | (Some(Reaction::Groan), None) => {
+                    loudest = reaction.map(|reaction| (reaction, seat.seat));
+                }
+                _ => {}
+            }
+        }
+
+        let Some((reaction, seat)) = loudest else {
+            continue;
+        };
+        // The crowd sound, sped up into a cheer or slowed down into a groan
+        let speed = match reaction {
+            Reaction::Cheer => 1.25,
+            Reaction::Groan => 0.7,
+        };
+        commands.spawn((
+            StateScoped(InGame),
+            AudioPlayer::new(asset_server.load("sounds/crowd.wav")),
+            table_sound(settings.audio.level(SoundChannel::Sfx) * REACTION_VOLUME)
+                .with_speed(speed),
+            Transform::from_translation(companion_position(&table_layout, seat)),
+            ReactionSound { age: 0.0 },
+        ));
+    }
+}
+
+// System that moves the companions' rounds along with ours when it's set straight to a
+// phase (the debug console), so they don't bet as if the puck were somewhere else. A
+// roll moves their rounds on by itself.
+fn companion_phase_system(
+    mut phase_events: EventReader<PhaseChanged>,
+    rules: Res<TableRules>,
+    mut companions_q: Query<&mut Companion>,
+) {
+    for changed in phase_events.read().filter(|changed| changed.jumped) {
+        for mut companion in &mut companions_q {
+            companion.table.phase = changed.to;
+            companion.place_bets(&rules);
+        }
+    }
+}
+
+// System that fades each cheer or groan away and stops it once it's had its moment
+fn reaction_fade_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut sounds_q: Query<(Entity, &mut ReactionSound, Option<&mut AudioSink>)>,
+) {
+    for (entity, mut sound, sink) in &mut sounds_q {
+        sound.age += time.delta_secs();
+        if sound.age >= REACTION_SECONDS {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        // Full volume for the first half, then down to nothing
+        let left = 1.0 - sound.age / REACTION_SECONDS;
+        if let Some(mut sink) = sink {
+            sink.set_volume(Volume::Linear(
+                settings.audio.level(SoundChannel::Sfx) * REACTION_VOLUME * (left * 2.0).min(1.0),
+            ));
+        }
+    }
+}
+
+// System that draws every companion's bets as small piles of markers in their colour.
+// Each companion's piles sit to one side of the bet's usual spot, so they don't land on
+// top of the player's chips or each other's.
+fn companion_markers_system(
+    mut commands: Commands,
+    companions_q: Query<(&Companion, &CompanionSeat)>,
+    changed_q: Query<(), Changed<Companion>>,
+    mut removed: RemovedComponents<Companion>,
+    markers_q: Query<Entity, With<CompanionMarker>>,
+    table_layout: Res<TableLayout>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut mesh: Local<Option<Handle<Mesh>>>,
+) {
+    let removed_any = removed.read().count() > 0;
+    if changed_q.is_empty() && !removed_any {
+        return;
+    }
+    let mesh = mesh
+        .get_or_insert_with(|| meshes.add(Cylinder::new(MARKER_RADIUS, MARKER_HEIGHT)))
+        .clone();
+
+    for marker in &markers_q {
+        commands.entity(marker).despawn();
+    }
+    for (companion, seat) in &companions_q {
+        // Spread round the spot, a third of a turn apart
+        let angle = std::f32::consts::FRAC_PI_4 + seat.seat as f32 * std::f32::consts::TAU / 3.0;
+        let offset = Vec2::new(angle.cos(), angle.sin()) * MARKER_SPREAD;
+        for bet in &companion.table.bets.bets {
+            let spot = table_layout.chip_anchor(bet.kind) + offset;
+            let count = break_into_chips(bet.amount)
+                .len()
+                .clamp(1, MAX_MARKERS_SHOWN);
+            commands
+                .spawn((
+                    StateScoped(InGame),
+                    CompanionMarker,
+                    Transform::from_xyz(spot.x, 0.0, spot.y),
+                    Visibility::default(),
+                    Name::new(format!("{} {:?}", companion.personality.name(), bet.kind)),
+                ))
+                .with_children(|parent| {
+                    for height in 0..count {
+                        parent.spawn((
+                            Mesh3d(mesh.clone()),
+                            MeshMaterial3d(seat.material.clone()),
+                            Transform::from_xyz(0.0, MARKER_HEIGHT * (height as f32 + 0.5), 0.0),
+                        ));
+                    }
+                });
+        }
+    }
+}
+
+fn setup_companion_panel(mut commands: Commands) {
+    // Low on the left-hand side, above the power meter. Filled in by
+    // companion_panel_system.
+    commands.spawn((
+        StateScoped(InGame),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(20.0),
+            bottom: Val::Percent(25.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(4.0),
+            padding: UiRect::all(Val::Px(10.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.05, 0.8)),
+        BorderRadius::all(Val::Px(6.0)),
+        Visibility::Hidden,
+        CompanionPanel,
+    ));
+}
+
+// System that lists each companion and their money, in their colour
+fn companion_panel_system(
+    mut commands: Commands,
+    companions_q: Query<(&Companion, &CompanionSeat)>,
+    changed_q: Query<(), Changed<Companion>>,
+    mut removed: RemovedComponents<Companion>,
+    locale: Res<Locale>,
+    mut panel_q: Query<(Entity, &mut Visibility), With<CompanionPanel>>,
+) {
+    let removed_any = removed.read().count() > 0;
+    if changed_q.is_empty() && !removed_any && !locale.is_changed() {
+        return;
+    }
+    let Ok((panel, mut visibility)) = panel_q.single_mut() else {
+        return;
+    };
+    let mut companions: Vec<_> = companions_q.iter().collect();
+    companions.sort_by_key(|(_, seat)| seat.seat);
+    *visibility = if companions.is_empty() {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    };
+
+    commands.entity(panel).despawn_related::<Children>();
+    commands.entity(panel).with_children(|parent| {
+        for (companion, seat) in companions {
+            let table = &companion.table;
+            parent.spawn((
+                Text::new(locale.fill(
+                    "{name}  {money} ({at_risk} on the table)",
+                    &[
+                        ("name", &companion.personality.name()),
+                        ("money", &format_money(table.bankroll.balance)),
+                        ("at_risk", &format_money(table.bets.total_at_risk())),
+                    ],
+                )),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(seat.color),
+            ));
+        }
+    });
+}
@@ -0,0 +1,237 @@
+// The debug console, for testing payouts and the UI without throwing the dice until the
+// right number comes up. Backtick (`) opens it along the top of the screen; type a
+// command (the list is in src/console.rs, or type `help`) and press Enter. Only
+// added with `--features dev` - `bankroll 500` would make the saved bankroll meaningless.
+use bevy::input::ButtonState;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::prelude::*;
+
+use super::dice::{Dice, DiceId, DiceValue, RollSettled, RollState, ThrowRng, showing_face};
+use super::lifecycle::{PhaseChanged, jump_phase};
+use super::net::NetClient;
+use super::{AppState, InGame};
+use crate::console::{ConsoleCommand, HELP};
+use crate::payout::{Bankroll, format_money};
+use crate::rules::{RoundPhase, TableRules, Variant};
+
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ConsoleState>()
+            .add_event::<ConsoleCommand>()
+            .add_systems(OnEnter(InGame), (setup_console, close_console))
+            .add_systems(
+                Update,
+                (
+                    toggle_console_system,
+                    console_typing_system,
+                    run_command_system,
+                    console_display_system,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Playing)),
+            );
+    }
+}
+
+// Whether the console is open, what's being typed, and the lines printed so far
+#[derive(Resource, Debug, Default)]
+pub struct ConsoleState {
+    pub open: bool,
+    line: String,
+    output: Vec<String>, // Oldest first
+}
+
+impl ConsoleState {
+    fn print(&mut self, text: impl Into<String>) {
+        self.output.push(text.into());
+        let excess = self.output.len().saturating_sub(CONSOLE_LINES);
+        self.output.drain(..excess);
+    }
+}
+
+const CONSOLE_LINES: usize = 8; // How many printed lines are kept above the prompt
+
+#[derive(Component)]
+struct ConsolePanel;
+
+#[derive(Component)]
+struct ConsoleText;
+
+// A fresh table starts with the console shut
+fn close_console(mut console: ResMut<ConsoleState>) {
+    console.open = false;
+}
+
+fn setup_console(mut commands: Commands) {
+    // A dark strip across the top of the screen, over everything else
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(0.0),
+                width: Val::Percent(100.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+            GlobalZIndex(2),
+            Visibility::Hidden,
+            ConsolePanel,
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.6, 1.0, 0.6)),
+                ConsoleText,
+            ));
+        });
+}
+
+// System that opens and closes the console with backtick
+fn toggle_console_system(keys: Res<ButtonInput<KeyCode>>, mut console: ResMut<ConsoleState>) {
+    if keys.just_pressed(KeyCode::Backquote) {
+        console.open = !console.open;
+    }
+}
+
+// System that types into the console while it's open, and reads each line as a
+// command when Enter is pressed. Escape closes it.
+fn console_typing_system(
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut console: ResMut<ConsoleState>,
+    mut command_events: EventWriter<ConsoleCommand>,
+) {
+    for event in keyboard_events.read() {
+        if !console.open || event.state != ButtonState::Pressed {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Enter => {
+                let line = std::mem::take(&mut console.line);
+                if line.trim().is_empty() {
+                    continue;
+                }
+                console.print(format!("> {line}"));
+                match ConsoleCommand::parse(&line) {
+                    Ok(command) => {
+                        command_events.write(command);
+                    }
+                    Err(error) => console.print(error.to_string()),
+                }
+            }
+            Key::Backspace => {
+                console.line.pop();
+            }
+            Key::Escape => console.open = false,
+            Key::Space => console.line.push(' '),
+            // The backtick that opened (or closes) the console isn't part of the command
+            Key::Character(text) if text.as_str() != "`" => console.line.push_str(text),
+            _ => {}
+        }
+    }
+}
+
+// System that carries out each command at the table
+fn run_command_system(
+    mut command_events: EventReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    mut bankroll: ResMut<Bankroll>,
+    mut phase: ResMut<RoundPhase>,
+    mut phase_events: EventWriter<PhaseChanged>,
+    mut throw_rng: ResMut<ThrowRng>,
+    roll: Res<RollState>,
+    rules: Res<TableRules>,
+    client: Option<Res<NetClient>>,
+    mut commands: Commands,
+    mut dice_q: Query<(Entity, &DiceId, &mut Transform), With<Dice>>,
+    mut settled_events: EventWriter<RollSettled>,
+) {
+    for &command in command_events.read() {
+        match command {
+            // The host's table decides the rolls and keeps our money
+            ConsoleCommand::Roll(_) | ConsoleCommand::Bankroll(_) | ConsoleCommand::State(_)
+                if client.is_some() =>
+            {
+                console.print("Not at someone else's table - the host decides that");
+            }
+            // A roll is two dice, and a sic bo table has three
+            ConsoleCommand::Roll(_) if rules.variant == Variant::SicBo => {
+                console.print("Not at a sic bo table - throw the dice instead");
+            }
+            ConsoleCommand::Roll(die_values) => {
+                if roll.in_flight() {
+                    console.print("Wait for the dice to stop first");
+                    continue;
+                }
+                // Turn the dice over to show the roll, then settle it like a real one
+                for (entity, id, mut transform) in &mut dice_q {
+                    let value = die_values[usize::from(id.0 - 1)]; // DiceId is 1-based
+                    transform.rotation = showing_face(transform.rotation, value);
+                    commands.entity(entity).insert(DiceValue(value));
+                }
+                let [first, second] = die_values;
+                settled_events.write(RollSettled {
+                    die_values,
+                    total: first + second,
+                    short_roll: false,
+                });
+                console.print(format!("Rolled {first} and {second}"));
+            }
+            ConsoleCommand::Bankroll(balance) => {
+                bankroll.balance = balance;
+                console.print(format!("Bankroll is now {}", format_money(balance)));
+            }
+            // Which numbers can be points depends on the game the table deals
+            ConsoleCommand::State(RoundPhase::PointEstablished(point))
+                if !rules.variant.is_point(point) =>
+            {
+                console.print(format!(
+                    "{point} isn't a point at this table - the points are {:?}",
+                    rules.variant.point_numbers()
+                ));
+            }
+            ConsoleCommand::State(new_phase) => {
+                jump_phase(&mut phase, new_phase, &mut phase_events);
+                match new_phase.point() {
+                    Some(point) => console.print(format!("The point is {point}")),
+                    None => console.print("Coming out"),
+                }
+            }
+            ConsoleCommand::Seed(seed) => {
+                *throw_rng = ThrowRng::new(seed);
+                console.print(format!("Throws now start from seed {seed}"));
+            }
+            ConsoleCommand::Help => console.print(HELP),
+        }
+    }
+}
+
+// System that shows the console while it's open, with what's been printed and typed
+fn console_display_system(
+    console: Res<ConsoleState>,
+    mut panel_q: Query<&mut Visibility, With<ConsolePanel>>,
+    mut text_q: Query<&mut Text, With<ConsoleText>>,
+) {
+    if !console.is_changed() {
+        return;
+    }
+    if let Ok(mut visibility) = panel_q.single_mut() {
+        *visibility = if console.open {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+    if let Ok(mut text) = text_q.single_mut() {
+        let mut lines = console.output.clone();
+        lines.push(format!("> {}_", console.line));
+        text.0 = lines.join("\n");
+    }
+}
@@ -0,0 +1,965 @@
+// Turns raw keyboard, mouse and gamepad input into what the player wants to do -
+// look around, throw, change chips, place a bet or take one down. Every other system reads the
+// `PlayerInput` resource instead of checking keys and buttons itself, so mouse and
+// gamepad work side by side and a new device only has to be added here.
+//
+// On touchscreens (phones, tablets, the browser build) there's no keyboard: dragging
+// a finger orbits the camera, holding the on-screen throw button charges the throw,
+// and tapping a bet zone puts a chip down.
+//
+// A VR controller (with `--features vr`, see vr.rs) is one more device: pointing it at
+// the layout aims, the trigger puts a chip down, and the grip holds the dice - squeeze,
+// swing and let go, and the hand's speed throws them.
+//
+// For flick throwing (see PowerMode in dice.rs) this also measures how fast the cursor,
+// or the finger holding the throw button, is moving while the dice are held.
+//
+// Buttons are looked up through the `InputMap`, which the player can change in the
+// controls panel (F1). It's kept with the rest of the player's settings (see
+// settings.rs), so the bindings are saved too. It's our own action map rather than a
+// crate like leafwing-input-manager: the bindings are plain data that save with the
+// settings, and touch, VR and flicks fill in the same `PlayerInput` as the buttons.
+// The only key read anywhere else is the `dev` console's backtick, which has to
+// work while this ignores the keyboard for the console's typing.
+use std::collections::VecDeque;
+
+use bevy::input::mouse::{MouseMotion, MouseWheel}; // Mouse movement and scroll wheel tracking
+use bevy::input::touch::Touches; // Every finger on the screen
+use bevy::input::{ButtonInput, InputSystem};
+use bevy::platform::collections::{HashMap, HashSet};
+use bevy::prelude::*;
+use bevy::ui::UiSystem;
+use bevy::window::PrimaryWindow;
+use serde::{Deserialize, Serialize};
+
+use super::InGame;
+use super::camera::{PlayerCamera, cursor_on_table, screen_to_table};
+use super::console::ConsoleState;
+use super::locale::Localized;
+use super::settings::Settings;
+use crate::layout::TableLayout;
+use crate::locale::Locale;
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerInput>()
+            .init_resource::<TrackedHand>()
+            .init_resource::<PadPointer>()
+            .init_resource::<TouchControls>()
+            .init_resource::<ActivePointer>()
+            .init_resource::<ControlsPanelState>()
+            .add_systems(
+                OnEnter(InGame),
+                (
+                    setup_controls_panel,
+                    setup_touch_controls,
+                    close_controls_panel,
+                ),
+            )
+            // Bevy updates its own input state (and which UI button is under the cursor
+            // or finger) in PreUpdate; we read it straight after, so everything in
+            // Update sees this frame's input
+            .add_systems(
+                PreUpdate,
+                read_input_system.after(InputSystem).after(UiSystem::Focus),
+            )
+            .add_systems(
+                Update,
+                (
+                    // Before capture, so the Escape that cancels a rebind doesn't close the panel too
+                    toggle_controls_panel_system.before(rebind_capture_system),
+                    // Capture first, so the click that starts a rebind isn't taken as the new binding
+                    (rebind_capture_system, rebind_click_system).chain(),
+                    (show_controls_panel_system, controls_panel_text_system),
+                )
+                    .run_if(in_state(InGame)), // The pause menu's Settings opens the panel too
+            );
+    }
+}
+
+// Everything the player asked for this frame, from whichever device they used
+#[derive(Resource, Debug, Default)]
+pub struct PlayerInput {
+    pub look: Vec2,                   // How far to turn the camera (radians: yaw, pitch)
+    pub mouse_look: bool,             // The mouse is steering the camera (so hide the cursor)
+    pub throw_started: bool,          // The throw button went down this frame
+    pub throw_held: bool,             // The throw button is down
+    pub throw_released: bool,         // The throw button came up this frame
+    pub chip_step: i32,               // +1 for a bigger chip, -1 for a smaller one
+    pub place_bet: bool,              // Put a chip down at `pointer`
+    pub take_down_bet: bool,          // Take the bet at `pointer` back off the table
+    pub repeat_bets: bool,            // Put the last roll's bets back down
+    pub undo_bet: bool,               // Take back the last chips put down
+    pub place_preset: Option<u8>,     // Put this bet preset down (Shift and its number key)
+    pub save_preset: Option<u8>,      // Save the layout as this preset (Ctrl and its number)
+    pub pointer: Option<Vec2>,        // The spot on the table being pointed at, as (x, z)
+    pub pointer_is_mouse: bool,       // `pointer` comes from the mouse, not the gamepad or a tap
+    pub camera_preset: Option<usize>, // Fly the camera to this preset (see camera.rs)
+    pub toggle_follow_camera: bool,   // Switch the dice-chasing camera on or off
+    pub instant_replay: bool,         // Replay the last roll
+    pub toggle_stats: bool,           // Open or close the session stats panel
+    pub toggle_histogram: bool,       // Show or hide the roll histogram
+    pub toggle_tutor: bool,           // Turn the bet tutor on or off
+    pub end_turn: bool,               // Done betting - pass control to the next player
+    pub pass_dice: bool,              // Turn the dice down and pass them to the next seat
+    pub pause: bool,                  // Pause the game, or carry on
+    // The grip was let go on a VR controller: where the hand was and how fast it moved
+    pub hand_throw: Option<(Transform, Vec3)>,
+    // While the throw button is held (and the frame it's let go): how fast the cursor,
+    // or the finger holding the button, has been moving lately. In screen heights a
+    // second, x to the right and y up the screen.
+    pub flick: Vec2,
+}
+
+// A VR controller, while the `vr` feature is tracking one (see vr.rs). Without it the
+// pose stays empty and nothing here changes.
+#[derive(Resource, Debug, Default)]
+pub struct TrackedHand {
+    pub pose: Option<Transform>, // Where the controller is, pointing along its forward (-z)
+    pub velocity: Vec3,          // How fast it's moving, in table units a second
+    pub grip: bool,              // Held to hold the dice, let go to throw them
+    pub trigger: bool,           // Puts a chip down where the controller points
+}
+
+// Something the player can do with a button. Which buttons do it is up to the InputMap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    ThrowCharge,    // Hold to charge the throw, let go to throw
+    PlaceBet,       // Put a chip down where you're pointing
+    TakeDownBet,    // Take the bet you're pointing at back down
+    RepeatBets,     // Put the last roll's bets back down
+    UndoBet,        // Take back the last chips put down (a key needs Ctrl with it)
+    Look,           // Hold to look around with the mouse
+    ChipUp,         // Pick a bigger chip
+    ChipDown,       // Pick a smaller chip
+    ShowControls,   // Open or close the controls panel
+    ShowStats,      // Open or close the session stats panel
+    ShowHistogram,  // Show or hide the roll histogram
+    ShowTutor,      // Turn the bet tutor on or off
+    EndTurn,        // Done betting: hand over to the next player (hotseat games)
+    PassDice,       // Turn the dice down when they're offered (hotseat games)
+    Pause,          // Pause the game, or carry on
+    CameraShooter,  // Fly the camera to the shooter's view
+    CameraOverhead, // ...straight down over the layout
+    CameraSide,     // ...low along the side rail
+    FollowCamera,   // Switch the dice-chasing camera on or off
+    InstantReplay,  // Watch the last roll again in slow motion
+}
+
+impl Action {
+    // Every action, in the order the controls panel lists them
+    pub const ALL: [Action; 20] = [
+        Action::ThrowCharge,
+        Action::PlaceBet,
+        Action::TakeDownBet,
+        Action::RepeatBets,
+        Action::UndoBet,
+        Action::Look,
+        Action::ChipUp,
+        Action::ChipDown,
+        Action::ShowControls,
+        Action::ShowStats,
+        Action::ShowHistogram,
+        Action::ShowTutor,
+        Action::EndTurn,
+        Action::PassDice,
+        Action::Pause,
+        Action::CameraShooter,
+        Action::CameraOverhead,
+        Action::CameraSide,
+        Action::FollowCamera,
+        Action::InstantReplay,
+    ];
+
+    // The camera preset actions, in the order of the presets in camera.rs
+    const CAMERA_PRESETS: [Action; 3] = [
+        Action::CameraShooter,
+        Action::CameraOverhead,
+        Action::CameraSide,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::ThrowCharge => "Throw",
+            Action::PlaceBet => "Place bet",
+            Action::TakeDownBet => "Take bet down",
+            Action::RepeatBets => "Same bets again",
+            Action::UndoBet => "Undo last bet (Ctrl and the key)",
+            Action::Look => "Look around",
+            Action::ChipUp => "Bigger chip",
+            Action::ChipDown => "Smaller chip",
+            Action::ShowControls => "Controls panel",
+            Action::ShowStats => "Session stats",
+            Action::ShowHistogram => "Roll histogram",
+            Action::ShowTutor => "Bet tutor",
+            Action::EndTurn => "Done betting",
+            Action::PassDice => "Pass the dice",
+            Action::Pause => "Pause",
+            Action::CameraShooter => "Shooter's view",
+            Action::CameraOverhead => "Overhead view",
+            Action::CameraSide => "Side view",
+            Action::FollowCamera => "Follow the dice",
+            Action::InstantReplay => "Instant replay",
+        }
+    }
+}
+
+// One physical button
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Pad(GamepadButton), // On any connected gamepad
+}
+
+impl Binding {
+    fn device(self) -> Device {
+        match self {
+            Binding::Key(_) | Binding::Mouse(_) => Device::KeyboardMouse,
+            Binding::Pad(_) => Device::Gamepad,
+        }
+    }
+}
+
+impl std::fmt::Display for Binding {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Binding::Key(key) => write!(f, "{key:?}"),
+            Binding::Mouse(button) => write!(f, "{button:?} Mouse"),
+            Binding::Pad(button) => write!(f, "Pad {button:?}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Device {
+    KeyboardMouse,
+    Gamepad,
+}
+
+// The moment of a button press we're asking about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Press {
+    Started,  // Went down this frame
+    Held,     // Is down
+    Released, // Came up this frame
+}
+
+// This frame's raw button state from every device
+pub struct RawInput<'a> {
+    pub keys: &'a ButtonInput<KeyCode>,
+    pub mouse: &'a ButtonInput<MouseButton>,
+    pub gamepads: Vec<&'a Gamepad>,
+}
+
+// Which buttons do which action. Each action can have one keyboard/mouse binding and
+// one gamepad binding, so both kinds of player are covered at once.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputMap {
+    bindings: HashMap<Action, Vec<Binding>>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        use Binding::{Key, Mouse, Pad};
+        let bindings = [
+            (
+                Action::ThrowCharge,
+                vec![Key(KeyCode::Space), Pad(GamepadButton::RightTrigger2)],
+            ),
+            (
+                Action::PlaceBet,
+                vec![Mouse(MouseButton::Left), Pad(GamepadButton::South)],
+            ),
+            // A right-click takes a bet down; held and dragged, the same button looks
+            (
+                Action::TakeDownBet,
+                vec![Mouse(MouseButton::Right), Pad(GamepadButton::RightTrigger)],
+            ),
+            (Action::Look, vec![Mouse(MouseButton::Right)]), // The right stick always looks
+            (Action::RepeatBets, vec![Key(KeyCode::KeyB)]),
+            (
+                Action::UndoBet,
+                vec![Key(KeyCode::KeyZ), Pad(GamepadButton::DPadLeft)],
+            ),
+            (
+                Action::ChipUp,
+                vec![Key(KeyCode::Equal), Pad(GamepadButton::DPadUp)],
+            ),
+            (
+                Action::ChipDown,
+                vec![Key(KeyCode::Minus), Pad(GamepadButton::DPadDown)],
+            ),
+            (
+                Action::ShowControls,
+                vec![Key(KeyCode::F1), Pad(GamepadButton::Select)],
+            ),
+            (Action::ShowStats, vec![Key(KeyCode::Tab)]),
+            (Action::ShowHistogram, vec![Key(KeyCode::KeyH)]),
+            (Action::ShowTutor, vec![Key(KeyCode::KeyT)]),
+            (
+                Action::EndTurn,
+                vec![Key(KeyCode::Enter), Pad(GamepadButton::East)],
+            ),
+            (
+                Action::PassDice,
+                vec![Key(KeyCode::KeyP), Pad(GamepadButton::LeftTrigger)],
+            ),
+            (
+                Action::Pause,
+                vec![Key(KeyCode::Escape), Pad(GamepadButton::Start)],
+            ),
+            (Action::CameraShooter, vec![Key(KeyCode::Digit1)]),
+            (Action::CameraOverhead, vec![Key(KeyCode::Digit2)]),
+            (Action::CameraSide, vec![Key(KeyCode::Digit3)]),
+            (
+                Action::FollowCamera,
+                vec![Key(KeyCode::KeyC), Pad(GamepadButton::North)],
+            ),
+            (
+                Action::InstantReplay,
+                vec![Key(KeyCode::KeyR), Pad(GamepadButton::West)],
+            ),
+        ];
+        Self {
+            bindings: bindings.into_iter().collect(),
+        }
+    }
+}
+
+impl InputMap {
+    // Give any action with no buttons at all its default ones - for bindings saved
+    // before the action existed
+    pub fn add_missing_defaults(&mut self) {
+        for (action, bindings) in Self::default().bindings {
+            self.bindings.entry(action).or_insert(bindings);
+        }
+    }
+
+    pub fn bindings(&self, action: Action) -> &[Binding] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    // Bind a button to an action, replacing whatever button of the same kind
+    // (keyboard/mouse or gamepad) it had before
+    pub fn bind(&mut self, action: Action, binding: Binding) {
+        let bound = self.bindings.entry(action).or_default();
+        bound.retain(|old| old.device() != binding.device());
+        bound.push(binding);
+    }
+
+    // Which kind of device (if any) is doing this action right now
+    pub fn triggered_by(&self, action: Action, press: Press, raw: &RawInput) -> Option<Device> {
+        self.bindings(action)
+            .iter()
+            .find(|binding| {
+                let pads = || raw.gamepads.iter();
+                match (**binding, press) {
+                    (Binding::Key(key), Press::Started) => raw.keys.just_pressed(key),
+                    (Binding::Key(key), Press::Held) => raw.keys.pressed(key),
+                    (Binding::Key(key), Press::Released) => raw.keys.just_released(key),
+                    (Binding::Mouse(button), Press::Started) => raw.mouse.just_pressed(button),
+                    (Binding::Mouse(button), Press::Held) => raw.mouse.pressed(button),
+                    (Binding::Mouse(button), Press::Released) => raw.mouse.just_released(button),
+                    (Binding::Pad(button), Press::Started) => {
+                        pads().any(|pad| pad.just_pressed(button))
+                    }
+                    (Binding::Pad(button), Press::Held) => pads().any(|pad| pad.pressed(button)),
+                    (Binding::Pad(button), Press::Released) => {
+                        pads().any(|pad| pad.just_released(button))
+                    }
+                }
+            })
+            .map(|binding| binding.device())
+    }
+
+    // Is the action being done right now, on any device?
+    pub fn check(&self, action: Action, press: Press, raw: &RawInput) -> bool {
+        self.triggered_by(action, press, raw).is_some()
+    }
+}
+
+// Whether the controls panel is showing, and which action (if any) is waiting for a
+// new button
+#[derive(Resource, Debug, Default)]
+pub struct ControlsPanelState {
+    pub open: bool,
+    pub waiting_for: Option<Action>,
+}
+
+#[derive(Component)]
+struct ControlsPanel; // The panel's root node
+
+#[derive(Component)]
+struct RebindButton(Action); // One row of the panel
+
+// Gamepads have no cursor, so the left stick slides this point around the felt instead
+#[derive(Resource, Debug, Default)]
+struct PadPointer {
+    position: Vec2,
+}
+
+// What the player last pointed at the table with
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum ActivePointer {
+    #[default]
+    Mouse,
+    Gamepad,
+    Touch,
+}
+
+// Which finger is doing what. A finger that lands on the throw button holds the
+// throw; the first other finger orbits the camera; any finger that lifts without
+// having moved much is a tap.
+#[derive(Resource, Debug, Default)]
+struct TouchControls {
+    throw_finger: Option<u64>,
+    look_finger: Option<u64>,
+    on_ui: HashSet<u64>,     // Fingers that landed on an on-screen button
+    tapped_at: Option<Vec2>, // The last spot on the table that was tapped, as (x, z)
+}
+
+#[derive(Component)]
+struct TouchThrowButton; // The on-screen throw button, shown once the screen is touched
+
+const THROW_BUTTON_SIZE: f32 = 120.0;
+const TOUCH_LOOK_SPEED: f32 = 0.005; // Radians per pixel of finger drag
+const TAP_SLOP: f32 = 12.0; // Pixels a finger can wander and still count as a tap
+
+// The number keys along the top of the keyboard, 1 to 9, for the bet presets
+const PRESET_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+const STICK_LOOK_SPEED: f32 = 2.5; // Radians per second at full stick
+const STICK_POINTER_SPEED: f32 = 3.0; // Table units per second at full stick
+const STICK_DEAD_ZONE: f32 = 0.15; // Ignore tiny stick drift
+const FLICK_WINDOW: f32 = 0.08; // Seconds of pointer movement a flick's speed is taken over
+
+// Zero out a stick reading that is only drift
+fn dead_zone(stick: Vec2) -> Vec2 {
+    if stick.length() < STICK_DEAD_ZONE {
+        Vec2::ZERO
+    } else {
+        stick
+    }
+}
+
+pub(crate) fn read_input_system(
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    mut mouse_motion_events: EventReader<MouseMotion>,
+    mut wheel_events: EventReader<MouseWheel>,
+    gamepads: Query<&Gamepad>,
+    window_q: Query<&Window, With<PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<PlayerCamera>>,
+    table_layout: Res<TableLayout>,
+    settings: Res<Settings>,
+    panel: Res<ControlsPanelState>,
+    console: Option<Res<ConsoleState>>, // Only there in `dev` builds
+    touches: Res<Touches>,
+    mut throw_button_q: Query<
+        (&ComputedNode, &GlobalTransform, &mut Visibility),
+        With<TouchThrowButton>,
+    >,
+    buttons_q: Query<&Interaction>, // Every on-screen button
+    mut pad_pointer: ResMut<PadPointer>,
+    mut touch: ResMut<TouchControls>,
+    mut active_pointer: ResMut<ActivePointer>,
+    hand: Res<TrackedHand>,
+    mut hand_was: Local<(bool, bool)>, // The grip and trigger last frame
+    mut take_down_drag: Local<f32>, // Pixels the mouse has moved since the take-down button went down
+    mut flick_trail: Local<VecDeque<(f32, Vec2)>>, // Where the pointer was, and when
+    mut input: ResMut<PlayerInput>,
+) {
+    let dt = time.delta_secs();
+    let mut next = PlayerInput::default();
+    let mouse_moved: Vec2 = mouse_motion_events.read().map(|motion| motion.delta).sum();
+    let scrolled: f32 = wheel_events.read().map(|wheel| wheel.y.signum()).sum();
+
+    // While the controls panel or the debug console is open, keys and clicks are for
+    // them, not the game
+    if panel.open || console.is_some_and(|console| console.open) {
+        *input = next;
+        return;
+    }
+
+    let raw = RawInput {
+        keys: &keys,
+        mouse: &mouse_buttons,
+        gamepads: gamepads.iter().collect(),
+    };
+    let input_map = &settings.keybinds;
+    // Inverted, pushing forward looks down, like a flight stick
+    let look_axes = Vec2::new(1.0, if settings.invert_y { -1.0 } else { 1.0 });
+
+    // --- Buttons, through the input map ---
+    next.mouse_look = input_map.check(Action::Look, Press::Held, &raw);
+    if next.mouse_look {
+        next.look += mouse_moved * look_axes * settings.mouse_sensitivity;
+    }
+    next.throw_started = input_map.check(Action::ThrowCharge, Press::Started, &raw);
+    next.throw_held = input_map.check(Action::ThrowCharge, Press::Held, &raw);
+    next.throw_released = input_map.check(Action::ThrowCharge, Press::Released, &raw);
+    next.chip_step = scrolled as i32; // Scroll up for bigger chips
+    if input_map.check(Action::ChipUp, Press::Started, &raw) {
+        next.chip_step += 1;
+    }
+    if input_map.check(Action::ChipDown, Press::Started, &raw) {
+        next.chip_step -= 1;
+    }
+    // Shift or Ctrl with a number key is a bet preset (see quick_bets.rs), so the
+    // number alone is left to the camera
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let ctrl = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let number = PRESET_KEYS
+        .iter()
+        .position(|&key| keys.just_pressed(key))
+        .map(|index| index as u8 + 1);
+    if ctrl {
+        next.save_preset = number;
+    } else if shift {
+        next.place_preset = number;
+    } else {
+        next.camera_preset = Action::CAMERA_PRESETS
+            .iter()
+            .position(|&action| input_map.check(action, Press::Started, &raw));
+    }
+    next.toggle_follow_camera = input_map.check(Action::FollowCamera, Press::Started, &raw);
+    next.instant_replay = input_map.check(Action::InstantReplay, Press::Started, &raw);
+    next.toggle_stats = input_map.check(Action::ShowStats, Press::Started, &raw);
+    next.toggle_histogram = input_map.check(Action::ShowHistogram, Press::Started, &raw);
+    next.toggle_tutor = input_map.check(Action::ShowTutor, Press::Started, &raw);
+    next.end_turn = input_map.check(Action::EndTurn, Press::Started, &raw);
+    next.pass_dice = input_map.check(Action::PassDice, Press::Started, &raw);
+    next.repeat_bets = input_map.check(Action::RepeatBets, Press::Started, &raw);
+    // Ctrl+Z on a keyboard, so Z alone can't take a bet back by accident
+    next.undo_bet = input_map
+        .triggered_by(Action::UndoBet, Press::Started, &raw)
+        .is_some_and(|device| device == Device::Gamepad || ctrl);
+    next.pause = input_map.check(Action::Pause, Press::Started, &raw);
+    let bet_device = input_map.triggered_by(Action::PlaceBet, Press::Started, &raw);
+    next.place_bet = bet_device.is_some();
+    // The take-down button shares the right mouse button with looking around, so it's
+    // only a take-down if the mouse hardly moved between pressing and letting go
+    if input_map.check(Action::TakeDownBet, Press::Started, &raw) {
+        *take_down_drag = 0.0;
+    }
+    *take_down_drag += mouse_moved.length();
+    let take_down_device = input_map
+        .triggered_by(Action::TakeDownBet, Press::Released, &raw)
+        .filter(|_| *take_down_drag <= TAP_SLOP);
+    next.take_down_bet = take_down_device.is_some();
+
+    // The mouse points at the table unless the gamepad or a finger was used more recently
+    let pointed_with = bet_device.or(take_down_device);
+    if mouse_moved != Vec2::ZERO || pointed_with == Some(Device::KeyboardMouse) {
+        *active_pointer = ActivePointer::Mouse;
+    }
+    if pointed_with == Some(Device::Gamepad) {
+        *active_pointer = ActivePointer::Gamepad;
+    }
+
+    // --- Gamepad sticks: the right stick looks, the left stick points ---
+    for gamepad in &raw.gamepads {
+        next.look += dead_zone(gamepad.right_stick())
+            * Vec2::new(1.0, -1.0)
+            * look_axes
+            * STICK_LOOK_SPEED
+            * dt;
+
+        let stick = dead_zone(gamepad.left_stick());
+        if stick != Vec2::ZERO {
+            *active_pointer = ActivePointer::Gamepad;
+            // The shooter stands at -x facing +x: stick up moves the pointer away from
+            // them (+x) and stick right moves it to their right (+z)
+            pad_pointer.position += Vec2::new(stick.y, stick.x) * STICK_POINTER_SPEED * dt;
+        }
+    }
+    // Keep the gamepad pointer on the felt
+    let half = table_layout.size / 2.0;
+    pad_pointer.position = pad_pointer.position.clamp(-half, half);
+
+    let on_felt = |point: &Vec2| point.x.abs() <= half.x && point.y.abs() <= half.y;
+    let camera = camera_q.single().ok();
+
+    // --- Touch: the throw button, dragging to look and tapping to bet ---
+    if let Ok((node, button_transform, mut visibility)) = throw_button_q.single_mut() {
+        if touches.any_just_pressed() {
+            *visibility = Visibility::Inherited; // Only touchscreen players need the button
+        }
+        // UI nodes are laid out in physical pixels, touches arrive in logical pixels
+        let to_physical = window_q.single().map_or(1.0, Window::scale_factor);
+        let center = button_transform.translation().truncate();
+        let half_size = node.size() / 2.0;
+        let on_throw_button = |position: Vec2| {
+            (position * to_physical - center)
+                .abs()
+                .cmple(half_size)
+                .all()
+        };
+        let button_shown = *visibility != Visibility::Hidden;
+
+        for finger in touches.iter_just_pressed() {
+            if button_shown && touch.throw_finger.is_none() && on_throw_button(finger.position()) {
+                touch.throw_finger = Some(finger.id());
+                next.throw_started = true;
+            } else if buttons_q
+                .iter()
+                .any(|interaction| *interaction != Interaction::None)
+            {
+                touch.on_ui.insert(finger.id()); // Tapping a button shouldn't bet or look
+            } else if touch.look_finger.is_none() {
+                touch.look_finger = Some(finger.id());
+            }
+        }
+    }
+    if let Some(finger) = touch.throw_finger {
+        if touches.get_pressed(finger).is_some() {
+            next.throw_held = true;
+        } else {
+            next.throw_released = true; // Lifted (or cancelled) - let the dice go
+            touch.throw_finger = None;
+        }
+    }
+    if let Some(finger) = touch.look_finger.and_then(|id| touches.get_pressed(id)) {
+        // Dragging pulls the view along with the finger, the way maps and photos work
+        next.look -= finger.delta() * TOUCH_LOOK_SPEED;
+    }
+    for finger in touches.iter_just_released() {
+        let id = finger.id();
+        if touch.look_finger == Some(id) {
+            touch.look_finger = None;
+        }
+        let was_on_ui = touch.on_ui.remove(&id);
+        let is_tap = finger.distance().length() <= TAP_SLOP;
+        if Some(id) != touch.throw_finger && !was_on_ui && is_tap {
+            if let Some((camera, camera_transform)) = camera {
+                touch.tapped_at =
+                    screen_to_table(camera, camera_transform, finger.position()).filter(on_felt);
+                if touch.tapped_at.is_some() {
+                    *active_pointer = ActivePointer::Touch;
+                    next.place_bet = true;
+                }
+            }
+        }
+    }
+    for finger in touches.iter_just_canceled() {
+        // The system took the touch away (a notification, a gesture) - forget the finger
+        if touch.look_finger == Some(finger.id()) {
+            touch.look_finger = None;
+        }
+        touch.on_ui.remove(&finger.id());
+    }
+
+    // --- Where the player is pointing ---
+    match *active_pointer {
+        ActivePointer::Gamepad => next.pointer = Some(pad_pointer.position),
+        ActivePointer::Touch => next.pointer = touch.tapped_at,
+        ActivePointer::Mouse => {
+            if let (Ok(window), Some((camera, camera_transform))) = (window_q.single(), camera) {
+                next.pointer = cursor_on_table(window, camera, camera_transform).filter(on_felt);
+                next.pointer_is_mouse = true;
+            }
+        }
+    }
+
+    // --- A VR controller ---
+    // While one is tracked it does the pointing, wherever the mouse is
+    if let Some(pose) = hand.pose {
+        let (was_gripping, was_triggering) = *hand_was;
+        next.throw_started |= hand.grip && !was_gripping;
+        next.throw_held |= hand.grip;
+        if was_gripping && !hand.grip {
+            next.throw_released = true;
+            next.hand_throw = Some((pose, hand.velocity));
+        }
+        // Where a ray out of the controller crosses the table top
+        let ray = Ray3d::new(pose.translation, pose.forward());
+        next.pointer = ray
+            .intersect_plane(Vec3::ZERO, InfinitePlane3d::new(Vec3::Y))
+            .map(|distance| ray.get_point(distance).xz())
+            .filter(on_felt);
+        next.pointer_is_mouse = false;
+        next.place_bet |= hand.trigger && !was_triggering && next.pointer.is_some();
+        *hand_was = (hand.grip, hand.trigger);
+    }
+
+    // --- Flicking: how fast the pointer holding the dice is moving ---
+    // Measured over the last few frames rather than one, so a single jumpy frame
+    // doesn't decide the throw. Nothing new is read the frame it's let go (a lifted
+    // finger is gone), so that frame's flick is the one just before.
+    if next.throw_held || next.throw_released {
+        let window = window_q.single().ok();
+        let now = time.elapsed_secs();
+        let held_at = touch
+            .throw_finger
+            .and_then(|id| touches.get_pressed(id))
+            .map(|finger| finger.position())
+            .or_else(|| window.and_then(Window::cursor_position));
+        if let Some(position) = held_at.filter(|_| next.throw_held) {
+            flick_trail.push_back((now, position));
+        }
+        while flick_trail
+            .front()
+            .is_some_and(|&(at, _)| now - at > FLICK_WINDOW)
+        {
+            flick_trail.pop_front();
+        }
+        let screen_height = window.map_or(1.0, |window| window.height().max(1.0));
+        // The screen counts down from the top; a flick counts up
+        next.flick = match (flick_trail.front(), flick_trail.back()) {
+            (Some(&(start, from)), Some(&(end, to))) if end > start => {
+                (to - from) / (end - start) / screen_height * Vec2::new(1.0, -1.0)
+            }
+            _ => Vec2::ZERO,
+        };
+    } else {
+        flick_trail.clear();
+    }
+
+    *input = next;
+}
+
+// System that opens and closes the controls panel with its key. Escape closes it too.
+fn toggle_controls_panel_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    settings: Res<Settings>,
+    mut panel: ResMut<ControlsPanelState>,
+) {
+    let raw = RawInput {
+        keys: &keys,
+        mouse: &mouse_buttons,
+        gamepads: gamepads.iter().collect(),
+    };
+    if panel.waiting_for.is_some() {
+        return; // Mid-rebind every key is a candidate binding, even the panel's own key
+    }
+    if settings
+        .keybinds
+        .check(Action::ShowControls, Press::Started, &raw)
+    {
+        panel.open = !panel.open;
+    } else if panel.open && keys.just_pressed(KeyCode::Escape) {
+        panel.open = false;
+    }
+}
+
+// System that shows or hides the controls panel, however it was opened
+fn show_controls_panel_system(
+    panel: Res<ControlsPanelState>,
+    mut panel_q: Query<&mut Visibility, With<ControlsPanel>>,
+) {
+    if !panel.is_changed() {
+        return;
+    }
+    if let Ok(mut visibility) = panel_q.single_mut() {
+        *visibility = if panel.open {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+// System that starts listening for a new binding when an action's row is clicked
+fn rebind_click_system(
+    mut panel: ResMut<ControlsPanelState>,
+    rows_q: Query<(&Interaction, &RebindButton), Changed<Interaction>>,
+) {
+    for (interaction, row) in &rows_q {
+        if panel.open && *interaction == Interaction::Pressed {
+            panel.waiting_for = Some(row.0);
+        }
+    }
+}
+
+// System that takes the next key, mouse button or gamepad button pressed as the new
+// binding for the action being rebound. Escape cancels.
+fn rebind_capture_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    mut panel: ResMut<ControlsPanelState>,
+    mut settings: ResMut<Settings>,
+) {
+    let Some(action) = panel.waiting_for else {
+        return;
+    };
+    if keys.just_pressed(KeyCode::Escape) {
+        panel.waiting_for = None;
+        return;
+    }
+    let pressed = keys
+        .get_just_pressed()
+        .next()
+        .map(|&key| Binding::Key(key))
+        .or_else(|| {
+            mouse_buttons
+                .get_just_pressed()
+                .next()
+                .map(|&button| Binding::Mouse(button))
+        })
+        .or_else(|| {
+            gamepads
+                .iter()
+                .find_map(|gamepad| gamepad.get_just_pressed().next().copied())
+                .map(Binding::Pad)
+        });
+    if let Some(binding) = pressed {
+        info!("{} is now bound to {binding}", action.label());
+        settings.keybinds.bind(action, binding);
+        panel.waiting_for = None;
+    }
+}
+
+// System that keeps every row of the controls panel showing the current bindings
+fn controls_panel_text_system(
+    settings: Res<Settings>,
+    panel: Res<ControlsPanelState>,
+    locale: Res<Locale>,
+    mut rows_q: Query<(&RebindButton, &Children)>,
+    mut text_q: Query<&mut Text>,
+) {
+    if !settings.is_changed() && !panel.is_changed() && !locale.is_changed() {
+        return;
+    }
+    for (row, children) in &mut rows_q {
+        let action = locale.tr(row.0.label());
+        let label = if panel.waiting_for == Some(row.0) {
+            locale.fill(
+                "{action}: press a key or button... (Esc to cancel)",
+                &[("action", &action)],
+            )
+        } else {
+            let bound: Vec<String> = settings
+                .keybinds
+                .bindings(row.0)
+                .iter()
+                .map(|binding| binding.to_string())
+                .collect();
+            format!("{action}: {}", bound.join(", "))
+        };
+        for &child in children {
+            if let Ok(mut text) = text_q.get_mut(child) {
+                text.0.clone_from(&label);
+            }
+        }
+    }
+}
+
+// A fresh table starts with the panel shut, even if it was left open on the last one
+fn close_controls_panel(mut panel: ResMut<ControlsPanelState>) {
+    *panel = ControlsPanelState::default();
+}
+
+fn setup_controls_panel(mut commands: Commands) {
+    // A dark box in the middle of the screen, hidden until it is opened
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(30.0),
+                top: Val::Percent(20.0),
+                width: Val::Percent(40.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(6.0),
+                padding: UiRect::all(Val::Px(16.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.05, 0.05, 0.05, 0.95)),
+            BorderRadius::all(Val::Px(8.0)),
+            GlobalZIndex(1), // Over the pause menu, which can open it
+            Visibility::Hidden,
+            ControlsPanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Localized("Controls"),
+                TextFont {
+                    font_size: 24.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+            for action in Action::ALL {
+                parent
+                    .spawn((
+                        Button,
+                        Node {
+                            padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+                        RebindButton(action),
+                    ))
+                    .with_children(|row| {
+                        row.spawn((
+                            Text::new(action.label()), // Bindings are filled in on the first frame
+                            TextFont {
+                                font_size: 16.0,
+                                ..default()
+                            },
+                            TextColor(Color::WHITE),
+                        ));
+                    });
+            }
+            parent.spawn((
+                Localized("Click an action, then press its new key or button. Keyboard and gamepad bindings are kept separately. Escape closes the panel."),
+                TextFont {
+                    font_size: 13.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.6, 0.6, 0.6)),
+            ));
+        });
+}
+
+fn setup_touch_controls(mut commands: Commands) {
+    // A big round button at the bottom of the screen - hold it to charge, let go to throw.
+    // It stays hidden until the screen is touched, so mouse and gamepad players never see it.
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(50.0),
+                margin: UiRect::left(Val::Px(-THROW_BUTTON_SIZE / 2.0)), // Centre it
+                bottom: Val::Px(30.0),
+                width: Val::Px(THROW_BUTTON_SIZE),
+                height: Val::Px(THROW_BUTTON_SIZE),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.8, 0.1, 0.1, 0.7)),
+            BorderRadius::MAX, // Fully round
+            Visibility::Hidden,
+            TouchThrowButton,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Localized("HOLD TO\nTHROW"),
+                TextFont {
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                TextLayout::new_with_justify(JustifyText::Center),
+            ));
+        });
+}
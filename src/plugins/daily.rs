@@ -0,0 +1,308 @@
+// The daily challenge (`--daily`, see daily.rs for how a day's table is dealt). A
+// panel on the right counts down the rolls; once the last one has been paid, or the
+// player can no longer cover the table minimum, the dice are put away and what they hold
+// is their score for the day. The score is kept with storage.rs next to every earlier
+// day's, and the closing panel gives a line to pass on to friends, who get the same
+// dice if they play the same day.
+//
+// The scores are loaded whether or not today's challenge is being played, so the main
+// menu can show them.
+use bevy::prelude::*;
+
+use super::announce::Announcement;
+use super::betting::RollResolved;
+use super::dice::DiceAuthority;
+use super::locale::Localized;
+use super::{AppState, InGame, RollStep};
+use crate::betting::TableBets;
+use crate::daily::{DAILY_BANKROLL, DAILY_ROLLS, DailyChallenge, DailyScores};
+use crate::locale::Locale;
+use crate::payout::{Bankroll, CHIP_DENOMINATIONS, Cents, format_money};
+use crate::rules::TableRules;
+use crate::storage;
+
+pub struct DailyPlugin;
+
+impl Plugin for DailyPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(load_scores());
+
+        // main.rs inserts the challenge before the plugins are added
+        if !app.world().contains_resource::<DailyChallenge>() {
+            return;
+        }
+        app.insert_resource(Bankroll {
+            balance: DAILY_BANKROLL,
+        })
+        .add_systems(OnEnter(InGame), setup_daily_panels)
+        .add_systems(
+            Update,
+            (
+                // Once the roll has been paid and told, so the challenge's end is
+                // read out after it
+                daily_roll_system.after(RollStep::Announce),
+                daily_text_system,
+                daily_menu_button_system,
+            )
+                .run_if(in_state(AppState::Playing)),
+        );
+    }
+}
+
+#[derive(Component)]
+struct DailyText; // "Daily challenge 2026-10-14, Roll 12 of 40"
+
+#[derive(Component)]
+struct DailyResultPanel;
+
+#[derive(Component)]
+struct DailyResultText;
+
+#[derive(Component)]
+struct DailyMenuButton;
+
+const SCORES_KEY: &str = "daily_scores"; // Where they're saved (see storage.rs)
+
+fn load_scores() -> DailyScores {
+    let Some(saved) = storage::load(SCORES_KEY) else {
+        return DailyScores::default(); // No challenge finished yet
+    };
+    ron::from_str(&saved).unwrap_or_else(|error| {
+        warn!("Couldn't read the daily challenge scores, starting afresh: {error}");
+        DailyScores::default()
+    })
+}
+
+fn save_scores(scores: &DailyScores) {
+    let saved = ron::ser::to_string_pretty(scores, ron::ser::PrettyConfig::default())
+        .map_err(|error| error.to_string())
+        .and_then(|text| storage::save(SCORES_KEY, &text).map_err(|error| error.to_string()));
+    if let Err(error) = saved {
+        warn!("Couldn't save the daily challenge scores: {error}");
+    }
+}
+
+fn setup_daily_panels(mut commands: Commands, daily: Res<DailyChallenge>) {
+    // Down the right-hand side, midway
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(20.0),
+                top: Val::Percent(40.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.05, 0.05, 0.05, 0.8)),
+            BorderRadius::all(Val::Px(6.0)),
+        ))
+        .with_child((
+            Text::new(""), // Filled in on the first frame
+            TextFont {
+                font_size: 14.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            DailyText,
+        ));
+
+    // The closing panel in the middle of the screen, hidden until the challenge is over
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(25.0),
+                top: Val::Percent(30.0),
+                width: Val::Percent(50.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(12.0),
+                padding: UiRect::all(Val::Px(16.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.05, 0.05, 0.05, 0.95)),
+            BorderRadius::all(Val::Px(8.0)),
+            GlobalZIndex(1), // Over the other readouts
+            if daily.finished {
+                Visibility::Inherited // Coming back to a challenge that's already over
+            } else {
+                Visibility::Hidden
+            },
+            DailyResultPanel,
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                Localized("Daily challenge over"),
+                TextFont {
+                    font_size: 32.0,
+                    ..default()
+                },
+                TextColor(Srgba::hex("#FF5300").unwrap().into()),
+            ));
+            panel.spawn((
+                Text::new(""), // Filled in when the challenge ends
+                TextFont {
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                TextLayout::new_with_justify(JustifyText::Center),
+                DailyResultText,
+            ));
+            panel
+                .spawn((
+                    Button,
+                    Node {
+                        padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.1, 0.1, 0.1)),
+                    BorderRadius::all(Val::Px(6.0)),
+                    DailyMenuButton,
+                ))
+                .with_child((
+                    Localized("Quit to Menu"),
+                    TextFont {
+                        font_size: 16.0,
+                        ..default()
+                    },
+                    TextColor(Color::WHITE),
+                ));
+        });
+}
+
+// System that counts the challenge's rolls, and ends it after the last one - or sooner,
+// if the player has run out of money to bet
+fn daily_roll_system(
+    mut resolved_events: EventReader<RollResolved>,
+    bankroll: Res<Bankroll>,
+    bets: Res<TableBets>,
+    rules: Res<TableRules>,
+    locale: Res<Locale>,
+    mut daily: ResMut<DailyChallenge>,
+    mut scores: ResMut<DailyScores>,
+    mut authority: ResMut<DiceAuthority>,
+    mut announcements: EventWriter<Announcement>,
+) {
+    if daily.finished {
+        resolved_events.clear();
+        return;
+    }
+    let mut last_roll = false;
+    for _ in resolved_events.read() {
+        last_roll |= daily.record_roll();
+    }
+    let smallest_chip = CHIP_DENOMINATIONS[CHIP_DENOMINATIONS.len() - 1];
+    let broke = bets.bets.is_empty() && bankroll.balance < rules.min_bet.max(smallest_chip);
+    if !last_roll && !broke {
+        return;
+    }
+
+    // Chips still on the layout count, the same as in the save
+    let holding: Cents = bankroll.balance + bets.bets.iter().map(|bet| bet.amount).sum::<Cents>();
+    daily.finished = true;
+    authority.may_throw = false;
+    if scores.record(&daily.date, holding) {
+        save_scores(&scores);
+    }
+    info!(
+        "Daily challenge {} over after {} rolls: {}",
+        daily.date,
+        daily.rolls,
+        format_money(holding)
+    );
+    announcements.write(Announcement(locale.fill(
+        "Daily challenge over. You finished with {money}.",
+        &[("money", &format_money(holding))],
+    )));
+}
+
+// System that keeps the countdown and the closing panel up to date
+fn daily_text_system(
+    daily: Res<DailyChallenge>,
+    scores: Res<DailyScores>,
+    locale: Res<Locale>,
+    bankroll: Res<Bankroll>,
+    bets: Res<TableBets>,
+    mut text_q: Query<&mut Text, With<DailyText>>,
+    mut result_q: Query<&mut Text, (With<DailyResultText>, Without<DailyText>)>,
+    mut panel_q: Query<&mut Visibility, With<DailyResultPanel>>,
+) {
+    let changed = daily.is_changed() || locale.is_changed();
+    for mut text in &mut text_q {
+        if !changed && !text.is_added() {
+            continue;
+        }
+        text.0 = if daily.finished {
+            locale.fill("Daily challenge {date}\nFinished", &[("date", &daily.date)])
+        } else {
+            locale.fill(
+                "Daily challenge {date}\nRoll {roll} of {rolls}",
+                &[
+                    ("date", &daily.date),
+                    ("roll", &(daily.rolls + 1)),
+                    ("rolls", &DAILY_ROLLS),
+                ],
+            )
+        };
+    }
+
+    if !daily.finished {
+        return;
+    }
+    for mut text in &mut result_q {
+        if !changed && !text.is_added() {
+            continue;
+        }
+        let holding = bankroll.balance + bets.bets.iter().map(|bet| bet.amount).sum::<Cents>();
+        let score = scores.on(&daily.date).unwrap_or(holding);
+        let mut lines = vec![locale.fill(
+            "You finished with {money}, from {start}.",
+            &[
+                ("money", &format_money(holding)),
+                ("start", &format_money(DAILY_BANKROLL)),
+            ],
+        )];
+        // A second go the same day is practice - the first one is the score
+        if score != holding {
+            lines.push(locale.fill(
+                "Only the first go counts - your score today is {money}.",
+                &[("money", &format_money(score))],
+            ));
+        }
+        if let Some(best) = scores.best() {
+            lines.push(locale.fill(
+                "Your best day: {money} on {date}.",
+                &[("money", &format_money(best.balance)), ("date", &best.date)],
+            ));
+        }
+        lines.push(
+            locale
+                .tr("Share your score - anyone who plays today gets the same dice:")
+                .to_string(),
+        );
+        lines.push(locale.fill(
+            "Bevy Craps daily {date}: {money}",
+            &[("date", &daily.date), ("money", &format_money(score))],
+        ));
+        text.0 = lines.join("\n");
+    }
+    if let Ok(mut visibility) = panel_q.single_mut() {
+        visibility.set_if_neq(Visibility::Inherited);
+    }
+}
+
+// System that goes back to the main menu from the closing panel
+fn daily_menu_button_system(
+    button_q: Query<&Interaction, (Changed<Interaction>, With<DailyMenuButton>)>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if button_q
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed)
+    {
+        next_state.set(AppState::MainMenu);
+    }
+}
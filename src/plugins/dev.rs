@@ -0,0 +1,80 @@
+// Developer tools, only built with `cargo run --features dev`. The egui world inspector
+// lists every entity and lets you poke at its components, and the debug panel beside
+// it shows the numbers that matter when a throw or a payout goes wrong: the power
+// meter, the round, the bets on the layout and how fast each die is moving.
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{EguiContextPass, EguiContexts, EguiPlugin, egui};
+use bevy_inspector_egui::quick::WorldInspectorPlugin;
+use bevy_rapier3d::prelude::*;
+
+use super::dice::{Dice, DiceId, DiceValue, RollState, ThrowPower};
+use crate::betting::TableBets;
+use crate::payout::{Bankroll, format_money};
+use crate::rules::RoundPhase;
+
+pub struct DevPlugin;
+
+impl Plugin for DevPlugin {
+    fn build(&self, app: &mut App) {
+        // A host game may already draw its own egui windows
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin {
+                enable_multipass_for_primary_context: true,
+            });
+        }
+        app.add_plugins(WorldInspectorPlugin::new())
+            // egui windows are drawn in their own schedule, once egui is ready for the frame
+            .add_systems(EguiContextPass, debug_panel_system);
+    }
+}
+
+// System that draws the debug panel with this frame's numbers
+fn debug_panel_system(
+    mut contexts: EguiContexts,
+    power: Res<ThrowPower>,
+    roll: Res<RollState>,
+    phase: Res<RoundPhase>,
+    bets: Res<TableBets>,
+    bankroll: Res<Bankroll>,
+    dice_q: Query<(&DiceId, &Velocity, Option<&DiceValue>), With<Dice>>,
+) {
+    let Some(ctx) = contexts.try_ctx_mut() else {
+        return; // No camera for egui to draw on yet
+    };
+    egui::Window::new("Table debug")
+        .default_pos([20.0, 120.0])
+        .show(ctx, |ui| {
+            ui.heading("Throw");
+            ui.label(format!("Power: {:.2} / {:.2}", power.current, power.max));
+            ui.label(format!("Dice in flight: {}", roll.in_flight()));
+
+            ui.separator();
+            ui.heading("Round");
+            ui.label(format!("{:?}", *phase));
+            ui.label(format!("Bankroll: {}", format_money(bankroll.balance)));
+
+            ui.separator();
+            ui.heading("Bets");
+            if bets.bets.is_empty() {
+                ui.label("None");
+            }
+            for bet in &bets.bets {
+                ui.label(format!("{:?}: {}", bet.kind, format_money(bet.amount)));
+            }
+
+            ui.separator();
+            ui.heading("Dice");
+            let mut dice: Vec<_> = dice_q.iter().collect();
+            dice.sort_by_key(|(id, _, _)| id.0);
+            for (id, velocity, value) in dice {
+                // A die only has a value once it's at rest
+                let showing = value.map_or("-".to_string(), |value| value.0.to_string());
+                ui.label(format!(
+                    "Die {}: moving {:.2}/s, spinning {:.2} rad/s, showing {showing}",
+                    id.0,
+                    velocity.linvel.length(),
+                    velocity.angvel.length()
+                ));
+            }
+        });
+}
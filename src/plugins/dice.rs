@@ -0,0 +1,1700 @@
+// The dice: throwing them, watching them tumble, and reading the result once they
+// have all come to rest. Everything downstream (the rules, bets, UI) only ever sees the
+// DiceSettled event this plugin sends, or at craps the RollSettled sent with it. Craps
+// is two dice; sic bo (see sicbo.rs) throws three the same way.
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*; // Physics engine - makes things fall, bounce, and collide realistically
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng; // A small, fast RNG that gives the same numbers on every platform
+use serde::{Deserialize, Serialize};
+
+use super::camera::PlayerCamera;
+use super::controls::PlayerInput;
+use super::lifecycle::{DiceLaunched, ThrowStarted};
+use super::menu::LoadingAssets;
+use super::replay::InstantReplay;
+use super::session::SessionPlayback;
+use super::table::BackWall;
+use super::{AppState, InGame, RollStep, redraw};
+use crate::daily::DailyChallenge;
+use crate::dice_set::{DiceSet, SetFaces};
+use crate::layout::TableLayout;
+use crate::ron_asset::RonLoader;
+use crate::rules::{RollMode, ShortRollPolicy, TableRules};
+
+pub struct DicePlugin;
+
+impl Plugin for DicePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ThrowPower::default()) // Add a shared "power meter" that all systems can access
+            // Resources are like global variables but safer
+            .init_resource::<ThrowRng>() // Keeps any seed the app inserted before us
+            .init_resource::<DiceShape>() // Size and corner rounding of the dice
+            .init_asset::<DiceConfig>() // How the dice bounce and fly, loaded from assets/
+            .register_asset_loader(RonLoader::<DiceConfig>::new(&["physics.ron"]))
+            .init_resource::<DiceConfig>() // The numbers in use, until the file has loaded
+            .init_resource::<DiceConfigFile>()
+            .init_asset::<DiceSkinLibrary>() // The skins file, loaded from assets/
+            .register_asset_loader(RonLoader::<DiceSkinLibrary>::new(&["skins.ron"]))
+            .init_resource::<DiceSkin>() // Which skin the dice wear
+            .init_resource::<SettleConfig>() // How still the dice must be, and for how long
+            .init_resource::<RollState>() // Is a throw currently in progress?
+            .init_resource::<ThrowTarget>() // The spot on the felt the shooter is aiming at
+            .init_asset::<ThrowConfig>() // How the throw button builds a throw, loaded from assets/
+            .register_asset_loader(RonLoader::<ThrowConfig>::new(&["meter.ron"]))
+            .init_resource::<ThrowConfig>()
+            .init_resource::<ThrowConfigFile>()
+            .init_resource::<DiceSet>() // How the shooter picks the dice up
+            .add_event::<DiceSettled>() // Announced once every die has come to rest...
+            .add_event::<RollSettled>() // ...and read as a craps roll when there are two
+            .add_event::<NoRoll>() // Announced when a throw doesn't count
+            .add_event::<ThrowStarted>() // The throw button went down
+            .add_event::<ThrowDice>() // The throw button was let go (or a remote shooter threw)
+            .add_event::<DiceLaunched>() // ...and the dice have left the hand
+            .init_resource::<DiceAuthority>() // Whether we may throw, and whether we run the dice
+            // Every throw runs through the roll steps in this order
+            .configure_sets(
+                Update,
+                (
+                    RollStep::Settle,
+                    RollStep::Judge,
+                    RollStep::Pay,
+                    RollStep::Announce,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Startup,
+                (wait_for_skins, wait_for_dice_config, wait_for_throw_config),
+            )
+            // The dice files usually arrive on the loading screen, so these always listen
+            .add_systems(
+                Update,
+                (
+                    (dice_config_system, dice_physics_system).chain(),
+                    throw_config_system,
+                ),
+            )
+            .add_systems(
+                OnEnter(InGame),
+                (spawn_dice, forget_throw, redraw::<DiceSkin>),
+            )
+            // The systems that push the dice run on the physics clock, one fixed step at
+            // a time, so a throw plays out the same however fast the game is drawing.
+            // FixedUpdate runs before Update, so a throw has started by the time the rest
+            // of the game looks at the dice each frame.
+            .add_systems(
+                FixedUpdate,
+                (launch_dice_system, steer_dice_system).run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(
+                Update,
+                (
+                    aim_system
+                        .before(throw_system)
+                        .before(trajectory_preview_system),
+                    throw_system,
+                    dice_face_system,
+                    dice_skin_system,
+                    trajectory_preview_system,
+                    off_table_system.before(RollStep::Settle),
+                    back_wall_system.before(RollStep::Settle),
+                    stuck_dice_system.before(RollStep::Settle),
+                    settle_system.in_set(RollStep::Settle),
+                )
+                    .run_if(in_state(AppState::Playing)),
+            );
+    }
+}
+
+#[derive(Component)]
+pub struct Dice; // Tags an entity as being a die - helps us find all dice later
+
+// This component stores data - the number in parentheses
+// It's called a "tuple struct" - like a struct with unnamed fields
+#[derive(Component)]
+pub struct DiceId(pub u8); // u8 = unsigned 8-bit integer (0-255)
+// Identifies which die is which (die #1, die #2, etc.)
+
+// The number showing on top of a die once it has come to rest (1-6).
+// Only present while the die is settled - it is removed again as soon as the die moves.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiceValue(pub u8);
+
+// Put on a die the first time it bounces off the back wall during a throw
+#[derive(Component)]
+pub struct HitBackWall;
+
+// The face a die has to end up showing, drawn when it was thrown.
+// Only present in RollMode::RngAuthoritative.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForcedFace(pub u8);
+
+const STEER_SPEED: f32 = 1.0; // Steering starts once a die slides slower than this
+const STEER_TURN_RATE: f32 = 6.0; // How fast (radians/second) a die is turned to its face
+
+// Which number is printed on each side of the die, in the die's own (local) space.
+// Opposite faces always add up to 7, just like a real die.
+const DIE_FACES: [(Vec3, u8); 6] = [
+    (Vec3::Y, 1),
+    (Vec3::NEG_Y, 6),
+    (Vec3::X, 2),
+    (Vec3::NEG_X, 5),
+    (Vec3::Z, 3),
+    (Vec3::NEG_Z, 4),
+];
+
+// Where the pips go on each face, as cells of a 3 x 3 grid:
+// 0 1 2
+// 3 4 5
+// 6 7 8
+// Shared with the dice icons in the UI so both always look the same.
+pub const PIP_CELLS: [&[usize]; 6] = [
+    &[4],
+    &[0, 8],
+    &[0, 4, 8],
+    &[0, 2, 6, 8],
+    &[0, 2, 4, 6, 8],
+    &[0, 2, 3, 5, 6, 8],
+];
+
+const PIP_RADIUS: f32 = 0.035;
+const PIP_SPACING: f32 = 0.11; // Distance from the middle of a face to an outer pip
+
+// One look for the dice, as written in assets/dice.skins.ron
+#[derive(Deserialize, Debug, Clone)]
+pub struct SkinStyle {
+    pub name: String,
+    pub body: String, // Hex colour of the die, like "#C8102E"
+    #[serde(default = "solid")]
+    pub alpha: f32, // 1.0 = solid, lower = see-through acrylic
+    pub pips: String, // Hex colour of the pips
+    #[serde(default)]
+    pub pip_style: PipStyle,
+}
+
+fn solid() -> f32 {
+    1.0
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PipStyle {
+    #[default]
+    Round, // Drilled, painted dots
+    Square, // Blocky dots, for a more modern look
+}
+
+// Every skin in a skins file
+#[derive(Asset, TypePath, Deserialize, Debug, Clone)]
+pub struct DiceSkinLibrary {
+    pub skins: Vec<SkinStyle>,
+}
+
+// The skin the dice are wearing: the loaded skins file and which entry in it is picked
+#[derive(Resource)]
+pub struct DiceSkin {
+    pub library: Handle<DiceSkinLibrary>,
+    pub selected: usize,
+}
+
+impl FromWorld for DiceSkin {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            library: world.resource::<AssetServer>().load("dice.skins.ron"),
+            selected: 0, // The first skin in the file
+        }
+    }
+}
+
+impl DiceSkin {
+    // The picked skin, once the file has loaded
+    pub fn current<'a>(&self, libraries: &'a Assets<DiceSkinLibrary>) -> Option<&'a SkinStyle> {
+        let skins = &libraries.get(&self.library)?.skins;
+        skins.get(self.selected % skins.len().max(1))
+    }
+
+    // Move on to the next skin in the file, wrapping back round to the first
+    pub fn next(&mut self, libraries: &Assets<DiceSkinLibrary>) {
+        let count = libraries
+            .get(&self.library)
+            .map_or(0, |library| library.skins.len());
+        if count > 0 {
+            self.selected = (self.selected + 1) % count;
+        }
+    }
+}
+
+// The materials and pip meshes shared by both dice, so a new skin only has to change
+// them in one place
+#[derive(Resource)]
+struct DiceAssets {
+    body_material: Handle<StandardMaterial>,
+    pip_material: Handle<StandardMaterial>,
+    round_pip: Handle<Mesh>,
+    square_pip: Handle<Mesh>,
+}
+
+// Marks the pips on a die, so their shape can be swapped when the skin changes
+#[derive(Component)]
+struct DicePip;
+
+// Turn a skin's hex colour into a colour, falling back to white for a typo
+fn skin_color(hex: &str) -> Color {
+    Srgba::hex(hex).map_or_else(
+        |_| {
+            warn!("Dice skin colour {hex:?} isn't a hex colour like \"#C8102E\"");
+            Color::WHITE
+        },
+        Color::from,
+    )
+}
+
+// The physical shape of the dice
+#[derive(Resource, Debug, Clone)]
+pub struct DiceShape {
+    pub size: f32, // Length of each edge
+    // Rounds off the collider's corners and edges by this much (0.0 = a sharp cube).
+    // Real casino dice have sharp edges, but a little rounding makes them tumble and
+    // roll more like worn dice instead of stopping dead.
+    pub corner_radius: f32,
+}
+
+impl Default for DiceShape {
+    fn default() -> Self {
+        Self {
+            size: 0.4,
+            corner_radius: 0.0, // Casino-sharp
+        }
+    }
+}
+
+impl DiceShape {
+    pub fn collider(&self) -> Collider {
+        let half = self.size / 2.0; // Colliders are sized by half-extents
+        if self.corner_radius > 0.0 {
+            // The rounding is added on the outside, so shrink the inner box to keep the size
+            let inner = (half - self.corner_radius).max(0.01);
+            Collider::round_cuboid(inner, inner, inner, self.corner_radius)
+        } else {
+            Collider::cuboid(half, half, half)
+        }
+    }
+}
+
+// How the dice move: their weight, bounce and grip, and how the throw sends them off.
+// Read from assets/dice.physics.ron, and read again whenever that file is saved (in a
+// `--features dev` build), so the dice's feel can be tuned while the game runs.
+#[derive(Asset, Resource, TypePath, Deserialize, Debug, Clone)]
+pub struct DiceConfig {
+    pub density: f32,           // Higher density = heavier dice
+    pub restitution: f32,       // Bounciness: 0.15 = a die keeps 15% of its energy
+    pub friction: f32,          // How much a die grips the felt and the walls
+    pub linear_damping: f32,    // Air resistance slowing the dice down
+    pub angular_damping: f32,   // Slows the dice's spin
+    pub impulse_per_power: f32, // How hard a throw pushes for each point on the power meter
+    pub spin: Vec3,             // Die #1's starting spin (die #2 spins the mirror way)
+    pub push_jitter: f32,       // Most that each die's push is nudged by, in any direction
+    pub spin_jitter: f32,       // Most that each die's spin is nudged by
+    pub strength_jitter: f32,   // How much harder or softer a throw can come out (0.08 = 8%)
+    pub spread: f32,            // Sideways push that sends die #2 away from die #1
+}
+
+impl Default for DiceConfig {
+    fn default() -> Self {
+        Self {
+            density: 2.0,
+            restitution: 0.15,
+            friction: 0.7,
+            linear_damping: 2.0,
+            angular_damping: 3.0,
+            impulse_per_power: 0.8,
+            spin: Vec3::new(0.1, 0.2, 0.05),
+            push_jitter: 0.3,
+            spin_jitter: 0.15,
+            strength_jitter: 0.08,
+            spread: 0.5,
+        }
+    }
+}
+
+impl DiceConfig {
+    // How hard (as a physics impulse) a throw at this much power pushes each die
+    fn throw_impulse(&self, power: f32) -> f32 {
+        power * self.impulse_per_power
+    }
+
+    fn damping(&self) -> Damping {
+        Damping {
+            linear_damping: self.linear_damping,
+            angular_damping: self.angular_damping,
+        }
+    }
+}
+
+// The dice file the game is using
+#[derive(Resource)]
+pub struct DiceConfigFile(pub Handle<DiceConfig>);
+
+impl FromWorld for DiceConfigFile {
+    fn from_world(world: &mut World) -> Self {
+        Self(world.resource::<AssetServer>().load("dice.physics.ron"))
+    }
+}
+
+// Where each pip of a face sits, in the die's own space. The pips are laid out on the
+// face's 3 x 3 grid, using two directions that lie flat on that face.
+fn pip_positions(normal: Vec3, value: u8, size: f32) -> Vec<Vec3> {
+    let across = if normal.y.abs() > 0.5 {
+        Vec3::X
+    } else {
+        Vec3::Y
+    };
+    let u = normal.cross(across).normalize();
+    let v = normal.cross(u);
+    let scale = PIP_SPACING * size / 0.4; // Pip spacing is tuned for a 0.4 die
+    PIP_CELLS[usize::from(value) - 1]
+        .iter()
+        .map(|&cell| {
+            let column = (cell % 3) as f32 - 1.0; // -1, 0 or 1
+            let row = (cell / 3) as f32 - 1.0;
+            normal * (size / 2.0) + (u * column + v * row) * scale
+        })
+        .collect()
+}
+
+// Tuning for deciding when the dice have stopped
+#[derive(Resource)]
+pub struct SettleConfig {
+    linear_speed: f32,  // Below this speed (units/second) a die isn't sliding anymore
+    angular_speed: f32, // Below this spin (radians/second) a die isn't rolling anymore
+    duration: f32,      // Both dice must stay still this many seconds to count as settled
+    // How closely the top face must point straight up (1.0 = perfectly flat). A die
+    // leaning on a wall, the other die or a pile of chips below this is "cocked" and has
+    // no clear number.
+    cocked_alignment: f32,
+    // A throw that still hasn't settled this many seconds after it left the hand (a die
+    // spinning on a corner, or jittering against the rubber wall) gets thicker and
+    // thicker air, up to `most_damping` more than usual...
+    slow_after: f32,
+    most_damping: f32,
+    // ...and this long after, every die is put down flat on the face nearest the top,
+    // where it lies, and read from there
+    give_up_after: f32,
+}
+
+impl Default for SettleConfig {
+    fn default() -> Self {
+        Self {
+            linear_speed: 0.05,
+            angular_speed: 0.05,
+            duration: 0.5, // Half a second stops a die that is merely rocking from counting
+            cocked_alignment: 0.97, // About 14 degrees of tilt
+            slow_after: 6.0, // A wild throw is still well over by now
+            most_damping: 20.0,
+            give_up_after: 12.0,
+        }
+    }
+}
+
+impl SettleConfig {
+    fn is_at_rest(&self, velocity: &Velocity) -> bool {
+        velocity.linvel.length() < self.linear_speed
+            && velocity.angvel.length() < self.angular_speed
+    }
+}
+
+// Tracks the throw that is currently rolling across the table
+#[derive(Resource, Default)]
+pub struct RollState {
+    in_flight: bool,  // True from the moment the dice leave the hand until they settle
+    flight_time: f32, // How long since they left the hand
+    still_time: f32,  // How long both dice have been at rest so far
+    cooldown: f32,    // Seconds left before the shooter may throw again after a no roll
+    retrieving: bool, // The stickman is bringing the dice back after a roll
+}
+
+impl RollState {
+    // Are the dice out of the shooter's hand and still moving?
+    pub fn in_flight(&self) -> bool {
+        self.in_flight
+    }
+
+    // Is the stickman still bringing the dice back to the shooter?
+    pub fn retrieving(&self) -> bool {
+        self.retrieving
+    }
+
+    // The stickman's stick (see stickman.rs) has gone for the dice, or has brought them back
+    pub(crate) fn set_retrieving(&mut self, retrieving: bool) {
+        self.retrieving = retrieving;
+    }
+}
+
+const NO_ROLL_COOLDOWN: f32 = 1.5; // The pause while the stickman returns the dice
+
+// Event fired exactly once per throw, when every die has come to rest: what each one
+// shows, however many were thrown
+#[derive(Event, Debug, Clone)]
+pub struct DiceSettled {
+    pub die_values: Vec<u8>, // Top face of each die, die #1 first
+    pub short_roll: bool,    // A die missed the back wall but the table let the roll count
+}
+
+// Event fired with DiceSettled when the game is craps: the two dice as a roll.
+// Events are like messages dropped in a mailbox - any system can read them.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct RollSettled {
+    pub die_values: [u8; 2], // Top face of die #1 and die #2
+    pub total: u8,           // Sum of both dice (2-12)
+    pub short_roll: bool,    // A die missed the back wall but the table let the roll count
+}
+
+// Ask for the dice to be thrown: how hard, from where (the shooter's camera) and at
+// which spot on the felt. The power meter sends one when the throw button is let go;
+// a networked table also sends them on behalf of remote shooters.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ThrowDice {
+    pub power: f32,
+    pub from: Transform,
+    pub target: Option<Vec2>,
+    // A throw made with a VR controller (see vr.rs): how fast the hand was moving as it
+    // let go. The dice leave the hand (`from`) at that speed instead of being pushed by
+    // the power meter, and `target` isn't used.
+    pub hand_velocity: Option<Vec3>,
+    // A flicked throw (see PowerMode::Flick): how far across itself the flick swept,
+    // from -1 (to the left) to 1 (to the right). The dice are spun about the way
+    // they're thrown by that much, like a sideways snap of the wrist. 0 otherwise.
+    pub twist: f32,
+    pub set: DiceSet, // The way round the shooter picked the dice up (see dice_set.rs)
+}
+
+// Who runs the dice on this machine. On a networked table only the shooter may
+// throw, and only the host simulates: everyone else is shown where the host's dice are.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiceAuthority {
+    pub may_throw: bool, // It's our turn to shoot
+    pub simulates: bool, // Our physics throws and reads the dice
+}
+
+impl Default for DiceAuthority {
+    fn default() -> Self {
+        Self {
+            may_throw: true,
+            simulates: true,
+        }
+    }
+}
+
+// Event fired when a throw doesn't count. Bets are left alone; the dice are rolled
+// again instead.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct NoRoll {
+    pub reason: NoRollReason,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoRollReason {
+    CockedDie(u8), // This die (numbered from 1) came to rest tilted, so it is re-rolled
+    OffTable(u8),  // This die left the table, so both dice go back to the shooter
+    ShortRoll,     // A die never reached the back wall and the table re-rolls short rolls
+}
+
+// #[derive(Resource)] marks this as shareable data across systems
+// Resources are like global variables that systems can access
+#[derive(Resource)]
+pub struct ThrowPower {
+    pub current: f32, // Current power level (0 to max)
+    pub max: f32,     // Maximum power allowed
+    charging: bool,   // Is spacebar currently held down?
+    falling: bool,    // In the sweeping mode: is the bar on its way back down?
+    progress: f32,    // How far through its run the meter is, 0 to 1 (see PowerCurve)
+}
+
+// impl Default tells Rust how to create a ThrowPower with default values
+// This is used when we call ThrowPower::default()
+impl Default for ThrowPower {
+    fn default() -> Self {
+        // Self = ThrowPower (shorthand when inside impl)
+        Self {
+            current: 0.0,    // Start with no power
+            max: 15.0,       // Maximum power units
+            charging: false, // Not charging initially
+            falling: false,
+            progress: 0.0,
+        }
+    }
+}
+
+impl ThrowPower {
+    // Whether the throw button is being held, building up a throw
+    pub fn is_charging(&self) -> bool {
+        self.charging
+    }
+}
+
+// How the power meter behaves while Space is held. The player picks one on the
+// settings screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PowerMode {
+    #[default]
+    Charge, // Fills up and stays full - hold longer for a harder throw
+    Sweep, // Bounces up and down on its own - let go at the right moment
+    // Holding just holds the dice: flick the mouse (or the finger on the throw button)
+    // up the screen and let go mid-flick. The faster the flick the harder the throw,
+    // and it goes the way the flick did.
+    Flick,
+}
+
+impl PowerMode {
+    pub fn next(self) -> Self {
+        match self {
+            PowerMode::Charge => PowerMode::Sweep,
+            PowerMode::Sweep => PowerMode::Flick,
+            PowerMode::Flick => PowerMode::Charge,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PowerMode::Charge => "Hold to charge",
+            PowerMode::Sweep => "Sweeping meter",
+            PowerMode::Flick => "Flick",
+        }
+    }
+}
+
+// Tuning for the throw's power meter. Read from assets/throw.meter.ron, and read again
+// whenever that file is saved (in a `--features dev` build), so the throw's feel can be
+// tuned while the game runs. The mode is the player's own, from the settings screen.
+#[derive(Asset, Resource, TypePath, Deserialize, Debug, Clone)]
+pub struct ThrowConfig {
+    #[serde(skip)]
+    pub mode: PowerMode,
+    // How fast the meter runs in Charge and Sweep modes, in power a second - as it
+    // would if the curve were Even
+    pub charge_rate: f32,
+    pub sweep_speed: f32,
+    pub curve: PowerCurve, // How the meter fills over its run
+    // The part of the meter (from 0 to 1) where a throw usually reaches the back wall
+    // without flying off the table, marked on the meter. None marks nothing.
+    pub sweet_spot: Option<(f32, f32)>,
+    // In Flick mode: how fast a flick has to be for a full-power throw, and the
+    // slowest that throws at all, in screen heights a second
+    pub flick_full_speed: f32,
+    pub flick_min_speed: f32,
+}
+
+impl Default for ThrowConfig {
+    fn default() -> Self {
+        Self {
+            mode: PowerMode::default(),
+            charge_rate: 30.0, // Half a second from empty to full
+            sweep_speed: 22.5, // Two-thirds of a second from bottom to top
+            curve: PowerCurve::default(),
+            sweet_spot: Some((0.5, 0.8)),
+            flick_full_speed: 4.0,
+            flick_min_speed: 0.5, // Slower is just moving the mouse, not throwing
+        }
+    }
+}
+
+// How full the power meter is as it runs from empty to full. The run itself always
+// takes the same time; the curve decides where the power goes along the way.
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+pub enum PowerCurve {
+    #[default]
+    Even, // Fills at a steady pace
+    // Slow to start and quicker near the top, for fine control of soft throws. The
+    // number is how strong the effect is: 1 is even, 2 is a gentle curve.
+    EaseIn(f32),
+    EaseOut(f32), // Quick off the mark and slowing near the top, for fine control of hard throws
+    // Straight runs between points, each (how far through the run, how full), in
+    // order - like [(0.5, 0.3), (0.8, 0.9)] for a slow start, a rush, then a crawl to
+    // the top. The run starts empty and ends full either side of them.
+    Stages(Vec<(f32, f32)>),
+}
+
+impl PowerCurve {
+    // How full the meter is (0 to 1) once it's this far (0 to 1) through its run
+    pub fn fill(&self, progress: f32) -> f32 {
+        let t = progress.clamp(0.0, 1.0);
+        let fill = match self {
+            PowerCurve::Even => t,
+            PowerCurve::EaseIn(strength) => t.powf(strength.max(0.1)),
+            PowerCurve::EaseOut(strength) => 1.0 - (1.0 - t).powf(strength.max(0.1)),
+            PowerCurve::Stages(points) => {
+                let mut from = (0.0, 0.0);
+                let mut fill = 1.0; // Past the last point and on to the top
+                for &to in points.iter().chain(&[(1.0, 1.0)]) {
+                    if t <= to.0 {
+                        let span = to.0 - from.0;
+                        fill = if span > 0.0 {
+                            from.1 + (to.1 - from.1) * (t - from.0) / span
+                        } else {
+                            to.1 // Two points at the same moment: a jump
+                        };
+                        break;
+                    }
+                    from = to;
+                }
+                fill
+            }
+        };
+        fill.clamp(0.0, 1.0)
+    }
+}
+
+// The meter file the game is using
+#[derive(Resource)]
+pub struct ThrowConfigFile(pub Handle<ThrowConfig>);
+
+impl FromWorld for ThrowConfigFile {
+    fn from_world(world: &mut World) -> Self {
+        Self(world.resource::<AssetServer>().load("throw.meter.ron"))
+    }
+}
+
+// The one source of randomness for throws. Every throw draws from it in the same order,
+// so the same seed plus the same throws (power and aim) gives the same dice.
+#[derive(Resource)]
+pub struct ThrowRng {
+    seed: u64,
+    rng: ChaCha8Rng,
+}
+
+impl Default for ThrowRng {
+    fn default() -> Self {
+        Self::new(rand::random()) // A fresh seed every session unless one is given
+    }
+}
+
+impl ThrowRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    // The seed this session started from - share it to let someone replay your rolls
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // A random number between -spread and +spread
+    fn jitter(&mut self, spread: f32) -> f32 {
+        let spread = spread.abs(); // A minus sign typed into dice.physics.ron can't panic
+        self.rng.gen_range(-spread..=spread)
+    }
+
+    // A small random nudge to a vector, up to `spread` along each axis
+    fn jitter_vec(&mut self, spread: f32) -> Vec3 {
+        Vec3::new(
+            self.jitter(spread),
+            self.jitter(spread),
+            self.jitter(spread),
+        )
+    }
+
+    // One fair die: 1 to 6, each equally likely
+    pub fn die(&mut self) -> u8 {
+        self.rng.gen_range(1..=6)
+    }
+
+    // A completely random orientation, so the dice don't leave the hand the same way up
+    fn rotation(&mut self) -> Quat {
+        let tau = std::f32::consts::TAU;
+        Quat::from_euler(
+            EulerRot::XYZ,
+            self.rng.gen_range(0.0..tau),
+            self.rng.gen_range(0.0..tau),
+            self.rng.gen_range(0.0..tau),
+        )
+    }
+}
+
+// Where dice wait, hidden and switched off, until the first throw (or after a no roll)
+const DICE_PARKED_AT: Vec3 = Vec3::new(0.0, -5.0, 0.0);
+
+// Take a die out of play: hidden, and ignored by the physics, until the next throw
+fn park_die(commands: &mut Commands, entity: Entity) {
+    commands.entity(entity).insert((
+        RigidBodyDisabled,
+        ColliderDisabled,
+        Visibility::Hidden,
+        Transform::from_translation(DICE_PARKED_AT),
+    ));
+}
+
+// Startup system that holds the loading screen until the skins file is in
+fn wait_for_skins(skin: Res<DiceSkin>, mut loading: ResMut<LoadingAssets>) {
+    loading.add(&skin.library);
+}
+
+fn wait_for_dice_config(file: Res<DiceConfigFile>, mut loading: ResMut<LoadingAssets>) {
+    loading.add(&file.0);
+}
+
+// System that takes up the dice file's numbers when it loads, and again each time it's saved
+fn dice_config_system(
+    file: Res<DiceConfigFile>,
+    configs: Res<Assets<DiceConfig>>,
+    mut config_events: EventReader<AssetEvent<DiceConfig>>,
+    mut config: ResMut<DiceConfig>,
+) {
+    let file_changed = config_events
+        .read()
+        .any(|event| event.is_loaded_with_dependencies(&file.0) || event.is_modified(&file.0));
+    if !file_changed {
+        return;
+    }
+    if let Some(loaded) = configs.get(&file.0) {
+        info!("Dice physics loaded from dice.physics.ron");
+        *config = loaded.clone();
+    }
+}
+
+fn wait_for_throw_config(file: Res<ThrowConfigFile>, mut loading: ResMut<LoadingAssets>) {
+    loading.add(&file.0);
+}
+
+// System that takes up the meter file's numbers when it loads, and again each time it's
+// saved, keeping the mode the player picked
+fn throw_config_system(
+    file: Res<ThrowConfigFile>,
+    configs: Res<Assets<ThrowConfig>>,
+    mut config_events: EventReader<AssetEvent<ThrowConfig>>,
+    mut config: ResMut<ThrowConfig>,
+) {
+    let file_changed = config_events
+        .read()
+        .any(|event| event.is_loaded_with_dependencies(&file.0) || event.is_modified(&file.0));
+    if !file_changed {
+        return;
+    }
+    if let Some(loaded) = configs.get(&file.0) {
+        info!("Power meter loaded from throw.meter.ron");
+        *config = ThrowConfig {
+            mode: config.mode,
+            ..loaded.clone()
+        };
+    }
+}
+
+// System that gives the dice on the table new physics when the numbers change. The
+// throw itself reads DiceConfig as it happens, so only the dice's own parts need it.
+fn dice_physics_system(
+    config: Res<DiceConfig>,
+    mut dice_q: Query<
+        (
+            &mut Restitution,
+            &mut Friction,
+            &mut Damping,
+            &mut ColliderMassProperties,
+        ),
+        With<Dice>,
+    >,
+) {
+    if !config.is_changed() {
+        return;
+    }
+    for (mut restitution, mut friction, mut damping, mut mass) in &mut dice_q {
+        *restitution = Restitution::coefficient(config.restitution);
+        *friction = Friction::coefficient(config.friction);
+        *damping = config.damping();
+        *mass = ColliderMassProperties::Density(config.density);
+    }
+}
+
+// A throw still rolling (or being charged) when the player left the table is
+// forgotten, so the new dice aren't waited on forever
+fn forget_throw(mut roll: ResMut<RollState>, mut power: ResMut<ThrowPower>) {
+    *roll = RollState::default();
+    *power = ThrowPower::default();
+}
+
+// The same dice are used for the whole session - they're created once here (two for
+// craps, three for sic bo) and every throw just picks them up again. Spawning new ones
+// (and new meshes) for each throw would pile up entities and assets over a long session.
+fn spawn_dice(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    shape: Res<DiceShape>,
+    config: Res<DiceConfig>,
+    rules: Res<TableRules>, // Says how many dice the game throws
+) {
+    let body_mesh = meshes.add(Cuboid::new(shape.size, shape.size, shape.size)); // Shared by every die
+    let body_material = materials.add(StandardMaterial {
+        base_color: Srgba::hex("#F4F1EA").unwrap().into(), // Ivory white
+        perceptual_roughness: 0.3,
+        ..default()
+    });
+    // Each pip is a thin black disc, half sunk into the face so it can't flicker.
+    // Square pips are the same idea in a flat box. The skin picks which one is used.
+    let pip_size = PIP_RADIUS * shape.size / 0.4;
+    let pip_mesh = meshes.add(Cylinder::new(pip_size, 0.01));
+    let square_pip = meshes.add(Cuboid::new(pip_size * 1.7, 0.01, pip_size * 1.7));
+    let pip_material = materials.add(StandardMaterial {
+        base_color: Color::BLACK,
+        perceptual_roughness: 0.6,
+        ..default()
+    });
+    commands.insert_resource(DiceAssets {
+        body_material: body_material.clone(),
+        pip_material: pip_material.clone(),
+        round_pip: pip_mesh.clone(),
+        square_pip,
+    });
+
+    for id in 1..=rules.variant.dice_count() {
+        let entity = commands
+            .spawn((
+                StateScoped(InGame),
+                // Group of components that make up a die
+                RigidBody::Dynamic, // Dynamic = affected by gravity and forces
+                shape.collider(),   // Physics collision box
+                Restitution::coefficient(config.restitution), // Bounciness
+                Friction::coefficient(config.friction), // How much it grips surfaces
+                config.damping(),   // Slows down over time (air resistance)
+                Ccd::enabled(),     // Continuous Collision Detection - prevents tunneling
+                ColliderMassProperties::Density(config.density), // How heavy it is
+                Mesh3d(body_mesh.clone()),
+                MeshMaterial3d(body_material.clone()),
+                Dice,                           // Tag as dice
+                DiceId(id),                     // Die #1, #2 (or #3 at sic bo)
+                Name::new(format!("Dice{id}")), // Debug name
+                Velocity::default(), // Rapier writes the die's current speed in here every step
+                ExternalImpulse::default(), // Filled in with the throw's push each throw
+            ))
+            .with_children(|die| {
+                // The pips are painted on to match DIE_FACES, so what you see on top is
+                // always what top_face() reads
+                for &(normal, value) in &DIE_FACES {
+                    // Cylinders stand along Y; tip each one over to face out of its side
+                    let facing = Quat::from_rotation_arc(Vec3::Y, normal);
+                    for position in pip_positions(normal, value, shape.size) {
+                        die.spawn((
+                            Mesh3d(pip_mesh.clone()),
+                            MeshMaterial3d(pip_material.clone()),
+                            Transform::from_translation(position).with_rotation(facing),
+                            DicePip,
+                        ));
+                    }
+                }
+            })
+            .id();
+        park_die(&mut commands, entity);
+    }
+}
+
+// System that dresses the dice in the chosen skin - when the skins file first loads,
+// when it is edited, or when the player picks another skin
+fn dice_skin_system(
+    skin: Res<DiceSkin>,
+    libraries: Res<Assets<DiceSkinLibrary>>,
+    mut library_events: EventReader<AssetEvent<DiceSkinLibrary>>,
+    dice_assets: Res<DiceAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut pips_q: Query<&mut Mesh3d, With<DicePip>>,
+) {
+    let library_changed = library_events.read().any(|event| {
+        event.is_loaded_with_dependencies(&skin.library) || event.is_modified(&skin.library)
+    });
+    if !library_changed && !skin.is_changed() {
+        return;
+    }
+    let Some(style) = skin.current(&libraries) else {
+        return; // Still loading - the dice keep their default look for now
+    };
+
+    if let Some(body) = materials.get_mut(&dice_assets.body_material) {
+        body.base_color = skin_color(&style.body).with_alpha(style.alpha);
+        // See-through dice need blending, and a glossy finish so they look like acrylic
+        if style.alpha < 1.0 {
+            body.alpha_mode = AlphaMode::Blend;
+            body.perceptual_roughness = 0.1;
+        } else {
+            body.alpha_mode = AlphaMode::Opaque;
+            body.perceptual_roughness = 0.3;
+        }
+    }
+    if let Some(pips) = materials.get_mut(&dice_assets.pip_material) {
+        pips.base_color = skin_color(&style.pips);
+    }
+    let pip_mesh = match style.pip_style {
+        PipStyle::Round => &dice_assets.round_pip,
+        PipStyle::Square => &dice_assets.square_pip,
+    };
+    for mut mesh in &mut pips_q {
+        mesh.0 = pip_mesh.clone();
+    }
+}
+
+// Where the mouse cursor points on the table - the throw goes towards it. None when
+// the cursor is off the table (or hidden while looking around), and the throw then
+// just goes straight ahead.
+#[derive(Resource, Default)]
+pub struct ThrowTarget(pub Option<Vec2>);
+
+// Where a throw starts from and which way it goes. The shooter's dice cup is held here.
+pub(crate) struct ThrowAim {
+    pub(crate) origin: Vec3, // Middle point between the two dice as they leave the hand
+    pub(crate) forward: Vec3, // Flat (no up/down) direction of the throw
+    pub(crate) right: Vec3,  // Flat direction to the shooter's right, for spacing the dice apart
+}
+
+// Keep a throw's starting point inside the table's walls
+fn over_table(mut origin: Vec3, table_size: Vec2) -> Vec3 {
+    let half = table_size / 2.0; // Half the table's width (x) and depth (y)
+    let margin = 0.3; // Safety margin from walls (30cm)
+
+    // clamp() limits value between min and max
+    origin.x = origin.x.clamp(-half.x + margin, half.x - margin);
+    origin.z = origin.z.clamp(-half.y + margin, half.y - margin);
+    origin
+}
+
+impl ThrowAim {
+    // Where a thrown ThrowDice goes from. A hand throw leaves from the hand itself (kept
+    // over the felt) the way the hand was moving; any other leaves from in front of
+    // the shooter's camera, towards the target.
+    pub(crate) fn of_throw(throw: &ThrowDice, table_size: Vec2) -> Self {
+        let aimed = Self::new(&throw.from, throw.target, table_size);
+        let Some(forward) = throw
+            .hand_velocity
+            .and_then(|velocity| velocity.with_y(0.0).try_normalize())
+        else {
+            return aimed; // Not a hand throw, or one straight up or down
+        };
+        let hand = throw.from.translation;
+        Self {
+            origin: over_table(hand.with_y(hand.y.clamp(0.3, 2.0)), table_size),
+            forward,
+            right: forward.cross(Vec3::Y).normalize(),
+        }
+    }
+
+    pub(crate) fn new(cam_transform: &Transform, target: Option<Vec2>, table_size: Vec2) -> Self {
+        let cam_forward = cam_transform.forward();
+
+        // Straight ahead from the camera, if there's nothing better to aim at
+        let ahead = Vec3::new(cam_forward.x, 0.0, cam_forward.z) // Remove Y component
+            .normalize(); // normalize() makes length = 1 (unit vector)
+
+        // Spawn dice 1 unit in front of camera
+        let mut origin = cam_transform.translation + ahead * 1.0;
+        origin.y = 0.5; // Fixed height above table
+
+        let origin = over_table(origin, table_size);
+
+        // Throw from the hand towards the target, flat along the table
+        let forward = target
+            .map(|point| Vec3::new(point.x, 0.0, point.y) - origin.with_y(0.0))
+            .and_then(|towards| towards.try_normalize()) // None if the target is right under the hand
+            .unwrap_or(ahead);
+
+        // Calculate right vector for separating dice
+        let right = forward.cross(Vec3::Y).normalize();
+        // cross product gives perpendicular vector
+
+        Self {
+            origin,
+            forward,
+            right,
+        }
+    }
+}
+
+// System that follows the cursor (or gamepad pointer) across the felt and draws a
+// target reticle where it points, so the shooter can see where the dice are going
+fn aim_system(input: Res<PlayerInput>, mut target: ResMut<ThrowTarget>, mut gizmos: Gizmos) {
+    if target.0 != input.pointer {
+        target.0 = input.pointer;
+    }
+
+    let Some(point) = input.pointer else {
+        return;
+    };
+    // A ring with a cross through it, lying flat on the felt
+    let center = Vec3::new(point.x, 0.01, point.y);
+    let color = Color::srgba(1.0, 1.0, 1.0, 0.7);
+    gizmos.circle(
+        Isometry3d::new(center, Quat::from_rotation_arc(Vec3::Z, Vec3::Y)), // Circles face +Z; lay it flat
+        0.15,
+        color,
+    );
+    gizmos.line(center - Vec3::X * 0.2, center + Vec3::X * 0.2, color);
+    gizmos.line(center - Vec3::Z * 0.2, center + Vec3::Z * 0.2, color);
+}
+
+const GRAVITY: f32 = 9.81; // Rapier's default pull, in units/second² downwards
+
+// System that draws the path the dice will roughly follow while a throw charges up.
+// The dice fly like a thrown ball slowed by air resistance, so we trace that curve
+// from the hand until they hit the felt, and ring the spot where they'll land.
+fn trajectory_preview_system(
+    power: Res<ThrowPower>,
+    shape: Res<DiceShape>,
+    config: Res<DiceConfig>,
+    throw_config: Res<ThrowConfig>,
+    cam_q: Query<&Transform, With<PlayerCamera>>,
+    target: Res<ThrowTarget>,
+    table_layout: Res<TableLayout>,
+    mut gizmos: Gizmos,
+) {
+    // A flick's direction isn't known until it's let go, so there's nothing to draw
+    if !power.charging || throw_config.mode == PowerMode::Flick {
+        return;
+    }
+    let Ok(cam_transform) = cam_q.single() else {
+        return;
+    };
+    let aim = ThrowAim::new(cam_transform, target.0, table_layout.size);
+
+    // Speed = push / weight
+    let mass = config.density * shape.size.powi(3);
+    let speed = config.throw_impulse(power.current) / mass;
+    let k = config.linear_damping;
+    let landing_height = shape.size / 2.0; // The die's centre when it sits on the table
+
+    // Sample the flight every 1/60th of a second for up to two seconds
+    let mut points = Vec::new();
+    for step in 0..=120 {
+        let t = step as f32 / 60.0;
+        let slowdown = 1.0 - (-k * t).exp(); // How much of its speed damping has taken away
+        let along = speed / k * slowdown;
+        let height = aim.origin.y - GRAVITY / k * t + GRAVITY / (k * k) * slowdown;
+        let point = aim.origin + aim.forward * along;
+        if height <= landing_height {
+            points.push(point.with_y(landing_height));
+            break;
+        }
+        points.push(point.with_y(height));
+    }
+
+    let color = Color::srgba(1.0, 0.33, 0.0, 0.8); // Papaya orange, slightly see-through
+    if let Some(&landing) = points.last() {
+        gizmos.circle(
+            Isometry3d::new(
+                landing.with_y(0.01),                      // Just above the felt
+                Quat::from_rotation_arc(Vec3::Z, Vec3::Y), // Circles face +Z; lay it flat
+            ),
+            shape.size,
+            color,
+        );
+    }
+    gizmos.linestrip(points, color);
+}
+
+// System that handles throwing dice when spacebar is pressed
+pub(crate) fn throw_system(
+    input: Res<PlayerInput>, // Space bar or gamepad trigger, through one interface
+    mut power_res: ResMut<ThrowPower>, // Our power meter data (ResMut = can modify)
+    time: Res<Time>,         // Game time - for frame-independent movement
+    cam_q: Query<&Transform, With<PlayerCamera>>, // Find camera position/rotation
+    target: Res<ThrowTarget>, // Where the cursor (or gamepad pointer) is aiming the throw
+    config: Res<ThrowConfig>, // How the power meter moves while Space is held
+    table_layout: Res<TableLayout>, // Where a flick's throw leaves from
+    dice_set: Res<DiceSet>,  // How the shooter has set the dice
+    mut roll: ResMut<RollState>, // For the pause after a no roll
+    mut started_events: EventWriter<ThrowStarted>, // Sent when the throw button goes down
+    mut throw_events: EventWriter<ThrowDice>, // Sent when the throw button is let go
+    authority: Res<DiceAuthority>, // Whether it's our turn to shoot
+    replay: Res<InstantReplay>, // No throwing while the last roll is being replayed
+    playback: Option<Res<SessionPlayback>>, // Or while a recorded session is playing
+) {
+    if replay.is_playing() || playback.is_some() || !authority.may_throw {
+        return;
+    }
+    // After a no roll the shooter waits for the dice to come back
+    if roll.cooldown > 0.0 {
+        roll.cooldown = (roll.cooldown - time.delta_secs()).max(0.0);
+        return;
+    }
+    // After a roll the dice have to be back in front of the shooter first
+    if roll.retrieving {
+        return;
+    }
+
+    // Start charging when space is first pressed
+    if input.throw_started {
+        // just_pressed = this exact frame
+        power_res.current = 0.0; // Reset power to zero
+        power_res.progress = 0.0;
+        power_res.charging = true; // Start charging up
+        power_res.falling = false;
+        started_events.write(ThrowStarted);
+    }
+    // While holding space, move the power meter
+    if input.throw_held && power_res.charging {
+        // delta_secs() = seconds since last frame - keeps the meter frame-independent
+        let dt = time.delta_secs();
+        // The meter runs evenly through `progress`, and the curve turns that into power
+        match config.mode {
+            PowerMode::Charge => {
+                let step = config.charge_rate / power_res.max * dt;
+                power_res.progress = (power_res.progress + step).min(1.0); // Stops when full
+                power_res.current = config.curve.fill(power_res.progress) * power_res.max;
+            }
+            PowerMode::Sweep => {
+                // Run up to the top, turn round, run down to the bottom, and again...
+                let step = config.sweep_speed / power_res.max * dt;
+                if power_res.falling {
+                    power_res.progress -= step;
+                    if power_res.progress <= 0.0 {
+                        power_res.progress = -power_res.progress; // Bounce off the bottom
+                        power_res.falling = false;
+                    }
+                } else {
+                    power_res.progress += step;
+                    if power_res.progress >= 1.0 {
+                        power_res.progress = 2.0 - power_res.progress; // Bounce off the top
+                        power_res.falling = true;
+                    }
+                }
+                power_res.progress = power_res.progress.clamp(0.0, 1.0);
+                power_res.current = config.curve.fill(power_res.progress) * power_res.max;
+            }
+            // The meter just shows how hard the flick would throw if it were let go now
+            PowerMode::Flick => {
+                power_res.current = flick_power(input.flick, &config, power_res.max);
+            }
+        }
+    }
+    if input.throw_released && power_res.charging {
+        power_res.charging = false;
+        // A VR hand throws from the hand; anything else from the camera
+        let throw = match input.hand_throw {
+            Some((hand, velocity)) => Some(ThrowDice {
+                power: power_res.current,
+                from: hand,
+                target: None,
+                hand_velocity: Some(velocity),
+                twist: 0.0,
+                set: *dice_set,
+            }),
+            None if config.mode == PowerMode::Flick => cam_q.single().ok().and_then(|&from| {
+                flick_throw(input.flick, from, &config, &power_res, &table_layout).map(|throw| {
+                    ThrowDice {
+                        set: *dice_set,
+                        ..throw
+                    }
+                })
+            }),
+            None => cam_q.single().ok().map(|&from| ThrowDice {
+                power: power_res.current,
+                from,
+                target: target.0,
+                hand_velocity: None,
+                twist: 0.0,
+                set: *dice_set,
+            }),
+        };
+        if let Some(throw) = throw {
+            throw_events.write(throw);
+        }
+        power_res.current = 0.0; // Reset power meter
+    }
+}
+
+// How hard a flick throws: faster is harder, up to the meter's top
+fn flick_power(flick: Vec2, config: &ThrowConfig, max: f32) -> f32 {
+    (flick.length() / config.flick_full_speed).min(1.0) * max
+}
+
+// The throw a flick makes, or None if the pointer was barely moving (or was heading
+// back towards the shooter) as the button was let go - then the dice stay in hand
+fn flick_throw(
+    flick: Vec2,
+    from: Transform,
+    config: &ThrowConfig,
+    power: &ThrowPower,
+    table_layout: &TableLayout,
+) -> Option<ThrowDice> {
+    if flick.y <= 0.0 || flick.length() < config.flick_min_speed {
+        return None;
+    }
+    // Up the screen is away down the table and right is to the shooter's right. It's
+    // kept within 45 degrees of straight ahead, so a wild flick still hits the far wall.
+    let across = (flick.x / flick.y).clamp(-1.0, 1.0);
+    let aim = ThrowAim::new(&from, None, table_layout.size);
+    let towards = aim.forward + aim.right * across;
+    Some(ThrowDice {
+        power: flick_power(flick, config, power.max),
+        from,
+        // Any spot that way will do - only the direction from the hand counts
+        target: Some(aim.origin.xz() + towards.xz()),
+        hand_velocity: None,
+        twist: across,
+        set: DiceSet::Random,
+    })
+}
+
+const FLICK_SPIN: f32 = 0.3; // Extra spin from a flick swept all the way across
+const SET_JITTER: f32 = 0.25; // How much of the usual random spin a set throw keeps
+
+// The way a set die starts out: the set's top face up and its other face looking back
+// along the throw at the shooter
+fn set_rotation(faces: SetFaces, forward: Vec3) -> Quat {
+    let normal = |value: u8| {
+        DIE_FACES
+            .iter()
+            .find(|&&(_, face)| face == value)
+            .map_or(Vec3::Y, |&(normal, _)| normal)
+    };
+    let (top, back) = (normal(faces.top), normal(faces.towards_shooter));
+    // Turn the die's own (top, back, side) directions onto (up, back, side) in the world
+    let own = Mat3::from_cols(top, back, top.cross(back));
+    let world = Mat3::from_cols(Vec3::Y, -forward, Vec3::Y.cross(-forward));
+    Quat::from_mat3(&(world * own.transpose()))
+}
+
+// System that picks the dice up and throws them, for every ThrowDice sent this frame
+fn launch_dice_system(
+    mut commands: Commands, // For putting the dice back into play
+    mut throw_events: EventReader<ThrowDice>,
+    authority: Res<DiceAuthority>,
+    mut roll: ResMut<RollState>, // Lets the settle detector know a throw is underway
+    // The dice, wherever the last throw left them
+    mut dice_q: Query<
+        (
+            Entity,
+            &DiceId,
+            &mut Transform,
+            &mut Velocity,
+            &mut ExternalImpulse,
+        ),
+        With<Dice>,
+    >,
+    mut throw_rng: ResMut<ThrowRng>, // Adds a little human wobble to every throw
+    rules: Res<TableRules>,          // Says whether the RNG or the physics picks the result
+    table_layout: Res<TableLayout>,  // Keeps the throw starting over the felt
+    config: Res<DiceConfig>,         // How hard and with how much spin the dice leave the hand
+    shape: Res<DiceShape>,           // How heavy a die is, for a hand throw
+    daily: Option<Res<DailyChallenge>>, // The day's dice, when it's the daily challenge
+    mut launched_events: EventWriter<DiceLaunched>,
+) {
+    if !authority.simulates {
+        return; // Another machine throws the dice (the throw is sent there instead)
+    }
+    for throw in throw_events.read() {
+        let ThrowAim {
+            origin: throw_origin,
+            forward: forward_flat,
+            right: right_vec,
+        } = ThrowAim::of_throw(throw, table_layout.size);
+
+        let impulse_main = match throw.hand_velocity {
+            // The dice keep the hand's speed: push = weight x speed
+            Some(velocity) => velocity * config.density * shape.size.powi(3),
+            // Convert power meter to physics impulse
+            None => forward_flat * config.throw_impulse(throw.power), // Direction * magnitude
+        };
+
+        // No two real throws are identical: each die gets a slightly different push,
+        // its own spin and a random starting orientation. Always drawn in this order,
+        // so a seed replays exactly.
+        let count = dice_q.iter().count();
+        let strength = 1.0 + throw_rng.jitter(config.strength_jitter);
+        // Die #1 goes straight, and the others are spread out to its left, the last
+        // one furthest
+        let spread = |index: usize| index as f32 / (count.max(2) - 1) as f32 * config.spread;
+        let impulses: Vec<Vec3> = (0..count)
+            .map(|index| {
+                impulse_main * strength - right_vec * spread(index)
+                    + throw_rng.jitter_vec(config.push_jitter)
+            })
+            .collect();
+        let mirrored_spin = config.spin * Vec3::new(-1.0, 1.0, -1.0);
+        // A flick across itself rolls every die the same way about the throw's line
+        let twist = forward_flat * throw.twist * FLICK_SPIN;
+        let set = throw.set.faces();
+        // Set dice are thrown with backspin about their set axis (the shooter's left
+        // to right), and keep only a little of the random spin. Otherwise every other
+        // die spins the mirror way.
+        let spin = |index: usize| match set {
+            Some(_) => right_vec * config.spin.length(),
+            None if index % 2 == 0 => config.spin,
+            None => mirrored_spin,
+        };
+        let spin_jitter = match set {
+            Some(_) => config.spin_jitter * SET_JITTER,
+            None => config.spin_jitter,
+        };
+        let torques: Vec<Vec3> = (0..count)
+            .map(|index| spin(index) + twist + throw_rng.jitter_vec(spin_jitter))
+            .collect();
+        // Drawn even for set dice, so the draws (and a seed's replay) stay in step
+        let mut rotations: Vec<Quat> = (0..count).map(|_| throw_rng.rotation()).collect();
+        if let Some(faces) = set {
+            // A set is for a pair of dice; any third die is picked up any way up
+            for (rotation, faces) in rotations.iter_mut().zip(faces) {
+                *rotation = set_rotation(faces, forward_flat);
+            }
+        }
+
+        // In RNG-authoritative mode the result is decided right now, before the dice land.
+        // The daily challenge's come from a stream of their own, which only moves on when
+        // a roll counts, so a no roll or a cocked die's hop can't shift the day's rolls.
+        let forced: Option<Vec<u8>> = match (rules.roll_mode, &daily) {
+            (RollMode::Physics, _) => None,
+            (RollMode::RngAuthoritative, Some(daily)) => Some(daily.dice(count)),
+            (RollMode::RngAuthoritative, None) => {
+                Some((0..count).map(|_| throw_rng.die()).collect())
+            }
+        };
+
+        // Pick the same dice up again and throw them from the shooter's hand, side by
+        // side: die #1 on the right, the last die on the left
+        let middle = (count as f32 - 1.0) / 2.0;
+        for (entity, id, mut transform, mut velocity, mut impulse) in &mut dice_q {
+            let index = usize::from(id.0 - 1).min(count - 1); // DiceId is 1-based
+            let offset = right_vec * (middle - index as f32) * 0.5;
+            *transform =
+                Transform::from_translation(throw_origin + offset).with_rotation(rotations[index]);
+            *velocity = Velocity::zero(); // Forget how it was moving at the end of the last throw
+            *impulse = ExternalImpulse {
+                impulse: impulses[index],       // Linear push
+                torque_impulse: torques[index], // Spin
+            };
+
+            // Back into play, with everything left over from the last throw cleared
+            let mut die = commands.entity(entity);
+            die.remove::<(
+                RigidBodyDisabled,
+                ColliderDisabled,
+                HitBackWall,
+                ForcedFace,
+                DiceValue,
+            )>()
+            // Any extra damping a stuck throw was given goes too
+            .insert((Visibility::Inherited, config.damping()));
+            if let Some(values) = &forced {
+                die.insert(ForcedFace(values[index]));
+            }
+        }
+
+        // The dice are out - the settle system takes it from here
+        roll.in_flight = true;
+        roll.flight_time = 0.0;
+        roll.still_time = 0.0;
+        launched_events.write(DiceLaunched { throw: *throw });
+    }
+}
+
+// A rotation that turns a die over so `value` is on top, keeping the way it faces -
+// for putting the dice down showing a result nobody threw (the debug console)
+pub fn showing_face(rotation: Quat, value: u8) -> Quat {
+    let Some(&(normal, _)) = DIE_FACES.iter().find(|&&(_, face)| face == value) else {
+        return rotation;
+    };
+    Quat::from_rotation_arc(rotation * normal, Vec3::Y) * rotation
+}
+
+// Figure out which face of a die points up, given the die's rotation.
+// We rotate each face's direction into world space and pick the one with the
+// largest Y (the one pointing closest to straight up).
+fn top_face(rotation: Quat) -> u8 {
+    DIE_FACES
+        .iter()
+        .max_by(|a, b| (rotation * a.0).y.total_cmp(&(rotation * b.0).y))
+        .map(|&(_, value)| value)
+        .unwrap_or(1) // DIE_FACES is never empty, so this never actually happens
+}
+
+// System that turns each die towards its forced face as it slows down (RNG-authoritative
+// mode only). While a die is flying the physics is left alone; once it is sliding
+// slowly we take over its spin and roll it over until the right face is on top.
+fn steer_dice_system(
+    time: Res<Time>,
+    mut dice_q: Query<(&ForcedFace, &mut Transform, &mut Velocity), With<Dice>>,
+) {
+    for (forced, mut transform, mut velocity) in &mut dice_q {
+        if velocity.linvel.length() > STEER_SPEED {
+            continue; // Still flying - let it bounce around naturally
+        }
+        let Some(&(normal, _)) = DIE_FACES.iter().find(|&&(_, value)| value == forced.0) else {
+            continue;
+        };
+
+        // Where the forced face points now, and how far it is from pointing straight up
+        let face_now = transform.rotation * normal;
+        let angle = face_now.angle_between(Vec3::Y);
+        if angle < 0.01 {
+            continue; // Already showing the right number
+        }
+
+        // Turn a little this frame, along the shortest way round
+        let correction = Quat::from_rotation_arc(face_now, Vec3::Y);
+        let step = (STEER_TURN_RATE * time.delta_secs() / angle).min(1.0);
+        transform.rotation = Quat::IDENTITY.slerp(correction, step) * transform.rotation;
+        velocity.angvel = Vec3::ZERO; // Stop the physics spinning it back the other way
+    }
+}
+
+// Has a die finished turning to its forced face? Dice without one always have.
+fn shows_forced_face(transform: &Transform, forced: Option<&ForcedFace>) -> bool {
+    forced.is_none_or(|face| top_face(transform.rotation) == face.0)
+}
+
+// System that spots a die that has jumped the wall or fallen through the table. The
+// throw doesn't count: both dice are taken off the table, no bets are touched, and the shooter
+// gets them back after a short pause.
+fn off_table_system(
+    mut commands: Commands,
+    table_layout: Res<TableLayout>,
+    mut roll: ResMut<RollState>,
+    dice_q: Query<(Entity, &DiceId, &Transform), With<Dice>>,
+    mut no_roll_events: EventWriter<NoRoll>,
+) {
+    if !roll.in_flight {
+        return;
+    }
+    let half = table_layout.size / 2.0;
+    let Some((_, stray, _)) = dice_q.iter().find(|(_, _, transform)| {
+        let position = transform.translation;
+        position.x.abs() > half.x || position.z.abs() > half.y || position.y < 0.0
+    }) else {
+        return; // Both dice are still on the felt
+    };
+
+    info!("Die {} left the table - no roll", stray.0);
+    for (entity, _, _) in &dice_q {
+        park_die(&mut commands, entity);
+    }
+    roll.in_flight = false;
+    roll.still_time = 0.0;
+    roll.cooldown = NO_ROLL_COOLDOWN;
+    no_roll_events.write(NoRoll {
+        reason: NoRollReason::OffTable(stray.0),
+    });
+}
+
+// System that listens to Rapier's collision events and marks each die that touches
+// the back wall. Events name the two colliders involved, in no particular order.
+fn back_wall_system(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    dice_q: Query<(), (With<Dice>, Without<HitBackWall>)>,
+    wall_q: Query<(), With<BackWall>>,
+) {
+    for event in collision_events.read() {
+        let CollisionEvent::Started(a, b, _) = *event else {
+            continue; // We only care about the moment of impact
+        };
+        for (die, other) in [(a, b), (b, a)] {
+            if dice_q.contains(die) && wall_q.contains(other) {
+                commands.entity(die).insert(HitBackWall);
+            }
+        }
+    }
+}
+
+// How close to straight up the die's top face points: 1.0 when it sits flat on a face,
+// about 0.7 when it is balanced on an edge
+fn up_alignment(rotation: Quat) -> f32 {
+    DIE_FACES
+        .iter()
+        .map(|&(normal, _)| (rotation * normal).y)
+        .fold(f32::MIN, f32::max)
+}
+
+// System that reads the top face of every die that has stopped moving
+fn dice_face_system(
+    mut commands: Commands,
+    settle: Res<SettleConfig>,
+    dice_q: Query<(Entity, &Transform, &Velocity, Option<&DiceValue>), With<Dice>>,
+) {
+    for (entity, transform, velocity, current) in &dice_q {
+        if !settle.is_at_rest(velocity) {
+            // Still tumbling - forget any old reading so nobody trusts a stale value
+            if current.is_some() {
+                commands.entity(entity).remove::<DiceValue>();
+            }
+            continue;
+        }
+
+        let value = DiceValue(top_face(transform.rotation));
+        if current != Some(&value) {
+            commands.entity(entity).insert(value);
+        }
+    }
+}
+
+// System that stops a throw from rolling for ever. Past the settle config's
+// `slow_after` the dice are slowed harder and harder; past `give_up_after` each die is
+// laid flat on the felt showing its nearest face (or the face it's being steered to)
+// and held there, and settle_system reads them as they lie.
+fn stuck_dice_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    settle: Res<SettleConfig>,
+    config: Res<DiceConfig>,
+    shape: Res<DiceShape>,
+    mut roll: ResMut<RollState>,
+    mut dice_q: Query<
+        (
+            Entity,
+            &DiceId,
+            &mut Transform,
+            &mut Velocity,
+            &mut Damping,
+            Option<&ForcedFace>,
+            Has<RigidBodyDisabled>,
+        ),
+        With<Dice>,
+    >,
+) {
+    if !roll.in_flight {
+        return;
+    }
+    roll.flight_time += time.delta_secs();
+    let late = roll.flight_time - settle.slow_after;
+    if late <= 0.0 {
+        return; // Most throws are long over by now
+    }
+
+    if roll.flight_time < settle.give_up_after {
+        // Up to the most damping by the time we give up
+        let ramp = late / (settle.give_up_after - settle.slow_after).max(f32::EPSILON);
+        let extra = settle.most_damping * ramp.min(1.0);
+        for (.., mut damping, _, _) in &mut dice_q {
+            damping.linear_damping = config.linear_damping + extra;
+            damping.angular_damping = config.angular_damping + extra;
+        }
+        return;
+    }
+
+    for (entity, id, mut transform, mut velocity, _, forced, disabled) in &mut dice_q {
+        let lying_flat = settle.is_at_rest(&velocity)
+            && up_alignment(transform.rotation) >= settle.cocked_alignment;
+        if disabled || lying_flat {
+            continue; // Already put down, or stopped on its own
+        }
+        let face = forced.map_or_else(|| top_face(transform.rotation), |forced| forced.0);
+        info!(
+            "Die {} wouldn't settle - putting it down showing {face}",
+            id.0
+        );
+        transform.rotation = showing_face(transform.rotation, face);
+        transform.translation.y = shape.size / 2.0; // Sitting on the felt
+        *velocity = Velocity::zero();
+        // The physics would only start it jittering again. The next throw turns it
+        // back on.
+        commands.entity(entity).insert(RigidBodyDisabled);
+    }
+}
+
+// System that waits for every die to stop, then announces the result with a DiceSettled
+// event (and a RollSettled too, for the two dice of craps)
+fn settle_system(
+    time: Res<Time>,
+    settle: Res<SettleConfig>,
+    mut roll: ResMut<RollState>,
+    mut dice_q: Query<
+        (
+            Entity,
+            &DiceId,
+            &Transform,
+            &Velocity,
+            Option<&ForcedFace>,
+            &mut ExternalImpulse,
+            Has<HitBackWall>,
+        ),
+        With<Dice>,
+    >,
+    mut commands: Commands,
+    rules: Res<TableRules>,
+    mut dice_events: EventWriter<DiceSettled>,
+    mut settled_events: EventWriter<RollSettled>,
+    mut no_roll_events: EventWriter<NoRoll>,
+    mut throw_rng: ResMut<ThrowRng>, // For the little flick that re-rolls a cocked die
+) {
+    if !roll.in_flight {
+        return; // Nothing has been thrown - nothing to wait for
+    }
+
+    // Every die must exist (spawn commands apply a frame late), all must be still and
+    // any die being steered to a forced face must have got there
+    let count = usize::from(rules.variant.dice_count());
+    let all_still = dice_q.iter().count() == count
+        && dice_q
+            .iter()
+            .all(|(_, _, transform, velocity, forced, _, _)| {
+                settle.is_at_rest(velocity) && shows_forced_face(transform, forced)
+            });
+    if !all_still {
+        roll.still_time = 0.0; // Any wobble restarts the countdown
+        return;
+    }
+
+    roll.still_time += time.delta_secs();
+    if roll.still_time < settle.duration {
+        return;
+    }
+
+    // A die resting tilted against a wall, the other die or a pile of chips has no
+    // clear top face.
+    // Rather than guess, call "no roll" and flick that die back into the air.
+    let mut cocked = false;
+    for (_, id, transform, _, _, mut impulse, _) in &mut dice_q {
+        if up_alignment(transform.rotation) >= settle.cocked_alignment {
+            continue;
+        }
+        info!("Die {} is cocked - no roll, re-rolling it", id.0);
+        impulse.impulse = Vec3::Y * 0.25 + throw_rng.jitter_vec(0.05); // A small hop
+        impulse.torque_impulse = throw_rng.jitter_vec(0.05); // Tumble it onto a face
+        no_roll_events.write(NoRoll {
+            reason: NoRollReason::CockedDie(id.0),
+        });
+        cocked = true;
+    }
+    if cocked {
+        roll.still_time = 0.0; // Wait for it to land again
+        return;
+    }
+
+    // Every die has to have bounced off the back wall for a fair throw
+    let short = dice_q.iter().any(|(.., hit_back_wall)| !hit_back_wall);
+    if short && rules.short_roll == ShortRollPolicy::ReRoll {
+        info!("Short roll - the dice go back to the shooter");
+        for (entity, ..) in &dice_q {
+            park_die(&mut commands, entity);
+        }
+        roll.in_flight = false;
+        roll.cooldown = NO_ROLL_COOLDOWN;
+        no_roll_events.write(NoRoll {
+            reason: NoRollReason::ShortRoll,
+        });
+        return;
+    }
+    let short_roll = short && rules.short_roll == ShortRollPolicy::Warn;
+
+    let mut die_values = vec![0u8; count];
+    for (_, id, transform, ..) in &dice_q {
+        // DiceId is 1-based, the list is 0-based
+        if let Some(slot) = die_values.get_mut(usize::from(id.0) - 1) {
+            *slot = top_face(transform.rotation);
+        }
+    }
+
+    roll.in_flight = false;
+    let total: u8 = die_values.iter().sum();
+    let shown: Vec<String> = die_values.iter().map(u8::to_string).collect();
+    info!("Dice settled: {} = {total}", shown.join(" + "));
+    if short_roll {
+        info!("Short roll - every die should hit the back wall");
+    }
+    if let [first, second] = die_values[..] {
+        settled_events.write(RollSettled {
+            die_values: [first, second],
+            total,
+            short_roll,
+        });
+    }
+    dice_events.write(DiceSettled {
+        die_values,
+        short_roll,
+    });
+}
@@ -0,0 +1,686 @@
+// Two to four players sharing one machine (`cargo run -- --players 3`). Each player is
+// an entity with their own bankroll, bets and chip colour. Before every throw, control
+// goes round the table: each player in turn puts their chips down and presses Enter
+// (or the Done button), finishing with the shooter, who bets and then throws. After a
+// seven-out the stickman slides the dice round to the next seat clockwise. A player
+// offered the dice can pass them on (P) instead of shooting.
+//
+// The rest of the game only knows about one bankroll and one set of bets - the
+// `Bankroll` and `TableBets` resources - so those always belong to whoever has
+// control. Handing over swaps the next player's money in and parks the last one's in
+// their `PlayerTable`. Parked bets are settled with the same `TableState` code the
+// network host uses for its remote players, and drawn on the felt as small markers in
+// their owner's colour, next to the usual chip piles.
+use bevy::color::prelude::*;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use super::betting::RollResolved;
+use super::controls::PlayerInput;
+use super::dice::{Dice, DiceAuthority, DiceId, RollSettled, RollState};
+use super::locale::Localized;
+use super::{AppState, InGame, RollStep};
+use crate::betting::TableBets;
+use crate::layout::TableLayout;
+use crate::locale::Locale;
+use crate::payout::{Bankroll, break_into_chips, format_money};
+use crate::rules::{RoundPhase, ShooterRotation, TableRules};
+use crate::session::TableState;
+
+pub struct HotseatPlugin;
+
+impl Plugin for HotseatPlugin {
+    fn build(&self, app: &mut App) {
+        // main.rs inserts the table before the plugins are added
+        if !app.world().contains_resource::<Hotseat>() {
+            return; // One player
+        }
+
+        app.add_event::<DicePassed>()
+            .add_systems(
+                OnEnter(InGame),
+                (spawn_players, setup_turn_panel, setup_dice_callout),
+            )
+            .add_systems(
+                Update,
+                (
+                    end_turn_system,
+                    decline_dice_system,
+                    // Once the player in control has been paid by the betting plugin
+                    hotseat_roll_system.after(RollStep::Pay),
+                    // A throw picks the dice up in FixedUpdate, before this runs, so a
+                    // throw always wins
+                    dice_pass_system,
+                    seat_markers_system,
+                    turn_panel_system,
+                    dice_callout_system,
+                )
+                    .run_if(in_state(AppState::Playing)),
+            );
+    }
+}
+
+// The players' colours, by seat. A networked table deals them out by seat number too.
+pub const PLAYER_COLORS: [&str; 4] = [
+    "#2E86DE", // Blue
+    "#E84393", // Pink
+    "#F1C40F", // Yellow
+    "#00CEC9", // Teal
+];
+
+pub const MIN_PLAYERS: usize = 2;
+pub const MAX_PLAYERS: usize = PLAYER_COLORS.len();
+
+// Whose turn it is at a hotseat table. Insert one before adding the game's plugins
+// to play with more than one person.
+#[derive(Resource, Debug)]
+pub struct Hotseat {
+    players: Vec<Entity>, // In seat order (clockwise), filled in as the table is set up
+    control: usize,       // The seat whose money is in Bankroll and TableBets right now
+    rotation: ShooterRotation, // Who has the dice
+}
+
+impl Hotseat {
+    pub fn new(count: usize) -> Self {
+        Self {
+            players: Vec::new(),
+            control: 0,
+            rotation: ShooterRotation::new(count.clamp(MIN_PLAYERS, MAX_PLAYERS)),
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.rotation.seats
+    }
+
+    pub fn rotation(&self) -> ShooterRotation {
+        self.rotation
+    }
+
+    // The player whose turn it is to bet (or throw)
+    pub fn in_control(&self) -> Option<Entity> {
+        self.players.get(self.control).copied()
+    }
+
+    pub fn shooter(&self) -> Option<Entity> {
+        self.players.get(self.rotation.shooter).copied()
+    }
+
+    fn shooter_in_control(&self) -> bool {
+        self.control == self.rotation.shooter
+    }
+}
+
+// Event fired when the dice move on to another seat, after a seven-out or because the
+// shooter turned them down
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DicePassed {
+    pub from: usize, // Seat numbers
+    pub to: usize,
+}
+
+// One person at the table
+#[derive(Component, Debug, Clone)]
+pub struct Player {
+    pub seat: usize,
+    pub name: String,
+    pub color: Color, // Their markers on the felt and their name on the panel
+}
+
+// A player's money while someone else has control. Out of date for the player in
+// control - theirs is in the Bankroll and TableBets resources.
+#[derive(Component, Debug, Clone)]
+pub struct PlayerTable(pub TableState);
+
+// The look of one player's bet markers, shared by all of them
+#[derive(Component)]
+struct PlayerMarkers {
+    material: Handle<StandardMaterial>,
+}
+
+// A pile of markers showing one parked bet
+#[derive(Component)]
+struct SeatMarker;
+
+#[derive(Component)]
+struct TurnText;
+
+#[derive(Component)]
+struct DoneButton;
+
+#[derive(Component)]
+struct PassDiceButton;
+
+#[derive(Component)]
+struct DiceCallout; // "Player 2 has the dice", for a moment after they move
+
+// The stickman pushing the dice round to the new shooter
+struct DicePass {
+    from: Vec<(Entity, Vec3)>, // Each die and where it started
+    to: [Vec3; 2],             // Where die #1 and die #2 end up
+    elapsed: f32,
+}
+
+const MARKER_RADIUS: f32 = 0.045;
+const MARKER_HEIGHT: f32 = 0.02;
+const MARKER_SPREAD: f32 = 0.16; // How far from the bet's usual spot each seat's pile sits
+const MAX_MARKERS_SHOWN: usize = 10;
+const PASS_SECONDS: f32 = 1.2; // How long the dice take to slide to the next seat
+const DICE_CALLOUT_SECONDS: f32 = 2.5;
+
+// System that seats everyone with the standard stack as the table is set up. The
+// betting starts with the player after the shooter, so the shooter is the last to bet
+// before throwing.
+fn spawn_players(
+    mut commands: Commands,
+    mut hotseat: ResMut<Hotseat>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    phase: Res<RoundPhase>,
+    mut bankroll: ResMut<Bankroll>,
+    mut bets: ResMut<TableBets>,
+    mut authority: ResMut<DiceAuthority>,
+    locale: Res<Locale>,
+) {
+    // Back from the main menu, the game starts over
+    hotseat.players.clear();
+    hotseat.rotation = ShooterRotation::new(hotseat.count());
+    for seat in 0..hotseat.count() {
+        let color: Color = Srgba::hex(PLAYER_COLORS[seat]).unwrap().into();
+        let player = commands
+            .spawn((
+                StateScoped(InGame),
+                Player {
+                    seat,
+                    name: locale.fill("Player {number}", &[("number", &(seat + 1))]),
+                    color,
+                },
+                PlayerTable(TableState {
+                    phase: *phase,
+                    bets: TableBets::default(),
+                    bankroll: Bankroll::default(),
+                }),
+                PlayerMarkers {
+                    material: materials.add(StandardMaterial {
+                        base_color: color,
+                        perceptual_roughness: 0.6,
+                        ..default()
+                    }),
+                },
+                Name::new(format!("Player {}", seat + 1)),
+            ))
+            .id();
+        hotseat.players.push(player);
+    }
+
+    hotseat.control = hotseat.rotation.next_seat(hotseat.rotation.shooter);
+    // Everyone starts the same, so the first player's money is just a fresh stack
+    *bankroll = Bankroll::default();
+    *bets = TableBets::default();
+    authority.may_throw = false;
+}
+
+// Move control to another seat: park the money on the table now with the player who
+// had control, and bring out the new player's
+fn hand_over(
+    hotseat: &mut Hotseat,
+    to: usize,
+    tables_q: &mut Query<&mut PlayerTable>,
+    phase: RoundPhase,
+    bankroll: &mut Bankroll,
+    bets: &mut TableBets,
+    authority: &mut DiceAuthority,
+) {
+    if let Ok(mut table) = tables_q.get_mut(hotseat.players[hotseat.control]) {
+        table.0 = TableState {
+            phase,
+            bets: bets.clone(),
+            bankroll: bankroll.clone(),
+        };
+    }
+    hotseat.control = to;
+    if let Ok(table) = tables_q.get(hotseat.players[to]) {
+        *bankroll = table.0.bankroll.clone();
+        *bets = table.0.bets.clone();
+    }
+    // The shooter bets last, then throws - nobody else touches the dice
+    authority.may_throw = hotseat.shooter_in_control();
+}
+
+// System that passes control on when the player in control says they're done betting.
+// The shooter doesn't hand over - they finish their turn by throwing.
+fn end_turn_system(
+    input: Res<PlayerInput>,
+    button_q: Query<&Interaction, (Changed<Interaction>, With<DoneButton>)>,
+    mut hotseat: ResMut<Hotseat>,
+    mut tables_q: Query<&mut PlayerTable>,
+    phase: Res<RoundPhase>,
+    mut bankroll: ResMut<Bankroll>,
+    mut bets: ResMut<TableBets>,
+    mut authority: ResMut<DiceAuthority>,
+) {
+    let clicked = button_q
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed);
+    if !input.end_turn && !clicked {
+        return;
+    }
+    if hotseat.shooter_in_control() {
+        return;
+    }
+    let next = hotseat.rotation.next_seat(hotseat.control);
+    hand_over(
+        &mut hotseat,
+        next,
+        &mut tables_q,
+        *phase,
+        &mut bankroll,
+        &mut bets,
+        &mut authority,
+    );
+}
+
+// System that lets the shooter turn the dice down before their first throw. The dice
+// go to the next seat, and so does control: that player has already bet this round.
+fn decline_dice_system(
+    input: Res<PlayerInput>,
+    button_q: Query<&Interaction, (Changed<Interaction>, With<PassDiceButton>)>,
+    roll: Res<RollState>,
+    mut hotseat: ResMut<Hotseat>,
+    mut tables_q: Query<&mut PlayerTable>,
+    phase: Res<RoundPhase>,
+    mut bankroll: ResMut<Bankroll>,
+    mut bets: ResMut<TableBets>,
+    mut authority: ResMut<DiceAuthority>,
+    mut passed_events: EventWriter<DicePassed>,
+) {
+    let clicked = button_q
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed);
+    if !input.pass_dice && !clicked {
+        return;
+    }
+    if !hotseat.shooter_in_control() || !hotseat.rotation.may_decline() || roll.in_flight() {
+        return;
+    }
+    let from = hotseat.rotation.shooter;
+    hotseat.rotation = hotseat.rotation.declined();
+    let to = hotseat.rotation.shooter;
+    info!("Player {} passes the dice to Player {}", from + 1, to + 1);
+    passed_events.write(DicePassed { from, to });
+    hand_over(
+        &mut hotseat,
+        to,
+        &mut tables_q,
+        *phase,
+        &mut bankroll,
+        &mut bets,
+        &mut authority,
+    );
+}
+
+// System that settles the parked players' bets against each roll, passes the dice on
+// after a seven-out, and starts the next round of betting
+fn hotseat_roll_system(
+    mut hotseat: ResMut<Hotseat>,
+    mut settled_events: EventReader<RollSettled>,
+    mut resolved_events: EventReader<RollResolved>,
+    rules: Res<TableRules>,
+    mut tables_q: Query<&mut PlayerTable>,
+    phase: Res<RoundPhase>,
+    mut bankroll: ResMut<Bankroll>,
+    mut bets: ResMut<TableBets>,
+    mut authority: ResMut<DiceAuthority>,
+    mut passed_events: EventWriter<DicePassed>,
+) {
+    let settled: Vec<RollSettled> = settled_events.read().copied().collect();
+    let from = hotseat.rotation.shooter;
+    for resolved in resolved_events.read() {
+        hotseat.rotation = hotseat.rotation.after(resolved.outcome);
+    }
+    if settled.is_empty() {
+        return;
+    }
+
+    // The player in control was paid by the betting plugin; everyone else is paid here
+    let in_control = hotseat.players[hotseat.control];
+    for &player in hotseat
+        .players
+        .iter()
+        .filter(|&&player| player != in_control)
+    {
+        if let Ok(mut table) = tables_q.get_mut(player) {
+            for roll in &settled {
+                table.0.roll(roll.die_values, &rules);
+            }
+        }
+    }
+
+    let to = hotseat.rotation.shooter;
+    if to != from {
+        info!("The dice pass to Player {}", to + 1);
+        passed_events.write(DicePassed { from, to });
+    }
+    let first = hotseat.rotation.next_seat(to);
+    hand_over(
+        &mut hotseat,
+        first,
+        &mut tables_q,
+        *phase,
+        &mut bankroll,
+        &mut bets,
+        &mut authority,
+    );
+}
+
+// System that draws every parked bet as a small pile of markers in its owner's colour.
+// Each seat's piles sit to one side of the bet's usual spot, so two players' bets on
+// the same line don't land on top of each other.
+fn seat_markers_system(
+    mut commands: Commands,
+    hotseat: Res<Hotseat>,
+    players_q: Query<(Entity, &Player, &PlayerTable, &PlayerMarkers)>,
+    changed_q: Query<(), Changed<PlayerTable>>,
+    markers_q: Query<Entity, With<SeatMarker>>,
+    table_layout: Res<TableLayout>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut mesh: Local<Option<Handle<Mesh>>>,
+) {
+    if !hotseat.is_changed() && changed_q.is_empty() {
+        return;
+    }
+    let mesh = mesh
+        .get_or_insert_with(|| meshes.add(Cylinder::new(MARKER_RADIUS, MARKER_HEIGHT)))
+        .clone();
+
+    for marker in &markers_q {
+        commands.entity(marker).despawn();
+    }
+    let in_control = hotseat.in_control();
+    for (entity, player, table, markers) in &players_q {
+        if Some(entity) == in_control {
+            continue; // Their bets are the normal chip piles
+        }
+        // Spread the seats round the spot: one each side, then in front and behind
+        let angle = player.seat as f32 * std::f32::consts::FRAC_PI_2;
+        let offset = Vec2::new(angle.cos(), angle.sin()) * MARKER_SPREAD;
+        for bet in &table.0.bets.bets {
+            let spot = table_layout.chip_anchor(bet.kind) + offset;
+            let count = break_into_chips(bet.amount)
+                .len()
+                .clamp(1, MAX_MARKERS_SHOWN);
+            commands
+                .spawn((
+                    StateScoped(InGame),
+                    SeatMarker,
+                    Transform::from_xyz(spot.x, 0.0, spot.y),
+                    Visibility::default(),
+                    Name::new(format!("{} {:?}", player.name, bet.kind)),
+                ))
+                .with_children(|parent| {
+                    for height in 0..count {
+                        parent.spawn((
+                            Mesh3d(mesh.clone()),
+                            MeshMaterial3d(markers.material.clone()),
+                            Transform::from_xyz(0.0, MARKER_HEIGHT * (height as f32 + 0.5), 0.0),
+                        ));
+                    }
+                });
+        }
+    }
+}
+
+// System that keeps the turn panel up to date: everyone's money, who has the dice, and
+// who's betting now
+fn turn_panel_system(
+    hotseat: Res<Hotseat>,
+    bankroll: Res<Bankroll>,
+    players_q: Query<(Entity, &Player, &PlayerTable)>,
+    locale: Res<Locale>,
+    mut text_q: Query<(&mut Text, &mut TextColor), With<TurnText>>,
+    mut buttons_q: Query<
+        (&mut Visibility, Has<PassDiceButton>),
+        Or<(With<DoneButton>, With<PassDiceButton>)>,
+    >,
+) {
+    if !hotseat.is_changed() && !bankroll.is_changed() && !locale.is_changed() {
+        return;
+    }
+    let Ok((mut text, mut color)) = text_q.single_mut() else {
+        return;
+    };
+
+    let shooting = hotseat.shooter_in_control();
+    let may_decline = shooting && hotseat.rotation.may_decline();
+    let mut players: Vec<_> = players_q.iter().collect();
+    players.sort_by_key(|(_, player, _)| player.seat);
+    let mut lines = Vec::new();
+    for (entity, player, table) in players {
+        let in_control = Some(entity) == hotseat.in_control();
+        let balance = if in_control {
+            bankroll.balance
+        } else {
+            table.0.bankroll.balance
+        };
+        let marker = if in_control { "> " } else { "  " };
+        let dice = if Some(entity) == hotseat.shooter() {
+            format!(" {}", locale.tr("(dice)"))
+        } else {
+            String::new()
+        };
+        lines.push(format!(
+            "{marker}{}{dice}  {}",
+            player.name,
+            format_money(balance)
+        ));
+        if in_control {
+            color.0 = player.color;
+            let turn = if may_decline {
+                "bet, then throw or pass the dice"
+            } else if shooting {
+                "bet, then throw"
+            } else {
+                "bet, then press Enter"
+            };
+            lines.insert(
+                0,
+                locale.fill(
+                    "{player}'s turn: {turn}",
+                    &[("player", &player.name), ("turn", &locale.tr(turn))],
+                ),
+            );
+        }
+    }
+    text.0 = lines.join("\n");
+
+    // The shooter ends their turn by throwing (or passing the dice), not with Done
+    for (mut visibility, pass_button) in &mut buttons_q {
+        let shown = if pass_button { may_decline } else { !shooting };
+        visibility.set_if_neq(if shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
+
+// System that starts the stickman sliding the dice over to the new shooter, and moves
+// them a little further each frame. A throw (which picks the dice up again) cuts it short.
+fn dice_pass_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    hotseat: Res<Hotseat>,
+    roll: Res<RollState>,
+    table_layout: Res<TableLayout>,
+    mut passed_events: EventReader<DicePassed>,
+    mut dice_q: Query<(Entity, &DiceId, &mut Transform, &Visibility), With<Dice>>,
+    mut passing: Local<Option<DicePass>>,
+) {
+    for passed in passed_events.read() {
+        // Dice that never came out (or went off the table) have nothing to slide
+        let dice: Vec<_> = dice_q
+            .iter()
+            .filter(|(.., visibility)| **visibility != Visibility::Hidden)
+            .map(|(entity, id, transform, _)| (entity, id.0, transform.translation))
+            .collect();
+        if dice.is_empty() {
+            continue;
+        }
+        // Side by side in front of the new shooter, across the line to the middle
+        let seat = table_layout.seat_position(passed.to, hotseat.count());
+        let across = seat.normalize_or_zero().perp() * 0.15;
+        let height = dice[0].2.y;
+        let spot = |offset: Vec2| {
+            let point = seat + offset;
+            Vec3::new(point.x, height, point.y)
+        };
+        for &(entity, ..) in &dice {
+            // Held still while it slides - the physics would only fight the stick
+            commands.entity(entity).insert(RigidBodyDisabled);
+        }
+        *passing = Some(DicePass {
+            from: dice
+                .iter()
+                .map(|&(entity, _, start)| (entity, start))
+                .collect(),
+            to: [spot(across), spot(-across)],
+            elapsed: 0.0,
+        });
+    }
+
+    let Some(pass) = passing.as_mut() else {
+        return;
+    };
+    if roll.in_flight() {
+        *passing = None; // Thrown - the dice are back in the physics' hands
+        return;
+    }
+    pass.elapsed += time.delta_secs();
+    let progress = (pass.elapsed / PASS_SECONDS).min(1.0);
+    let eased = progress * progress * (3.0 - 2.0 * progress); // Slow start, slow stop
+    for &(entity, start) in &pass.from {
+        let Ok((_, id, mut transform, _)) = dice_q.get_mut(entity) else {
+            continue;
+        };
+        let end = pass.to[usize::from(id.0 - 1).min(1)]; // DiceId is 1-based
+        transform.translation = start.lerp(end, eased);
+    }
+    if progress >= 1.0 {
+        *passing = None; // They stay there, physics off, until the shooter throws
+    }
+}
+
+// System that puts "Player 2 has the dice" up in the middle of the screen whenever the
+// dice move on, in that player's colour, and takes it down again after a moment
+fn dice_callout_system(
+    time: Res<Time>,
+    mut passed_events: EventReader<DicePassed>,
+    players_q: Query<&Player>,
+    locale: Res<Locale>,
+    mut callout_q: Query<(&mut Text, &mut TextColor, &mut Visibility), With<DiceCallout>>,
+    mut shown_for: Local<Option<f32>>,
+) {
+    let Ok((mut text, mut color, mut visibility)) = callout_q.single_mut() else {
+        return;
+    };
+    if let Some(passed) = passed_events.read().last() {
+        if let Some(player) = players_q.iter().find(|player| player.seat == passed.to) {
+            text.0 = locale.fill("{player} has the dice", &[("player", &player.name)]);
+            color.0 = player.color;
+            *visibility = Visibility::Inherited;
+            *shown_for = Some(0.0);
+        }
+    }
+    if let Some(seconds) = shown_for.as_mut() {
+        *seconds += time.delta_secs();
+        if *seconds > DICE_CALLOUT_SECONDS {
+            *visibility = Visibility::Hidden;
+            *shown_for = None;
+        }
+    }
+}
+
+// One of the panel's buttons
+fn spawn_panel_button(panel: &mut ChildSpawnerCommands, label: &'static str, marker: impl Bundle) {
+    panel
+        .spawn((
+            Button,
+            Node {
+                padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.1, 0.1, 0.1)),
+            BorderRadius::all(Val::Px(6.0)),
+            marker,
+        ))
+        .with_children(|button| {
+            button.spawn((
+                Localized(label),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+        });
+}
+
+fn setup_turn_panel(mut commands: Commands) {
+    // Down the right-hand side, midway, with the buttons underneath
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(20.0),
+                top: Val::Percent(40.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(8.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.05, 0.05, 0.05, 0.8)),
+            BorderRadius::all(Val::Px(6.0)),
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                Text::new(""), // Filled in on the first frame
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                TurnText,
+            ));
+            spawn_panel_button(panel, "Done betting (Enter)", DoneButton);
+            spawn_panel_button(panel, "Pass the dice (P)", PassDiceButton);
+        });
+}
+
+fn setup_dice_callout(mut commands: Commands) {
+    // Low in the middle of the screen, clear of the roll callout that a seven-out
+    // puts up at the same moment
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Percent(22.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 30.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                Visibility::Hidden,
+                DiceCallout,
+            ));
+        });
+}
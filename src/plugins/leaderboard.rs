@@ -0,0 +1,264 @@
+// The leaderboard of the player's best sessions (see leaderboard.rs). When the player
+// leaves the table - back to the main menu, or closing the game - the session's
+// `SessionStats` are offered to the `Leaderboard`, which is saved if they made a board.
+// The main menu's Leaderboard button shows every board, with the best daily challenge
+// days (see daily.rs) underneath.
+//
+// A replayed session was played long ago, the money at someone else's networked table
+// is the host's, and hotseat players share one set of stats, so none of those count.
+use bevy::prelude::*;
+
+use super::hotseat::Hotseat;
+use super::locale::Localized;
+use super::net::NetClient;
+use super::session::SessionPlayback;
+use super::{AppState, InGame};
+use crate::daily::{DailyScores, todays_date};
+use crate::leaderboard::{Board, LEADERBOARD_PLACES, Leaderboard, SessionRecord};
+use crate::locale::Locale;
+use crate::payout::format_money;
+use crate::stats::SessionStats;
+use crate::storage;
+
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(load_leaderboard())
+            .init_resource::<LeaderboardScreen>()
+            .add_systems(OnEnter(AppState::MainMenu), setup_leaderboard_screen)
+            .add_systems(
+                Update,
+                (leaderboard_screen_system, show_leaderboard_screen_system)
+                    .run_if(in_state(AppState::MainMenu)),
+            );
+
+        // main.rs inserts these before the plugins are added
+        let world = app.world();
+        if world.contains_resource::<SessionPlayback>()
+            || world.contains_resource::<NetClient>()
+            || world.contains_resource::<Hotseat>()
+        {
+            return;
+        }
+        app.add_systems(OnExit(InGame), record_session)
+            .add_systems(Last, record_session_on_exit.run_if(in_state(InGame)));
+    }
+}
+
+// Whether the leaderboard is showing over the main menu
+#[derive(Resource, Debug, Default)]
+pub struct LeaderboardScreen {
+    pub open: bool,
+}
+
+#[derive(Component)]
+struct LeaderboardPanel;
+
+// The places on one board, or on the daily challenge's (None)
+#[derive(Component)]
+struct BoardText(Option<Board>);
+
+#[derive(Component)]
+struct CloseLeaderboardButton;
+
+const LEADERBOARD_KEY: &str = "leaderboard"; // Where it's saved (see storage.rs)
+
+fn load_leaderboard() -> Leaderboard {
+    let Some(saved) = storage::load(LEADERBOARD_KEY) else {
+        return Leaderboard::default(); // No sessions finished yet
+    };
+    ron::from_str(&saved).unwrap_or_else(|error| {
+        warn!("Couldn't read the saved leaderboard, starting afresh: {error}");
+        Leaderboard::default()
+    })
+}
+
+fn save_leaderboard(leaderboard: &Leaderboard) {
+    let saved = ron::ser::to_string_pretty(leaderboard, ron::ser::PrettyConfig::default())
+        .map_err(|error| error.to_string())
+        .and_then(|text| storage::save(LEADERBOARD_KEY, &text).map_err(|error| error.to_string()));
+    if let Err(error) = saved {
+        warn!("Couldn't save the leaderboard: {error}");
+    }
+}
+
+// Offer the session just finished to the leaderboard. A session with no rolls in it
+// has nothing to show.
+fn finish_session(stats: &SessionStats, leaderboard: &mut Leaderboard) {
+    if stats.rolls == 0 {
+        return;
+    }
+    if leaderboard.record(SessionRecord::from_stats(stats, todays_date())) {
+        info!("The session made the leaderboard");
+        save_leaderboard(leaderboard);
+    }
+}
+
+// System that records the session as the player leaves the table
+fn record_session(stats: Option<Res<SessionStats>>, mut leaderboard: ResMut<Leaderboard>) {
+    if let Some(stats) = stats {
+        finish_session(&stats, &mut leaderboard);
+    }
+}
+
+// System that records the session if the game is closed at the table
+fn record_session_on_exit(
+    mut exit_events: EventReader<AppExit>,
+    stats: Option<Res<SessionStats>>,
+    mut leaderboard: ResMut<Leaderboard>,
+) {
+    if exit_events.read().count() == 0 {
+        return;
+    }
+    if let Some(stats) = stats {
+        finish_session(&stats, &mut leaderboard);
+    }
+}
+
+// The boards, built hidden with the main menu
+fn setup_leaderboard_screen(mut commands: Commands, mut screen: ResMut<LeaderboardScreen>) {
+    screen.open = false; // Coming back to the menu shows the menu
+    commands
+        .spawn((
+            StateScoped(AppState::MainMenu),
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(25.0),
+                top: Val::Percent(10.0),
+                width: Val::Percent(50.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(8.0),
+                padding: UiRect::all(Val::Px(16.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.05, 0.05, 0.05, 0.97)),
+            BorderRadius::all(Val::Px(8.0)),
+            GlobalZIndex(1), // Over the menu's buttons
+            Visibility::Hidden,
+            LeaderboardPanel,
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                Localized("Leaderboard"),
+                TextFont {
+                    font_size: 24.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+            let boards = Board::ALL
+                .into_iter()
+                .map(|board| (board.title(), Some(board)))
+                .chain([("Daily challenge", None)]);
+            for (title, board) in boards {
+                panel.spawn((
+                    Localized(title),
+                    TextFont {
+                        font_size: 18.0,
+                        ..default()
+                    },
+                    TextColor(Srgba::hex("#FF5300").unwrap().into()),
+                    Node {
+                        margin: UiRect::top(Val::Px(6.0)),
+                        ..default()
+                    },
+                ));
+                panel.spawn((
+                    Text::new(""), // Filled in by leaderboard_screen_system
+                    TextFont {
+                        font_size: 14.0,
+                        ..default()
+                    },
+                    TextColor(Color::WHITE),
+                    BoardText(board),
+                ));
+            }
+            panel
+                .spawn((
+                    Button,
+                    Node {
+                        padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                        margin: UiRect::top(Val::Px(6.0)),
+                        align_self: AlignSelf::Start,
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.1, 0.1, 0.1)),
+                    BorderRadius::all(Val::Px(6.0)),
+                    CloseLeaderboardButton,
+                ))
+                .with_child((
+                    Localized("Back"),
+                    TextFont {
+                        font_size: 16.0,
+                        ..default()
+                    },
+                    TextColor(Color::WHITE),
+                ));
+        });
+}
+
+// System that closes the leaderboard with its Back button, and writes out the places
+// in the player's language
+fn leaderboard_screen_system(
+    leaderboard: Res<Leaderboard>,
+    daily: Res<DailyScores>,
+    locale: Res<Locale>,
+    mut screen: ResMut<LeaderboardScreen>,
+    button_q: Query<&Interaction, (Changed<Interaction>, With<CloseLeaderboardButton>)>,
+    mut text_q: Query<(&mut Text, &BoardText)>,
+) {
+    if button_q
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed)
+    {
+        screen.open = false;
+    }
+    for (mut text, board) in &mut text_q {
+        if !locale.is_changed() && !text.is_added() {
+            continue;
+        }
+        let places: Vec<String> = match board.0 {
+            Some(board) => leaderboard
+                .top(board)
+                .into_iter()
+                .enumerate()
+                .map(|(index, session)| board.entry(index + 1, session, &locale))
+                .collect(),
+            None => daily
+                .top(LEADERBOARD_PLACES)
+                .into_iter()
+                .enumerate()
+                .map(|(index, day)| {
+                    locale.fill(
+                        "{place}. {money} - {date}",
+                        &[
+                            ("place", &(index + 1)),
+                            ("money", &format_money(day.balance)),
+                            ("date", &day.date),
+                        ],
+                    )
+                })
+                .collect(),
+        };
+        text.0 = if places.is_empty() {
+            locale.tr("Nothing here yet").to_string()
+        } else {
+            places.join("\n")
+        };
+    }
+}
+
+// System that shows the leaderboard while it's open
+fn show_leaderboard_screen_system(
+    screen: Res<LeaderboardScreen>,
+    mut panel_q: Query<&mut Visibility, With<LeaderboardPanel>>,
+) {
+    if let Ok(mut visibility) = panel_q.single_mut() {
+        visibility.set_if_neq(if screen.open {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
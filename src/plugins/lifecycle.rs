@@ -0,0 +1,68 @@
+// The events every throw goes through, in the order they're sent:
+//
+//   ThrowStarted  The shooter picked the dice up - the throw button went down
+//   ThrowDice     ...and let go: how hard, from where, at which spot (a networked
+//                 table sends these for its remote shooters too)
+//   DiceLaunched  The dice are out of the hand and the physics has them
+//   DiceSettled   Every die has stopped, with what each one shows (and RollSettled
+//                 with it when the game is craps)
+//   RollResolved  What the rules made of the roll: a natural, a point, a seven out...
+//   BetResolved   One for each bet the roll decided, and what went back to the player
+//   PhaseChanged  The round moved on - the puck went on, or came off
+//
+// Between throws, BetPlaced is sent for every bet that goes down (and BetTakenDown or
+// BetUndone for chips that come back off). Sounds, the UI, the network and anything a
+// mod adds with a plugin of its own can listen for these instead of watching the game's
+// resources change. The new events are defined here and registered by the plugins that
+// send them; the older ones live with their plugins and are re-exported from here, so
+// the whole pipeline can be used from one place.
+//
+// Winnings are paid from the events: the craps payout, the side bets and sic bo only
+// take the decided bets off `TableBets` and send a BetResolved for each, and the
+// betting plugin's `bank_winnings_system` credits the `Bankroll` with what they're
+// owed. The systems that put a bet down, take one off or undo it still change the
+// `Bankroll` and `TableBets` themselves, and send the event alongside. A few don't
+// move any chips at all - they swap in a whole other player's money and layout - and
+// send nothing: the hotseat handing the table to the next player, a joined player
+// taking the host's figures, the session scrubber rebuilding the table at a roll and
+// the debug console's `bankroll` command.
+use bevy::prelude::*;
+
+pub use super::betting::{BetPlaced, BetResolved, BetTakenDown, BetUndone, RollResolved};
+pub use super::dice::{DiceSettled, NoRoll, RollSettled, ThrowDice};
+use crate::rules::RoundPhase;
+
+// Event fired the moment the shooter starts a throw (the power meter begins to fill).
+// The throw may yet come to nothing: a flick that never got going leaves the dice in
+// the hand.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ThrowStarted;
+
+// Event fired when a throw's dice leave the hand, on the machine running the dice
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DiceLaunched {
+    pub throw: ThrowDice, // The throw they were launched by
+}
+
+// Event fired whenever the round's phase changes
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PhaseChanged {
+    pub from: RoundPhase,
+    pub to: RoundPhase,
+    // Set straight there (the debug console, joining a table, the session scrubber)
+    // rather than by a roll - anything keeping its own copy of the round should follow
+    pub jumped: bool,
+}
+
+// Put the round straight into `to`, and say so - for the places that set the phase
+// rather than roll it there
+pub fn jump_phase(phase: &mut RoundPhase, to: RoundPhase, events: &mut EventWriter<PhaseChanged>) {
+    if *phase != to {
+        events.write(PhaseChanged {
+            from: *phase,
+            to,
+            jumped: true,
+        });
+    }
+    *phase = to;
+}
@@ -0,0 +1,127 @@
+// The game's words in the player's language (see locale.rs for how the language files
+// work). The language is picked on the settings screen and kept in `Settings`; this
+// plugin loads the files, switches the `Locale` over when the choice changes, and keeps
+// the text on screen in step with it.
+//
+// Text that never changes - titles, button labels - is spawned with a `Localized`
+// holding its English, and is translated again whenever the language changes. Readouts
+// that are written as the game goes ask the `Locale` themselves, and are written again
+// when it changes.
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use super::menu::LoadingAssets;
+use super::settings::Settings;
+use crate::locale::{ENGLISH, LANGUAGES, LanguageFile, Locale};
+use crate::ron_asset::RonLoader;
+
+pub struct LocalePlugin;
+
+impl Plugin for LocalePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<LanguageFile>() // Translations, loaded from assets/lang/
+            .register_asset_loader(RonLoader::<LanguageFile>::new(&["lang.ron"]))
+            .init_resource::<Locale>()
+            .init_resource::<LanguageFiles>()
+            .init_resource::<LocaleFont>()
+            .add_systems(Startup, wait_for_language_files)
+            // The menus are translated too, so this runs in every state
+            .add_systems(
+                Update,
+                (locale_system, (localized_text_system, locale_font_system)).chain(),
+            );
+    }
+}
+
+// Text that always says the same thing, in whichever language is on. The English is
+// the key into the language file.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+#[require(Text)]
+pub struct Localized(pub &'static str);
+
+// The language files, by language code. English is built in, so it has none.
+#[derive(Resource)]
+struct LanguageFiles(HashMap<&'static str, Handle<LanguageFile>>);
+
+impl FromWorld for LanguageFiles {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        Self(
+            LANGUAGES
+                .iter()
+                .filter(|(code, _)| *code != ENGLISH)
+                .map(|&(code, _)| (code, asset_server.load(format!("lang/{code}.lang.ron"))))
+                .collect(),
+        )
+    }
+}
+
+// The font every piece of text is drawn in: the language file's own, or Bevy's
+// built-in one (the default handle)
+#[derive(Resource, Default)]
+struct LocaleFont(Handle<Font>);
+
+fn wait_for_language_files(files: Res<LanguageFiles>, mut loading: ResMut<LoadingAssets>) {
+    for handle in files.0.values() {
+        loading.add(handle);
+    }
+}
+
+// System that switches the language when the player picks another, and takes up the
+// language's file when it loads (or is saved, in a `--features dev` build)
+fn locale_system(
+    settings: Res<Settings>,
+    files: Res<LanguageFiles>,
+    languages: Res<Assets<LanguageFile>>,
+    mut file_events: EventReader<AssetEvent<LanguageFile>>,
+    asset_server: Res<AssetServer>,
+    mut locale: ResMut<Locale>,
+    mut font: ResMut<LocaleFont>,
+) {
+    let handle = files.0.get(settings.language.as_str());
+    let file_changed = file_events.read().any(|event| {
+        handle.is_some_and(|handle| {
+            event.is_loaded_with_dependencies(handle) || event.is_modified(handle)
+        })
+    });
+    // Settings change all the time (every step of a slider), so check it's the language
+    if !file_changed && locale.language == settings.language {
+        return;
+    }
+    // Until its file is in, a language shows the English
+    let file = handle
+        .and_then(|handle| languages.get(handle))
+        .cloned()
+        .unwrap_or_default();
+    *locale = Locale::new(&settings.language, &file);
+    let typeface: Handle<Font> = file
+        .font
+        .map_or_else(Handle::default, |path| asset_server.load(path));
+    if font.0 != typeface {
+        font.0 = typeface;
+    }
+}
+
+// System that translates the fixed text, as it's spawned and whenever the language changes
+fn localized_text_system(locale: Res<Locale>, mut text_q: Query<(Ref<Localized>, &mut Text)>) {
+    for (localized, mut text) in &mut text_q {
+        if !locale.is_changed() && !localized.is_changed() {
+            continue;
+        }
+        let words = locale.tr(localized.0);
+        if text.0 != words {
+            text.0 = words.to_string();
+        }
+    }
+}
+
+// System that draws all the text in the language's font - everything on screen when the
+// font changes, and anything new as it's spawned
+fn locale_font_system(font: Res<LocaleFont>, mut fonts_q: Query<&mut TextFont>) {
+    for mut text_font in &mut fonts_q {
+        if (font.is_changed() || text_font.is_added()) && text_font.font != font.0 {
+            text_font.font = font.0.clone();
+        }
+    }
+}
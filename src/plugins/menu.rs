@@ -0,0 +1,406 @@
+// The screens around the game: the main menu the app opens on, a loading screen while
+// the table's files arrive, and the pause menu. Which one is showing is the `AppState` (see
+// mod.rs). The table itself is only set up once the game moves into Playing, and every
+// screen's entities are scoped to their state, so leaving a screen clears it away.
+use bevy::asset::UntypedAssetId;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use super::achievements::AchievementsScreen;
+use super::controls::{ControlsPanelState, PlayerInput};
+use super::hotseat::Hotseat;
+use super::leaderboard::LeaderboardScreen;
+use super::locale::Localized;
+use super::net::{NetClient, NetHost};
+use super::session::SessionPlayback;
+use super::settings::SettingsScreen;
+use super::{AppState, InGame};
+use crate::betting::TableBets;
+use crate::daily::{DailyChallenge, DailyScores};
+use crate::locale::Locale;
+use crate::payout::{Bankroll, Cents, format_money};
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<AppState>()
+            .add_computed_state::<InGame>()
+            .enable_state_scoped_entities::<AppState>()
+            .enable_state_scoped_entities::<InGame>()
+            .init_resource::<LoadingAssets>() // Filled in by the plugins as they start up
+            .add_systems(OnEnter(AppState::MainMenu), setup_main_menu)
+            .add_systems(OnEnter(AppState::Loading), setup_loading_screen)
+            .add_systems(
+                OnEnter(AppState::Paused),
+                (setup_pause_menu, freeze_physics),
+            )
+            .add_systems(OnExit(AppState::Paused), unfreeze_physics)
+            .add_systems(
+                Update,
+                (
+                    (main_menu_system, menu_description_system)
+                        .run_if(in_state(AppState::MainMenu)),
+                    loading_system.run_if(in_state(AppState::Loading)),
+                    pause_system.run_if(in_state(InGame)),
+                    pause_menu_system.run_if(in_state(AppState::Paused)),
+                ),
+            );
+    }
+}
+
+// Files the table needs before it can be played: the dice skins, the sounds and so on.
+// Each plugin adds its own at startup, and the loading screen waits for all of them.
+#[derive(Resource, Debug, Default)]
+pub struct LoadingAssets {
+    waiting_for: Vec<UntypedAssetId>,
+}
+
+impl LoadingAssets {
+    pub fn add(&mut self, id: impl Into<UntypedAssetId>) {
+        self.waiting_for.push(id.into());
+    }
+}
+
+#[derive(Component)]
+enum MenuButton {
+    Play,
+    Achievements, // Opens the list of achievements
+    Leaderboard,  // Opens the best sessions
+    Quit,         // Not in the browser - a page can't close itself
+}
+
+#[derive(Component)]
+enum PauseButton {
+    Resume,
+    Settings, // Opens the settings screen
+    QuitToMenu,
+}
+
+#[derive(Component)]
+struct PauseMenu; // The buttons' column, tucked away while a screen is open over it
+
+#[derive(Component)]
+struct MenuDescription;
+
+#[derive(Component)]
+struct LoadingText;
+
+// A file counts as done once it has loaded, or failed to - a missing sound shouldn't
+// keep anyone from playing (the load error is in the log)
+fn finished_loading(asset_server: &AssetServer, id: UntypedAssetId) -> bool {
+    asset_server.is_loaded_with_dependencies(id) || asset_server.load_state(id).is_failed()
+}
+
+// What the Play button starts, so a launch option (see main.rs) is visible before
+// the table appears
+fn game_description(world: &World) -> String {
+    let locale = world.resource::<Locale>();
+    if world.contains_resource::<SessionPlayback>() {
+        locale.tr("Replaying your last session").to_string()
+    } else if world.contains_resource::<NetHost>() {
+        locale.tr("Hosting a table for other players").to_string()
+    } else if world.contains_resource::<NetClient>() {
+        locale.tr("Joining a table over the network").to_string()
+    } else if let Some(hotseat) = world.get_resource::<Hotseat>() {
+        locale.fill(
+            "{count} players at one table",
+            &[("count", &hotseat.count())],
+        )
+    } else if let Some(daily) = world.get_resource::<DailyChallenge>() {
+        match world.resource::<DailyScores>().on(&daily.date) {
+            Some(score) => locale.fill(
+                "Daily challenge {date} - your score today is {money}",
+                &[("date", &daily.date), ("money", &format_money(score))],
+            ),
+            None => locale.fill(
+                "Daily challenge {date} - {rolls} rolls to go",
+                &[("date", &daily.date), ("rolls", &daily.rolls_left())],
+            ),
+        }
+    } else {
+        let bankroll = world.resource::<Bankroll>();
+        let on_layout: Cents = world
+            .resource::<TableBets>()
+            .bets
+            .iter()
+            .map(|bet| bet.amount)
+            .sum();
+        locale.fill(
+            "Bankroll {money}",
+            &[("money", &format_money(bankroll.balance + on_layout))],
+        )
+    }
+}
+
+// Exclusive so it can look at whichever launch resources happen to exist
+fn setup_main_menu(world: &mut World) {
+    let description = game_description(world);
+    world.spawn((Camera2d, StateScoped(AppState::MainMenu)));
+    world
+        .spawn((
+            StateScoped(AppState::MainMenu),
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(16.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.02, 0.18, 0.08)), // Felt green
+        ))
+        .with_children(|menu| {
+            menu.spawn((
+                Text::new("BEVY CRAPS"),
+                TextFont {
+                    font_size: 64.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+            menu.spawn((
+                Text::new(description),
+                TextFont {
+                    font_size: 20.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.8, 0.8, 0.8)),
+                Node {
+                    margin: UiRect::bottom(Val::Px(24.0)),
+                    ..default()
+                },
+                MenuDescription,
+            ));
+            spawn_menu_button(menu, "Play", MenuButton::Play);
+            spawn_menu_button(menu, "Achievements", MenuButton::Achievements);
+            spawn_menu_button(menu, "Leaderboard", MenuButton::Leaderboard);
+            #[cfg(not(target_arch = "wasm32"))]
+            spawn_menu_button(menu, "Quit", MenuButton::Quit);
+        });
+}
+
+// System that writes the description again in a new language - the language file can
+// come in while the menu is up
+fn menu_description_system(world: &mut World) {
+    if !world.is_resource_changed::<Locale>() {
+        return;
+    }
+    let description = game_description(world);
+    let mut text_q = world.query_filtered::<&mut Text, With<MenuDescription>>();
+    for mut text in text_q.iter_mut(world) {
+        text.0.clone_from(&description);
+    }
+}
+
+fn spawn_menu_button(menu: &mut ChildSpawner, label: &'static str, button: impl Component) {
+    menu.spawn((
+        Button,
+        Node {
+            width: Val::Px(220.0),
+            padding: UiRect::vertical(Val::Px(10.0)),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        BackgroundColor(Color::srgb(0.1, 0.1, 0.1)),
+        BorderRadius::all(Val::Px(6.0)),
+        button,
+    ))
+    .with_children(|button| {
+        button.spawn((
+            Localized(label),
+            TextFont {
+                font_size: 24.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+        ));
+    });
+}
+
+// System that handles the main menu's buttons
+fn main_menu_system(
+    buttons_q: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut achievements: ResMut<AchievementsScreen>,
+    mut leaderboard: ResMut<LeaderboardScreen>,
+    mut exit_events: EventWriter<AppExit>,
+) {
+    for (interaction, button) in &buttons_q {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match button {
+            MenuButton::Play => next_state.set(AppState::Loading),
+            MenuButton::Achievements => achievements.open = true,
+            MenuButton::Leaderboard => leaderboard.open = true,
+            MenuButton::Quit => {
+                exit_events.write(AppExit::Success);
+            }
+        }
+    }
+}
+
+fn setup_loading_screen(mut commands: Commands) {
+    commands.spawn((Camera2d, StateScoped(AppState::Loading)));
+    commands
+        .spawn((
+            StateScoped(AppState::Loading),
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.02, 0.18, 0.08)),
+        ))
+        .with_children(|screen| {
+            screen.spawn((
+                Text::new(""), // Filled in by loading_system
+                TextFont {
+                    font_size: 28.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                LoadingText,
+            ));
+        });
+}
+
+// System that moves on to the table once every file it needs is in
+fn loading_system(
+    asset_server: Res<AssetServer>,
+    loading: Res<LoadingAssets>,
+    locale: Res<Locale>,
+    mut text_q: Query<&mut Text, With<LoadingText>>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let done = loading
+        .waiting_for
+        .iter()
+        .filter(|&&id| finished_loading(&asset_server, id))
+        .count();
+    if done == loading.waiting_for.len() {
+        next_state.set(AppState::Playing);
+        return;
+    }
+    if let Ok(mut text) = text_q.single_mut() {
+        text.0 = locale.fill(
+            "Setting up the table... {done} of {count}",
+            &[("done", &done), ("count", &loading.waiting_for.len())],
+        );
+    }
+}
+
+// System that pauses the game with Escape (or Start on a gamepad), and carries on again.
+// With the settings screen open, Escape goes back to the pause menu first.
+fn pause_system(
+    input: Res<PlayerInput>,
+    state: Res<State<AppState>>,
+    mut screen: ResMut<SettingsScreen>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if !input.pause {
+        return;
+    }
+    match state.get() {
+        AppState::Playing => next_state.set(AppState::Paused),
+        AppState::Paused if screen.open => screen.open = false,
+        AppState::Paused => next_state.set(AppState::Playing),
+        _ => {}
+    }
+}
+
+fn setup_pause_menu(mut commands: Commands) {
+    // Dim the table and put the choices over it, until the game carries on
+    commands
+        .spawn((
+            StateScoped(AppState::Paused),
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        ))
+        .with_children(|overlay| {
+            overlay
+                .spawn((
+                    Node {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(16.0),
+                        ..default()
+                    },
+                    PauseMenu,
+                ))
+                .with_children(|menu| {
+                    menu.spawn((
+                        Localized("PAUSED"),
+                        TextFont {
+                            font_size: 48.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                        Node {
+                            margin: UiRect::bottom(Val::Px(24.0)),
+                            ..default()
+                        },
+                    ));
+                    spawn_menu_button(menu, "Resume", PauseButton::Resume);
+                    spawn_menu_button(menu, "Settings", PauseButton::Settings);
+                    spawn_menu_button(menu, "Quit to Menu", PauseButton::QuitToMenu);
+                });
+        });
+}
+
+// System that handles the pause menu's buttons
+fn pause_menu_system(
+    buttons_q: Query<(&Interaction, &PauseButton), Changed<Interaction>>,
+    mut menu_q: Query<&mut Visibility, With<PauseMenu>>,
+    panel: Res<ControlsPanelState>,
+    mut screen: ResMut<SettingsScreen>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for (interaction, button) in &buttons_q {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match button {
+            PauseButton::Resume => next_state.set(AppState::Playing),
+            PauseButton::Settings => screen.open = true,
+            // Everything on the table is scoped to InGame, so this clears it all away.
+            // The bankroll stays.
+            PauseButton::QuitToMenu => next_state.set(AppState::MainMenu),
+        }
+    }
+
+    // The settings screen and the controls panel sit where the buttons are, so only
+    // one shows at a time
+    if let Ok(mut visibility) = menu_q.single_mut() {
+        visibility.set_if_neq(if screen.open || panel.open {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        });
+    }
+}
+
+// Rapier keeps stepping on its own, so a pause has to stop it - or the dice would
+// carry on rolling under the menu
+fn freeze_physics(mut config_q: Query<&mut RapierConfiguration>) {
+    for mut config in &mut config_q {
+        config.physics_pipeline_active = false;
+    }
+}
+
+fn unfreeze_physics(mut config_q: Query<&mut RapierConfiguration>) {
+    for mut config in &mut config_q {
+        config.physics_pipeline_active = true;
+    }
+}
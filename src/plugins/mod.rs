@@ -0,0 +1,131 @@
+// The game is split into plugins, one per part of the table. Each plugin owns its
+// own components, resources and systems, and main.rs just adds them all to the app.
+use bevy::prelude::*;
+
+pub mod achievements; // Unlock toasts and the list of achievements
+pub mod announce; // Each roll told in words, for screen readers and captions
+pub mod betting; // The round, the bankroll, placing bets and paying them
+pub mod camera; // The free-look camera and mouse picking
+pub mod celebration; // Confetti and sparks when the shooter makes the point
+pub mod companions; // Computer players at a table for one
+pub mod console; // The debug console, with `--features dev`
+pub mod controls; // Keyboard, mouse and gamepad input, turned into player actions
+pub mod daily; // The daily challenge's countdown and scores
+#[cfg(feature = "dev")]
+pub mod dev; // The inspector and debug panel, with `--features dev`
+pub mod dice; // Throwing the dice and reading them once they stop
+pub mod hotseat; // Two to four players taking turns at one machine
+pub mod leaderboard; // The best sessions, shown from the main menu
+pub mod lifecycle; // The events every throw goes through, in one place
+pub mod locale; // Loading the language files and keeping the text on screen translated
+pub mod menu; // The main menu, the loading screen and pausing
+pub mod net; // Playing at one table over the network, with the host in charge
+pub mod press; // Pressing or parlaying a bet that has just won
+pub mod quick_bets; // Putting the last roll's bets down again in one go
+pub mod rail; // The player's rail, with the bankroll in chips in its rack
+pub mod replay; // Recording each throw and playing it back in slow motion
+pub mod room; // The casino room round the table, reflected in the rails
+pub mod rules; // The house rules file, reloaded when it's saved
+pub mod save; // Keeping the bankroll, stats and table choices between launches
+pub mod session; // Recording the whole session and replaying it with a scrubber
+pub mod settings; // The player's preferences and the settings screen
+pub mod shooter; // The shooter's dice cup, shaken and tipped out for each throw
+pub mod sicbo; // Paying sic bo bets when three dice settle
+pub mod side_bets; // Paying the side bets players write as scripts
+pub mod sound; // Sound effects: the dice hitting the table
+pub mod stats; // The session statistics panel
+pub mod stickman; // The stickman calling out each roll
+pub mod streak; // The table heating up for a shooter on a streak
+pub mod table; // The felt, layout, walls, lights and puck
+pub mod tooltip; // What the bet zone under the pointer pays
+pub mod tutor; // The bet tutor: recommended bets and house edges
+pub mod ui; // The on-screen readouts and result callout
+pub mod undo; // Taking back the chips put down since the last throw
+#[cfg(feature = "vr")]
+pub mod vr; // Throwing with a VR controller, with `--features vr`
+
+pub use achievements::AchievementsPlugin;
+pub use announce::AnnouncePlugin;
+pub use betting::BettingPlugin;
+pub use camera::CameraPlugin;
+pub use celebration::CelebrationPlugin;
+pub use companions::CompanionsPlugin;
+pub use console::ConsolePlugin;
+pub use controls::ControlsPlugin;
+pub use daily::DailyPlugin;
+#[cfg(feature = "dev")]
+pub use dev::DevPlugin;
+pub use dice::DicePlugin;
+pub use hotseat::HotseatPlugin;
+pub use leaderboard::LeaderboardPlugin;
+pub use locale::LocalePlugin;
+pub use menu::MenuPlugin;
+pub use net::NetPlugin;
+pub use press::PressPlugin;
+pub use quick_bets::QuickBetsPlugin;
+pub use rail::RailPlugin;
+pub use replay::ReplayPlugin;
+pub use room::RoomPlugin;
+pub use rules::RulesPlugin;
+pub use save::SavePlugin;
+pub use session::SessionPlugin;
+pub use settings::SettingsPlugin;
+pub use shooter::ShooterPlugin;
+pub use sicbo::SicBoPlugin;
+pub use side_bets::SideBetsPlugin;
+pub use sound::SoundPlugin;
+pub use stats::StatsPlugin;
+pub use stickman::StickmanPlugin;
+pub use streak::StreakPlugin;
+pub use table::TablePlugin;
+pub use tooltip::TooltipPlugin;
+pub use tutor::TutorPlugin;
+pub use ui::UiPlugin;
+pub use undo::UndoPlugin;
+#[cfg(feature = "vr")]
+pub use vr::VrPlugin;
+
+// The steps a throw goes through once the dice stop, in order. Each step lives in a
+// different plugin, so the order is set here rather than with .chain() - that way a
+// roll is still settled, judged, paid and announced all in the same frame.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RollStep {
+    Settle,   // Every die has come to rest (DicePlugin)
+    Judge,    // The rules decide what the roll meant (BettingPlugin)
+    Pay,      // Bets are settled against it (BettingPlugin and SideBetsPlugin, or SicBoPlugin)
+    Announce, // The UI tells the player (UiPlugin)
+}
+
+// Where the app is. It opens on the main menu, loads what the table needs, then plays;
+// pausing stops the game without leaving the table.
+#[derive(States, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AppState {
+    #[default]
+    MainMenu,
+    Loading,
+    Playing,
+    Paused,
+}
+
+// At the table - playing or paused. The table is set up on entering this state, and
+// everything on it is `StateScoped(InGame)`, so pausing keeps it all and quitting to
+// the main menu clears it away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InGame;
+
+impl ComputedStates for InGame {
+    type SourceStates = AppState;
+
+    fn compute(state: AppState) -> Option<Self> {
+        matches!(state, AppState::Playing | AppState::Paused).then_some(InGame)
+    }
+}
+
+// Mark a resource as changed, so everything drawn from it is drawn again. Readouts and
+// chip piles are only rebuilt when what they show changes, so coming back to the table
+// from the main menu would otherwise leave the fresh ones empty.
+pub(crate) fn redraw<R: Resource>(resource: Option<ResMut<R>>) {
+    if let Some(mut resource) = resource {
+        resource.set_changed();
+    }
+}
@@ -0,0 +1,1328 @@
+// The networked table. Start one game with `--host` and the others with
+// `--join <address>`; everyone bets with their own bankroll and the dice pass round the
+// table, moving to the next player after each seven-out.
+//
+// The host is in charge of everything that matters. Its physics throws the dice (a
+// remote shooter only sends how hard and where they threw, kept to what a real throw
+// could be), it reads the result, and it keeps every player's bets and money, checking
+// each bet with the same code a local click uses. A host started with `--fair-dice`
+// doesn't even let the throw decide: its own random draw picks every roll. Joined
+// players are sent snapshots of the dice positions while they roll (which they play
+// back smoothly, a moment behind), the result once they stop, and their own money after
+// every bet and roll - which replaces whatever their own game worked out. Everyone is
+// also sent everyone else's bets and what the last roll did for each of them, so each
+// player's chips show on the layout in their own colour and the seats panel keeps
+// everyone's results apart.
+//
+// `--watch <address>` comes to the table just to watch: the dice, the rolls, everyone's
+// bets and the last few rolls on the seats panel, but no money and no bets. Its Take a
+// seat button joins the line for a seat, and the host sits the first in line down
+// (with the standard stack) whenever one is free - a table seats as many as there are
+// players' colours. Anyone joining a full table with `--join` waits in the same line.
+use std::collections::VecDeque;
+use std::net::{TcpListener, TcpStream};
+
+use bevy::color::prelude::*;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use super::betting::{
+    BetPlaced, BetResolved, BetTakenDown, BetUndone, BettingWindow, RollResolved,
+};
+use super::dice::{Dice, DiceAuthority, DiceId, RollSettled, RollState, ThrowDice, ThrowPower};
+use super::hotseat::PLAYER_COLORS;
+use super::lifecycle::{PhaseChanged, jump_phase};
+use super::locale::Localized;
+use super::{AppState, InGame, RollStep, redraw};
+use crate::betting::{Bet, BetError, BetResult, TableBets};
+use crate::layout::TableLayout;
+use crate::locale::Locale;
+use crate::net::{
+    ClientMessage, Connection, DEFAULT_PORT, DicePose, HOST_ID, HostMessage, NetError, PlayerId,
+    RECENT_ROLLS, Seat, Watcher,
+};
+use crate::payout::{
+    Bankroll, Cents, break_into_chips, format_money, format_signed_money, roll_net,
+};
+use crate::registry::BetRegistry;
+use crate::rules::{RollOutcome, RoundPhase, TableRules};
+use crate::session::TableState;
+
+pub struct NetPlugin;
+
+impl Plugin for NetPlugin {
+    fn build(&self, app: &mut App) {
+        // main.rs inserts the host or the connection before the plugins are added
+        let hosting = app.world().contains_resource::<NetHost>();
+        let joined = app.world().contains_resource::<NetClient>();
+        if !hosting && !joined {
+            return; // Playing alone
+        }
+
+        app.init_resource::<TableSeats>()
+            .add_systems(OnEnter(InGame), (setup_seats_panel, redraw::<TableSeats>))
+            .add_systems(
+                Update,
+                (seats_panel_system, seat_chips_system).run_if(in_state(AppState::Playing)),
+            );
+        if hosting {
+            app.add_systems(
+                Update,
+                (
+                    (accept_players_system, host_receive_system).chain(),
+                    host_dice_system.after(RollStep::Settle),
+                    host_roll_system.after(RollStep::Pay), // Once the host's own bets are paid
+                )
+                    .run_if(resource_exists::<NetHost>)
+                    .run_if(in_state(AppState::Playing)),
+            );
+        }
+        if joined {
+            // The host's physics runs the dice; ours just shows them
+            app.insert_resource(DiceAuthority {
+                may_throw: false,
+                simulates: false,
+            })
+            .init_resource::<DiceSnapshots>()
+            .add_systems(
+                Update,
+                (
+                    (client_receive_system, client_dice_system)
+                        .chain()
+                        .before(RollStep::Settle),
+                    client_send_system,
+                    client_seat_system,
+                    // After our own payout system, so the host's numbers have the last word
+                    client_money_system.after(RollStep::Pay),
+                )
+                    .run_if(resource_exists::<NetClient>)
+                    .run_if(in_state(AppState::Playing)),
+            );
+        }
+    }
+}
+
+// Everyone at the table, for the seats panel. Kept up to date on the host and on
+// every joined player.
+#[derive(Resource, Debug, Default)]
+pub struct TableSeats {
+    pub seats: Vec<Seat>,
+    pub shooter: PlayerId,
+    pub me: Option<PlayerId>, // Our own seat, once the host has told us
+    pub watchers: Vec<Watcher>,
+    pub rolls: Vec<[u8; 2]>, // The last few rolls, oldest first
+}
+
+impl TableSeats {
+    // Whether we have a seat - a watcher, or someone still waiting for one, doesn't
+    pub fn seated(&self) -> bool {
+        self.me
+            .is_some_and(|me| self.seats.iter().any(|seat| seat.id == me))
+    }
+
+    // Whether we're in the line for a seat
+    fn waiting(&self) -> bool {
+        self.watchers
+            .iter()
+            .any(|watcher| Some(watcher.id) == self.me && watcher.waiting)
+    }
+
+    fn note_roll(&mut self, die_values: [u8; 2]) {
+        self.rolls.push(die_values);
+        let extra = self.rolls.len().saturating_sub(RECENT_ROLLS);
+        self.rolls.drain(..extra);
+    }
+}
+
+// The host's snapshots of the dice that a joined player hasn't finished showing,
+// oldest first, and how far through them (on the host's clock) the dice on screen are
+#[derive(Resource, Debug, Default)]
+struct DiceSnapshots {
+    buffer: VecDeque<DiceSnapshot>,
+    playback: f32,
+}
+
+#[derive(Debug)]
+struct DiceSnapshot {
+    at: f32, // On the host's clock
+    poses: Vec<(Vec3, Quat)>,
+    shown: bool,
+}
+
+impl DiceSnapshots {
+    fn add(&mut self, snapshot: DiceSnapshot) {
+        let gap = self
+            .buffer
+            .back()
+            .map_or(f32::INFINITY, |last| snapshot.at - last.at);
+        if gap > NEW_THROW_GAP || gap < 0.0 {
+            // A new throw (or a host that has started again): show it from its start
+            self.buffer.clear();
+            self.playback = snapshot.at - SHOWN_BEHIND;
+        }
+        self.buffer.push_back(snapshot);
+    }
+}
+
+// As many as there are players' colours, the host included
+const MAX_SEATS: usize = PLAYER_COLORS.len();
+
+// Hosting a table: listening for players and keeping everyone's money
+#[derive(Resource)]
+pub struct NetHost {
+    listener: TcpListener,
+    name: String, // The host's own name at the table
+    players: Vec<RemotePlayer>,
+    next_id: PlayerId,
+    shooter: PlayerId,
+    last_roll: i64,       // What the last roll did for the host's own bets
+    queue: Vec<PlayerId>, // Watchers waiting for a seat, first in line first
+}
+
+// A player who has connected to our table
+struct RemotePlayer {
+    id: PlayerId,
+    name: Option<String>, // None until they've said who they are
+    connection: Connection,
+    table: TableState, // Their bets and bankroll - the real ones
+    last_roll: i64,    // What the last roll did for them
+    seated: bool,      // False while they're only watching
+    color: usize,      // Which of the players' colours is theirs, once seated
+}
+
+impl RemotePlayer {
+    // Only a seated player bets
+    fn check_seated(&self) -> Result<(), BetError> {
+        if self.seated {
+            Ok(())
+        } else {
+            Err(BetError::Watching)
+        }
+    }
+}
+
+impl NetHost {
+    pub fn listen(port: u16, name: String) -> Result<Self, NetError> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        listener.set_nonblocking(true)?; // Check for new players without waiting for one
+        Ok(Self {
+            listener,
+            name,
+            players: Vec::new(),
+            next_id: HOST_ID + 1,
+            shooter: HOST_ID,
+            last_roll: 0,
+            queue: Vec::new(),
+        })
+    }
+
+    // Everyone seated, host first, then in the order they joined. The host's own
+    // money is in our Bankroll and TableBets.
+    fn seats(&self, bankroll: &Bankroll, bets: &TableBets) -> Vec<Seat> {
+        let host = Seat {
+            id: HOST_ID,
+            name: self.name.clone(),
+            balance: bankroll.balance,
+            bets: bets.bets.clone(),
+            last_roll: self.last_roll,
+            color: 0,
+        };
+        let players = self.seated().map(|player| Seat {
+            id: player.id,
+            name: player.name.clone().unwrap_or_default(),
+            balance: player.table.bankroll.balance,
+            bets: player.table.bets.bets.clone(),
+            last_roll: player.last_roll,
+            color: player.color,
+        });
+        std::iter::once(host).chain(players).collect()
+    }
+
+    // The players with a seat, not counting the host
+    fn seated(&self) -> impl Iterator<Item = &RemotePlayer> {
+        self.players
+            .iter()
+            .filter(|player| player.seated && player.name.is_some())
+    }
+
+    // Everyone watching, the line for a seat first
+    fn watchers(&self) -> Vec<Watcher> {
+        let watcher = |player: &RemotePlayer| Watcher {
+            id: player.id,
+            name: player.name.clone().unwrap_or_default(),
+            waiting: self.queue.contains(&player.id),
+        };
+        let waiting = self
+            .queue
+            .iter()
+            .filter_map(|&id| self.players.iter().find(|player| player.id == id));
+        let watching = self.players.iter().filter(|player| {
+            !player.seated && player.name.is_some() && !self.queue.contains(&player.id)
+        });
+        waiting.chain(watching).map(watcher).collect()
+    }
+
+    // Sit the first in line down while there are seats free, each with the standard
+    // stack and the first colour nobody has. Says whether anyone sat down.
+    fn fill_seats(&mut self, phase: RoundPhase) -> bool {
+        let mut seated_any = false;
+        while self.seated().count() + 1 < MAX_SEATS && !self.queue.is_empty() {
+            let id = self.queue.remove(0);
+            let taken: Vec<usize> = self.seated().map(|player| player.color).collect();
+            let color = (1..PLAYER_COLORS.len())
+                .find(|color| !taken.contains(color))
+                .unwrap_or(0); // The host is always colour 0
+            let Some(player) = self.players.iter_mut().find(|player| player.id == id) else {
+                continue;
+            };
+            info!("Player {id} takes a seat");
+            player.seated = true;
+            player.color = color;
+            player.last_roll = 0;
+            player.table = TableState {
+                phase,
+                bets: TableBets::default(),
+                bankroll: Bankroll::default(),
+            };
+            send_money(player);
+            seated_any = true;
+        }
+        seated_any
+    }
+
+    // Pass the dice to the next seat along, wrapping back round to the host
+    fn next_shooter(&mut self) {
+        let seated: Vec<PlayerId> = std::iter::once(HOST_ID)
+            .chain(self.seated().map(|player| player.id))
+            .collect();
+        let current = seated.iter().position(|&id| id == self.shooter);
+        let next = current.map_or(0, |index| (index + 1) % seated.len());
+        self.shooter = seated[next];
+    }
+
+    // Tell everyone at the table something, watchers included
+    fn broadcast(&mut self, message: &HostMessage) {
+        for player in self
+            .players
+            .iter_mut()
+            .filter(|player| player.name.is_some())
+        {
+            // A failed send shows up as an error on the next receive, which drops them
+            let _ = player.connection.send(message);
+        }
+    }
+}
+
+// Joined to someone else's table
+#[derive(Resource)]
+pub struct NetClient {
+    connection: Connection,
+    pending_money: Option<(Cents, TableBets)>, // The host's word on our money, applied after payouts
+}
+
+impl NetClient {
+    // Join the table at `address` to play, or (with `watch`) just to watch
+    pub fn connect(address: &str, name: String, watch: bool) -> Result<Self, NetError> {
+        // "192.168.1.20" is fine on its own - the port is added if it's missing
+        let stream = if address.contains(':') {
+            TcpStream::connect(address)?
+        } else {
+            TcpStream::connect((address, DEFAULT_PORT))?
+        };
+        let mut connection = Connection::new(stream)?;
+        connection.send(&if watch {
+            ClientMessage::Watch { name }
+        } else {
+            ClientMessage::Join { name }
+        })?;
+        Ok(Self {
+            connection,
+            pending_money: None,
+        })
+    }
+}
+
+#[derive(Component)]
+struct SeatsList; // The seats panel's lines, one per player
+
+#[derive(Component)]
+struct TakeSeatButton; // A watcher asking for a seat
+
+#[derive(Component)]
+struct LeaveQueueButton; // ...and no longer waiting for one
+
+// A pile of markers showing another player's bet
+#[derive(Component)]
+struct SeatChips;
+
+const MARKER_RADIUS: f32 = 0.045;
+const MARKER_HEIGHT: f32 = 0.02;
+const MARKER_SPREAD: f32 = 0.16; // How far from the bet's usual spot each seat's pile sits
+const MAX_MARKERS_SHOWN: usize = 10;
+
+// How often the host sends where the dice are while they roll, in seconds. Everyone
+// else moves their dice smoothly from one snapshot to the next, showing the dice
+// `SHOWN_BEHIND` late so the next snapshot has nearly always arrived by the time it's
+// needed. A longer wait than `NEW_THROW_GAP` between snapshots means the dice stopped
+// in between, so the next ones are a new throw: the dice jump straight to them rather
+// than sliding back across the table to the shooter.
+const SNAPSHOT_INTERVAL: f32 = 1.0 / 20.0;
+const SHOWN_BEHIND: f32 = SNAPSHOT_INTERVAL * 2.0;
+const NEW_THROW_GAP: f32 = 0.25;
+
+// A camera's or hand's place at full size, as sent with a throw
+fn pose(transform: &Transform) -> ([f32; 3], [f32; 4]) {
+    (
+        transform.translation.to_array(),
+        transform.rotation.to_array(),
+    )
+}
+
+// Send one player their money as the host has it
+fn send_money(player: &mut RemotePlayer) {
+    let _ = player.connection.send(&HostMessage::Money {
+        balance: player.table.bankroll.balance,
+        bets: player.table.bets.clone(),
+    });
+}
+
+// Greet someone who has just said who they are: their number, the table they've come
+// to and the money they have on it - none, until they're seated
+fn welcome(player: &mut RemotePlayer, rules: &TableRules, phase: RoundPhase, rolls: &[[u8; 2]]) {
+    let _ = player.connection.send(&HostMessage::Welcome {
+        id: player.id,
+        rules: rules.clone(),
+        phase,
+        rolls: rolls.to_vec(),
+    });
+    send_money(player);
+}
+
+// The fastest a VR shooter's hand may be moving as it lets go, in metres a second - a
+// hard throw across a real table is well under this
+const MAX_HAND_SPEED: f32 = 10.0;
+
+// A remote shooter's throw, made into one a player at our own table could have made.
+// Our physics rolls the dice whatever they send, but only from numbers a real throw
+// could have: no harder than the power meter goes, no faster than a hand moves and no
+// more twisted than a flick. None for a throw nobody could make at all - one with
+// numbers that aren't numbers, or no way to face.
+fn fair_throw(throw: ThrowDice, max_power: f32) -> Option<ThrowDice> {
+    let ThrowDice {
+        power,
+        from,
+        target,
+        hand_velocity,
+        twist,
+        set,
+    } = throw;
+    let finite = power.is_finite()
+        && twist.is_finite()
+        && from.translation.is_finite()
+        && from.rotation.is_finite()
+        && target.is_none_or(Vec2::is_finite)
+        && hand_velocity.is_none_or(Vec3::is_finite);
+    if !finite {
+        return None;
+    }
+    // A camera has to face some way
+    let rotation = Quat::from_vec4(Vec4::from(from.rotation).try_normalize()?);
+    Some(ThrowDice {
+        power: power.clamp(0.0, max_power),
+        from: from.with_rotation(rotation),
+        target,
+        hand_velocity: hand_velocity.map(|velocity| velocity.clamp_length_max(MAX_HAND_SPEED)),
+        twist: twist.clamp(-1.0, 1.0),
+        set,
+    })
+}
+
+// System that lets in anyone who has connected since last frame. They're given a seat
+// (or a place in line for one) once they've said who they are.
+fn accept_players_system(mut host: ResMut<NetHost>, phase: Res<RoundPhase>) {
+    loop {
+        let stream = match host.listener.accept() {
+            Ok((stream, address)) => {
+                info!("Player connecting from {address}");
+                stream
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => return,
+            Err(error) => {
+                warn!("Couldn't accept a player: {error}");
+                return;
+            }
+        };
+        let connection = match Connection::new(stream) {
+            Ok(connection) => connection,
+            Err(error) => {
+                warn!("Couldn't set up a player's connection: {error}");
+                continue;
+            }
+        };
+        let id = host.next_id;
+        host.next_id += 1;
+        host.players.push(RemotePlayer {
+            id,
+            name: None,
+            connection,
+            // Nobody has any money until they're given a seat (see fill_seats)
+            table: TableState {
+                phase: *phase,
+                bets: TableBets::default(),
+                bankroll: Bankroll { balance: 0 },
+            },
+            last_roll: 0,
+            seated: false,
+            color: 0,
+        });
+    }
+}
+
+// System that handles what the players have sent: joining (to play or to watch),
+// asking for a seat, bets and throws. Anyone whose connection has failed is dropped, and
+// whoever's first in line sits down as soon as there's a seat.
+fn host_receive_system(
+    mut host: ResMut<NetHost>,
+    rules: Res<TableRules>,
+    phase: Res<RoundPhase>,
+    roll: Res<RollState>,
+    window: Res<BettingWindow>, // Our own table's - the same for everyone at it
+    registry: Res<BetRegistry>, // Empty at a networked table, so no side bets
+    bankroll: Res<Bankroll>,
+    bets: Res<TableBets>,
+    mut seats: ResMut<TableSeats>,
+    mut authority: ResMut<DiceAuthority>,
+    power_meter: Res<ThrowPower>, // How hard anyone may throw
+    mut throw_events: EventWriter<ThrowDice>,
+) {
+    let mut seats_changed = false;
+    let shooter = host.shooter;
+    let mut dropped = Vec::new();
+    let mut in_line = Vec::new(); // Asking for a seat
+    let mut out_of_line = Vec::new(); // No longer asking
+    // The dice aren't in flight until the throw is launched later in the frame, so a
+    // second throw in the same batch would get past the in-flight check
+    let mut thrown = false;
+
+    for player in &mut host.players {
+        let messages = match player.connection.receive::<ClientMessage>() {
+            Ok(messages) => messages,
+            Err(error) => {
+                info!("Player {} left the table: {error}", player.id);
+                dropped.push(player.id);
+                continue;
+            }
+        };
+        for message in messages {
+            let message = match message {
+                Ok(message) => message,
+                // Just that line is lost - the player stays at the table
+                Err(error) => {
+                    warn!("Player {} sent something we can't read: {error}", player.id);
+                    continue;
+                }
+            };
+            match message {
+                // Saying who you are happens once. Joining again would put a seated
+                // player back in line, and sitting down again starts them afresh -
+                // a new stack for a broke player, and their bets wiped.
+                ClientMessage::Join { .. } | ClientMessage::Watch { .. }
+                    if player.name.is_some() => {}
+                ClientMessage::Join { name } => {
+                    info!("{name} came to the table");
+                    player.name = Some(name);
+                    welcome(player, &rules, *phase, &seats.rolls);
+                    in_line.push(player.id); // Seated straight away if there's room
+                    seats_changed = true;
+                }
+                ClientMessage::Watch { name } => {
+                    info!("{name} came to watch");
+                    player.name = Some(name);
+                    welcome(player, &rules, *phase, &seats.rolls);
+                    seats_changed = true;
+                }
+                ClientMessage::TakeSeat if !player.seated => in_line.push(player.id),
+                ClientMessage::TakeSeat => {}
+                ClientMessage::LeaveQueue => out_of_line.push(player.id),
+                ClientMessage::PlaceBet { kind, amount } => {
+                    // Checked and paid for exactly like a click on our own layout
+                    let placed = player
+                        .check_seated()
+                        .and_then(|()| window.check())
+                        .and_then(|()| registry.check_open(kind, *phase))
+                        .and_then(|()| player.table.place_bet(kind, amount, &rules));
+                    if let Err(error) = placed {
+                        let _ = player.connection.send(&HostMessage::BetRefused {
+                            kind,
+                            reason: error.to_string(),
+                        });
+                    }
+                    send_money(player); // Either way, so their game matches ours
+                    seats_changed = true;
+                }
+                ClientMessage::TakeDownBet { kind } => {
+                    let taken = player
+                        .check_seated()
+                        .and_then(|()| window.check())
+                        .and_then(|()| player.table.take_down(kind));
+                    if let Err(error) = taken {
+                        let _ = player.connection.send(&HostMessage::BetRefused {
+                            kind,
+                            reason: error.to_string(),
+                        });
+                    }
+                    send_money(player);
+                    seats_changed = true;
+                }
+                ClientMessage::UndoBet { kind, amount } => {
+                    let undone = player
+                        .check_seated()
+                        .and_then(|()| window.check())
+                        .and_then(|()| player.table.undo_bet(Bet::new(kind, amount), &rules));
+                    if let Err(error) = undone {
+                        let _ = player.connection.send(&HostMessage::BetRefused {
+                            kind,
+                            reason: error.to_string(),
+                        });
+                    }
+                    send_money(player);
+                    seats_changed = true;
+                }
+                ClientMessage::Throw {
+                    power,
+                    from: (translation, rotation),
+                    target,
+                    hand_velocity,
+                    twist,
+                    set,
+                } => {
+                    // Only the shooter throws, only once the dice are back, and only once
+                    if player.id != shooter || roll.in_flight() || thrown {
+                        continue;
+                    }
+                    let throw = ThrowDice {
+                        power,
+                        from: Transform::from_translation(Vec3::from(translation))
+                            .with_rotation(Quat::from_array(rotation)),
+                        target: target.map(Vec2::from),
+                        hand_velocity: hand_velocity.map(Vec3::from),
+                        twist,
+                        set,
+                    };
+                    match fair_throw(throw, power_meter.max) {
+                        Some(throw) => {
+                            throw_events.write(throw);
+                            thrown = true;
+                        }
+                        // Nothing happens, and they can throw again
+                        None => warn!("Player {} sent a throw that can't be made", player.id),
+                    }
+                }
+            }
+        }
+    }
+
+    if !dropped.is_empty() {
+        if dropped.contains(&host.shooter) {
+            host.next_shooter(); // Their seat is still counted until they go
+        }
+        host.players.retain(|player| !dropped.contains(&player.id));
+        host.queue.retain(|id| !dropped.contains(id));
+        seats_changed = true;
+    }
+    for id in in_line {
+        if !host.queue.contains(&id) {
+            host.queue.push(id);
+            seats_changed = true;
+        }
+    }
+    if !out_of_line.is_empty() {
+        host.queue.retain(|id| !out_of_line.contains(id));
+        seats_changed = true;
+    }
+    if host.fill_seats(*phase) {
+        seats_changed = true;
+    }
+    // The host's own bets show on everyone's layout too
+    if seats_changed || bankroll.is_changed() || bets.is_changed() {
+        update_seats(&mut host, &bankroll, &bets, &mut seats, &mut authority);
+    }
+}
+
+// Tell everyone (including our own seats panel) who's at the table, who's shooting and
+// who's watching
+fn update_seats(
+    host: &mut NetHost,
+    bankroll: &Bankroll,
+    bets: &TableBets,
+    seats: &mut TableSeats,
+    authority: &mut DiceAuthority,
+) {
+    seats.seats = host.seats(bankroll, bets);
+    seats.shooter = host.shooter;
+    seats.me = Some(HOST_ID);
+    seats.watchers = host.watchers();
+    authority.may_throw = host.shooter == HOST_ID;
+    let message = HostMessage::Table {
+        seats: seats.seats.clone(),
+        shooter: host.shooter,
+        watchers: seats.watchers.clone(),
+    };
+    host.broadcast(&message);
+}
+
+// System that sends everyone snapshots of the dice positions while they roll, plus one
+// last one when they stop so everyone sees them where they came to rest
+fn host_dice_system(
+    mut host: ResMut<NetHost>,
+    time: Res<Time>,
+    roll: Res<RollState>,
+    dice_q: Query<(&DiceId, &Transform, &Visibility), With<Dice>>,
+    mut was_rolling: Local<bool>,
+    mut last_sent: Local<f32>,
+) {
+    // Rolling, or being pushed back to the shooter by the stickman
+    let rolling = roll.in_flight() || roll.retrieving();
+    if !rolling && !*was_rolling {
+        return;
+    }
+    let now = time.elapsed_secs();
+    let starting = rolling && !*was_rolling;
+    let stopping = !rolling;
+    *was_rolling = rolling;
+    if !starting && !stopping && now - *last_sent < SNAPSHOT_INTERVAL {
+        return; // Everyone fills in between the snapshots themselves
+    }
+    *last_sent = now;
+    let mut dice: Vec<_> = dice_q.iter().collect();
+    dice.sort_by_key(|(id, ..)| id.0);
+    let shown = dice
+        .iter()
+        .all(|(.., visibility)| **visibility != Visibility::Hidden);
+    let poses = dice
+        .iter()
+        .map(|(_, transform, _)| {
+            DicePose::new(
+                transform.translation.to_array(),
+                transform.rotation.to_array(),
+            )
+        })
+        .collect();
+    host.broadcast(&HostMessage::Dice {
+        at: now,
+        poses,
+        shown,
+    });
+}
+
+// System that settles every remote player's bets against the roll the host's dice made,
+// and passes the dice on after a seven-out. Each player's bets are settled against
+// their own money, and what the roll did for each of them goes round with the seats.
+fn host_roll_system(
+    mut host: ResMut<NetHost>,
+    rules: Res<TableRules>,
+    bankroll: Res<Bankroll>,
+    bets: Res<TableBets>,
+    mut settled_events: EventReader<RollSettled>,
+    mut resolved_events: EventReader<RollResolved>,
+    mut paid_events: EventReader<BetResolved>, // The host's own bets, paid by the betting plugin
+    mut seats: ResMut<TableSeats>,
+    mut authority: ResMut<DiceAuthority>,
+) {
+    let settled: Vec<RollSettled> = settled_events.read().copied().collect();
+    let seven_out = resolved_events
+        .read()
+        .any(|resolved| resolved.outcome == RollOutcome::SevenOut);
+    let paid: Vec<(Bet, BetResult)> = paid_events
+        .read()
+        .map(|paid| (paid.bet, paid.result))
+        .collect();
+    if settled.is_empty() {
+        return;
+    }
+    host.last_roll = roll_net(&paid);
+
+    for roll in &settled {
+        host.broadcast(&HostMessage::Rolled {
+            die_values: roll.die_values,
+            short_roll: roll.short_roll,
+        });
+        seats.note_roll(roll.die_values);
+        for player in host.players.iter_mut().filter(|player| player.seated) {
+            let (_, decisions) = player.table.roll(roll.die_values, &rules);
+            player.last_roll = roll_net(&decisions);
+            send_money(player);
+        }
+    }
+    if seven_out {
+        host.next_shooter();
+        info!("The dice pass to player {}", host.shooter);
+    }
+    update_seats(&mut host, &bankroll, &bets, &mut seats, &mut authority);
+}
+
+// System that handles what the host has sent. If the connection drops we carry on as
+// a table of one.
+fn client_receive_system(
+    mut commands: Commands,
+    mut client: ResMut<NetClient>,
+    mut rules: ResMut<TableRules>,
+    mut phase: ResMut<RoundPhase>,
+    mut phase_events: EventWriter<PhaseChanged>,
+    mut seats: ResMut<TableSeats>,
+    mut authority: ResMut<DiceAuthority>,
+    mut window: ResMut<BettingWindow>,
+    mut bankroll: ResMut<Bankroll>,
+    mut settled_events: EventWriter<RollSettled>,
+    mut snapshots: ResMut<DiceSnapshots>,
+    dice_q: Query<(Entity, Has<RigidBodyDisabled>), With<Dice>>,
+) {
+    let messages = match client.connection.receive::<HostMessage>() {
+        Ok(messages) => messages,
+        Err(error) => {
+            warn!("Lost the connection to the table ({error}) - playing on alone");
+            commands.remove_resource::<NetClient>();
+            *authority = DiceAuthority::default();
+            if !seats.seated() {
+                // A watcher has no money, so they sit down with the standard stack
+                *bankroll = Bankroll::default();
+                *window = BettingWindow::Open;
+            }
+            *seats = TableSeats::default();
+            *snapshots = DiceSnapshots::default();
+            return;
+        }
+    };
+    for message in messages {
+        let message = match message {
+            Ok(message) => message,
+            Err(error) => {
+                warn!("The host sent something we can't read: {error}");
+                continue;
+            }
+        };
+        match message {
+            HostMessage::Welcome {
+                id,
+                rules: house_rules,
+                phase: table_phase,
+                rolls,
+            } => {
+                info!("Joined the table as player {id}");
+                seats.me = Some(id);
+                seats.rolls = rolls;
+                *rules = house_rules;
+                jump_phase(&mut phase, table_phase, &mut phase_events);
+            }
+            HostMessage::Table {
+                seats: seated,
+                shooter,
+                watchers,
+            } => {
+                seats.seats = seated;
+                seats.shooter = shooter;
+                seats.watchers = watchers;
+                authority.may_throw = seats.me == Some(shooter);
+            }
+            HostMessage::Dice { at, poses, shown } => {
+                for (die, disabled) in &dice_q {
+                    if !disabled {
+                        // Our physics would only fight the host's
+                        commands
+                            .entity(die)
+                            .insert((RigidBodyDisabled, ColliderDisabled));
+                    }
+                }
+                // client_dice_system moves the dice through them
+                snapshots.add(DiceSnapshot {
+                    at,
+                    poses: poses
+                        .iter()
+                        .map(|pose| {
+                            let rotation = Vec4::from_array(pose.rotation())
+                                .try_normalize()
+                                .map_or(Quat::IDENTITY, Quat::from_vec4);
+                            (Vec3::from(pose.translation()), rotation)
+                        })
+                        .collect(),
+                    shown,
+                });
+            }
+            HostMessage::Rolled {
+                die_values,
+                short_roll,
+            } => {
+                seats.note_roll(die_values);
+                let [first, second] = die_values;
+                settled_events.write(RollSettled {
+                    die_values,
+                    total: first + second,
+                    short_roll,
+                });
+            }
+            HostMessage::Money { balance, bets } => {
+                client.pending_money = Some((balance, bets));
+            }
+            HostMessage::BetRefused { kind, reason } => {
+                info!("The table refused the {kind:?} bet: {reason}");
+            }
+        }
+    }
+}
+
+// System that moves the dice smoothly between the host's snapshots, a little behind
+// the newest one
+fn client_dice_system(
+    time: Res<Time>,
+    mut snapshots: ResMut<DiceSnapshots>,
+    mut dice_q: Query<(&DiceId, &mut Transform, &mut Visibility), With<Dice>>,
+) {
+    let Some(newest) = snapshots.buffer.back().map(|snapshot| snapshot.at) else {
+        return; // Nothing's been thrown yet
+    };
+    // Keep up with the host's clock, without running ahead of what's arrived or
+    // falling further behind than the delay
+    let playback =
+        (snapshots.playback + time.delta_secs()).clamp(newest - SHOWN_BEHIND * 2.0, newest);
+    snapshots.playback = playback;
+    // The snapshots just before and just after where we've got to. The older ones
+    // aren't needed again.
+    while snapshots
+        .buffer
+        .get(1)
+        .is_some_and(|next| next.at <= playback)
+    {
+        snapshots.buffer.pop_front();
+    }
+    let from = &snapshots.buffer[0];
+    let to = snapshots.buffer.get(1).unwrap_or(from);
+    let between = if to.at > from.at {
+        ((playback - from.at) / (to.at - from.at)).clamp(0.0, 1.0)
+    } else {
+        1.0
+    };
+
+    for (id, mut transform, mut visibility) in &mut dice_q {
+        // DiceId is 1-based, the poses are 0-based
+        let index = usize::from(id.0) - 1;
+        let (Some(&(start, start_turn)), Some(&(end, end_turn))) =
+            (from.poses.get(index), to.poses.get(index))
+        else {
+            continue;
+        };
+        transform.translation = start.lerp(end, between);
+        transform.rotation = start_turn.slerp(end_turn, between);
+        visibility.set_if_neq(if from.shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
+
+// System that sends the host our bets (and the ones we take down or back) and, on our turn, our
+// throw
+fn client_send_system(
+    mut client: ResMut<NetClient>,
+    mut placed_events: EventReader<BetPlaced>,
+    mut taken_events: EventReader<BetTakenDown>,
+    mut undone_events: EventReader<BetUndone>,
+    mut throw_events: EventReader<ThrowDice>,
+) {
+    for placed in placed_events.read() {
+        let _ = client.connection.send(&ClientMessage::PlaceBet {
+            kind: placed.kind,
+            amount: placed.amount,
+        });
+    }
+    for taken in taken_events.read() {
+        let _ = client
+            .connection
+            .send(&ClientMessage::TakeDownBet { kind: taken.kind });
+    }
+    for undone in undone_events.read() {
+        let _ = client.connection.send(&ClientMessage::UndoBet {
+            kind: undone.kind,
+            amount: undone.amount,
+        });
+    }
+    for throw in throw_events.read() {
+        let _ = client.connection.send(&ClientMessage::Throw {
+            power: throw.power,
+            from: pose(&throw.from),
+            target: throw.target.map(|target| target.to_array()),
+            hand_velocity: throw.hand_velocity.map(|velocity| velocity.to_array()),
+            twist: throw.twist,
+            set: throw.set,
+        });
+    }
+}
+
+// System that keeps a watcher's chips off the layout until they're given a seat, and
+// asks the host for one (or stops asking) when the seats panel's button is pressed
+fn client_seat_system(
+    mut client: ResMut<NetClient>,
+    seats: Res<TableSeats>,
+    mut window: ResMut<BettingWindow>,
+    take_q: Query<&Interaction, (Changed<Interaction>, With<TakeSeatButton>)>,
+    leave_q: Query<&Interaction, (Changed<Interaction>, With<LeaveQueueButton>)>,
+) {
+    if seats.is_changed() {
+        if !seats.seated() {
+            window.set_if_neq(BettingWindow::Watching);
+        } else if *window == BettingWindow::Watching {
+            *window = BettingWindow::Open; // betting_window_system takes it from here
+        }
+    }
+    let pressed = |interaction: &Interaction| *interaction == Interaction::Pressed;
+    if take_q.iter().any(pressed) {
+        let _ = client.connection.send(&ClientMessage::TakeSeat);
+    }
+    if leave_q.iter().any(pressed) {
+        let _ = client.connection.send(&ClientMessage::LeaveQueue);
+    }
+}
+
+// System that replaces our money with the host's figures. Bets go down straight away
+// on a click so the table feels responsive; if the host disagrees, its word stands.
+fn client_money_system(
+    mut client: ResMut<NetClient>,
+    mut bankroll: ResMut<Bankroll>,
+    mut bets: ResMut<TableBets>,
+) {
+    let Some((balance, table_bets)) = client.pending_money.take() else {
+        return;
+    };
+    bankroll.balance = balance;
+    *bets = table_bets;
+}
+
+// System that lists the players, their money, what the last roll did for them and who
+// has the dice, each in their seat's colour - then who's watching, and the last few
+// rolls. A watcher gets a button to ask for a seat, or to stop waiting for one.
+fn seats_panel_system(
+    mut commands: Commands,
+    seats: Res<TableSeats>,
+    locale: Res<Locale>,
+    list_q: Query<Entity, With<SeatsList>>,
+    mut buttons_q: Query<
+        (&mut Visibility, Has<LeaveQueueButton>),
+        Or<(With<TakeSeatButton>, With<LeaveQueueButton>)>,
+    >,
+) {
+    if !seats.is_changed() && !locale.is_changed() {
+        return;
+    }
+    let Ok(list) = list_q.single() else {
+        return;
+    };
+    let watching = seats.me.is_some() && !seats.seated();
+    for (mut visibility, leave_button) in &mut buttons_q {
+        let shown = watching && leave_button == seats.waiting();
+        visibility.set_if_neq(if shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+    let line = |text: String, color: Color| {
+        (
+            Text::new(text),
+            TextFont {
+                font_size: 14.0,
+                ..default()
+            },
+            TextColor(color),
+        )
+    };
+
+    commands.entity(list).despawn_related::<Children>();
+    commands.entity(list).with_children(|parent| {
+        if seats.seats.is_empty() {
+            parent.spawn(line(
+                locale.tr("Waiting for the table...").to_string(),
+                Color::WHITE,
+            ));
+            return;
+        }
+        for seat in &seats.seats {
+            let shooting = if seat.id == seats.shooter { "> " } else { "  " };
+            let you = if Some(seat.id) == seats.me {
+                format!(" {}", locale.tr("(you)"))
+            } else {
+                String::new()
+            };
+            let last_roll = if seat.last_roll == 0 {
+                String::new()
+            } else {
+                format!("  ({})", format_signed_money(seat.last_roll))
+            };
+            parent.spawn(line(
+                format!(
+                    "{shooting}{}{you}  {}{last_roll}",
+                    seat.name,
+                    format_money(seat.balance)
+                ),
+                seat_color(seat.color),
+            ));
+        }
+
+        if !seats.watchers.is_empty() {
+            parent.spawn(line(locale.tr("Watching:").to_string(), Color::WHITE));
+        }
+        for watcher in &seats.watchers {
+            let you = if Some(watcher.id) == seats.me {
+                format!(" {}", locale.tr("(you)"))
+            } else {
+                String::new()
+            };
+            let waiting = if watcher.waiting {
+                format!(" {}", locale.tr("(waiting for a seat)"))
+            } else {
+                String::new()
+            };
+            parent.spawn(line(
+                format!("  {}{you}{waiting}", watcher.name),
+                Color::srgb(0.7, 0.7, 0.7),
+            ));
+        }
+
+        if !seats.rolls.is_empty() {
+            let totals: Vec<String> = seats
+                .rolls
+                .iter()
+                .map(|[first, second]| (first + second).to_string())
+                .collect();
+            parent.spawn(line(
+                locale.fill("Last rolls: {rolls}", &[("rolls", &totals.join(" "))]),
+                Color::WHITE,
+            ));
+        }
+    });
+}
+
+// System that draws every other player's bets as small piles of markers in their
+// seat's colour. Each seat's piles sit to their own side of the bet's usual spot, so
+// several players' bets on one spot stay apart, and each pile is only ever its
+// owner's money. Our own bets are the normal chip piles.
+fn seat_chips_system(
+    mut commands: Commands,
+    seats: Res<TableSeats>,
+    table_layout: Res<TableLayout>,
+    chips_q: Query<Entity, With<SeatChips>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut looks: Local<Option<(Handle<Mesh>, Vec<Handle<StandardMaterial>>)>>,
+) {
+    if !seats.is_changed() {
+        return;
+    }
+    let (mesh, seat_materials) = looks.get_or_insert_with(|| {
+        let materials = PLAYER_COLORS
+            .iter()
+            .map(|hex| {
+                materials.add(StandardMaterial {
+                    base_color: Srgba::hex(hex).unwrap().into(),
+                    perceptual_roughness: 0.6,
+                    ..default()
+                })
+            })
+            .collect();
+        (
+            meshes.add(Cylinder::new(MARKER_RADIUS, MARKER_HEIGHT)),
+            materials,
+        )
+    });
+
+    for chips in &chips_q {
+        commands.entity(chips).despawn();
+    }
+    for (index, seat) in seats.seats.iter().enumerate() {
+        if Some(seat.id) == seats.me {
+            continue;
+        }
+        // Spread the seats evenly round the spot
+        let angle = index as f32 * std::f32::consts::TAU / seats.seats.len() as f32;
+        let offset = Vec2::new(angle.cos(), angle.sin()) * MARKER_SPREAD;
+        let material = &seat_materials[seat.color % PLAYER_COLORS.len()];
+        for bet in &seat.bets {
+            let spot = table_layout.chip_anchor(bet.kind) + offset;
+            let count = break_into_chips(bet.amount)
+                .len()
+                .clamp(1, MAX_MARKERS_SHOWN);
+            commands
+                .spawn((
+                    StateScoped(InGame),
+                    SeatChips,
+                    Transform::from_xyz(spot.x, 0.0, spot.y),
+                    Visibility::default(),
+                    Name::new(format!("{} {:?}", seat.name, bet.kind)),
+                ))
+                .with_children(|parent| {
+                    for height in 0..count {
+                        parent.spawn((
+                            Mesh3d(mesh.clone()),
+                            MeshMaterial3d(material.clone()),
+                            Transform::from_xyz(0.0, MARKER_HEIGHT * (height as f32 + 0.5), 0.0),
+                        ));
+                    }
+                });
+        }
+    }
+}
+
+// The colour a player's markers and their line on the seats panel are drawn in. The
+// host hands each player one nobody else at the table has as they sit down.
+fn seat_color(color: usize) -> Color {
+    Srgba::hex(PLAYER_COLORS[color % PLAYER_COLORS.len()])
+        .unwrap()
+        .into()
+}
+
+fn setup_seats_panel(mut commands: Commands) {
+    // Down the right-hand side, midway
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(20.0),
+                top: Val::Percent(40.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(6.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.05, 0.05, 0.05, 0.8)),
+            BorderRadius::all(Val::Px(6.0)),
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                Localized("At the table (> shooting)"),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+            panel.spawn((
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(2.0),
+                    ..default()
+                },
+                SeatsList, // Filled in on the first frame
+            ));
+            spawn_seat_button(panel, "Take a seat", TakeSeatButton);
+            spawn_seat_button(panel, "Leave the queue", LeaveQueueButton);
+        });
+}
+
+// One of the watcher's buttons under the list, hidden until seats_panel_system shows it
+fn spawn_seat_button(panel: &mut ChildSpawnerCommands, label: &'static str, marker: impl Bundle) {
+    panel
+        .spawn((
+            Button,
+            Node {
+                padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.1, 0.1, 0.1)),
+            BorderRadius::all(Val::Px(6.0)),
+            Visibility::Hidden,
+            marker,
+        ))
+        .with_children(|button| {
+            button.spawn((
+                Localized(label),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::betting::BetKind;
+
+    // A host with its table's resources, and a player's end of a connection to it
+    fn host_app() -> (App, TcpStream) {
+        let host = NetHost::listen(0, "Host".to_string()).unwrap();
+        let port = host.listener.local_addr().unwrap().port();
+        let mut app = App::new();
+        app.insert_resource(host)
+            .init_resource::<TableRules>()
+            .init_resource::<RoundPhase>()
+            .init_resource::<RollState>()
+            .init_resource::<BettingWindow>()
+            .init_resource::<BetRegistry>()
+            .init_resource::<Bankroll>()
+            .init_resource::<TableBets>()
+            .init_resource::<TableSeats>()
+            .init_resource::<DiceAuthority>()
+            .init_resource::<ThrowPower>()
+            .add_event::<ThrowDice>()
+            .add_systems(Update, (accept_players_system, host_receive_system).chain());
+        let client = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        (app, client)
+    }
+
+    fn send(client: &mut TcpStream, message: &ClientMessage) {
+        let mut line = ron::to_string(message).unwrap();
+        line.push('\n');
+        client.write_all(line.as_bytes()).unwrap();
+    }
+
+    // Run the host for long enough to have read what was sent
+    fn run(app: &mut App) {
+        for _ in 0..20 {
+            app.update();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn joining_again_leaves_the_money_alone() {
+        let (mut app, mut client) = host_app();
+        send(
+            &mut client,
+            &ClientMessage::Join {
+                name: "Ann".to_string(),
+            },
+        );
+        run(&mut app);
+        {
+            let mut host = app.world_mut().resource_mut::<NetHost>();
+            let player = &mut host.players[0];
+            assert!(player.seated);
+            // Down to their last $2, with $10 on the pass line
+            player.table.bankroll.balance = 200;
+            player.table.bets.place(BetKind::PassLine, 1_000, 0);
+        }
+        for name in ["Ann", "Someone else"] {
+            send(
+                &mut client,
+                &ClientMessage::Join {
+                    name: name.to_string(),
+                },
+            );
+        }
+        run(&mut app);
+
+        let host = app.world().resource::<NetHost>();
+        let player = &host.players[0];
+        assert!(player.seated);
+        assert!(host.queue.is_empty());
+        assert_eq!(player.name.as_deref(), Some("Ann"));
+        assert_eq!(player.table.bankroll.balance, 200);
+        assert_eq!(player.table.bets.amount_on(BetKind::PassLine), 1_000);
+    }
+}
@@ -0,0 +1,216 @@
+// Letting a win ride. When a bet wins, a little menu pops up over the spot it won on
+// with two buttons: press (double the bet out of the winnings, or raise it by all of
+// them if it won less than that) and parlay (put it back up with everything it won).
+// Either one buys the bet straight back in, exactly like clicking the layout that many
+// times, so the table's limits still apply and a refusal shakes the chips off the same
+// way. The menus go away with the next throw.
+//
+// Only bets that can be made again straight away get a menu - a pass line bet that won
+// on the come-out, say, but not the odds behind a point that has just been made.
+use bevy::prelude::*;
+
+use super::betting::{BetPlaced, BetRejected, BetResolved, BettingWindow};
+use super::camera::PlayerCamera;
+use super::dice::ThrowDice;
+use super::session::SessionPlayback;
+use super::{AppState, InGame, RollStep};
+use crate::betting::{Bet, BetResult, LetItRide, TableBets};
+use crate::layout::TableLayout;
+use crate::locale::Locale;
+use crate::payout::{Bankroll, Cents, format_money};
+use crate::registry::BetRegistry;
+use crate::rules::{RoundPhase, TableRules};
+
+pub struct PressPlugin;
+
+impl Plugin for PressPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                // Once the roll has been paid, so the round has moved on to what comes next
+                offer_system.after(RollStep::Pay),
+                press_button_system,
+                menu_position_system,
+            )
+                .run_if(in_state(AppState::Playing)),
+        );
+    }
+}
+
+// The menu over one winning bet
+#[derive(Component)]
+struct RideMenu {
+    bet: Bet,      // The bet as it won
+    profit: Cents, // What it won on top
+}
+
+// One of the menu's buttons
+#[derive(Component)]
+struct RideButton(LetItRide);
+
+const MENU_LIFT: Vec2 = Vec2::new(-40.0, -60.0); // Up and to the left of the spot, clear of the chips
+const BUTTON_COLOR: Color = Color::srgb(0.1, 0.25, 0.12); // Felt green
+const BUTTON_HOVERED: Color = Color::srgb(0.15, 0.38, 0.18);
+const BUTTON_PRESSED: Color = Color::srgb(0.22, 0.5, 0.26);
+
+// System that opens a menu over each bet a roll just paid a win on, and closes the
+// old ones as soon as the dice are thrown again
+fn offer_system(
+    mut commands: Commands,
+    mut payout_events: EventReader<BetResolved>,
+    mut throw_events: EventReader<ThrowDice>,
+    phase: Res<RoundPhase>,
+    rules: Res<TableRules>,
+    bets: Res<TableBets>,
+    registry: Res<BetRegistry>,
+    locale: Res<Locale>,
+    playback: Option<Res<SessionPlayback>>, // A replayed session only ever does what it did
+    menus_q: Query<Entity, With<RideMenu>>,
+) {
+    let wins: Vec<(Bet, Cents)> = payout_events
+        .read()
+        .filter_map(|payout| match payout.result {
+            BetResult::Win(profit) => Some((payout.bet, profit)),
+            _ => None,
+        })
+        .collect();
+    if throw_events.read().count() == 0 && wins.is_empty() {
+        return;
+    }
+    for menu in &menus_q {
+        commands.entity(menu).despawn();
+    }
+    if playback.is_some() {
+        return;
+    }
+
+    for (bet, profit) in wins {
+        let open = registry
+            .check_open(bet.kind, *phase)
+            .and_then(|()| bets.check_open(bet.kind, *phase, &rules));
+        if open.is_err() {
+            continue; // Can't go back up right now
+        }
+        commands
+            .spawn((
+                StateScoped(InGame),
+                Node {
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(4.0),
+                    ..default()
+                },
+                Visibility::Hidden, // Until it's been put over its spot
+                RideMenu { bet, profit },
+            ))
+            .with_children(|menu| {
+                for ride in LetItRide::ALL {
+                    let stake = ride.stake(bet.kind, bet.amount, profit, *phase, &rules);
+                    menu.spawn((
+                        Button,
+                        Node {
+                            padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                            ..default()
+                        },
+                        BackgroundColor(BUTTON_COLOR),
+                        BorderRadius::all(Val::Px(4.0)),
+                        RideButton(ride),
+                    ))
+                    .with_children(|button| {
+                        button.spawn((
+                            Text::new(
+                                locale.fill(ride.label(), &[("money", &format_money(stake))]),
+                            ),
+                            TextFont {
+                                font_size: 14.0,
+                                ..default()
+                            },
+                            TextColor(Color::WHITE),
+                        ));
+                    });
+                }
+            });
+    }
+}
+
+// System that keeps each menu over the spot its bet won on as the camera moves
+fn menu_position_system(
+    table_layout: Res<TableLayout>,
+    ui_scale: Res<UiScale>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<PlayerCamera>>,
+    mut menus_q: Query<(&RideMenu, &mut Node, &mut Visibility)>,
+) {
+    let Ok((camera, camera_transform)) = camera_q.single() else {
+        return;
+    };
+    for (menu, mut node, mut visibility) in &mut menus_q {
+        let anchor = table_layout.chip_anchor(menu.bet.kind);
+        let Ok(on_screen) =
+            camera.world_to_viewport(camera_transform, Vec3::new(anchor.x, 0.0, anchor.y))
+        else {
+            visibility.set_if_neq(Visibility::Hidden); // Behind the camera
+            continue;
+        };
+        let at = on_screen / ui_scale.0 + MENU_LIFT;
+        node.left = Val::Px(at.x);
+        node.top = Val::Px(at.y);
+        visibility.set_if_neq(Visibility::Inherited);
+    }
+}
+
+// System that puts a winning bet back up when one of its buttons is clicked
+fn press_button_system(
+    mut commands: Commands,
+    phase: Res<RoundPhase>,
+    rules: Res<TableRules>,
+    window: Res<BettingWindow>,
+    registry: Res<BetRegistry>,
+    mut bets: ResMut<TableBets>,
+    mut bankroll: ResMut<Bankroll>,
+    mut button_q: Query<
+        (&Interaction, &RideButton, &ChildOf, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    menus_q: Query<&RideMenu>,
+    mut placed_events: EventWriter<BetPlaced>,
+    mut rejected_events: EventWriter<BetRejected>,
+) {
+    for (interaction, button, parent, mut color) in &mut button_q {
+        color.0 = match interaction {
+            Interaction::Pressed => BUTTON_PRESSED,
+            Interaction::Hovered => BUTTON_HOVERED,
+            Interaction::None => BUTTON_COLOR,
+        };
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Ok(menu) = menus_q.get(parent.parent()) else {
+            continue;
+        };
+        let kind = menu.bet.kind;
+        let amount = button
+            .0
+            .stake(kind, menu.bet.amount, menu.profit, *phase, &rules);
+        let bought = window
+            .check()
+            .and_then(|()| registry.check_open(kind, *phase))
+            .and_then(|()| bets.buy_in(kind, amount, *phase, &rules, &mut bankroll));
+        match bought {
+            Ok(_) => {
+                info!("{:?}: {} back on {kind:?}", button.0, format_money(amount));
+                placed_events.write(BetPlaced { kind, amount });
+            }
+            Err(error) => {
+                info!("Can't {:?} {kind:?}: {error}", button.0);
+                rejected_events.write(BetRejected {
+                    kind,
+                    amount,
+                    error,
+                });
+            }
+        }
+        // Used (or refused) - one go per win
+        commands.entity(parent.parent()).despawn();
+    }
+}
@@ -0,0 +1,346 @@
+// Betting in one go. A player who bets the same way every hand shouldn't have to click
+// it all out again after each roll: the "Same bets" button (or B) puts back whatever
+// was on the layout when the last roll was thrown, topping up each spot that came
+// down or was paid off. It's all or nothing - if the bankroll won't cover the lot, or
+// one of them breaks the table's limits, nothing goes down and the refused bet's
+// chips are shaken off as usual. Bets the round has moved past (odds behind a point
+// that was made) are skipped.
+//
+// Bet presets work the same way, from patterns kept in the settings: Shift and a
+// number key puts that preset down, Ctrl and the number keeps whatever is on the
+// layout as the preset (an empty layout clears it). A line over the buttons says what
+// was saved or placed.
+use bevy::prelude::*;
+
+use super::betting::{BetPlaced, BetRejected, BettingWindow};
+use super::controls::PlayerInput;
+use super::dice::{DiceSettled, RollSettled};
+use super::hotseat::Hotseat;
+use super::locale::Localized;
+use super::session::SessionPlayback;
+use super::settings::Settings;
+use super::{AppState, InGame, RollStep};
+use crate::betting::{Bet, BetError, TableBets};
+use crate::locale::Locale;
+use crate::payout::{Bankroll, format_money};
+use crate::registry::BetRegistry;
+use crate::rules::{RoundPhase, TableRules};
+
+pub struct QuickBetsPlugin;
+
+impl Plugin for QuickBetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LastBets>()
+            .add_systems(OnEnter(InGame), setup_same_bets_button)
+            .add_systems(
+                Update,
+                (
+                    // Before the roll is paid, while its bets are still on the table
+                    remember_bets_system
+                        .after(RollStep::Settle)
+                        .before(RollStep::Judge),
+                    same_bets_system,
+                    (preset_system, preset_notice_system).chain(),
+                )
+                    .run_if(in_state(AppState::Playing)),
+            );
+    }
+}
+
+// The bets that were on the layout for the last roll
+#[derive(Resource, Debug, Default)]
+pub struct LastBets(pub TableBets);
+
+#[derive(Component)]
+struct SameBetsButton; // Above the dice set button, shown once there's a roll to repeat
+
+// What was last done with a preset, shown over the buttons for a moment
+#[derive(Component)]
+struct PresetNotice {
+    age: f32,
+}
+
+const BUTTON_COLOR: Color = Color::srgb(0.1, 0.1, 0.1);
+const NOTICE_SECONDS: f32 = 3.0;
+
+fn setup_same_bets_button(mut commands: Commands) {
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Button,
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(20.0),
+                bottom: Val::Px(128.0),
+                padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                ..default()
+            },
+            BackgroundColor(BUTTON_COLOR),
+            BorderRadius::all(Val::Px(6.0)),
+            Visibility::Hidden,
+            SameBetsButton,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Localized("Same bets (B)"),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+        });
+
+    // At the top of the button column, wide enough for a long pattern
+    commands.spawn((
+        StateScoped(InGame),
+        Text::new(""),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(20.0),
+            bottom: Val::Px(200.0), // Above the undo button
+            max_width: Val::Px(360.0),
+            ..default()
+        },
+        Visibility::Hidden,
+        PresetNotice {
+            age: NOTICE_SECONDS,
+        },
+    ));
+}
+
+// A pattern of bets in words: "$10 on Pass line + $20 on Pass line odds"
+fn describe_pattern(bets: &[Bet], registry: &BetRegistry, locale: &Locale) -> String {
+    let each: Vec<String> = bets
+        .iter()
+        .map(|bet| {
+            locale.fill(
+                "{money} on {bet}",
+                &[
+                    ("money", &format_money(bet.amount)),
+                    ("bet", &registry.bet_name(bet.kind, locale)),
+                ],
+            )
+        })
+        .collect();
+    each.join(" + ")
+}
+
+// System that writes down the bets on the table as each roll settles
+fn remember_bets_system(
+    mut settled_events: EventReader<RollSettled>,
+    mut dice_events: EventReader<DiceSettled>, // Sic bo has no RollSettled
+    bets: Res<TableBets>,
+    mut last: ResMut<LastBets>,
+) {
+    // Read both, so neither is left over for the next frame
+    let rolled = settled_events.read().count() + dice_events.read().count() > 0;
+    if rolled && !bets.bets.is_empty() {
+        last.0 = bets.clone();
+    }
+}
+
+// System that puts the last roll's bets back down when the button (or B) is pressed
+fn same_bets_system(
+    input: Res<PlayerInput>,
+    phase: Res<RoundPhase>,
+    rules: Res<TableRules>,
+    window: Res<BettingWindow>,
+    registry: Res<BetRegistry>,
+    last: Res<LastBets>,
+    mut bets: ResMut<TableBets>,
+    mut bankroll: ResMut<Bankroll>,
+    mut button_q: Query<
+        (Ref<Interaction>, &mut BackgroundColor, &mut Visibility),
+        With<SameBetsButton>,
+    >,
+    mut placed_events: EventWriter<BetPlaced>,
+    mut rejected_events: EventWriter<BetRejected>,
+    hotseat: Option<Res<Hotseat>>, // Each player has their own bets - and the last were someone else's
+    playback: Option<Res<SessionPlayback>>,
+) {
+    let Ok((interaction, mut color, mut visibility)) = button_q.single_mut() else {
+        return;
+    };
+    let offered = !last.0.bets.is_empty() && hotseat.is_none() && playback.is_none();
+    visibility.set_if_neq(if offered {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    });
+    if interaction.is_changed() {
+        color.0 = match *interaction {
+            Interaction::Pressed => Color::srgb(0.3, 0.3, 0.3),
+            Interaction::Hovered => Color::srgb(0.2, 0.2, 0.2),
+            Interaction::None => BUTTON_COLOR,
+        };
+    }
+    let clicked = interaction.is_changed() && *interaction == Interaction::Pressed;
+    if !offered || !(clicked || input.repeat_bets) {
+        return;
+    }
+
+    let placed = match window.check() {
+        Err(error) => Err((last.0.bets[0], error)),
+        Ok(()) => {
+            let pattern = registry.open_part(&last.0.bets, *phase);
+            bets.buy_in_pattern(&pattern, *phase, &rules, &mut bankroll)
+        }
+    };
+    match placed {
+        Ok(placed) => {
+            for bet in placed {
+                info!(
+                    "Placed {} on {:?} again",
+                    format_money(bet.amount),
+                    bet.kind
+                );
+                placed_events.write(BetPlaced {
+                    kind: bet.kind,
+                    amount: bet.amount,
+                });
+            }
+        }
+        Err((bet, error)) => {
+            info!("Can't put the same bets down: {:?} {error}", bet.kind);
+            rejected_events.write(BetRejected {
+                kind: bet.kind,
+                amount: bet.amount,
+                error,
+            });
+        }
+    }
+}
+
+// System that keeps the layout as a preset on Ctrl and a number key, and puts a preset
+// down on Shift and the number
+fn preset_system(
+    input: Res<PlayerInput>,
+    phase: Res<RoundPhase>,
+    rules: Res<TableRules>,
+    window: Res<BettingWindow>,
+    registry: Res<BetRegistry>,
+    locale: Res<Locale>,
+    mut settings: ResMut<Settings>, // The presets are saved with the rest of the settings
+    mut bets: ResMut<TableBets>,
+    mut bankroll: ResMut<Bankroll>,
+    mut notice_q: Query<(&mut Text, &mut PresetNotice)>,
+    mut placed_events: EventWriter<BetPlaced>,
+    mut rejected_events: EventWriter<BetRejected>,
+    playback: Option<Res<SessionPlayback>>,
+) {
+    let Ok((mut text, mut notice)) = notice_q.single_mut() else {
+        return;
+    };
+    if playback.is_some() {
+        return;
+    }
+    let mut say = |words: String| {
+        text.0 = words;
+        notice.age = 0.0;
+    };
+
+    if let Some(number) = input.save_preset {
+        // Only the bets a player puts down themselves - come bets on their numbers
+        // travelled there
+        let pattern: Vec<Bet> = bets
+            .bets
+            .iter()
+            .filter(|bet| {
+                registry.check_open(bet.kind, *phase).is_ok()
+                    && bets.check_open(bet.kind, *phase, &rules).is_ok()
+            })
+            .copied()
+            .collect();
+        if pattern.is_empty() {
+            settings.bet_presets.remove(&number);
+            say(locale.fill("Preset {number} cleared", &[("number", &number)]));
+        } else {
+            say(locale.fill(
+                "Preset {number} saved: {bets}",
+                &[
+                    ("number", &number),
+                    ("bets", &describe_pattern(&pattern, &registry, &locale)),
+                ],
+            ));
+            settings.bet_presets.insert(number, pattern);
+        }
+    }
+
+    let Some(number) = input.place_preset else {
+        return;
+    };
+    let Some(pattern) = settings.bet_presets.get(&number) else {
+        say(locale.fill(
+            "No preset {number} yet - Ctrl+{number} keeps the bets on the layout as one",
+            &[("number", &number)],
+        ));
+        return;
+    };
+    let placed = match window.check() {
+        Err(error) => Err((pattern[0], error)),
+        Ok(()) => {
+            let open = registry.open_part(pattern, *phase);
+            bets.buy_in_pattern(&open, *phase, &rules, &mut bankroll)
+        }
+    };
+    match placed {
+        Ok(placed) if placed.is_empty() => {
+            // Already down, or none of it can be bet at this point in the round
+            let first = pattern[0];
+            let error = registry
+                .check_open(first.kind, *phase)
+                .and_then(|()| bets.check_open(first.kind, *phase, &rules))
+                .err()
+                .unwrap_or(BetError::WrongPhase);
+            rejected_events.write(BetRejected {
+                kind: first.kind,
+                amount: first.amount,
+                error,
+            });
+        }
+        Ok(placed) => {
+            say(locale.fill(
+                "Preset {number}: {bets}",
+                &[
+                    ("number", &number),
+                    ("bets", &describe_pattern(&placed, &registry, &locale)),
+                ],
+            ));
+            for bet in placed {
+                placed_events.write(BetPlaced {
+                    kind: bet.kind,
+                    amount: bet.amount,
+                });
+            }
+        }
+        Err((bet, error)) => {
+            info!("Can't put preset {number} down: {:?} {error}", bet.kind);
+            rejected_events.write(BetRejected {
+                kind: bet.kind,
+                amount: bet.amount,
+                error,
+            });
+        }
+    }
+}
+
+// System that shows the preset line for a moment, then hides it
+fn preset_notice_system(
+    time: Res<Time>,
+    mut notice_q: Query<(&mut PresetNotice, &mut Visibility)>,
+) {
+    for (mut notice, mut visibility) in &mut notice_q {
+        notice.age += time.delta_secs();
+        visibility.set_if_neq(if notice.age < NOTICE_SECONDS {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
@@ -0,0 +1,200 @@
+// The rail in front of the player: a padded armrest along the top of the near wall,
+// and the chip rack beside it holding the player's bankroll as real chips - a pile for
+// each kind they have (see `rack_chips` in payout.rs), a full pile more once one
+// grows past twenty. The rack is built again whenever the bankroll changes, so it
+// empties as bets go down and fills as the dealer pays. A line along the bottom of the
+// screen counts what's in it.
+use bevy::color::prelude::*;
+use bevy::prelude::*;
+
+use super::betting::{CHIP_RADIUS, ChipAssets, MAX_CHIPS_SHOWN, spawn_chips};
+use super::locale::Localized;
+use super::table::{TableChoice, TableConfig};
+use super::{AppState, InGame, redraw};
+use crate::layout::TableLayout;
+use crate::locale::Locale;
+use crate::payout::{Bankroll, Cents, format_money, rack_chips};
+
+pub struct RailPlugin;
+
+impl Plugin for RailPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(InGame),
+            (setup_rail, setup_rack_readout, redraw::<Bankroll>),
+        )
+        .add_systems(
+            Update,
+            (rack_system, rack_readout_system).run_if(in_state(AppState::Playing)),
+        );
+    }
+}
+
+// One pile of chips in the rack, cleared away when the rack is built again
+#[derive(Component)]
+struct RackPile;
+
+#[derive(Component)]
+struct RackText; // The count of the rack's chips along the bottom of the screen
+
+const PAD_RADIUS: f32 = 0.09; // The padded armrest is a long round cushion
+const TRAY_HEIGHT: f32 = 0.02;
+const RACK_COLUMNS: usize = 5; // Piles of one kind of chip, at most, before the rest are left out
+const PILE_SPACING: f32 = CHIP_RADIUS * 2.1; // Between the piles of one kind of chip
+const KIND_SPACING: f32 = CHIP_RADIUS * 1.5; // The extra gap between kinds of chip
+
+// Build the armrest and the rack's tray on top of the near wall
+fn setup_rail(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    table_layout: Res<TableLayout>,
+    choice: Res<TableChoice>,
+    configs: Res<Assets<TableConfig>>,
+) {
+    let config = choice.current(&configs);
+    let rail = config.rail_top(table_layout.size, -1.0);
+    let length = table_layout.size.x + config.wall_thickness * 2.0;
+
+    // The armrest runs along the outside edge of the wall, where the player leans,
+    // leaving the inside of the wall's top for the rack
+    let pad_at = rail - Vec3::Z * (config.wall_thickness / 2.0 + PAD_RADIUS / 2.0);
+    commands.spawn((
+        StateScoped(InGame),
+        Mesh3d(meshes.add(Capsule3d::new(PAD_RADIUS, length))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: Color::srgb(0.1, 0.07, 0.05), // Dark leather
+            perceptual_roughness: 0.7,
+            ..default()
+        })),
+        // A capsule stands up - laid on its side, along the wall
+        Transform::from_translation(pad_at)
+            .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2)),
+        Name::new("Player's rail"),
+    ));
+
+    // The rack: a wooden tray along the middle of the wall's top, for the chips
+    commands.spawn((
+        StateScoped(InGame),
+        Mesh3d(meshes.add(Cuboid::new(
+            table_layout.size.x * 0.75,
+            TRAY_HEIGHT,
+            config.wall_thickness * 0.6,
+        ))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: Color::srgb(0.23, 0.15, 0.09),
+            perceptual_roughness: 0.5,
+            ..default()
+        })),
+        Transform::from_translation(rail + Vec3::Y * TRAY_HEIGHT / 2.0),
+        Name::new("Chip rack"),
+    ));
+}
+
+// System that fills the rack with the bankroll, in piles by kind of chip from the
+// biggest on the left, whenever the bankroll changes
+fn rack_system(
+    mut commands: Commands,
+    bankroll: Res<Bankroll>,
+    table_layout: Res<TableLayout>,
+    chip_assets: Res<ChipAssets>,
+    choice: Res<TableChoice>,
+    configs: Res<Assets<TableConfig>>,
+    piles_q: Query<Entity, With<RackPile>>,
+) {
+    if !bankroll.is_changed() && !table_layout.is_changed() {
+        return;
+    }
+    for pile in &piles_q {
+        commands.entity(pile).despawn();
+    }
+
+    // Each kind of chip in as many full piles as it takes, and what's left on top
+    let piles: Vec<Vec<Vec<Cents>>> = rack_chips(bankroll.balance)
+        .into_iter()
+        .map(|(value, count)| {
+            vec![value; count]
+                .chunks(MAX_CHIPS_SHOWN)
+                .take(RACK_COLUMNS)
+                .map(<[Cents]>::to_vec)
+                .collect()
+        })
+        .collect();
+    let columns: usize = piles.iter().map(Vec::len).sum();
+    let width = columns.saturating_sub(1) as f32 * PILE_SPACING
+        + piles.len().saturating_sub(1) as f32 * KIND_SPACING;
+
+    let config = choice.current(&configs);
+    let tray_top = config.rail_top(table_layout.size, -1.0) + Vec3::Y * TRAY_HEIGHT;
+    let mut x = -width / 2.0;
+    for kind in piles {
+        for chips in kind {
+            let pile = spawn_chips(&mut commands, &chip_assets, &chips, tray_top + Vec3::X * x);
+            commands.entity(pile).insert(RackPile);
+            x += PILE_SPACING;
+        }
+        x += KIND_SPACING; // A gap before the next kind
+    }
+}
+
+fn setup_rack_readout(mut commands: Commands) {
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(4.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center, // Across the middle of the rail
+                column_gap: Val::Px(6.0),
+                ..default()
+            },
+        ))
+        .with_children(|row| {
+            row.spawn((
+                Localized("In the rack:"),
+                TextFont {
+                    font_size: 13.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.7, 0.7, 0.7)),
+            ));
+            row.spawn((
+                Text::new(""), // Filled in by rack_readout_system
+                TextFont {
+                    font_size: 13.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                RackText,
+            ));
+        });
+}
+
+// System that counts the rack's chips on screen: "6 x $100  13 x $25  13 x $5"
+fn rack_readout_system(
+    bankroll: Res<Bankroll>,
+    locale: Res<Locale>,
+    mut text_q: Query<&mut Text, With<RackText>>,
+) {
+    if !bankroll.is_changed() && !locale.is_changed() {
+        return;
+    }
+    let Ok(mut text) = text_q.single_mut() else {
+        return;
+    };
+    let kinds: Vec<String> = rack_chips(bankroll.balance)
+        .into_iter()
+        .map(|(value, count)| {
+            locale.fill(
+                "{count} x {chip}",
+                &[("count", &count), ("chip", &format_money(value))],
+            )
+        })
+        .collect();
+    text.0 = if kinds.is_empty() {
+        locale.tr("empty").to_string()
+    } else {
+        kinds.join("  ")
+    };
+}
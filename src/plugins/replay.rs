@@ -0,0 +1,239 @@
+// Instant replay. Every frame of a throw, the dice's positions are written down; once
+// they settle, the replay button (or R) plays the roll back at quarter speed from a
+// low camera beside where the dice came to rest. The dice's physics is switched off
+// during the replay so Rapier doesn't fight the recording.
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use super::camera::PlayerCamera;
+use super::controls::PlayerInput;
+use super::dice::{Dice, DiceSettled, RollState};
+use super::{AppState, InGame, RollStep};
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InstantReplay>()
+            .add_event::<ReplayRequested>() // Sent by the replay button
+            .add_systems(OnEnter(InGame), forget_replay)
+            .add_systems(
+                Update,
+                (
+                    // Record after Settle, so the frame the dice stop is kept too
+                    record_roll_system.after(RollStep::Settle),
+                    replay_playback_system.after(record_roll_system),
+                )
+                    .run_if(in_state(AppState::Playing)),
+            );
+    }
+}
+
+// The dice are new each time the table is set up, so an old recording can't be shown
+fn forget_replay(mut replay: ResMut<InstantReplay>) {
+    *replay = InstantReplay::default();
+}
+
+// Ask for the last roll to be replayed
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ReplayRequested;
+
+// Where one die was at one moment of the throw
+#[derive(Debug, Clone, Copy)]
+pub struct DiePose {
+    pub die: Entity,
+    pub translation: Vec3,
+    pub rotation: Quat,
+}
+
+// Both dice at one moment of the throw
+#[derive(Debug, Clone)]
+pub struct ReplayFrame {
+    pub time: f32, // Seconds since the dice left the hand
+    pub dice: Vec<DiePose>,
+}
+
+// The recording of the last throw, and the replay of it if one is playing
+#[derive(Resource, Debug, Default)]
+pub struct InstantReplay {
+    frames: Vec<ReplayFrame>,
+    recording: bool,           // A throw is being written down right now
+    ready: bool,               // The last throw settled, so there's something to show
+    playing: Option<f32>,      // How far into the recording the replay has got, in seconds
+    camera_spot: Option<Vec3>, // Where the replay camera stands
+}
+
+const REPLAY_SPEED: f32 = 0.25; // Quarter speed
+const MAX_RECORDED_FRAMES: usize = 60 * 30; // About half a minute at 60 frames a second
+// The replay camera crouches low beside where the dice stopped, off to the side
+const REPLAY_CAMERA_OFFSET: Vec3 = Vec3::new(0.8, 0.45, -1.6);
+
+impl InstantReplay {
+    pub fn is_playing(&self) -> bool {
+        self.playing.is_some()
+    }
+
+    // Is there a settled roll to replay?
+    pub fn is_ready(&self) -> bool {
+        self.ready && !self.recording
+    }
+
+    // The last throw, frame by frame (empty until a throw has been made)
+    pub fn recording(&self) -> &[ReplayFrame] {
+        &self.frames
+    }
+
+    fn duration(&self) -> f32 {
+        self.frames.last().map_or(0.0, |frame| frame.time)
+    }
+
+    // Where every die was `time` seconds into the recording, blending between the two
+    // recorded frames either side of it
+    fn poses_at(&self, time: f32) -> Vec<DiePose> {
+        // The first frame recorded after `time`
+        let after = self.frames.partition_point(|frame| frame.time < time);
+        let (Some(end), Some(start)) = (
+            self.frames
+                .get(after.min(self.frames.len().saturating_sub(1))),
+            self.frames.get(after.saturating_sub(1)),
+        ) else {
+            return Vec::new();
+        };
+        let span = end.time - start.time;
+        let t = if span > 0.0 {
+            ((time - start.time) / span).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        start
+            .dice
+            .iter()
+            .filter_map(|from| {
+                let to = end.dice.iter().find(|pose| pose.die == from.die)?;
+                Some(DiePose {
+                    die: from.die,
+                    translation: from.translation.lerp(to.translation, t),
+                    rotation: from.rotation.slerp(to.rotation, t),
+                })
+            })
+            .collect()
+    }
+}
+
+// System that writes down where the dice are every frame of a throw
+pub(crate) fn record_roll_system(
+    time: Res<Time>,
+    roll: Res<RollState>,
+    mut settled_events: EventReader<DiceSettled>, // Any throw, craps or sic bo
+    mut replay: ResMut<InstantReplay>,
+    dice_q: Query<(Entity, &Transform, &Visibility), With<Dice>>,
+) {
+    if replay.is_playing() {
+        return; // Don't record the replay itself
+    }
+    let settled = settled_events.read().count() > 0;
+
+    if roll.in_flight() && !replay.recording {
+        // A new throw - forget the last one
+        replay.frames.clear();
+        replay.recording = true;
+        replay.ready = false;
+    }
+    if !replay.recording {
+        return;
+    }
+
+    let time_in_throw = replay
+        .frames
+        .last()
+        .map_or(0.0, |frame| frame.time + time.delta_secs());
+    if replay.frames.len() < MAX_RECORDED_FRAMES {
+        let dice = dice_q
+            .iter()
+            .filter(|(_, _, visibility)| **visibility != Visibility::Hidden)
+            .map(|(die, transform, _)| DiePose {
+                die,
+                translation: transform.translation,
+                rotation: transform.rotation,
+            })
+            .collect();
+        replay.frames.push(ReplayFrame {
+            time: time_in_throw,
+            dice,
+        });
+    }
+
+    if !roll.in_flight() {
+        // The throw is over. Only a roll that settled is worth replaying - after a
+        // die leaves the table there's nothing left to look at.
+        replay.recording = false;
+        replay.ready = settled;
+    }
+}
+
+// System that starts a replay when asked, moves the dice along the recording while it
+// plays, and hands everything back at the end
+fn replay_playback_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    input: Res<PlayerInput>,
+    roll: Res<RollState>,
+    mut requests: EventReader<ReplayRequested>,
+    mut replay: ResMut<InstantReplay>,
+    mut dice_q: Query<(Entity, &mut Transform), (With<Dice>, Without<PlayerCamera>)>,
+    mut cam_q: Query<(&mut PlayerCamera, &mut Transform)>,
+) {
+    let asked = requests.read().count() > 0 || input.instant_replay;
+    if asked && replay.is_ready() && !replay.is_playing() && !roll.in_flight() {
+        info!("Instant replay");
+        // Freeze the dice's physics: the recording moves them now
+        for (die, _) in &dice_q {
+            commands.entity(die).insert(RigidBodyDisabled);
+        }
+        let resting = replay.frames.last().map(|frame| middle_of(&frame.dice));
+        replay.camera_spot = resting.map(|middle| middle + REPLAY_CAMERA_OFFSET);
+        replay.playing = Some(0.0);
+        if let Ok((mut cam, transform)) = cam_q.single_mut() {
+            cam.take_over(&transform);
+        }
+    }
+
+    let Some(played) = replay.playing else {
+        return;
+    };
+    let played = (played + time.delta_secs() * REPLAY_SPEED).min(replay.duration());
+    let poses = replay.poses_at(played);
+    for pose in &poses {
+        if let Ok((_, mut transform)) = dice_q.get_mut(pose.die) {
+            transform.translation = pose.translation;
+            transform.rotation = pose.rotation;
+        }
+    }
+    // The camera stays put and turns to keep the dice in the middle of the picture
+    if let (Ok((mut cam, mut transform)), Some(spot)) = (cam_q.single_mut(), replay.camera_spot) {
+        if !poses.is_empty() {
+            cam.point(&mut transform, spot, middle_of(&poses));
+        }
+    }
+
+    if played < replay.duration() {
+        replay.playing = Some(played);
+        return;
+    }
+    // Finished: the dice are back where they came to rest, so give them back to Rapier
+    replay.playing = None;
+    for (die, _) in &dice_q {
+        commands
+            .entity(die)
+            .remove::<RigidBodyDisabled>()
+            .insert(Velocity::zero());
+    }
+    if let Ok((mut cam, mut transform)) = cam_q.single_mut() {
+        cam.release(&mut transform);
+    }
+}
+
+// The point halfway between the dice
+fn middle_of(poses: &[DiePose]) -> Vec3 {
+    poses.iter().map(|pose| pose.translation).sum::<Vec3>() / poses.len().max(1) as f32
+}
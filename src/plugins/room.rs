@@ -0,0 +1,262 @@
+// The casino room round the table: a carpeted floor, panelled walls, a ceiling hung
+// with lights and a pedestal for the table to stand on, so it isn't floating in a void.
+// None of it has a collider - a die that leaves the table is still caught by the
+// off-table check (see dice.rs), not the carpet.
+//
+// The polished rails need something to reflect, so the camera also gets an environment
+// map: a small cube picture of the room, painted here pixel by pixel rather than loaded
+// from a file - dark ceiling with bright lamps, burgundy walls with a brass band, red
+// carpet. It's blurry and simple, but the reflections in the rails are of a room now.
+use bevy::asset::RenderAssetUsages;
+use bevy::pbr::NotShadowCaster;
+use bevy::prelude::*;
+use bevy::render::render_resource::{
+    Extent3d, TextureDimension, TextureFormat, TextureViewDescriptor, TextureViewDimension,
+};
+
+use super::InGame;
+use super::camera::PlayerCamera;
+use crate::layout::TableLayout;
+
+pub struct RoomPlugin;
+
+impl Plugin for RoomPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_room_map)
+            .add_systems(OnEnter(InGame), setup_room)
+            // The camera is spawned with the table, so it's given the map as it appears
+            .add_systems(Update, environment_map_system);
+    }
+}
+
+// The cube picture of the room, reflected in everything shiny
+#[derive(Resource)]
+struct RoomMap(Handle<Image>);
+
+pub const FLOOR_Y: f32 = -2.2; // The felt is at 0, about waist height
+const CEILING_Y: f32 = 12.0; // Over the overhead camera view
+const ROOM_HALF_SIZE: Vec2 = Vec2::new(16.0, 12.0); // Half the room's width (x) and depth (z)
+const BAND_Y: f32 = 2.5; // The brass band round the walls
+// Where the ceiling lamps hang (x and z): two rows of three, over and round the table
+const LAMPS: [Vec2; 6] = [
+    Vec2::new(-8.0, -5.0),
+    Vec2::new(0.0, -5.0),
+    Vec2::new(8.0, -5.0),
+    Vec2::new(-8.0, 5.0),
+    Vec2::new(0.0, 5.0),
+    Vec2::new(8.0, 5.0),
+];
+const CARPET_COLOR: &str = "#3A0A12";
+const WALL_COLOR: &str = "#3D1518";
+const BRASS_COLOR: &str = "#B08A3A";
+const CEILING_COLOR: &str = "#16110E";
+const PEDESTAL_COLOR: &str = "#1E120A"; // Dark wood
+const LAMP_COLOR: LinearRgba = LinearRgba::rgb(12.0, 9.0, 6.0); // Warm, and bright enough to bloom
+const LAMP_INTENSITY: f32 = 150_000.0; // Lumens - a bright stage lamp each
+const MAP_SIZE: u32 = 32; // Pixels along each side of each face of the cube
+const MAP_INTENSITY: f32 = 1500.0; // How brightly the room lights what reflects it
+
+fn hex(color: &str) -> Color {
+    Srgba::hex(color).unwrap_or(Srgba::WHITE).into()
+}
+
+fn setup_room(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    table_layout: Res<TableLayout>,
+) {
+    let matte = |color: &str| StandardMaterial {
+        base_color: hex(color),
+        perceptual_roughness: 0.9,
+        ..default()
+    };
+    let room_height = CEILING_Y - FLOOR_Y;
+
+    // The floor and ceiling, each facing into the room
+    commands.spawn((
+        StateScoped(InGame),
+        Mesh3d(meshes.add(Plane3d::new(Vec3::Y, ROOM_HALF_SIZE))),
+        MeshMaterial3d(materials.add(matte(CARPET_COLOR))),
+        Transform::from_xyz(0.0, FLOOR_Y, 0.0),
+    ));
+    commands.spawn((
+        StateScoped(InGame),
+        Mesh3d(meshes.add(Plane3d::new(Vec3::NEG_Y, ROOM_HALF_SIZE))),
+        MeshMaterial3d(materials.add(matte(CEILING_COLOR))),
+        Transform::from_xyz(0.0, CEILING_Y, 0.0),
+        NotShadowCaster, // Or it would shade the whole room from the sun
+    ));
+
+    // The walls, with a brass band round them at about head height standing just
+    // proud of them
+    let wall_material = materials.add(matte(WALL_COLOR));
+    let brass = materials.add(StandardMaterial {
+        base_color: hex(BRASS_COLOR),
+        metallic: 1.0,
+        perceptual_roughness: 0.4,
+        ..default()
+    });
+    let (half_x, half_z) = (ROOM_HALF_SIZE.x, ROOM_HALF_SIZE.y);
+    // Where each wall stands and how long it is (its height is filled in below)
+    let walls = [
+        (
+            Vec3::new(-half_x, 0.0, 0.0),
+            Vec3::new(0.1, 0.0, half_z * 2.0),
+        ),
+        (
+            Vec3::new(half_x, 0.0, 0.0),
+            Vec3::new(0.1, 0.0, half_z * 2.0),
+        ),
+        (
+            Vec3::new(0.0, 0.0, -half_z),
+            Vec3::new(half_x * 2.0, 0.0, 0.1),
+        ),
+        (
+            Vec3::new(0.0, 0.0, half_z),
+            Vec3::new(half_x * 2.0, 0.0, 0.1),
+        ),
+    ];
+    for (at, size) in walls {
+        commands.spawn((
+            StateScoped(InGame),
+            Mesh3d(meshes.add(Cuboid::from_size(size.with_y(room_height)))),
+            MeshMaterial3d(wall_material.clone()),
+            Transform::from_translation(at.with_y(FLOOR_Y + room_height / 2.0)),
+            NotShadowCaster,
+        ));
+        commands.spawn((
+            StateScoped(InGame),
+            Mesh3d(meshes.add(Cuboid::from_size(size.with_y(0.16) + Vec3::splat(0.02)))),
+            MeshMaterial3d(brass.clone()),
+            Transform::from_translation(at.with_y(BAND_Y)),
+            NotShadowCaster,
+        ));
+    }
+
+    // The table's pedestal, from the carpet up to just under the felt
+    let footprint = table_layout.size * Vec2::new(0.7, 0.6);
+    let pedestal_height = -FLOOR_Y - 0.06;
+    commands.spawn((
+        StateScoped(InGame),
+        Mesh3d(meshes.add(Cuboid::new(footprint.x, pedestal_height, footprint.y))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: hex(PEDESTAL_COLOR),
+            perceptual_roughness: 0.6,
+            ..default()
+        })),
+        Transform::from_xyz(0.0, FLOOR_Y + pedestal_height / 2.0, 0.0),
+    ));
+
+    // The lamps on the ceiling, each a glowing disc with a light under it. They don't
+    // cast shadows - the sun over the table does that.
+    let lamp_mesh = meshes.add(Cylinder::new(0.6, 0.1));
+    let lamp_material = materials.add(StandardMaterial {
+        base_color: Color::WHITE,
+        emissive: LAMP_COLOR,
+        ..default()
+    });
+    for lamp in LAMPS {
+        commands.spawn((
+            StateScoped(InGame),
+            Mesh3d(lamp_mesh.clone()),
+            MeshMaterial3d(lamp_material.clone()),
+            Transform::from_xyz(lamp.x, CEILING_Y - 0.05, lamp.y),
+            NotShadowCaster,
+        ));
+        commands.spawn((
+            StateScoped(InGame),
+            PointLight {
+                color: Color::srgb(1.0, 0.85, 0.7),
+                intensity: LAMP_INTENSITY,
+                range: 30.0,
+                shadows_enabled: false,
+                ..default()
+            },
+            Transform::from_xyz(lamp.x, CEILING_Y - 0.5, lamp.y),
+        ));
+    }
+}
+
+// Paint the room's cube picture once, at startup
+fn setup_room_map(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let face_pixels = (MAP_SIZE * MAP_SIZE * 4) as usize;
+    let mut pixels = Vec::with_capacity(face_pixels * 6);
+    // The cube's faces in the order the graphics card expects them (+x, -x, +y, -y, +z,
+    // -z), each turning a spot on the face (u right, v down, both -1 to 1) into the
+    // direction it looks in
+    let faces: [fn(f32, f32) -> Vec3; 6] = [
+        |u, v| Vec3::new(1.0, -v, -u),
+        |u, v| Vec3::new(-1.0, -v, u),
+        |u, v| Vec3::new(u, 1.0, v),
+        |u, v| Vec3::new(u, -1.0, -v),
+        |u, v| Vec3::new(u, -v, 1.0),
+        |u, v| Vec3::new(-u, -v, -1.0),
+    ];
+    for face in faces {
+        for y in 0..MAP_SIZE {
+            for x in 0..MAP_SIZE {
+                let u = (x as f32 + 0.5) / MAP_SIZE as f32 * 2.0 - 1.0;
+                let v = (y as f32 + 0.5) / MAP_SIZE as f32 * 2.0 - 1.0;
+                let color = room_color(face(u, v).normalize()).to_srgba();
+                pixels.extend_from_slice(&color.to_u8_array());
+            }
+        }
+    }
+    let mut image = Image::new(
+        Extent3d {
+            width: MAP_SIZE,
+            height: MAP_SIZE,
+            depth_or_array_layers: 6,
+        },
+        TextureDimension::D2,
+        pixels,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    // Six square layers read as the faces of a cube
+    image.texture_view_descriptor = Some(TextureViewDescriptor {
+        dimension: Some(TextureViewDimension::Cube),
+        ..default()
+    });
+    commands.insert_resource(RoomMap(images.add(image)));
+}
+
+// What the room looks like from the table in one direction
+fn room_color(direction: Vec3) -> Color {
+    if direction.y > 0.5 {
+        // The ceiling, with the lamps as bright spots: follow the direction up to the
+        // ceiling and see if it lands on one
+        let on_ceiling = (direction * CEILING_Y / direction.y).xz();
+        if LAMPS.iter().any(|lamp| lamp.distance(on_ceiling) < 1.0) {
+            Color::srgb(1.0, 0.92, 0.8)
+        } else {
+            hex(CEILING_COLOR)
+        }
+    } else if direction.y < -0.3 {
+        hex(CARPET_COLOR)
+    } else if (0.12..0.2).contains(&direction.y) {
+        hex(BRASS_COLOR) // The band, seen from the middle of the room
+    } else {
+        hex(WALL_COLOR)
+    }
+}
+
+// System that gives the camera the room's reflections as it's spawned
+fn environment_map_system(
+    mut commands: Commands,
+    map: Option<Res<RoomMap>>,
+    cameras_q: Query<Entity, Added<PlayerCamera>>,
+) {
+    let Some(map) = map else {
+        return;
+    };
+    for camera in &cameras_q {
+        commands.entity(camera).insert(EnvironmentMapLight {
+            diffuse_map: map.0.clone(),
+            specular_map: map.0.clone(),
+            intensity: MAP_INTENSITY,
+            ..default()
+        });
+    }
+}
@@ -0,0 +1,130 @@
+// The house rules the table deals by, read from a file in assets/rules/: `--rules
+// downtown` picks assets/rules/downtown.rules.ron, and without it the table deals by
+// standard.rules.ron. `--crapless` and `--odds` change the file's rules on top, and
+// the daily challenge has the dice drawn from its seed. In a `--features dev` build,
+// saving the file changes the rules while the game runs, so a casino's rule set can be
+// tried out without a restart.
+//
+// A replayed session and a table joined over the network play by the rules they bring
+// with them, so the file is left alone for those.
+use bevy::prelude::*;
+
+use super::menu::LoadingAssets;
+use super::net::NetClient;
+use super::session::SessionPlayback;
+use crate::ron_asset::RonLoader;
+use crate::rules::{OddsLimit, RollMode, TableRules, Variant};
+
+pub struct RulesPlugin;
+
+impl Plugin for RulesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<TableRules>() // House rules files, loaded from assets/rules/
+            .register_asset_loader(RonLoader::<TableRules>::new(&["rules.ron"]))
+            .init_resource::<RulesChoice>(); // Keeps any choice main.rs inserted
+
+        // main.rs inserts these before the plugins are added
+        let world = app.world();
+        if world.contains_resource::<SessionPlayback>() || world.contains_resource::<NetClient>() {
+            app.init_resource::<TableRules>(); // Replaced by the session's or the host's
+            return;
+        }
+
+        // Until the file is in, deal by the built-in rules with the command line's changes
+        let rules = app
+            .world()
+            .resource::<RulesChoice>()
+            .apply(TableRules::default());
+        app.insert_resource(rules)
+            .init_resource::<RulesFile>()
+            .add_systems(Startup, wait_for_rules_file)
+            // The file usually arrives on the loading screen, so this always listens
+            .add_systems(Update, rules_file_system);
+    }
+}
+
+// Which rules file to deal by, and what the command line changes on top of it
+#[derive(Resource, Debug, Clone)]
+pub struct RulesChoice {
+    pub file: String,                // The name in assets/rules/, without ".rules.ron"
+    pub variant: Option<Variant>,    // `--crapless`
+    pub odds: Option<OddsLimit>,     // `--odds`
+    pub roll_mode: Option<RollMode>, // `--daily` draws every roll from the seed
+}
+
+impl Default for RulesChoice {
+    fn default() -> Self {
+        Self {
+            file: "standard".to_string(),
+            variant: None,
+            odds: None,
+            roll_mode: None,
+        }
+    }
+}
+
+impl RulesChoice {
+    // The daily challenge's rules: the standard table, with the dice drawn from the
+    // day's seed so everyone gets the same rolls (see daily.rs)
+    pub fn daily() -> Self {
+        Self {
+            roll_mode: Some(RollMode::RngAuthoritative),
+            ..default()
+        }
+    }
+
+    pub fn path(&self) -> String {
+        format!("rules/{}.rules.ron", self.file)
+    }
+
+    // The rules from the file, with the command line's changes made
+    pub fn apply(&self, mut rules: TableRules) -> TableRules {
+        if let Some(variant) = self.variant {
+            rules.variant = variant;
+        }
+        if let Some(odds) = self.odds {
+            rules.odds = odds;
+        }
+        if let Some(roll_mode) = self.roll_mode {
+            rules.roll_mode = roll_mode;
+        }
+        rules
+    }
+}
+
+// The rules file the table is using
+#[derive(Resource)]
+struct RulesFile(Handle<TableRules>);
+
+impl FromWorld for RulesFile {
+    fn from_world(world: &mut World) -> Self {
+        let path = world.resource::<RulesChoice>().path();
+        Self(world.resource::<AssetServer>().load(path))
+    }
+}
+
+fn wait_for_rules_file(file: Res<RulesFile>, mut loading: ResMut<LoadingAssets>) {
+    loading.add(&file.0);
+}
+
+// System that takes up the rules file when it loads, and again each time it's saved.
+// Bets already on the table stay where they are and are settled by the new rules. The
+// session being recorded keeps the rules it started with.
+fn rules_file_system(
+    file: Res<RulesFile>,
+    files: Res<Assets<TableRules>>,
+    mut file_events: EventReader<AssetEvent<TableRules>>,
+    choice: Res<RulesChoice>,
+    mut rules: ResMut<TableRules>,
+) {
+    let file_changed = file_events
+        .read()
+        .any(|event| event.is_loaded_with_dependencies(&file.0) || event.is_modified(&file.0));
+    if !file_changed {
+        return;
+    }
+    if let Some(loaded) = files.get(&file.0) {
+        info!("House rules loaded from {}", choice.path());
+        *rules = choice.apply(loaded.clone());
+    }
+}
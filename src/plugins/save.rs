@@ -0,0 +1,173 @@
+// Carrying the player's game over from one launch to the next. The bankroll, a few
+// all-time statistics and the table settings (chip, dice skin, follow camera) are
+// saved when the game closes - and after every roll, since a browser tab can be closed
+// without warning - then loaded back when it starts. The player's settings keep their
+// own file (see settings.rs), as they're saved the moment a slider is let go.
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::betting::{RollResolved, SelectedChip};
+use super::camera::CameraSettings;
+use super::dice::DiceSkin;
+use super::hotseat::Hotseat;
+use super::net::NetClient;
+use super::session::SessionPlayback;
+use super::sicbo::SicBoResolved;
+use super::{AppState, RollStep};
+use crate::betting::TableBets;
+use crate::daily::DailyChallenge;
+use crate::payout::{Bankroll, CHIP_DENOMINATIONS, Cents};
+use crate::rules::{RollOutcome, RoundPhase};
+use crate::storage;
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        // A replayed session plays with its own money, at someone else's networked
+        // table the host keeps our money, a hotseat table has several players' money,
+        // and the daily challenge starts everyone on the same stack - none of them
+        // should touch the save
+        if app.world().contains_resource::<SessionPlayback>()
+            || app.world().contains_resource::<NetClient>()
+            || app.world().contains_resource::<Hotseat>()
+            || app.world().contains_resource::<DailyChallenge>()
+        {
+            return;
+        }
+
+        // Loaded straight away rather than in a startup system, so everything that
+        // starts up (the session recorder, the UI) already sees the saved bankroll
+        let saved = SaveGame::load();
+        app.insert_resource(Bankroll {
+            balance: saved.balance,
+        })
+        .insert_resource(SelectedChip(saved.chip.min(CHIP_DENOMINATIONS.len() - 1)))
+        .insert_resource(CameraSettings {
+            follow_dice: saved.follow_dice,
+        })
+        .insert_resource(saved.stats);
+        // The skins file loads later; a skin that's since been removed wraps round
+        if let Some(mut skin) = app.world_mut().get_resource_mut::<DiceSkin>() {
+            skin.selected = saved.skin;
+        }
+
+        app.add_systems(
+            Update,
+            lifetime_stats_system
+                .after(RollStep::Pay) // Once the roll has been paid
+                .run_if(in_state(AppState::Playing)),
+        )
+        .add_systems(Last, save_game_system);
+    }
+}
+
+// Numbers kept across every session the player has ever played
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LifetimeStats {
+    pub rolls: u32,
+    pub points_made: u32,
+    pub seven_outs: u32,
+    pub best_balance: Cents, // The most the bankroll has ever held
+}
+
+// Everything in the save file. `#[serde(default)]` means a save from an older version
+// still loads, with anything it's missing set to the default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct SaveGame {
+    balance: Cents,
+    chip: usize, // The chip in hand, as an index into CHIP_DENOMINATIONS
+    skin: usize, // Which entry in the dice skins file
+    follow_dice: bool,
+    stats: LifetimeStats,
+}
+
+impl Default for SaveGame {
+    fn default() -> Self {
+        Self {
+            balance: Bankroll::default().balance,
+            chip: SelectedChip::default().0,
+            skin: 0,
+            follow_dice: CameraSettings::default().follow_dice,
+            stats: LifetimeStats::default(),
+        }
+    }
+}
+
+const SAVE_KEY: &str = "save_game";
+
+impl SaveGame {
+    fn load() -> Self {
+        let Some(saved) = storage::load(SAVE_KEY) else {
+            return Self::default(); // First launch
+        };
+        let mut save: Self = ron::from_str(&saved).unwrap_or_else(|error| {
+            warn!("Couldn't read the saved game, starting afresh: {error}");
+            Self::default()
+        });
+        // A player who went broke last time gets a fresh stack rather than a table
+        // they can't bet at
+        if save.balance < CHIP_DENOMINATIONS[CHIP_DENOMINATIONS.len() - 1] {
+            info!("Bankroll was empty - sitting down with a fresh stack");
+            save.balance = Bankroll::default().balance;
+        }
+        save
+    }
+}
+
+// System that keeps the all-time numbers up to date as rolls are decided
+fn lifetime_stats_system(
+    mut resolved_events: EventReader<RollResolved>,
+    bankroll: Res<Bankroll>,
+    mut stats: ResMut<LifetimeStats>,
+) {
+    for resolved in resolved_events.read() {
+        stats.rolls += 1;
+        match resolved.outcome {
+            RollOutcome::PointMade(_) => stats.points_made += 1,
+            RollOutcome::SevenOut => stats.seven_outs += 1,
+            _ => {}
+        }
+    }
+    if bankroll.balance > stats.best_balance {
+        stats.best_balance = bankroll.balance;
+    }
+}
+
+// System that writes the save file after each roll - a craps roll or a sic bo throw -
+// and when the game closes
+fn save_game_system(
+    mut resolved_events: EventReader<RollResolved>,
+    mut sic_bo_events: EventReader<SicBoResolved>,
+    mut exit_events: EventReader<AppExit>,
+    bankroll: Res<Bankroll>,
+    bets: Res<TableBets>,
+    phase: Res<RoundPhase>,
+    selected: Res<SelectedChip>,
+    skin: Res<DiceSkin>,
+    camera: Res<CameraSettings>,
+    stats: Res<LifetimeStats>,
+) {
+    let rolled = resolved_events.read().count() + sic_bo_events.read().count() > 0;
+    let exiting = exit_events.read().count() > 0;
+    if !rolled && !exiting {
+        return;
+    }
+    let save = SaveGame {
+        // Chips still on the layout go back in the player's pocket - except a contract
+        // bet, which can't be taken down and so is lost by walking away from it
+        balance: bankroll.balance + bets.cash_out(*phase),
+        chip: selected.0,
+        skin: skin.selected,
+        follow_dice: camera.follow_dice,
+        stats: stats.clone(),
+    };
+    let saved = ron::ser::to_string_pretty(&save, ron::ser::PrettyConfig::default())
+        .map_err(|error| error.to_string())
+        .and_then(|text| storage::save(SAVE_KEY, &text).map_err(|error| error.to_string()));
+    if let Err(error) = saved {
+        warn!("Couldn't save the game: {error}");
+    }
+}
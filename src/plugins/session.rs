@@ -0,0 +1,503 @@
+// Whole-session recording and playback. While you play, every bet and every throw is
+// written to a session log (see src/session.rs), saved after each roll. Start the game
+// with `--replay` and instead of a live table you get that session played back: the
+// dice follow the recorded throws and the bets go down (and come back off) as they did,
+// while the betting plugin settles the money exactly as it did live. A scrubber bar
+// along the bottom pauses the replay or jumps to any roll.
+use bevy::input::touch::Touches;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_rapier3d::prelude::*;
+
+use super::betting::{BetPlaced, BetTakenDown, BetUndone};
+use super::dice::{Dice, DiceId, RollSettled, ThrowRng};
+use super::hotseat::Hotseat;
+use super::lifecycle::{PhaseChanged, jump_phase};
+use super::locale::Localized;
+use super::replay::{InstantReplay, record_roll_system};
+use super::{AppState, InGame, RollStep, redraw};
+use crate::betting::{Bet, TableBets};
+use crate::locale::Locale;
+use crate::payout::Bankroll;
+use crate::rules::{RoundPhase, TableRules, Variant};
+use crate::session::{RecordedFrame, RecordedRoll, SessionEvent, SessionLog, TableState};
+use crate::storage;
+
+// The storage key the session being played is saved under (`session.ron` on the desktop)
+pub const SESSION_KEY: &str = "session";
+
+pub struct SessionPlugin;
+
+impl Plugin for SessionPlugin {
+    fn build(&self, app: &mut App) {
+        // A replay was asked for before the plugins were added (see main.rs): set the
+        // table up the way the recorded session started
+        let opening = app
+            .world()
+            .get_resource::<SessionPlayback>()
+            .map(|playback| playback.log.clone());
+        if let Some(log) = opening {
+            app.insert_resource(ThrowRng::new(log.seed))
+                .insert_resource(Bankroll {
+                    balance: log.starting_balance,
+                })
+                .insert_resource(log.rules);
+        }
+
+        app.add_systems(
+            OnEnter(InGame),
+            (start_recording, setup_scrubber, redraw::<SessionPlayback>),
+        )
+        .add_systems(
+            Update,
+            (
+                // After the instant replay has written down the last frame of the throw
+                record_session_system
+                    .after(record_roll_system)
+                    .run_if(resource_exists::<SessionRecorder>),
+                (
+                    scrubber_control_system,
+                    playback_system.before(RollStep::Settle),
+                    scrubber_display_system,
+                )
+                    .chain()
+                    .run_if(resource_exists::<SessionPlayback>),
+            )
+                .run_if(in_state(AppState::Playing)),
+        );
+    }
+}
+
+// The session being played live, written down as it happens
+#[derive(Resource)]
+struct SessionRecorder {
+    log: SessionLog,
+}
+
+// A recorded session being played back instead of a live game. Insert one before
+// adding the game's plugins to watch a replay.
+#[derive(Resource)]
+pub struct SessionPlayback {
+    log: SessionLog,
+    next: usize,                          // The next event in the log to play
+    rolling: Option<(RecordedRoll, f32)>, // The throw on screen and how far into it we are
+    wait: f32,                            // Seconds to pause before the next event
+    paused: bool,
+}
+
+impl SessionPlayback {
+    pub fn new(log: SessionLog) -> Self {
+        Self {
+            log,
+            next: 0,
+            rolling: None,
+            wait: START_PAUSE,
+            paused: false,
+        }
+    }
+
+    // How many rolls have been played back so far, out of how many
+    fn progress(&self) -> (usize, usize) {
+        let rolls = self.log.roll_indices();
+        let played = rolls.iter().filter(|&&index| index < self.next).count();
+        (played, rolls.len())
+    }
+}
+
+const START_PAUSE: f32 = 1.0; // A moment to take in the table before the first event
+const AFTER_BET_PAUSE: f32 = 0.4; // So each bet can be seen going down
+const AFTER_ROLL_PAUSE: f32 = 1.5; // Time for the callout before play carries on
+const RECORDED_FRAME_RATE: f32 = 30.0; // Frames kept per second of a throw
+
+// The scrubber bar along the bottom of the screen, only spawned during a replay
+#[derive(Component)]
+struct PlayPauseButton;
+
+#[derive(Component)]
+struct PlayPauseText;
+
+#[derive(Component)]
+struct ScrubberTrack; // Click or drag along it to jump to a roll
+
+#[derive(Component)]
+struct ScrubberFill; // How much of the session has been played
+
+#[derive(Component)]
+struct ScrubberText; // "Roll 3 of 20"
+
+const SCRUBBER_WIDTH: f32 = 360.0;
+const SCRUBBER_HEIGHT: f32 = 14.0;
+
+// System that opens a new session log as the table is set up, unless we're watching
+// an old one. A log follows one player's money, so hotseat games aren't recorded, and
+// its rolls are two dice, so sic bo isn't either.
+fn start_recording(
+    mut commands: Commands,
+    playback: Option<Res<SessionPlayback>>,
+    hotseat: Option<Res<Hotseat>>,
+    throw_rng: Res<ThrowRng>,
+    bankroll: Res<Bankroll>,
+    rules: Res<TableRules>,
+) {
+    if playback.is_some() || hotseat.is_some() || rules.variant == Variant::SicBo {
+        return;
+    }
+    commands.insert_resource(SessionRecorder {
+        log: SessionLog::new(throw_rng.seed(), bankroll.balance, rules.clone()),
+    });
+}
+
+// System that adds each bet and each settled roll to the log, and saves the log after
+// every roll so a crash or a closed tab loses at most the bets since
+fn record_session_system(
+    mut recorder: ResMut<SessionRecorder>,
+    mut placed_events: EventReader<BetPlaced>,
+    mut taken_events: EventReader<BetTakenDown>,
+    mut undone_events: EventReader<BetUndone>,
+    mut settled_events: EventReader<RollSettled>,
+    replay: Res<InstantReplay>,
+    dice_q: Query<&DiceId, With<Dice>>,
+) {
+    // Bets are placed before the roll is judged, so they go in the log first
+    for placed in placed_events.read() {
+        recorder.log.events.push(SessionEvent::BetPlaced {
+            kind: placed.kind,
+            amount: placed.amount,
+        });
+    }
+    for taken in taken_events.read() {
+        // A bet's odds come down with it, so on playback their own event finds nothing
+        recorder
+            .log
+            .events
+            .push(SessionEvent::BetTakenDown { kind: taken.kind });
+    }
+    for undone in undone_events.read() {
+        recorder.log.events.push(SessionEvent::BetUndone {
+            kind: undone.kind,
+            amount: undone.amount,
+        });
+    }
+    let Some(settled) = settled_events.read().last() else {
+        return;
+    };
+
+    // The instant replay already has the throw frame by frame - keep enough of it for
+    // smooth playback, with each die's pose stored in DiceId order
+    let mut frames: Vec<RecordedFrame> = Vec::new();
+    let recording = replay.recording();
+    for (index, frame) in recording.iter().enumerate() {
+        let is_last = index + 1 == recording.len();
+        let due = frames
+            .last()
+            .is_none_or(|kept| frame.time - kept.time >= 1.0 / RECORDED_FRAME_RATE);
+        if !due && !is_last {
+            continue;
+        }
+        let mut dice: Vec<_> = frame
+            .dice
+            .iter()
+            .filter_map(|pose| Some((dice_q.get(pose.die).ok()?.0, pose)))
+            .collect();
+        dice.sort_by_key(|(id, _)| *id);
+        frames.push(RecordedFrame {
+            time: frame.time,
+            dice: dice
+                .into_iter()
+                .map(|(_, pose)| (pose.translation.to_array(), pose.rotation.to_array()))
+                .collect(),
+        });
+    }
+    recorder.log.events.push(SessionEvent::Rolled(RecordedRoll {
+        die_values: settled.die_values,
+        short_roll: settled.short_roll,
+        frames,
+    }));
+
+    // Compact rather than pretty: a long session has a lot of frames
+    let saved = ron::to_string(&recorder.log)
+        .map_err(|error| error.to_string())
+        .and_then(|text| storage::save(SESSION_KEY, &text).map_err(|error| error.to_string()));
+    if let Err(error) = saved {
+        warn!("Couldn't save the session: {error}");
+    }
+}
+
+// System that plays the recorded session: puts each bet down, moves the dice along each
+// recorded throw, and announces the roll once the dice reach where they stopped. The
+// betting plugin hears the same RollSettled it heard live and pays out the same way.
+fn playback_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut playback: ResMut<SessionPlayback>,
+    mut bets: ResMut<TableBets>,
+    mut bankroll: ResMut<Bankroll>,
+    phase: Res<RoundPhase>,
+    mut placed_events: EventWriter<BetPlaced>,
+    mut taken_events: EventWriter<BetTakenDown>,
+    mut undone_events: EventWriter<BetUndone>,
+    mut settled_events: EventWriter<RollSettled>,
+    mut dice_q: Query<(Entity, &DiceId, &mut Transform, &mut Visibility), With<Dice>>,
+) {
+    if playback.paused {
+        return;
+    }
+
+    // Partway through a throw: move the dice on
+    if let Some((roll, played)) = playback.rolling.take() {
+        let played = played + time.delta_secs();
+        let poses = roll.poses_at(played);
+        for (_, id, mut transform, _) in &mut dice_q {
+            // DiceId is 1-based, the poses are 0-based
+            if let Some(&(translation, rotation)) = poses.get(usize::from(id.0) - 1) {
+                transform.translation = translation;
+                transform.rotation = rotation;
+            }
+        }
+        if played < roll.duration() {
+            playback.rolling = Some((roll, played));
+        } else {
+            let [first, second] = roll.die_values;
+            settled_events.write(RollSettled {
+                die_values: roll.die_values,
+                total: first + second,
+                short_roll: roll.short_roll,
+            });
+            playback.wait = AFTER_ROLL_PAUSE;
+        }
+        return;
+    }
+
+    playback.wait -= time.delta_secs();
+    if playback.wait > 0.0 {
+        return;
+    }
+    let Some(event) = playback.log.events.get(playback.next).cloned() else {
+        return; // The end of the session
+    };
+    playback.next += 1;
+
+    match event {
+        SessionEvent::BetPlaced { kind, amount } => {
+            // The same check and payment as a click on the layout
+            match bets.buy_in(kind, amount, *phase, &playback.log.rules, &mut bankroll) {
+                Ok(_) => {
+                    placed_events.write(BetPlaced { kind, amount });
+                }
+                Err(error) => warn!("The recorded {kind:?} bet doesn't fit any more: {error}"),
+            }
+            playback.wait = AFTER_BET_PAUSE;
+        }
+        SessionEvent::BetTakenDown { kind } => {
+            match bets.take_down(kind, *phase, &mut bankroll) {
+                Ok(taken) => {
+                    for bet in taken {
+                        taken_events.write(BetTakenDown {
+                            kind: bet.kind,
+                            amount: bet.amount,
+                        });
+                    }
+                }
+                Err(error) => warn!("The recorded {kind:?} bet can't come down any more: {error}"),
+            }
+            playback.wait = AFTER_BET_PAUSE;
+        }
+        SessionEvent::BetUndone { kind, amount } => {
+            let bet = Bet::new(kind, amount);
+            match bets.undo_placement(bet, &playback.log.rules, &mut bankroll) {
+                Ok(()) => {
+                    undone_events.write(BetUndone { kind, amount });
+                }
+                Err(error) => warn!("The recorded {kind:?} chips can't be taken back: {error}"),
+            }
+            playback.wait = AFTER_BET_PAUSE;
+        }
+        SessionEvent::Rolled(roll) => {
+            // The recording moves the dice, so keep Rapier's hands off them
+            for (die, ..) in &dice_q {
+                commands.entity(die).insert((
+                    RigidBodyDisabled,
+                    ColliderDisabled,
+                    Visibility::Inherited,
+                ));
+            }
+            playback.rolling = Some((roll, 0.0));
+        }
+    }
+}
+
+// System that handles the play/pause button and jumping about with the scrubber.
+// Jumping rebuilds the table as it stood just before that roll from the log.
+fn scrubber_control_system(
+    mut playback: ResMut<SessionPlayback>,
+    mut phase: ResMut<RoundPhase>,
+    mut phase_events: EventWriter<PhaseChanged>,
+    mut bets: ResMut<TableBets>,
+    mut bankroll: ResMut<Bankroll>,
+    window_q: Query<&Window, With<PrimaryWindow>>,
+    touches: Res<Touches>,
+    button_q: Query<Ref<Interaction>, With<PlayPauseButton>>,
+    track_q: Query<(&Interaction, &ComputedNode, &GlobalTransform), With<ScrubberTrack>>,
+    mut dragged_to: Local<Option<usize>>, // The roll the scrubber was last dragged onto
+) {
+    if let Ok(interaction) = button_q.single() {
+        if interaction.is_changed() && *interaction == Interaction::Pressed {
+            playback.paused = !playback.paused;
+        }
+    }
+
+    let Ok((interaction, node, transform)) = track_q.single() else {
+        return;
+    };
+    let window = window_q.single().ok();
+    let pointer = window
+        .and_then(Window::cursor_position)
+        .or_else(|| touches.first_pressed_position());
+    let (Interaction::Pressed, Some(pointer)) = (*interaction, pointer) else {
+        *dragged_to = None; // Let go
+        return;
+    };
+    // UI nodes are laid out in physical pixels, the pointer is in logical pixels
+    let pointer = pointer * window.map_or(1.0, Window::scale_factor);
+    let width = node.size().x;
+    let left = transform.translation().x - width / 2.0;
+    let along = ((pointer.x - left) / width).clamp(0.0, 1.0);
+
+    let rolls = playback.log.roll_indices();
+    if rolls.is_empty() {
+        return;
+    }
+    let roll = ((along * rolls.len() as f32) as usize).min(rolls.len() - 1);
+    if *dragged_to == Some(roll) {
+        return; // Still held over the same roll - let it play
+    }
+    *dragged_to = Some(roll);
+    let index = rolls[roll];
+    let TableState {
+        phase: then,
+        bets: bets_then,
+        bankroll: bankroll_then,
+    } = playback.log.state_before(index);
+    jump_phase(&mut phase, then, &mut phase_events);
+    *bets = bets_then;
+    *bankroll = bankroll_then;
+    playback.next = index;
+    playback.rolling = None;
+    playback.wait = AFTER_BET_PAUSE;
+}
+
+// System that keeps the scrubber showing how far through the session we are
+fn scrubber_display_system(
+    playback: Res<SessionPlayback>,
+    locale: Res<Locale>,
+    mut fill_q: Query<&mut Node, With<ScrubberFill>>,
+    mut text_q: Query<&mut Text, With<ScrubberText>>,
+    mut button_text_q: Query<&mut Localized, With<PlayPauseText>>,
+) {
+    if !playback.is_changed() && !locale.is_changed() {
+        return;
+    }
+    let (played, total) = playback.progress();
+    if let Ok(mut node) = fill_q.single_mut() {
+        node.width = Val::Percent(played as f32 / total.max(1) as f32 * 100.0);
+    }
+    if let Ok(mut text) = text_q.single_mut() {
+        text.0 = locale.fill(
+            "Replaying session (seed {seed}): roll {played} of {total}",
+            &[
+                ("seed", &playback.log.seed),
+                ("played", &played),
+                ("total", &total),
+            ],
+        );
+    }
+    if let Ok(mut label) = button_text_q.single_mut() {
+        label.set_if_neq(Localized(if playback.paused { "Play" } else { "Pause" }));
+    }
+}
+
+// System that builds the scrubber bar with the table, but only for a replayed session
+fn setup_scrubber(mut commands: Commands, playback: Option<Res<SessionPlayback>>) {
+    if playback.is_none() {
+        return;
+    }
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(20.0),
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center, // Centre the bar across the bottom of the screen
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+        ))
+        .with_children(|column| {
+            column.spawn((
+                Text::new(""), // Filled in on the first frame
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                ScrubberText,
+            ));
+            column
+                .spawn(Node {
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(10.0),
+                    ..default()
+                })
+                .with_children(|row| {
+                    row.spawn((
+                        Button,
+                        Node {
+                            min_width: Val::Px(64.0), // Wider for a longer word
+                            padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.1, 0.1, 0.1)),
+                        BorderRadius::all(Val::Px(6.0)),
+                        PlayPauseButton,
+                    ))
+                    .with_children(|button| {
+                        button.spawn((
+                            Localized("Pause"),
+                            TextFont {
+                                font_size: 14.0,
+                                ..default()
+                            },
+                            TextColor(Color::WHITE),
+                            PlayPauseText,
+                        ));
+                    });
+                    // The track is a button so Bevy tells us when it's being dragged
+                    row.spawn((
+                        Button,
+                        Node {
+                            width: Val::Px(SCRUBBER_WIDTH),
+                            height: Val::Px(SCRUBBER_HEIGHT),
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
+                        BorderRadius::all(Val::Px(SCRUBBER_HEIGHT / 2.0)),
+                        ScrubberTrack,
+                    ))
+                    .with_children(|track| {
+                        track.spawn((
+                            Node {
+                                width: Val::Percent(0.0),
+                                height: Val::Percent(100.0),
+                                ..default()
+                            },
+                            BackgroundColor(Color::srgb(0.9, 0.6, 0.1)),
+                            BorderRadius::all(Val::Px(SCRUBBER_HEIGHT / 2.0)),
+                            ScrubberFill,
+                        ));
+                    });
+                });
+        });
+}
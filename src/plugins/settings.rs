@@ -0,0 +1,763 @@
+// The player's preferences: how the camera handles, how the table looks and sounds,
+// and which buttons do what. They're kept together in one `Settings` resource, saved
+// between sessions, and changed on the settings screen - opened from the pause menu,
+// or the Settings button in the corner of the table.
+use std::collections::BTreeMap;
+
+use bevy::core_pipeline::bloom::Bloom;
+use bevy::input::touch::Touches;
+use bevy::pbr::DirectionalLightShadowMap;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use serde::{Deserialize, Serialize};
+
+use super::camera::Vignette;
+use super::controls::{ControlsPanelState, InputMap};
+use super::dice::{PowerMode, ThrowConfig};
+use super::locale::Localized;
+use super::sound::AudioSettings;
+use super::{AppState, InGame, redraw};
+use crate::betting::{Bet, BetKind};
+use crate::companions::MAX_COMPANIONS;
+use crate::locale::{ENGLISH, Locale, language_name, next_language};
+use crate::palette::Palette;
+use crate::storage;
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        // Loaded straight away, so the sounds that start up already play at the right volume
+        app.insert_resource(Settings::load())
+            .init_resource::<SettingsScreen>()
+            // Readouts and sounds built with the table (the controls panel, the crowd
+            // loop) pick the settings up
+            .add_systems(OnEnter(InGame), redraw::<Settings>)
+            .add_systems(OnEnter(AppState::Paused), setup_settings_screen)
+            .add_systems(OnExit(AppState::Paused), close_settings_screen)
+            .add_systems(
+                Update,
+                (
+                    (
+                        settings_button_system,
+                        setting_slider_system,
+                        settings_display_system,
+                        show_settings_screen_system,
+                    )
+                        .run_if(in_state(AppState::Paused)),
+                    graphics_quality_system.run_if(in_state(InGame)),
+                    throw_mode_system,
+                    ui_scale_system, // The menus are scaled too
+                    save_settings_system,
+                ),
+            );
+    }
+}
+
+// Everything the player can set. `#[serde(default)]` means settings saved by an older
+// version still load, with anything they're missing set to the default.
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub mouse_sensitivity: f32, // Radians the camera turns per pixel of mouse movement
+    pub invert_y: bool,         // Push the mouse or stick up to look down
+    pub fov: f32,               // The camera's field of view, in degrees (narrow = zoomed in)
+    pub graphics: GraphicsQuality,
+    pub throw_mode: PowerMode, // Charging, sweeping or flicking (see dice.rs)
+    pub audio: AudioSettings,
+    pub keybinds: InputMap, // Which buttons do what - rebindable in the controls panel
+    pub companions: usize,  // Computer players at a table for one (see companions.rs)
+    pub tutor: bool,        // Light up good bets and explain each one (see tutor.rs)
+    pub language: String,   // One of the codes in locale::LANGUAGES
+    pub palette: Palette,   // The chip, layout and power meter colours (see palette.rs)
+    pub chip_labels: bool,  // Print each chip's value round its edge
+    pub ui_scale: f32,      // How big everything on screen is drawn - 2.0 for a 4K monitor
+    pub captions: bool,     // Each roll written out along the bottom (see announce.rs)
+    pub read_aloud: bool,   // ...and spoken, in a `--features tts` build
+    // Patterns of bets put down with Shift and a number key, by number (see quick_bets.rs)
+    pub bet_presets: BTreeMap<u8, Vec<Bet>>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            mouse_sensitivity: 0.004,
+            invert_y: false,
+            fov: 35.0,
+            graphics: GraphicsQuality::default(),
+            throw_mode: PowerMode::default(),
+            audio: AudioSettings::default(),
+            keybinds: InputMap::default(),
+            companions: 2,
+            tutor: false,
+            language: ENGLISH.to_string(),
+            palette: Palette::default(),
+            chip_labels: false,
+            ui_scale: 1.0,
+            captions: false,
+            read_aloud: false,
+            bet_presets: default_bet_presets(),
+        }
+    }
+}
+
+// One preset to start with, on Shift+1: $10 on the pass line with $20 odds behind it,
+// and the 6 and 8 bought for $10 each. The pass line goes down on the come-out and the
+// odds once the point is on, so it's pressed once for each.
+fn default_bet_presets() -> BTreeMap<u8, Vec<Bet>> {
+    let bet = |kind, dollars: u64| Bet::new(kind, dollars * 100);
+    BTreeMap::from([(
+        1,
+        vec![
+            bet(BetKind::PassLine, 10),
+            bet(BetKind::PassOdds, 20),
+            bet(BetKind::Buy(6), 10),
+            bet(BetKind::Buy(8), 10),
+        ],
+    )])
+}
+
+const SETTINGS_KEY: &str = "settings"; // Where the settings are saved (see storage.rs)
+const OLD_AUDIO_KEY: &str = "audio_settings"; // Older versions only saved the volumes, here
+
+impl Settings {
+    // The settings from last time, or the defaults on the first run
+    pub fn load() -> Self {
+        let mut settings = if let Some(saved) = storage::load(SETTINGS_KEY) {
+            ron::from_str(&saved).unwrap_or_else(|error| {
+                warn!("Couldn't read the saved settings, using the defaults: {error}");
+                Self::default()
+            })
+        } else if let Some(saved) = storage::load(OLD_AUDIO_KEY) {
+            // Keep the volumes from before there was a settings screen
+            Self {
+                audio: ron::from_str(&saved).unwrap_or_default(),
+                ..Self::default()
+            }
+        } else {
+            Self::default()
+        };
+        // Actions added since the bindings were saved get their usual buttons
+        settings.keybinds.add_missing_defaults();
+        settings
+    }
+}
+
+// How much work the graphics card is asked to do. Low is for older laptops and phones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum GraphicsQuality {
+    Low,    // No shadows, no anti-aliasing, no glow
+    Medium, // Softer, blurrier shadows and a gentler glow
+    #[default]
+    High,
+}
+
+impl GraphicsQuality {
+    fn next(self) -> Self {
+        match self {
+            GraphicsQuality::Low => GraphicsQuality::Medium,
+            GraphicsQuality::Medium => GraphicsQuality::High,
+            GraphicsQuality::High => GraphicsQuality::Low,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            GraphicsQuality::Low => "Low",
+            GraphicsQuality::Medium => "Medium",
+            GraphicsQuality::High => "High",
+        }
+    }
+
+    // Smoothing off the jagged edges of the table and dice
+    pub fn msaa(self) -> Msaa {
+        match self {
+            GraphicsQuality::Low => Msaa::Off,
+            GraphicsQuality::Medium | GraphicsQuality::High => Msaa::Sample4,
+        }
+    }
+
+    // Drawing in high dynamic range, so the neon trim on the rails (and the celebration
+    // sparks) can be brighter than white. Bloom needs it.
+    pub fn hdr(self) -> bool {
+        self != GraphicsQuality::Low
+    }
+
+    // The soft glow that bleeds out of anything brighter than white. None means no glow.
+    pub fn bloom(self) -> Option<Bloom> {
+        match self {
+            GraphicsQuality::Low => None,
+            GraphicsQuality::Medium => Some(Bloom {
+                intensity: 0.1,
+                ..Bloom::NATURAL
+            }),
+            GraphicsQuality::High => Some(Bloom::NATURAL),
+        }
+    }
+
+    // Darkening the corners of the screen, which draws the eye in to the table
+    pub fn vignette(self) -> bool {
+        self != GraphicsQuality::Low
+    }
+
+    // The size of the texture shadows are drawn into - bigger is sharper. None means no
+    // shadows at all.
+    pub fn shadow_map_size(self) -> Option<usize> {
+        match self {
+            GraphicsQuality::Low => None,
+            GraphicsQuality::Medium => Some(1024),
+            GraphicsQuality::High => Some(2048),
+        }
+    }
+}
+
+// Whether the settings screen is showing. It only opens over the pause menu.
+#[derive(Resource, Debug, Default)]
+pub struct SettingsScreen {
+    pub open: bool,
+}
+
+#[derive(Component)]
+struct SettingsPanel; // The screen's root node
+
+// One of the settings screen's sliders
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum SettingSlider {
+    MouseSensitivity,
+    Fov,
+    UiScale,
+    Master,
+    Music,
+    Sfx,
+}
+
+impl SettingSlider {
+    const ALL: [SettingSlider; 6] = [
+        SettingSlider::MouseSensitivity,
+        SettingSlider::Fov,
+        SettingSlider::UiScale,
+        SettingSlider::Master,
+        SettingSlider::Music,
+        SettingSlider::Sfx,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            SettingSlider::MouseSensitivity => "Mouse look",
+            SettingSlider::Fov => "Field of view",
+            SettingSlider::UiScale => "Interface size",
+            SettingSlider::Master => "Master volume",
+            SettingSlider::Music => "Ambience",
+            SettingSlider::Sfx => "Table sounds",
+        }
+    }
+
+    // The values at the left and right ends of the track
+    fn range(self) -> (f32, f32) {
+        match self {
+            SettingSlider::MouseSensitivity => (0.001, 0.012),
+            SettingSlider::Fov => (25.0, 70.0),
+            SettingSlider::UiScale => (0.75, 2.0),
+            SettingSlider::Master | SettingSlider::Music | SettingSlider::Sfx => (0.0, 1.0),
+        }
+    }
+
+    fn value(self, settings: &Settings) -> f32 {
+        match self {
+            SettingSlider::MouseSensitivity => settings.mouse_sensitivity,
+            SettingSlider::Fov => settings.fov,
+            SettingSlider::UiScale => settings.ui_scale,
+            SettingSlider::Master => settings.audio.master,
+            SettingSlider::Music => settings.audio.music,
+            SettingSlider::Sfx => settings.audio.sfx,
+        }
+    }
+
+    fn set(self, settings: &mut Settings, value: f32) {
+        match self {
+            SettingSlider::MouseSensitivity => settings.mouse_sensitivity = value,
+            SettingSlider::Fov => settings.fov = value,
+            SettingSlider::UiScale => settings.ui_scale = value,
+            SettingSlider::Master => settings.audio.master = value,
+            SettingSlider::Music => settings.audio.music = value,
+            SettingSlider::Sfx => settings.audio.sfx = value,
+        }
+    }
+
+    // How far along the track the value is, 0.0 to 1.0
+    fn fraction(self, settings: &Settings) -> f32 {
+        let (min, max) = self.range();
+        ((self.value(settings) - min) / (max - min)).clamp(0.0, 1.0)
+    }
+
+    // The number shown next to the track
+    fn display(self, settings: &Settings) -> String {
+        let value = self.value(settings);
+        match self {
+            // As a multiple of the default, which is easier to judge than radians
+            SettingSlider::MouseSensitivity => {
+                format!("{:.1}x", value / Settings::default().mouse_sensitivity)
+            }
+            SettingSlider::Fov => format!("{value:.0}°"),
+            SettingSlider::UiScale
+            | SettingSlider::Master
+            | SettingSlider::Music
+            | SettingSlider::Sfx => {
+                format!("{:.0}%", value * 100.0)
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+struct SliderFill(SettingSlider); // The coloured part of a slider's track
+
+#[derive(Component)]
+struct SliderText(SettingSlider); // The number next to a slider
+
+// The screen's buttons. The toggles show their current setting on their label.
+#[derive(Component, Debug, Clone, Copy)]
+enum SettingsButton {
+    InvertY,
+    Graphics,
+    ThrowMode,
+    StickmanVoice,
+    Companions,
+    Tutor,
+    Language,
+    Palette,
+    ChipLabels,
+    Captions,
+    ReadAloud,
+    KeyBindings, // Opens the controls panel
+    Back,
+}
+
+impl SettingsButton {
+    fn label(self, settings: &Settings, locale: &Locale) -> String {
+        let on_off = |on: bool| locale.tr(if on { "On" } else { "Off" });
+        let setting = |english: &str, value: &str| locale.fill(english, &[("value", &value)]);
+        match self {
+            SettingsButton::InvertY => setting("Invert Y: {value}", on_off(settings.invert_y)),
+            SettingsButton::Graphics => {
+                setting("Graphics: {value}", locale.tr(settings.graphics.label()))
+            }
+            SettingsButton::ThrowMode => {
+                setting("Throw: {value}", locale.tr(settings.throw_mode.label()))
+            }
+            SettingsButton::StickmanVoice => setting(
+                "Stickman voice: {value}",
+                on_off(settings.audio.stickman_voice),
+            ),
+            SettingsButton::Companions => locale.fill(
+                "Table companions: {value}",
+                &[("value", &settings.companions)],
+            ),
+            SettingsButton::Tutor => setting("Bet tutor: {value}", on_off(settings.tutor)),
+            // Each language is named in itself, so anyone can find their own
+            SettingsButton::Language => {
+                setting("Language: {value}", language_name(&settings.language))
+            }
+            SettingsButton::Palette => {
+                setting("Colours: {value}", locale.tr(settings.palette.label()))
+            }
+            SettingsButton::ChipLabels => {
+                setting("Values on chips: {value}", on_off(settings.chip_labels))
+            }
+            SettingsButton::Captions => setting("Captions: {value}", on_off(settings.captions)),
+            SettingsButton::ReadAloud => {
+                setting("Read aloud: {value}", on_off(settings.read_aloud))
+            }
+            SettingsButton::KeyBindings => locale.tr("Key bindings...").to_string(),
+            SettingsButton::Back => locale.tr("Back").to_string(),
+        }
+    }
+}
+
+const SLIDER_WIDTH: f32 = 200.0;
+const SLIDER_HEIGHT: f32 = 14.0;
+
+fn close_settings_screen(mut screen: ResMut<SettingsScreen>) {
+    screen.open = false;
+}
+
+// The screen is built each time the game pauses, already showing the current settings.
+// The Settings button on the table opens it straight away.
+fn setup_settings_screen(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    screen: Res<SettingsScreen>,
+) {
+    commands
+        .spawn((
+            StateScoped(AppState::Paused),
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(30.0),
+                top: Val::Percent(15.0),
+                width: Val::Percent(40.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(8.0),
+                padding: UiRect::all(Val::Px(16.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.05, 0.05, 0.05, 0.95)),
+            BorderRadius::all(Val::Px(8.0)),
+            GlobalZIndex(1), // Over the pause menu
+            if screen.open {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            },
+            SettingsPanel,
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                Localized("Settings"),
+                TextFont {
+                    font_size: 24.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+            for slider in SettingSlider::ALL {
+                spawn_slider(panel, slider, &settings);
+            }
+            for button in [
+                SettingsButton::InvertY,
+                SettingsButton::Graphics,
+                SettingsButton::ThrowMode,
+                SettingsButton::StickmanVoice,
+                SettingsButton::Companions,
+                SettingsButton::Tutor,
+                SettingsButton::Language,
+                SettingsButton::KeyBindings,
+            ] {
+                spawn_settings_button(panel, button, &settings, &locale);
+            }
+            // Settings for players who need the table to look different to be able to play
+            panel.spawn((
+                Localized("Accessibility"),
+                TextFont {
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                Node {
+                    margin: UiRect::top(Val::Px(6.0)),
+                    ..default()
+                },
+            ));
+            for button in [
+                SettingsButton::Palette,
+                SettingsButton::ChipLabels,
+                SettingsButton::Captions,
+            ] {
+                spawn_settings_button(panel, button, &settings, &locale);
+            }
+            // Only a build with a text-to-speech voice can read aloud
+            if cfg!(feature = "tts") {
+                spawn_settings_button(panel, SettingsButton::ReadAloud, &settings, &locale);
+            }
+            spawn_settings_button(panel, SettingsButton::Back, &settings, &locale);
+        });
+}
+
+// A button with its label (and, for a toggle, its setting) on it
+fn spawn_settings_button(
+    panel: &mut ChildSpawnerCommands,
+    button: SettingsButton,
+    settings: &Settings,
+    locale: &Locale,
+) {
+    panel
+        .spawn((
+            Button,
+            Node {
+                padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.1, 0.1, 0.1)),
+            BorderRadius::all(Val::Px(6.0)),
+            button,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(button.label(settings, locale)),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+        });
+}
+
+// A label, a track to drag along, and the value
+fn spawn_slider(panel: &mut ChildSpawnerCommands, slider: SettingSlider, settings: &Settings) {
+    panel
+        .spawn(Node {
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(10.0),
+            ..default()
+        })
+        .with_children(|row| {
+            row.spawn((
+                Localized(slider.label()),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                Node {
+                    width: Val::Px(110.0),
+                    ..default()
+                },
+            ));
+            // The track is a button so Bevy tells us when it's being dragged
+            row.spawn((
+                Button,
+                Node {
+                    width: Val::Px(SLIDER_WIDTH),
+                    height: Val::Px(SLIDER_HEIGHT),
+                    ..default()
+                },
+                BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
+                BorderRadius::all(Val::Px(SLIDER_HEIGHT / 2.0)),
+                slider,
+            ))
+            .with_children(|track| {
+                track.spawn((
+                    Node {
+                        width: Val::Percent(slider.fraction(settings) * 100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.9, 0.6, 0.1)),
+                    BorderRadius::all(Val::Px(SLIDER_HEIGHT / 2.0)),
+                    SliderFill(slider),
+                ));
+            });
+            row.spawn((
+                Text::new(slider.display(settings)),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                Node {
+                    width: Val::Px(48.0),
+                    ..default()
+                },
+                SliderText(slider),
+            ));
+        });
+}
+
+// System that handles the settings screen's buttons
+fn settings_button_system(
+    mut settings: ResMut<Settings>,
+    mut screen: ResMut<SettingsScreen>,
+    mut panel: ResMut<ControlsPanelState>,
+    mut button_q: Query<
+        (&Interaction, &SettingsButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+) {
+    for (interaction, button, mut color) in &mut button_q {
+        color.0 = match interaction {
+            Interaction::Pressed => Color::srgb(0.3, 0.3, 0.3),
+            Interaction::Hovered => Color::srgb(0.2, 0.2, 0.2),
+            Interaction::None => Color::srgb(0.1, 0.1, 0.1),
+        };
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match button {
+            SettingsButton::InvertY => settings.invert_y = !settings.invert_y,
+            SettingsButton::Graphics => settings.graphics = settings.graphics.next(),
+            SettingsButton::ThrowMode => settings.throw_mode = settings.throw_mode.next(),
+            SettingsButton::StickmanVoice => {
+                settings.audio.stickman_voice = !settings.audio.stickman_voice;
+            }
+            // None, then one more each press, then back to none
+            SettingsButton::Companions => {
+                settings.companions = (settings.companions + 1) % (MAX_COMPANIONS + 1);
+            }
+            SettingsButton::Tutor => settings.tutor = !settings.tutor,
+            SettingsButton::Language => {
+                settings.language = next_language(&settings.language).to_string();
+            }
+            SettingsButton::Palette => settings.palette = settings.palette.next(),
+            SettingsButton::ChipLabels => settings.chip_labels = !settings.chip_labels,
+            SettingsButton::Captions => settings.captions = !settings.captions,
+            SettingsButton::ReadAloud => settings.read_aloud = !settings.read_aloud,
+            SettingsButton::KeyBindings => panel.open = true,
+            SettingsButton::Back => screen.open = false,
+        }
+    }
+}
+
+// System that sets a slider's value from wherever the pointer is along its track while
+// it's held down (Bevy keeps a button "pressed" until it's let go, even off the track)
+fn setting_slider_system(
+    mut settings: ResMut<Settings>,
+    window_q: Query<&Window, With<PrimaryWindow>>,
+    touches: Res<Touches>,
+    sliders_q: Query<(
+        &Interaction,
+        &SettingSlider,
+        &ComputedNode,
+        &GlobalTransform,
+    )>,
+) {
+    let window = window_q.single().ok();
+    let Some(pointer) = window
+        .and_then(Window::cursor_position)
+        .or_else(|| touches.first_pressed_position())
+    else {
+        return;
+    };
+    // UI nodes are laid out in physical pixels, the pointer is in logical pixels
+    let pointer = pointer * window.map_or(1.0, Window::scale_factor);
+    for (interaction, &slider, node, transform) in &sliders_q {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let width = node.size().x;
+        let left = transform.translation().x - width / 2.0;
+        let (min, max) = slider.range();
+        let value = min + ((pointer.x - left) / width).clamp(0.0, 1.0) * (max - min);
+        if slider.value(&settings) != value {
+            slider.set(&mut settings, value); // Only touch the settings when they change
+        }
+    }
+}
+
+// System that keeps the sliders and toggles showing the current settings
+fn settings_display_system(
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    mut fills_q: Query<(&mut Node, &SliderFill)>,
+    mut slider_texts_q: Query<(&mut Text, &SliderText)>,
+    buttons_q: Query<(&SettingsButton, &Children)>,
+    mut text_q: Query<&mut Text, Without<SliderText>>,
+) {
+    if !settings.is_changed() && !locale.is_changed() {
+        return;
+    }
+    for (mut node, fill) in &mut fills_q {
+        node.width = Val::Percent(fill.0.fraction(&settings) * 100.0);
+    }
+    for (mut text, label) in &mut slider_texts_q {
+        text.0 = label.0.display(&settings);
+    }
+    for (button, children) in &buttons_q {
+        for &child in children {
+            if let Ok(mut text) = text_q.get_mut(child) {
+                text.0 = button.label(&settings, &locale);
+            }
+        }
+    }
+}
+
+// System that shows the settings screen while it's open - and tucks it away while the
+// controls panel, which sits in the same spot, is open over it
+fn show_settings_screen_system(
+    screen: Res<SettingsScreen>,
+    panel: Res<ControlsPanelState>,
+    mut screen_q: Query<&mut Visibility, With<SettingsPanel>>,
+) {
+    if let Ok(mut visibility) = screen_q.single_mut() {
+        visibility.set_if_neq(if screen.open && !panel.open {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
+
+// System that applies the graphics quality to the camera and the lights, whenever it
+// changes or the table (and so the camera and lights) is built again
+fn graphics_quality_system(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut shadow_map: ResMut<DirectionalLightShadowMap>,
+    mut cameras_q: Query<(Entity, &mut Camera, &mut Msaa), With<Camera3d>>,
+    mut lights_q: Query<&mut DirectionalLight>,
+    mut vignette_q: Query<&mut Visibility, With<Vignette>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    let quality = settings.graphics;
+    for (entity, mut camera, mut msaa) in &mut cameras_q {
+        msaa.set_if_neq(quality.msaa());
+        if camera.hdr != quality.hdr() {
+            camera.hdr = quality.hdr();
+        }
+        match quality.bloom() {
+            Some(bloom) => commands.entity(entity).insert(bloom),
+            None => commands.entity(entity).remove::<Bloom>(),
+        };
+    }
+    for mut visibility in &mut vignette_q {
+        visibility.set_if_neq(if quality.vignette() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+    for mut light in &mut lights_q {
+        light.shadows_enabled = quality.shadow_map_size().is_some();
+    }
+    if let Some(size) = quality.shadow_map_size() {
+        shadow_map.size = size;
+    }
+}
+
+// System that tells the dice which way the player likes to throw
+fn throw_mode_system(settings: Res<Settings>, mut config: ResMut<ThrowConfig>) {
+    if settings.is_changed() && config.mode != settings.throw_mode {
+        config.mode = settings.throw_mode;
+    }
+}
+
+// System that draws the whole interface at the chosen size. While the size slider is
+// being dragged it waits for the player to let go, or the slider would grow and shrink
+// under the pointer.
+fn ui_scale_system(
+    settings: Res<Settings>,
+    sliders_q: Query<(&Interaction, &SettingSlider)>,
+    mut ui_scale: ResMut<UiScale>,
+) {
+    let dragging = sliders_q.iter().any(|(interaction, &slider)| {
+        slider == SettingSlider::UiScale && *interaction == Interaction::Pressed
+    });
+    // A hand-edited settings file could ask for anything
+    let (min, max) = SettingSlider::UiScale.range();
+    let scale = settings.ui_scale.clamp(min, max);
+    if !dragging && ui_scale.0 != scale {
+        ui_scale.0 = scale;
+    }
+}
+
+// System that saves the settings once the player stops changing them (dragging a
+// slider changes them every frame - there's no need to save every one of those)
+fn save_settings_system(settings: Res<Settings>, mut unsaved: Local<bool>) {
+    if settings.is_changed() && !settings.is_added() {
+        *unsaved = true;
+        return;
+    }
+    if !*unsaved {
+        return;
+    }
+    *unsaved = false;
+    let saved = ron::ser::to_string_pretty(&*settings, ron::ser::PrettyConfig::default())
+        .map_err(|error| error.to_string())
+        .and_then(|text| storage::save(SETTINGS_KEY, &text).map_err(|error| error.to_string()));
+    if let Err(error) = saved {
+        warn!("Couldn't save the settings: {error}");
+    }
+}
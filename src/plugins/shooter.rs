@@ -0,0 +1,243 @@
+// The shooter's dice cup. While the throw button is held the cup is shaken where the
+// dice will leave from, faster as the power meter rises, and when it's let go the cup
+// tips forward and the dice fly out of it - rather than appearing out of thin air over
+// the felt. The dice themselves don't change: they're thrown from the same spot with
+// the same push as before (see dice.rs), the cup just gives them somewhere to come from.
+//
+// The cup's moves are keyframe clips (see keyframes.rs) read from
+// assets/shooter.clips.ron, so how the shooter shakes can be changed without a rebuild.
+// A remote shooter's throw on a networked table tips the cup at their end too, and a
+// VR player (see vr.rs) holds the cup in their hand.
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::camera::PlayerCamera;
+use super::controls::TrackedHand;
+use super::dice::{ThrowAim, ThrowDice, ThrowPower, ThrowTarget, throw_system};
+use super::menu::LoadingAssets;
+use super::{AppState, InGame};
+use crate::keyframes::{Clip, Keyframe};
+use crate::layout::TableLayout;
+use crate::ron_asset::RonLoader;
+
+pub struct ShooterPlugin;
+
+impl Plugin for ShooterPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<ShooterClips>() // The cup's moves, loaded from assets/
+            .register_asset_loader(RonLoader::<ShooterClips>::new(&["clips.ron"]))
+            .init_resource::<ShooterClipsFile>()
+            .add_systems(Startup, wait_for_clips)
+            .add_systems(OnEnter(InGame), setup_cup)
+            // After the throw button is read, so the cup tips out the frame it's let go
+            .add_systems(
+                Update,
+                cup_system
+                    .after(throw_system)
+                    .run_if(in_state(AppState::Playing)),
+            );
+    }
+}
+
+// The cup's clips, as written in assets/shooter.clips.ron
+#[derive(Asset, TypePath, Deserialize, Debug, Clone)]
+pub struct ShooterClips {
+    pub shake: Clip,   // Played over and over while the throw charges
+    pub release: Clip, // Played once as the dice leave
+}
+
+// The moves the game was built with, used if the file is missing or broken
+impl Default for ShooterClips {
+    fn default() -> Self {
+        let key = |time, offset: [f32; 3], turn: [f32; 3]| Keyframe {
+            time,
+            offset: Vec3::from(offset),
+            turn: Vec3::from(turn),
+        };
+        Self {
+            shake: Clip {
+                keys: vec![
+                    key(0.0, [0.0, 0.0, 0.0], [0.0, 0.0, 0.0]),
+                    key(0.09, [0.04, 0.06, 0.02], [-8.0, 0.0, 10.0]),
+                    key(0.18, [0.0, 0.0, 0.0], [0.0, 0.0, 0.0]),
+                    key(0.27, [-0.04, 0.06, -0.02], [8.0, 0.0, -10.0]),
+                    key(0.36, [0.0, 0.0, 0.0], [0.0, 0.0, 0.0]),
+                ],
+            },
+            release: Clip {
+                keys: vec![
+                    key(0.0, [0.0, 0.0, 0.0], [0.0, 0.0, 0.0]),
+                    key(0.12, [0.0, 0.05, 0.15], [75.0, 0.0, 0.0]),
+                    key(0.3, [0.0, 0.1, 0.1], [60.0, 0.0, 0.0]),
+                    key(0.6, [0.0, 0.3, -0.4], [10.0, 0.0, 0.0]),
+                ],
+            },
+        }
+    }
+}
+
+// The clips file the game is using
+#[derive(Resource)]
+struct ShooterClipsFile(Handle<ShooterClips>);
+
+impl FromWorld for ShooterClipsFile {
+    fn from_world(world: &mut World) -> Self {
+        Self(world.resource::<AssetServer>().load("shooter.clips.ron"))
+    }
+}
+
+// The cup, and what it's doing
+#[derive(Component, Default)]
+struct DiceCup {
+    motion: CupMotion,
+}
+
+#[derive(Clone, Copy, Default)]
+enum CupMotion {
+    #[default]
+    Away, // Put down out of sight between throws
+    Shaking {
+        time: f32, // How far through the shake clip, which speeds up with the power
+    },
+    Releasing {
+        time: f32,
+        at: Transform, // Where the dice left from, facing the way they went
+    },
+}
+
+const CUP_RADIUS: f32 = 0.35; // Wide enough to hold both dice side by side
+const CUP_HEIGHT: f32 = 0.5;
+
+fn wait_for_clips(file: Res<ShooterClipsFile>, mut loading: ResMut<LoadingAssets>) {
+    loading.add(&file.0);
+}
+
+// The cup is a leather cylinder with a brass rim round the top, waiting out of sight
+fn setup_cup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Transform::default(),
+            Visibility::Hidden,
+            DiceCup::default(),
+            Name::new("Dice Cup"),
+        ))
+        .with_children(|cup| {
+            cup.spawn((
+                Mesh3d(meshes.add(Cylinder::new(CUP_RADIUS, CUP_HEIGHT))),
+                MeshMaterial3d(materials.add(StandardMaterial {
+                    base_color: Srgba::hex("#4A2614").unwrap().into(), // Dark leather
+                    perceptual_roughness: 0.7,
+                    ..default()
+                })),
+            ));
+            cup.spawn((
+                Mesh3d(meshes.add(Torus::new(CUP_RADIUS - 0.02, CUP_RADIUS + 0.02))),
+                MeshMaterial3d(materials.add(StandardMaterial {
+                    base_color: Srgba::hex("#B08A3A").unwrap().into(), // Brass
+                    metallic: 1.0,
+                    perceptual_roughness: 0.35,
+                    ..default()
+                })),
+                Transform::from_xyz(0.0, CUP_HEIGHT / 2.0, 0.0),
+            ));
+        });
+}
+
+// Where the cup is held for a throw: on the throw's starting point, facing the way
+// the dice will go
+fn cup_place(aim: &ThrowAim) -> Transform {
+    Transform::from_translation(aim.origin).looking_to(aim.forward, Vec3::Y)
+}
+
+// System that shakes the cup while a throw charges and tips it out as the dice go
+fn cup_system(
+    time: Res<Time>,
+    power: Res<ThrowPower>,
+    target: Res<ThrowTarget>,
+    table_layout: Res<TableLayout>,
+    hand: Res<TrackedHand>, // A VR controller, if there is one
+    file: Res<ShooterClipsFile>,
+    clips: Res<Assets<ShooterClips>>,
+    mut throw_events: EventReader<ThrowDice>,
+    cam_q: Query<&Transform, (With<PlayerCamera>, Without<DiceCup>)>,
+    mut cup_q: Query<(&mut DiceCup, &mut Transform, &mut Visibility)>,
+) {
+    let Ok((mut cup, mut transform, mut visibility)) = cup_q.single_mut() else {
+        return;
+    };
+    let built_in;
+    let clips = match clips.get(&file.0) {
+        Some(clips) => clips,
+        None => {
+            built_in = ShooterClips::default();
+            &built_in
+        }
+    };
+    let dt = time.delta_secs();
+
+    // Every throw leaves from the cup, whoever threw it
+    for throw in throw_events.read() {
+        let aim = ThrowAim::of_throw(throw, table_layout.size);
+        cup.motion = CupMotion::Releasing {
+            time: 0.0,
+            at: cup_place(&aim),
+        };
+    }
+
+    let pose = match cup.motion {
+        CupMotion::Releasing { time, at } => {
+            let time = time + dt;
+            if time > clips.release.duration() {
+                cup.motion = CupMotion::Away;
+                None
+            } else {
+                cup.motion = CupMotion::Releasing { time, at };
+                Some(at * clips.release.sample(time))
+            }
+        }
+        // A VR player holds the cup in their hand and does the shaking themselves
+        _ if power.is_charging() && hand.pose.is_some() => {
+            cup.motion = CupMotion::Shaking { time: 0.0 };
+            hand.pose.map(|pose| {
+                Transform::from_translation(pose.translation).with_rotation(pose.rotation)
+            })
+        }
+        _ if power.is_charging() => {
+            let Ok(cam_transform) = cam_q.single() else {
+                return;
+            };
+            let aim = ThrowAim::new(cam_transform, target.0, table_layout.size);
+            // Shaken faster and harder as the meter fills
+            let vigour = power.current / power.max;
+            let shaken = match cup.motion {
+                CupMotion::Shaking { time } => time + dt * (1.0 + vigour),
+                _ => 0.0, // Just picked up
+            };
+            cup.motion = CupMotion::Shaking { time: shaken };
+            let shake = clips.shake.sample_looped(shaken);
+            let strength = 0.6 + 0.4 * vigour;
+            let shake = Transform::from_translation(shake.translation * strength)
+                .with_rotation(Quat::IDENTITY.slerp(shake.rotation, strength));
+            Some(cup_place(&aim) * shake)
+        }
+        _ => {
+            cup.motion = CupMotion::Away; // Let go without a throw, like leaving the table
+            None
+        }
+    };
+
+    match pose {
+        Some(pose) => {
+            *transform = pose;
+            visibility.set_if_neq(Visibility::Inherited);
+        }
+        None => {
+            visibility.set_if_neq(Visibility::Hidden);
+        }
+    }
+}
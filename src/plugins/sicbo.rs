@@ -0,0 +1,69 @@
+// Sic bo at the craps table: the same felt, walls and physics, three dice instead of
+// two and the sic bo layout printed in place of the craps one (see layout.rs). Bets go
+// down with the usual click and chips, and this plugin pays them when the three dice
+// settle - read by the same settle and face checks as a craps roll - then sends a
+// SicBoResolved for the callout. `--sicbo` (or `?sicbo`) deals it.
+use bevy::prelude::*;
+
+use super::betting::{BetResolved, bank_winnings_system};
+use super::dice::DiceSettled;
+use super::{AppState, RollStep};
+use crate::betting::TableBets;
+use crate::payout::returned_to_player;
+use crate::rules::{TableRules, Variant};
+use crate::sicbo::{SicBoDice, total};
+
+pub struct SicBoPlugin;
+
+impl Plugin for SicBoPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SicBoResolved>() // A sic bo throw, paid
+            .add_systems(
+                Update,
+                sic_bo_payout_system
+                    .in_set(RollStep::Pay)
+                    .before(bank_winnings_system) // Which pays what it's owed
+                    .run_if(in_state(AppState::Playing)),
+            );
+    }
+}
+
+// Event fired once a sic bo throw's bets have all been paid, the way RollResolved is
+// for craps
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SicBoResolved {
+    pub die_values: SicBoDice,
+    pub short_roll: bool, // Counted, but a die never reached the back wall
+}
+
+// System that settles every sic bo bet against the three dice. The BetResolved events
+// it sends pay the winners and move the chips like any other win.
+fn sic_bo_payout_system(
+    mut dice_events: EventReader<DiceSettled>,
+    rules: Res<TableRules>,
+    mut bets: ResMut<TableBets>,
+    mut payout_events: EventWriter<BetResolved>,
+    mut resolved_events: EventWriter<SicBoResolved>,
+) {
+    for settled in dice_events.read() {
+        if rules.variant != Variant::SicBo {
+            continue; // Craps - the round system has it
+        }
+        let Ok(die_values) = SicBoDice::try_from(settled.die_values.as_slice()) else {
+            continue;
+        };
+        info!("Sic bo: {die_values:?}, a total of {}", total(die_values));
+
+        for (bet, result) in bets.resolve_sic_bo(die_values) {
+            payout_events.write(BetResolved {
+                bet,
+                result,
+                returned: returned_to_player(&bet, result),
+            });
+        }
+        resolved_events.write(SicBoResolved {
+            die_values,
+            short_roll: settled.short_roll,
+        });
+    }
+}
@@ -0,0 +1,148 @@
+// The side bets: every bet in the BetRegistry (see registry.rs), which the layout
+// prints a box for along the front of the pass line. After every roll each one on the
+// layout is decided by its rule, against all the rolls since it went down, and paid
+// like any other bet.
+//
+// A `--features side-bets` build also registers players' own scripts (see
+// side_bets.rs): every `.rhai` file in the save folder's `side_bets/`, read at startup
+// in name order. A script that won't load is skipped with a warning in the log.
+// Scripts are for a table of one (companions included) - at a hotseat or networked
+// table the other players' bets are settled by code that can't run them.
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use super::betting::{BetResolved, BetTakenDown, RollResolved, bank_winnings_system};
+use super::{AppState, RollStep};
+use crate::betting::{BetKind, TableBets};
+use crate::payout::returned_to_player;
+use crate::registry::{BetRegistry, SideRoll};
+
+pub struct SideBetsPlugin;
+
+impl Plugin for SideBetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SideBetRolls>().add_systems(
+            Update,
+            side_bet_payout_system
+                .in_set(RollStep::Pay)
+                .before(bank_winnings_system) // Which pays what it's owed
+                .run_if(in_state(AppState::Playing)),
+        );
+
+        // main.rs inserts a hotseat or networked table before the plugins are added
+        #[cfg(all(feature = "side-bets", not(target_arch = "wasm32")))]
+        {
+            use super::hotseat::Hotseat;
+            use super::net::{NetClient, NetHost};
+            let world = app.world();
+            let shared = world.contains_resource::<Hotseat>()
+                || world.contains_resource::<NetHost>()
+                || world.contains_resource::<NetClient>();
+            if shared {
+                info!("Side bets are for a table of one - not reading any scripts");
+            } else {
+                app.add_systems(Startup, load_side_bets_system);
+            }
+        }
+    }
+}
+
+// The rolls each side bet on the layout has seen since it went down
+#[derive(Resource, Debug, Default)]
+pub struct SideBetRolls(pub HashMap<u8, Vec<SideRoll>>);
+
+// System that reads the side bet scripts from the save folder into the registry
+#[cfg(all(feature = "side-bets", not(target_arch = "wasm32")))]
+fn load_side_bets_system(mut registry: ResMut<BetRegistry>) {
+    use crate::locale::Locale;
+    use crate::registry::{BetRule, MAX_REGISTERED_BETS};
+    use crate::side_bets::load_script;
+
+    let folder = crate::storage::folder("side_bets");
+    let Ok(entries) = std::fs::read_dir(&folder) else {
+        info!(
+            "No side bets - put .rhai scripts in {} to add some",
+            folder.display()
+        );
+        return;
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "rhai")
+        })
+        .collect();
+    paths.sort(); // The same boxes in the same places every launch
+
+    for path in paths {
+        if registry.len() == MAX_REGISTERED_BETS {
+            warn!("Only {MAX_REGISTERED_BETS} side bets fit on the layout - the rest are left out");
+            break;
+        }
+        let loaded = std::fs::read_to_string(&path)
+            .map_err(|error| error.to_string())
+            .and_then(|source| load_script(&source));
+        match loaded {
+            Ok(bet) => {
+                let name = bet.name(&Locale::default());
+                if let Some(kind) = registry.register(bet) {
+                    info!("Side bet \"{name}\" ({kind:?}) from {}", path.display());
+                }
+            }
+            Err(error) => warn!("Skipping the side bet in {}: {error}", path.display()),
+        }
+    }
+}
+
+// System that hands each roll to the side bets on the layout and settles the ones their
+// rules decide. The BetResolveds it sends pay them and move the chips like any other
+// bet's.
+fn side_bet_payout_system(
+    mut resolved_events: EventReader<RollResolved>,
+    mut taken_events: EventReader<BetTakenDown>,
+    registry: Res<BetRegistry>,
+    mut rolls: ResMut<SideBetRolls>,
+    mut bets: ResMut<TableBets>,
+    mut payout_events: EventWriter<BetResolved>,
+) {
+    // A side bet taken down and put back starts counting again
+    for taken in taken_events.read() {
+        if let BetKind::Side(index) = taken.kind {
+            rolls.0.remove(&index);
+        }
+    }
+
+    for resolved in resolved_events.read() {
+        // Forget the bets that came off some other way (undone, or a new table)
+        rolls
+            .0
+            .retain(|&index, _| bets.amount_on(BetKind::Side(index)) > 0);
+        let roll = SideRoll {
+            dice: resolved.die_values,
+            point: resolved.previous.point(),
+        };
+        for bet in &bets.bets {
+            if let BetKind::Side(index) = bet.kind {
+                rolls.0.entry(index).or_default().push(roll);
+            }
+        }
+
+        let seen = &rolls.0;
+        let decisions = bets.resolve_registered(&registry, |index| {
+            seen.get(&index).map_or(&[][..], Vec::as_slice)
+        });
+        for (bet, result) in decisions {
+            if let BetKind::Side(index) = bet.kind {
+                rolls.0.remove(&index);
+            }
+            payout_events.write(BetResolved {
+                bet,
+                result,
+                returned: returned_to_player(&bet, result),
+            });
+        }
+    }
+}
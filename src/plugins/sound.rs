@@ -0,0 +1,216 @@
+// Sound effects and the player's sound settings. The casino hums along in the
+// background, and the dice knock against the felt, the walls and each other, louder
+// the harder they hit. The volumes are part of the player's settings (see settings.rs).
+//
+// Table sounds are spatial: they play from where they happen, and the camera carries
+// the `SpatialListener` (our two ears), so a die hitting the far wall sounds further
+// away than one landing in front of you, and pans left or right as you look around.
+use bevy::audio::{AudioSinkPlayback, SpatialScale, Volume};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::AppState;
+use super::dice::Dice;
+use super::menu::LoadingAssets;
+use super::settings::Settings;
+use super::table::Wall;
+
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, (load_sounds, start_ambience))
+            .add_systems(
+                Update,
+                (
+                    (listen_for_impacts, impact_sound_system)
+                        .chain()
+                        .run_if(in_state(AppState::Playing)),
+                    // The casino keeps humming in the menus
+                    ambience_volume_system,
+                ),
+            );
+    }
+}
+
+// Which volume slider a sound listens to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundChannel {
+    Music, // The background: the casino's ambience
+    Sfx,   // Everything that happens at the table: dice, chips, the stickman
+}
+
+// The player's sound preferences. Volumes run from 0.0 (silent) to 1.0 (full).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)] // Settings saved by an older version keep working
+pub struct AudioSettings {
+    pub master: f32,          // Everything
+    pub music: f32,           // The background
+    pub sfx: f32,             // Table sounds
+    pub stickman_voice: bool, // The stickman calls out every roll
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master: 0.8,
+            music: 0.5,
+            sfx: 1.0,
+            stickman_voice: true,
+        }
+    }
+}
+
+impl AudioSettings {
+    // How loud a sound on this channel plays, before its own volume is applied
+    pub fn level(&self, channel: SoundChannel) -> f32 {
+        let channel_level = match channel {
+            SoundChannel::Music => self.music,
+            SoundChannel::Sfx => self.sfx,
+        };
+        self.master * channel_level
+    }
+}
+
+#[derive(Component)]
+struct Ambience; // The looping background sound
+
+const AMBIENCE_VOLUME: f32 = 0.6; // The hum sits under everything else
+
+// Sounds fade with distance. With the camera about 12 units from the table that would
+// make every die a whisper, so table distances are shrunk to a quarter for hearing.
+pub const TABLE_SPATIAL_SCALE: f32 = 0.25;
+
+// Playback settings for a one-off table sound played at its own Transform
+pub fn table_sound(volume: f32) -> PlaybackSettings {
+    PlaybackSettings::DESPAWN // Clean up once the sound has played
+        .with_volume(Volume::Linear(volume))
+        .with_spatial(true)
+        .with_spatial_scale(SpatialScale::new(TABLE_SPATIAL_SCALE))
+}
+
+// The impact sounds, loaded once at startup
+#[derive(Resource)]
+struct ImpactSounds {
+    felt: Handle<AudioSource>,
+    wall: Handle<AudioSource>,
+    dice: Handle<AudioSource>, // One die clacking into the other
+}
+
+// When a die last made a sound, so a die sliding along the felt (which touches it a
+// little harder every physics step) doesn't buzz
+#[derive(Component, Default)]
+struct LastImpact(f32); // Seconds since startup
+
+// Rapier reports contact forces in newtons. A die resting on the felt pushes down
+// with about 1.3 N, so anything quieter than this is sliding or resting, not hitting.
+const IMPACT_FORCE_THRESHOLD: f32 = 6.0;
+const LOUDEST_IMPACT_FORCE: f32 = 80.0; // Hits this hard or harder play at full volume
+const IMPACT_COOLDOWN: f32 = 0.06; // Seconds before the same die can sound again
+
+fn load_sounds(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut loading: ResMut<LoadingAssets>,
+) {
+    let sounds = ImpactSounds {
+        felt: asset_server.load("sounds/dice_felt.wav"),
+        wall: asset_server.load("sounds/dice_wall.wav"),
+        dice: asset_server.load("sounds/dice_dice.wav"),
+    };
+    for sound in [&sounds.felt, &sounds.wall, &sounds.dice] {
+        loading.add(sound);
+    }
+    commands.insert_resource(sounds);
+}
+
+// Start the casino hum, looping forever
+fn start_ambience(mut commands: Commands, asset_server: Res<AssetServer>, settings: Res<Settings>) {
+    commands.spawn((
+        AudioPlayer::new(asset_server.load("sounds/casino_ambience.wav")),
+        PlaybackSettings::LOOP.with_volume(Volume::Linear(
+            settings.audio.level(SoundChannel::Music) * AMBIENCE_VOLUME,
+        )),
+        Ambience,
+    ));
+}
+
+// System that follows the volume sliders. Short sounds pick their volume up when they
+// start, but the ambience never stops, so its volume is changed while it plays.
+fn ambience_volume_system(
+    settings: Res<Settings>,
+    mut sinks_q: Query<&mut AudioSink, With<Ambience>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    for mut sink in &mut sinks_q {
+        sink.set_volume(Volume::Linear(
+            settings.audio.level(SoundChannel::Music) * AMBIENCE_VOLUME,
+        ));
+    }
+}
+
+// System that asks Rapier to report how hard each new die hits things. Rapier only
+// does this for colliders that ask, and only above the threshold.
+fn listen_for_impacts(mut commands: Commands, new_dice_q: Query<Entity, Added<Dice>>) {
+    for die in &new_dice_q {
+        commands.entity(die).insert((
+            ActiveEvents::CONTACT_FORCE_EVENTS,
+            ContactForceEventThreshold(IMPACT_FORCE_THRESHOLD),
+            LastImpact::default(),
+        ));
+    }
+}
+
+// System that plays a knock for every hard contact between a die and something else
+fn impact_sound_system(
+    mut commands: Commands,
+    mut contact_events: EventReader<ContactForceEvent>,
+    sounds: Res<ImpactSounds>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+    mut dice_q: Query<(&mut LastImpact, &GlobalTransform), With<Dice>>,
+    wall_q: Query<(), With<Wall>>,
+) {
+    let now = time.elapsed_secs();
+    for event in contact_events.read() {
+        let (a, b) = (event.collider1, event.collider2);
+        // Whichever collider is the die; if both are, it's the two dice together
+        let (die, other) = if dice_q.contains(a) { (a, b) } else { (b, a) };
+        let Ok((mut last_impact, die_transform)) = dice_q.get_mut(die) else {
+            continue; // Neither is a die
+        };
+        if now - last_impact.0 < IMPACT_COOLDOWN {
+            continue;
+        }
+        last_impact.0 = now;
+        let position = die_transform.translation(); // The knock comes from the die
+
+        let sound = if dice_q.contains(other) {
+            sounds.dice.clone()
+        } else if wall_q.contains(other) {
+            sounds.wall.clone()
+        } else {
+            sounds.felt.clone() // The table top is the only other thing a die can touch
+        };
+
+        // How hard it hit, from 0 (barely) to 1 (as hard as it gets). Our ears hear
+        // loudness on a curve, so the square root keeps soft bumps audible.
+        let strength = ((event.total_force_magnitude - IMPACT_FORCE_THRESHOLD)
+            / (LOUDEST_IMPACT_FORCE - IMPACT_FORCE_THRESHOLD))
+            .clamp(0.0, 1.0)
+            .sqrt();
+        // A slightly different pitch each time so repeated hits don't sound copy-pasted.
+        // This is just for the ears, so it doesn't draw from the seeded ThrowRng.
+        let pitch = rand::thread_rng().gen_range(0.9..1.1);
+        commands.spawn((
+            AudioPlayer::new(sound),
+            table_sound(settings.audio.level(SoundChannel::Sfx) * (0.1 + 0.9 * strength))
+                .with_speed(pitch),
+            Transform::from_translation(position),
+        ));
+    }
+}
@@ -0,0 +1,494 @@
+// The session statistics panel (Tab, or the Stats button) and the roll histogram (H).
+// Every judged roll is written in the `SessionLedger` (see src/ledger.rs) with the bets
+// it decided, and counted from there in `SessionStats` (see src/stats.rs); a sic bo
+// throw is written down too, but isn't counted. Both last the whole session - finishing
+// a round or passing the dice doesn't clear them. On the desktop the panel's export
+// buttons write the ledger to the save folder's `exports/` as a CSV or JSON file, for a
+// spreadsheet.
+//
+// The histogram puts a bar for how often each total has actually come up next to a
+// line for how often fair dice should show it. Over a few hundred rolls the bars
+// should settle onto the lines; if they don't, the physics dice are loaded.
+use bevy::color::prelude::*;
+use bevy::prelude::*;
+
+use super::betting::{BetPlaced, BetResolved, BetTakenDown, BetUndone, RollResolved};
+use super::controls::PlayerInput;
+use super::locale::Localized;
+use super::sicbo::SicBoResolved;
+use super::{AppState, InGame, RollStep};
+use crate::betting::{Bet, TableBets};
+use crate::ledger::{ChipMove, Decision, SessionLedger};
+use crate::locale::Locale;
+use crate::payout::{Bankroll, Cents, format_signed_money};
+use crate::stats::{SessionStats, chance_of_total};
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(InGame),
+            (start_stats, setup_stats_panel, setup_histogram),
+        )
+        .add_systems(
+            Update,
+            (
+                ledger_moves_system,
+                // Once the roll has been paid
+                record_stats_system
+                    .after(RollStep::Pay)
+                    .after(ledger_moves_system),
+                toggle_stats_panel_system,
+                stats_text_system.after(record_stats_system),
+                toggle_histogram_system,
+                histogram_system.after(record_stats_system),
+            )
+                .run_if(in_state(AppState::Playing)),
+        );
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(Update, export_system.run_if(in_state(AppState::Playing)));
+    }
+}
+
+#[derive(Component)]
+struct StatsPanel;
+
+#[derive(Component)]
+struct StatsText;
+
+#[derive(Component)]
+struct StatsButton; // Opens and closes the panel, under the replay button
+
+// Writes the ledger out as a file (not in the browser, which has nowhere to put one)
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Component)]
+struct ExportButton(crate::ledger::ExportFormat);
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Component)]
+struct ExportNote; // Where the last export went, or why it couldn't be written
+
+#[derive(Component)]
+struct Histogram;
+
+#[derive(Component)]
+struct HistogramBar(u8); // How often this total has come up
+
+#[derive(Component)]
+struct HistogramExpected(u8); // How often it should come up
+
+const HISTOGRAM_HEIGHT: f32 = 100.0;
+const HISTOGRAM_COLUMN: f32 = 16.0; // Width of each total's bar
+// The chart's top is never lower than this share of rolls, so the expected lines (7
+// is the tallest, at 1 in 6) always fit and early lucky streaks don't fill the chart
+const HISTOGRAM_MIN_SCALE: f32 = 0.25;
+
+// Everything the player has, counting chips still on the layout
+fn holding(bankroll: &Bankroll, bets: &TableBets) -> Cents {
+    bankroll.balance + bets.bets.iter().map(|bet| bet.amount).sum::<Cents>()
+}
+
+// System that starts counting, as the table is set up, from what the player sat
+// down with. Any saved bankroll has been loaded by now (see save.rs).
+fn start_stats(mut commands: Commands, bankroll: Res<Bankroll>, bets: Res<TableBets>) {
+    let starting = holding(&bankroll, &bets);
+    commands.insert_resource(SessionStats::new(starting));
+    commands.insert_resource(SessionLedger::new(starting));
+}
+
+// System that notes chips going on and off the layout, for the ledger's next roll
+fn ledger_moves_system(
+    mut placed_events: EventReader<BetPlaced>,
+    mut taken_events: EventReader<BetTakenDown>,
+    mut undone_events: EventReader<BetUndone>,
+    mut ledger: ResMut<SessionLedger>,
+) {
+    for placed in placed_events.read() {
+        ledger.note_move(ChipMove::Placed(Bet::new(placed.kind, placed.amount)));
+    }
+    for taken in taken_events.read() {
+        ledger.note_move(ChipMove::TakenDown(Bet::new(taken.kind, taken.amount)));
+    }
+    for undone in undone_events.read() {
+        ledger.note_move(ChipMove::Undone(Bet::new(undone.kind, undone.amount)));
+    }
+}
+
+// System that writes each roll in the ledger once the rules have judged it and it has
+// been paid, and counts it in the stats from there. A sic bo throw is written down
+// too, so its bets reach the export, but the craps numbers don't count it.
+fn record_stats_system(
+    mut resolved_events: EventReader<RollResolved>,
+    mut sic_bo_events: EventReader<SicBoResolved>,
+    mut payout_events: EventReader<BetResolved>,
+    bankroll: Res<Bankroll>,
+    bets: Res<TableBets>,
+    mut ledger: ResMut<SessionLedger>,
+    mut stats: ResMut<SessionStats>,
+) {
+    let mut decided: Vec<Decision> = payout_events
+        .read()
+        .map(|payout| Decision {
+            bet: payout.bet,
+            result: payout.result,
+            returned: payout.returned,
+        })
+        .collect();
+    for resolved in resolved_events.read() {
+        let line = ledger.record(
+            &resolved.die_values,
+            Some(resolved.outcome),
+            std::mem::take(&mut decided),
+            holding(&bankroll, &bets),
+        );
+        stats.record(line.total, resolved.outcome);
+        stats.note_holding(line.holding);
+    }
+    for resolved in sic_bo_events.read() {
+        let line = ledger.record(
+            &resolved.die_values,
+            None,
+            std::mem::take(&mut decided),
+            holding(&bankroll, &bets),
+        );
+        stats.note_holding(line.holding);
+    }
+}
+
+// System that writes the ledger to a file when an export button is clicked, named
+// for the moment it was written so no export replaces another
+#[cfg(not(target_arch = "wasm32"))]
+fn export_system(
+    ledger: Res<SessionLedger>,
+    locale: Res<Locale>,
+    button_q: Query<(&Interaction, &ExportButton), Changed<Interaction>>,
+    mut note_q: Query<&mut Text, With<ExportNote>>,
+) {
+    for (interaction, button) in &button_q {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let seconds = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        let folder = crate::storage::folder("exports");
+        let path = folder.join(format!("session-{seconds}.{}", button.0.extension()));
+        let written = std::fs::create_dir_all(&folder)
+            .and_then(|()| std::fs::write(&path, ledger.export(button.0)));
+        let note = match written {
+            Ok(()) => {
+                info!("Exported the session to {}", path.display());
+                locale.fill("Saved to {path}", &[("path", &path.display())])
+            }
+            Err(error) => {
+                warn!("Couldn't export the session to {}: {error}", path.display());
+                locale.fill("Couldn't export: {error}", &[("error", &error)])
+            }
+        };
+        if let Ok(mut text) = note_q.single_mut() {
+            text.0 = note;
+        }
+    }
+}
+
+// System that opens and closes the panel from the keyboard or the button
+fn toggle_stats_panel_system(
+    input: Res<PlayerInput>,
+    button_q: Query<&Interaction, (Changed<Interaction>, With<StatsButton>)>,
+    mut panel_q: Query<&mut Visibility, With<StatsPanel>>,
+) {
+    let clicked = button_q
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed);
+    if !input.toggle_stats && !clicked {
+        return;
+    }
+    if let Ok(mut visibility) = panel_q.single_mut() {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Inherited,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+// System that rewrites the panel whenever a number on it changes
+fn stats_text_system(
+    stats: Res<SessionStats>,
+    bankroll: Res<Bankroll>,
+    bets: Res<TableBets>,
+    locale: Res<Locale>,
+    mut text_q: Query<&mut Text, With<StatsText>>,
+) {
+    if !stats.is_changed() && !bankroll.is_changed() && !bets.is_changed() && !locale.is_changed() {
+        return;
+    }
+    let Ok(mut text) = text_q.single_mut() else {
+        return;
+    };
+
+    let net = format_signed_money(stats.net(holding(&bankroll, &bets)));
+    let average = stats
+        .average_hand()
+        .map_or("-".to_string(), |average| format!("{average:.1}"));
+    // Totals in two rows so the panel stays narrow: 2-7, then 8-12
+    let totals = |range: std::ops::RangeInclusive<usize>| {
+        range
+            .map(|total| format!("{total}: {}", stats.totals[total]))
+            .collect::<Vec<_>>()
+            .join("  ")
+    };
+
+    let line =
+        |english: &str, value: &dyn std::fmt::Display| locale.fill(english, &[("value", value)]);
+    text.0 = [
+        line("Rolls: {value}", &stats.rolls),
+        line("Points made: {value}", &stats.points_made),
+        line("Seven-outs: {value}", &stats.seven_outs),
+        line("This shooter: {value} rolls", &stats.current_hand),
+        line("Longest hand: {value} rolls", &stats.longest_hand()),
+        line("Average hand: {value} rolls", &average),
+        line("Net: {value}", &net),
+        String::new(),
+        locale.tr("Totals").to_string(),
+        totals(2..=7),
+        totals(8..=12),
+    ]
+    .join("\n");
+}
+
+// System that shows and hides the histogram
+fn toggle_histogram_system(
+    input: Res<PlayerInput>,
+    mut histogram_q: Query<&mut Visibility, With<Histogram>>,
+) {
+    if !input.toggle_histogram {
+        return;
+    }
+    if let Ok(mut visibility) = histogram_q.single_mut() {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Inherited,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+// System that resizes the bars as rolls come in
+fn histogram_system(
+    stats: Res<SessionStats>,
+    mut bars_q: Query<(&mut Node, &HistogramBar), Without<HistogramExpected>>,
+    mut expected_q: Query<(&mut Node, &HistogramExpected)>,
+) {
+    if !stats.is_changed() {
+        return;
+    }
+    // Scale the chart to the tallest bar, so a long session still shows the shape
+    let scale = (2..=12)
+        .map(|total| stats.frequency(total))
+        .fold(HISTOGRAM_MIN_SCALE, f32::max);
+    for (mut node, bar) in &mut bars_q {
+        node.height = Val::Percent(stats.frequency(bar.0) / scale * 100.0);
+    }
+    for (mut node, expected) in &mut expected_q {
+        node.bottom = Val::Percent(chance_of_total(expected.0) / scale * 100.0);
+    }
+}
+
+fn setup_stats_panel(mut commands: Commands) {
+    // Stats button in the top-right corner, under the replay button
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Button,
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(20.0),
+                top: Val::Px(56.0),
+                padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.1, 0.1, 0.1)),
+            BorderRadius::all(Val::Px(6.0)),
+            StatsButton,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Localized("Stats (Tab)"),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+        });
+
+    // ...and the panel it opens underneath
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(20.0),
+                top: Val::Px(92.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(6.0),
+                padding: UiRect::all(Val::Px(12.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.05, 0.05, 0.05, 0.9)),
+            BorderRadius::all(Val::Px(6.0)),
+            Visibility::Hidden,
+            StatsPanel,
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                Localized("This session"),
+                TextFont {
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+            panel.spawn((
+                Text::new(""), // Filled in on the first frame
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                StatsText,
+            ));
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                panel
+                    .spawn(Node {
+                        column_gap: Val::Px(6.0),
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        for format in crate::ledger::ExportFormat::ALL {
+                            row.spawn((
+                                Button,
+                                Node {
+                                    padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+                                    ..default()
+                                },
+                                BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
+                                BorderRadius::all(Val::Px(4.0)),
+                                ExportButton(format),
+                            ))
+                            .with_children(|button| {
+                                button.spawn((
+                                    Localized(format.label()),
+                                    TextFont {
+                                        font_size: 12.0,
+                                        ..default()
+                                    },
+                                    TextColor(Color::WHITE),
+                                ));
+                            });
+                        }
+                    });
+                panel.spawn((
+                    Text::new(""),
+                    TextFont {
+                        font_size: 11.0,
+                        ..default()
+                    },
+                    TextColor(Color::srgb(0.7, 0.7, 0.7)),
+                    ExportNote,
+                ));
+            }
+        });
+}
+
+fn setup_histogram(mut commands: Commands) {
+    // A small chart on the left edge, one column per total from 2 to 12
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(20.0),
+                top: Val::Percent(40.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.05, 0.05, 0.05, 0.8)),
+            BorderRadius::all(Val::Px(6.0)),
+            Visibility::Hidden,
+            Histogram,
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                Localized("Rolled vs fair dice (H)"),
+                TextFont {
+                    font_size: 12.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+            panel
+                .spawn(Node {
+                    column_gap: Val::Px(3.0),
+                    ..default()
+                })
+                .with_children(|columns| {
+                    for total in 2..=12 {
+                        columns
+                            .spawn(Node {
+                                flex_direction: FlexDirection::Column,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            })
+                            .with_children(|column| {
+                                // The bar grows up from the bottom of its column, with
+                                // a thin line across it at the expected height
+                                column
+                                    .spawn((
+                                        Node {
+                                            width: Val::Px(HISTOGRAM_COLUMN),
+                                            height: Val::Px(HISTOGRAM_HEIGHT),
+                                            ..default()
+                                        },
+                                        BackgroundColor(Color::srgba(1.0, 1.0, 1.0, 0.05)),
+                                    ))
+                                    .with_children(|track| {
+                                        track.spawn((
+                                            Node {
+                                                position_type: PositionType::Absolute,
+                                                bottom: Val::Px(0.0),
+                                                width: Val::Percent(100.0),
+                                                height: Val::Percent(0.0),
+                                                ..default()
+                                            },
+                                            BackgroundColor(Color::srgb(0.9, 0.6, 0.1)),
+                                            HistogramBar(total),
+                                        ));
+                                        track.spawn((
+                                            Node {
+                                                position_type: PositionType::Absolute,
+                                                bottom: Val::Percent(0.0),
+                                                width: Val::Percent(100.0),
+                                                height: Val::Px(2.0),
+                                                ..default()
+                                            },
+                                            BackgroundColor(Color::WHITE),
+                                            HistogramExpected(total),
+                                        ));
+                                    });
+                                column.spawn((
+                                    Text::new(total.to_string()),
+                                    TextFont {
+                                        font_size: 11.0,
+                                        ..default()
+                                    },
+                                    TextColor(Color::WHITE),
+                                ));
+                            });
+                    }
+                });
+        });
+}
@@ -0,0 +1,379 @@
+// The stickman. After every roll he calls it out - "Seven out, line away!",
+// "Yo-leven!", "Hard eight!" - using the recordings listed in assets/stickman.voice.ron,
+// then reaches out with his stick and pushes the dice back to the shooter. The next
+// throw waits until the dice are back, which gives the table its rhythm between rolls.
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::seq::SliceRandom;
+use serde::Deserialize;
+
+use super::betting::RollResolved;
+use super::dice::{Dice, DiceAuthority, DiceId, RollState};
+use super::hotseat::Hotseat;
+use super::menu::LoadingAssets;
+use super::session::SessionPlayback;
+use super::settings::Settings;
+use super::sound::{SoundChannel, table_sound};
+use super::{AppState, InGame, RollStep};
+use crate::layout::TableLayout;
+use crate::ron_asset::RonLoader;
+use crate::rules::RollOutcome;
+
+pub struct StickmanPlugin;
+
+impl Plugin for StickmanPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<StickmanManifest>() // Which recording goes with which call
+            .register_asset_loader(RonLoader::<StickmanManifest>::new(&["voice.ron"]))
+            .init_resource::<StickmanVoice>()
+            .init_resource::<DiceReturn>() // The stick bringing the dice back, if it's out
+            .add_systems(Startup, wait_for_manifest)
+            .add_systems(OnEnter(InGame), setup_stick)
+            .add_systems(
+                Update,
+                (
+                    // Always, so the manifest arriving during the loading screen is seen
+                    load_voice_lines_system,
+                    (
+                        stickman_system.in_set(RollStep::Announce),
+                        start_return_system.after(RollStep::Pay),
+                        stick_system.after(start_return_system),
+                    )
+                        .run_if(in_state(AppState::Playing)),
+                ),
+            );
+    }
+}
+
+// Everything the stickman can say about a roll
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum StickmanCall {
+    Winner,        // 7 on the come-out: "Seven, winner winner!"
+    Yo,            // 11 on the come-out: "Yo-leven!"
+    Craps(u8),     // 2, 3 or 12 on the come-out: "Aces, craps!"
+    PointIs(u8),   // "The point is six"
+    PointMade(u8), // "Six, winner! Pay the line"
+    SevenOut,      // "Seven out, line away!"
+    Hard(u8),      // 4, 6, 8 or 10 as a pair: "Hard eight!"
+    Easy(u8),      // 4, 6, 8 or 10 any other way: "Easy six"
+    Number(u8),    // Anything else with the point on: "Nine, center field nine"
+}
+
+// What the stickman calls for a roll
+pub fn stickman_call(outcome: RollOutcome, die_values: [u8; 2]) -> StickmanCall {
+    match outcome {
+        RollOutcome::Natural(7) => StickmanCall::Winner,
+        RollOutcome::Natural(_) => StickmanCall::Yo,
+        RollOutcome::Craps(total) => StickmanCall::Craps(total),
+        RollOutcome::PointSet(point) => StickmanCall::PointIs(point),
+        RollOutcome::PointMade(point) => StickmanCall::PointMade(point),
+        RollOutcome::SevenOut => StickmanCall::SevenOut,
+        RollOutcome::NoDecision(total @ (4 | 6 | 8 | 10)) => {
+            if die_values[0] == die_values[1] {
+                StickmanCall::Hard(total)
+            } else {
+                StickmanCall::Easy(total)
+            }
+        }
+        RollOutcome::NoDecision(total) => StickmanCall::Number(total),
+    }
+}
+
+// The voice file: for each call, the recordings to pick from (paths inside assets/)
+#[derive(Asset, TypePath, Deserialize, Debug, Clone)]
+pub struct StickmanManifest {
+    pub calls: HashMap<StickmanCall, Vec<String>>,
+}
+
+// The loaded manifest and the recordings it points to
+#[derive(Resource)]
+pub struct StickmanVoice {
+    manifest: Handle<StickmanManifest>,
+    lines: HashMap<StickmanCall, Vec<Handle<AudioSource>>>, // Empty until the manifest loads
+}
+
+impl FromWorld for StickmanVoice {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            manifest: world.resource::<AssetServer>().load("stickman.voice.ron"),
+            lines: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Component)]
+struct StickmanLine; // The call being spoken right now
+
+// The stickman's long stick, and the hooked end that pushes the dice
+#[derive(Component)]
+struct Stick;
+
+#[derive(Component)]
+struct StickHook;
+
+// The stick fetching the dice after a roll: where each die was left, where it's going,
+// and how far along the whole move is
+#[derive(Resource, Default)]
+struct DiceReturn {
+    dice: Vec<(Entity, Vec3)>, // Each die and where it came to rest
+    to: [Vec3; 2],             // In front of the shooter, for die #1 and die #2
+    elapsed: f32,              // Seconds since the roll was decided
+    active: bool,
+}
+
+// The stick's timeline after each roll, in seconds
+const RETURN_WAIT: f32 = 0.8; // The dice sit for a moment so everyone can read them
+const STICK_REACH: f32 = 0.4; // The stick goes out to the dice
+const STICK_SWEEP: f32 = 1.0; // The dice are pushed back to the shooter
+const STICK_RETRACT: f32 = 0.4; // And the stick comes home again
+const STICK_LENGTH_HOME: f32 = 0.6; // How far the stick pokes out over the rail at rest
+
+// Where the stickman stands: at the middle of the long +z side, across from the
+// dealers, at about head height
+fn stickman_position(table_layout: &TableLayout) -> Vec3 {
+    Vec3::new(0.0, 1.6, table_layout.size.y / 2.0 + 0.8)
+}
+
+// "Smoothstep" easing - starts slow, speeds up, then slows into place
+fn ease(progress: f32) -> f32 {
+    let t = progress.clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+// Startup system that holds the loading screen until the manifest is in. The
+// recordings it lists load in the background - a missing call is only skipped.
+fn wait_for_manifest(voice: Res<StickmanVoice>, mut loading: ResMut<LoadingAssets>) {
+    loading.add(&voice.manifest);
+}
+
+// System that starts loading every recording once the manifest has loaded (and again
+// whenever it is edited)
+fn load_voice_lines_system(
+    mut voice: ResMut<StickmanVoice>,
+    manifests: Res<Assets<StickmanManifest>>,
+    mut manifest_events: EventReader<AssetEvent<StickmanManifest>>,
+    asset_server: Res<AssetServer>,
+) {
+    let manifest_changed = manifest_events.read().any(|event| {
+        event.is_loaded_with_dependencies(&voice.manifest) || event.is_modified(&voice.manifest)
+    });
+    if !manifest_changed {
+        return;
+    }
+    let Some(manifest) = manifests.get(&voice.manifest) else {
+        return;
+    };
+    voice.lines = manifest
+        .calls
+        .iter()
+        .map(|(&call, paths)| {
+            let sounds = paths.iter().map(|path| asset_server.load(path)).collect();
+            (call, sounds)
+        })
+        .collect();
+}
+
+// System that has the stickman call out every roll
+fn stickman_system(
+    mut commands: Commands,
+    mut resolved_events: EventReader<RollResolved>,
+    voice: Res<StickmanVoice>,
+    settings: Res<Settings>,
+    table_layout: Res<TableLayout>,
+    speaking_q: Query<Entity, With<StickmanLine>>,
+) {
+    for resolved in resolved_events.read() {
+        if !settings.audio.stickman_voice {
+            continue;
+        }
+        let call = stickman_call(resolved.outcome, resolved.die_values);
+        // A call missing from the manifest simply isn't said
+        let Some(recording) = voice
+            .lines
+            .get(&call)
+            .and_then(|lines| lines.choose(&mut rand::thread_rng()))
+        else {
+            continue;
+        };
+        // He doesn't talk over himself: a new roll cuts off the last call
+        for entity in &speaking_q {
+            commands.entity(entity).despawn();
+        }
+        commands.spawn((
+            AudioPlayer::new(recording.clone()),
+            table_sound(settings.audio.level(SoundChannel::Sfx)),
+            Transform::from_translation(stickman_position(&table_layout)),
+            StickmanLine,
+        ));
+    }
+}
+
+// The stick is one long cylinder from the stickman's hands to its tip, stretched to
+// reach, with a flat hook across the end. It waits poking out over the rail.
+fn setup_stick(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut dice_return: ResMut<DiceReturn>,
+) {
+    *dice_return = DiceReturn::default(); // Nothing half-fetched from the last table
+    let wood = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.45, 0.28, 0.15),
+        perceptual_roughness: 0.7,
+        ..default()
+    });
+    commands.spawn((
+        StateScoped(InGame),
+        Mesh3d(meshes.add(Cylinder::new(0.015, 1.0))), // Stretched to length along Y
+        MeshMaterial3d(wood.clone()),
+        Transform::default(),
+        Stick,
+    ));
+    commands.spawn((
+        StateScoped(InGame),
+        Mesh3d(meshes.add(Cuboid::new(0.3, 0.03, 0.04))),
+        MeshMaterial3d(wood),
+        Transform::default(),
+        StickHook,
+    ));
+}
+
+// System that sends the stick for the dice once a roll has been decided and paid
+fn start_return_system(
+    mut commands: Commands,
+    mut resolved_events: EventReader<RollResolved>,
+    mut dice_return: ResMut<DiceReturn>,
+    mut roll: ResMut<RollState>,
+    authority: Res<DiceAuthority>,
+    table_layout: Res<TableLayout>,
+    hotseat: Option<Res<Hotseat>>,
+    playback: Option<Res<SessionPlayback>>,
+    dice_q: Query<(Entity, &Transform, &Visibility), With<Dice>>,
+) {
+    for resolved in resolved_events.read() {
+        // Another machine's dice (it pushes them back itself), or a recording
+        if !authority.simulates || playback.is_some() {
+            continue;
+        }
+        // At a hotseat table a seven-out sends the dice on to the next shooter instead
+        if hotseat.is_some() && resolved.outcome == RollOutcome::SevenOut {
+            continue;
+        }
+        let dice: Vec<_> = dice_q
+            .iter()
+            .filter(|(.., visibility)| **visibility != Visibility::Hidden)
+            .map(|(entity, transform, _)| (entity, transform.translation))
+            .collect();
+        if dice.is_empty() {
+            continue;
+        }
+
+        // Side by side in front of the shooter, across the line to the middle. On your
+        // own that's the end of the table you throw from.
+        let (seat, seats) = hotseat.as_ref().map_or((0, 1), |hotseat| {
+            (hotseat.rotation().shooter, hotseat.count())
+        });
+        let shooter_at = table_layout.seat_position(seat, seats);
+        let across = shooter_at.normalize_or_zero().perp() * 0.15;
+        let height = dice[0].1.y;
+        let spot = |offset: Vec2| {
+            let point = shooter_at + offset;
+            Vec3::new(point.x, height, point.y)
+        };
+        for &(entity, _) in &dice {
+            // Pushed along by the stick. The physics would only fight it, so it's off
+            // until the shooter throws again.
+            commands.entity(entity).insert(RigidBodyDisabled);
+        }
+        *dice_return = DiceReturn {
+            dice,
+            to: [spot(across), spot(-across)],
+            elapsed: 0.0,
+            active: true,
+        };
+        roll.set_retrieving(true);
+    }
+}
+
+// System that plays out the stick's reach, sweep and retract, moving the dice with it,
+// and lets the shooter throw again as soon as the dice are back
+fn stick_system(
+    time: Res<Time>,
+    table_layout: Res<TableLayout>,
+    mut dice_return: ResMut<DiceReturn>,
+    mut roll: ResMut<RollState>,
+    mut dice_q: Query<(&DiceId, &mut Transform), (With<Dice>, Without<Stick>, Without<StickHook>)>,
+    mut stick_q: Query<&mut Transform, (With<Stick>, Without<StickHook>)>,
+    mut hook_q: Query<&mut Transform, (With<StickHook>, Without<Stick>)>,
+) {
+    let hands = stickman_position(&table_layout) - Vec3::Y * 0.5; // Held at waist height
+    let home = hands - Vec3::Z * (0.8 + STICK_LENGTH_HOME); // Out over the felt
+
+    // A throw (another player's, over the network) picks the dice up mid-sweep
+    if dice_return.active && roll.in_flight() {
+        dice_return.active = false;
+        roll.set_retrieving(false);
+    }
+
+    let mut tip = home;
+    let mut push_dir = -Vec3::Z; // Which way the hook faces
+    if dice_return.active {
+        dice_return.elapsed += time.delta_secs();
+        let start: Vec3 = dice_return.dice.iter().map(|&(_, at)| at).sum::<Vec3>()
+            / dice_return.dice.len() as f32;
+        let end = (dice_return.to[0] + dice_return.to[1]) / 2.0;
+        push_dir = (end - start).with_y(0.0).normalize_or_zero();
+        let behind = |middle: Vec3| (middle - push_dir * 0.25).with_y(0.05);
+
+        let reach_starts = RETURN_WAIT;
+        let sweep_starts = reach_starts + STICK_REACH;
+        let retract_starts = sweep_starts + STICK_SWEEP;
+        let elapsed = dice_return.elapsed;
+        let sweep = ease((elapsed - sweep_starts) / STICK_SWEEP);
+
+        tip = if elapsed < sweep_starts {
+            home.lerp(behind(start), ease((elapsed - reach_starts) / STICK_REACH))
+        } else if elapsed < retract_starts {
+            behind(start.lerp(end, sweep))
+        } else {
+            behind(end).lerp(home, ease((elapsed - retract_starts) / STICK_RETRACT))
+        };
+
+        if elapsed >= sweep_starts {
+            for &(entity, from) in &dice_return.dice {
+                let Ok((id, mut transform)) = dice_q.get_mut(entity) else {
+                    continue;
+                };
+                let to = dice_return.to[usize::from(id.0 - 1).min(1)]; // DiceId is 1-based
+                transform.translation = from.lerp(to, sweep);
+            }
+        }
+        if elapsed >= retract_starts && roll.retrieving() {
+            roll.set_retrieving(false); // The dice are back - the shooter may throw
+        }
+        if elapsed >= retract_starts + STICK_RETRACT {
+            dice_return.active = false;
+        }
+    }
+
+    // Lay the stick from the stickman's hands to its tip
+    if let Ok(mut transform) = stick_q.single_mut() {
+        let reach = tip - hands;
+        *transform = Transform::from_translation((hands + tip) / 2.0)
+            .with_rotation(Quat::from_rotation_arc(
+                Vec3::Y,
+                reach.normalize_or(Vec3::Y),
+            ))
+            .with_scale(Vec3::new(1.0, reach.length(), 1.0));
+    }
+    // The hook lies flat across the way it's pushing
+    if let Ok(mut transform) = hook_q.single_mut() {
+        let across = push_dir.cross(Vec3::Y);
+        *transform = Transform::from_translation(tip).with_rotation(Quat::from_rotation_arc(
+            Vec3::X,
+            across.normalize_or(Vec3::X),
+        ));
+    }
+}
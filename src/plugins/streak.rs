@@ -0,0 +1,185 @@
+// The table getting excited about a hot shooter. Every point the shooter makes in a
+// row (the `ShooterStreak`, kept by the round system) turns things up a notch: the rail
+// starts to glow, the crowd gets louder, and from two points on a "HOT SHOOTER" banner
+// goes up. A seven-out ends the streak and everything calms down again.
+use bevy::audio::{AudioSinkPlayback, Volume};
+use bevy::color::prelude::*;
+use bevy::prelude::*;
+
+use super::settings::Settings;
+use super::sound::SoundChannel;
+use super::table::Wall;
+use super::{AppState, InGame};
+use crate::locale::Locale;
+use crate::rules::{Heat, ShooterStreak};
+
+pub struct StreakPlugin;
+
+impl Plugin for StreakPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(InGame), (start_crowd, setup_banner))
+            .add_systems(
+                Update,
+                (rail_glow_system, crowd_volume_system, banner_system)
+                    .run_if(in_state(AppState::Playing)),
+            );
+    }
+}
+
+#[derive(Component)]
+struct Crowd; // The crowd noise, always looping, silent until the shooter gets hot
+
+#[derive(Component)]
+struct HotShooterBanner;
+
+// What each level of heat looks and sounds like
+struct HeatEffects {
+    glow: f32,  // How brightly the rail glows (0 = not at all)
+    pulse: f32, // How fast the glow throbs, in beats per second
+    crowd: f32, // Crowd volume, before the player's volume settings
+}
+
+fn effects(heat: Heat) -> HeatEffects {
+    match heat {
+        Heat::Cold => HeatEffects {
+            glow: 0.0,
+            pulse: 0.0,
+            crowd: 0.0,
+        },
+        Heat::Warm => HeatEffects {
+            glow: 0.6,
+            pulse: 0.5,
+            crowd: 0.15,
+        },
+        Heat::Hot => HeatEffects {
+            glow: 1.5,
+            pulse: 1.0,
+            crowd: 0.4,
+        },
+        Heat::OnFire => HeatEffects {
+            glow: 3.0,
+            pulse: 2.0,
+            crowd: 0.8,
+        },
+    }
+}
+
+const GLOW_COLOR: LinearRgba = LinearRgba::rgb(1.0, 0.3, 0.0); // The papaya rail, lit up
+const CROWD_FADE_SPEED: f32 = 0.5; // Volume change per second - a swell, not a jump
+
+// Start the crowd loop, silent for now
+fn start_crowd(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        StateScoped(InGame),
+        AudioPlayer::new(asset_server.load("sounds/crowd.wav")),
+        PlaybackSettings::LOOP.with_volume(Volume::Linear(0.0)),
+        Crowd,
+    ));
+}
+
+// System that makes the rail glow, throbbing faster the hotter the shooter. The walls
+// all share one material, so lighting it lights the whole rail.
+fn rail_glow_system(
+    time: Res<Time>,
+    streak: Res<ShooterStreak>,
+    walls_q: Query<&MeshMaterial3d<StandardMaterial>, With<Wall>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut glowing: Local<bool>,
+) {
+    let effects = effects(streak.heat());
+    if effects.glow == 0.0 && !*glowing {
+        return; // Already dark - don't touch the material every frame
+    }
+    *glowing = effects.glow > 0.0;
+    // Between half and full brightness, in time with the pulse
+    let beat = (time.elapsed_secs() * effects.pulse * std::f32::consts::TAU).sin();
+    let strength = effects.glow * (0.75 + 0.25 * beat);
+    for material in &walls_q {
+        if let Some(material) = materials.get_mut(&material.0) {
+            material.emissive = GLOW_COLOR * strength;
+        }
+    }
+}
+
+// System that swells the crowd up (or lets it die away) to match the streak
+fn crowd_volume_system(
+    time: Res<Time>,
+    streak: Res<ShooterStreak>,
+    settings: Res<Settings>,
+    mut sinks_q: Query<&mut AudioSink, With<Crowd>>,
+    mut volume: Local<f32>, // Where the swell has got to
+) {
+    let target = effects(streak.heat()).crowd;
+    let step = CROWD_FADE_SPEED * time.delta_secs();
+    let next = if *volume < target {
+        (*volume + step).min(target)
+    } else {
+        (*volume - step).max(target)
+    };
+    if next == *volume && !settings.is_changed() {
+        return;
+    }
+    *volume = next;
+    for mut sink in &mut sinks_q {
+        sink.set_volume(Volume::Linear(
+            next * settings.audio.level(SoundChannel::Sfx),
+        ));
+    }
+}
+
+// System that puts the banner up for a hot shooter, and takes it down on a seven-out
+fn banner_system(
+    time: Res<Time>,
+    streak: Res<ShooterStreak>,
+    locale: Res<Locale>,
+    mut banner_q: Query<(&mut Text, &mut TextColor, &mut Visibility), With<HotShooterBanner>>,
+) {
+    let Ok((mut text, mut color, mut visibility)) = banner_q.single_mut() else {
+        return;
+    };
+    let heat = streak.heat();
+    if heat < Heat::Hot {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    }
+    visibility.set_if_neq(Visibility::Inherited);
+    if streak.is_changed() || locale.is_changed() {
+        text.0 = match heat {
+            Heat::OnFire => locale.fill(
+                "ON FIRE! {points} POINTS IN A ROW",
+                &[("points", &streak.points_made)],
+            ),
+            _ => locale.tr("HOT SHOOTER!").to_string(),
+        };
+    }
+    // Flicker between orange and yellow, faster when the shooter is on fire
+    let beat = (time.elapsed_secs() * effects(heat).pulse * std::f32::consts::TAU).sin();
+    color.0 = Color::srgb(1.0, 0.55 + 0.3 * beat.max(0.0), 0.1);
+}
+
+fn setup_banner(mut commands: Commands) {
+    // Across the top of the screen, above where the roll callout appears
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(20.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("HOT SHOOTER!"),
+                TextFont {
+                    font_size: 36.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 0.55, 0.1)),
+                Visibility::Hidden,
+                HotShooterBanner,
+            ));
+        });
+}
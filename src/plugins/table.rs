@@ -0,0 +1,831 @@
+// The craps table itself: the felt, the printed layout, the walls that keep the dice
+// in, the lights over it and the dealer's ON/OFF puck. The table's size, colours and
+// bounciness come from assets/craps.table.ron, so trying a bigger table or a livelier
+// felt doesn't need a rebuild.
+use bevy::asset::RenderAssetUsages;
+use bevy::color::prelude::*; // Color tools - for painting our 3D objects
+use bevy::image::{ImageAddressMode, ImageSampler, ImageSamplerDescriptor};
+use bevy::math::Affine2;
+use bevy::pbr::NotShadowCaster;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy_rapier3d::prelude::*; // Physics engine - makes things fall, bounce, and collide realistically
+use serde::Deserialize;
+
+use super::menu::LoadingAssets;
+use super::settings::Settings;
+use super::{AppState, InGame, redraw};
+use crate::betting::BetKind;
+use crate::layout::{BetSpot, REFERENCE_SIZE, TableLayout};
+use crate::locale::Locale;
+use crate::palette::{Palette, lettering_pixels};
+use crate::registry::BetRegistry;
+use crate::ron_asset::RonLoader;
+use crate::rules::{RoundPhase, TableRules, Variant};
+use crate::sicbo::SicBoBet;
+
+pub struct TablePlugin;
+
+impl Plugin for TablePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TableLayout>() // The table's size and its printed bet zones
+            .init_asset::<TableConfig>() // The table file, loaded from assets/
+            .register_asset_loader(RonLoader::<TableConfig>::new(&["table.ron"]))
+            .init_resource::<TableChoice>()
+            .add_systems(Startup, wait_for_table_config)
+            // The file is in by the time loading finishes, and the rest of the game
+            // only sees the table's size through TableLayout
+            .add_systems(OnExit(AppState::Loading), apply_table_config)
+            // The puck is built OFF, so it's told the phase again to find its place
+            .add_systems(OnEnter(InGame), (setup_table, redraw::<RoundPhase>))
+            .add_systems(
+                Update,
+                (relayout_system, puck_system)
+                    .chain()
+                    .run_if(in_state(AppState::Playing)),
+            );
+    }
+}
+
+// How the table is built, as written in assets/craps.table.ron
+#[derive(Asset, TypePath, Deserialize, Debug, Clone)]
+pub struct TableConfig {
+    pub size: TableSize,
+    pub wall_height: f32,
+    pub wall_thickness: f32,
+    pub felt: Felt,
+    pub walls: Surface,
+    pub layout_lines: String, // Hex colour of the outlines printed round each bet zone
+    #[serde(default)] // Table files from before the rails had trim
+    pub trim: Trim,
+}
+
+// The size of the playing surface, either one of the presets or any width and depth
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum TableSize {
+    Standard, // The 8 x 4 table the layout is drawn for
+    // A casino's 12-foot table is 12 by 5 feet, so it's stretched to those
+    // proportions while keeping the standard table's depth (and the dice's size)
+    Regulation12Ft,
+    Custom(f32, f32), // Width (x) and depth (z)
+}
+
+impl TableSize {
+    pub fn dimensions(self) -> Vec2 {
+        match self {
+            TableSize::Standard => REFERENCE_SIZE,
+            TableSize::Regulation12Ft => Vec2::new(REFERENCE_SIZE.y * 12.0 / 5.0, REFERENCE_SIZE.y),
+            TableSize::Custom(width, depth) => Vec2::new(width, depth),
+        }
+    }
+}
+
+// What the walls are made of: how they look and how the dice bounce off them
+#[derive(Deserialize, Debug, Clone)]
+pub struct Surface {
+    pub color: String, // Hex colour, like "#0B0B0B"
+    pub metallic: f32,
+    pub roughness: f32,   // 0.0 = mirror-smooth, 1.0 = completely matte
+    pub restitution: f32, // Bounciness: 0.1 = a die keeps 10% of its energy
+    pub friction: f32,
+}
+
+impl Surface {
+    fn material(&self) -> StandardMaterial {
+        StandardMaterial {
+            base_color: Srgba::hex(&self.color).unwrap_or(Srgba::WHITE).into(),
+            metallic: self.metallic,
+            perceptual_roughness: self.roughness,
+            ..default()
+        }
+    }
+
+    // What the dice feel when they hit it
+    fn physics(&self) -> (Restitution, Friction) {
+        (
+            Restitution::coefficient(self.restitution),
+            Friction::coefficient(self.friction),
+        )
+    }
+}
+
+// The felt: how it looks and how the dice roll on it, written in one place so the
+// table top that's drawn and the one the dice land on can't drift apart. The cloth's
+// weave isn't a picture in assets/ - it's painted in code (see paint_weave) and tiled
+// across the table, as colour and as a normal map that catches the light.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Felt {
+    pub color: String,    // Hex colour, like "#0B0B0B"
+    pub roughness: f32,   // 0.0 = mirror-smooth, 1.0 = completely matte; cloth is near 1.0
+    pub restitution: f32, // Bounciness: 0.1 = a die keeps 10% of its energy
+    pub friction: f32,
+    #[serde(default = "default_weave")] // Table files from before the felt had a weave
+    pub weave: f32, // How deep the weave looks - 0.0 is smooth cloth
+}
+
+fn default_weave() -> f32 {
+    1.0
+}
+
+impl Felt {
+    fn material(&self, images: &mut Assets<Image>, table_size: Vec2) -> StandardMaterial {
+        let (color, normal) = paint_weave(self.weave);
+        StandardMaterial {
+            base_color: Srgba::hex(&self.color).unwrap_or(Srgba::WHITE).into(),
+            base_color_texture: Some(images.add(color)),
+            normal_map_texture: Some(images.add(normal)),
+            perceptual_roughness: self.roughness,
+            // The weave is one small square, repeated over the whole table
+            uv_transform: Affine2::from_scale(table_size / WEAVE_TILE),
+            ..default()
+        }
+    }
+
+    fn physics(&self) -> (Restitution, Friction) {
+        (
+            Restitution::coefficient(self.restitution),
+            Friction::coefficient(self.friction),
+        )
+    }
+}
+
+// Paint one square of woven cloth: a colour picture (the threads' light and shade, to
+// tint the felt's colour with) and a normal map (which way the cloth faces at each
+// pixel, so the light picks out the threads). Threads run both ways, each one passing
+// over and under the threads crossing it in turn. Both pictures wrap round at the
+// edges, so the squares join up without a seam.
+fn paint_weave(depth: f32) -> (Image, Image) {
+    let size = WEAVE_PIXELS as usize;
+    let thread = size / WEAVE_THREADS;
+    // How high the cloth stands at each pixel, 0.0 to 1.0
+    let height = |x: usize, y: usize| -> f32 {
+        let (x, y) = (x % size, y % size);
+        let across = ((x % thread) as f32 + 0.5) / thread as f32;
+        let along = ((y % thread) as f32 + 0.5) / thread as f32;
+        let rounded = |at: f32| (at * std::f32::consts::PI).sin();
+        // Where the thread running up the picture is on top, and where the one running
+        // across it is
+        let (top, under) = if (x / thread + y / thread) % 2 == 0 {
+            (rounded(across), rounded(along))
+        } else {
+            (rounded(along), rounded(across))
+        };
+        // A little fuzz, the same every time the same pixel is painted
+        let fuzz = ((x * 7919 + y * 104_729) % 97) as f32 / 97.0;
+        top * 0.75 + under * 0.15 + fuzz * 0.1
+    };
+
+    let mut color = Vec::with_capacity(size * size * 4);
+    let mut normal = Vec::with_capacity(size * size * 4);
+    for y in 0..size {
+        for x in 0..size {
+            let shade = (0.8 + height(x, y) * 0.2) * 255.0;
+            color.extend_from_slice(&[shade as u8, shade as u8, shade as u8, 255]);
+            // The slope each way, measured across the pixels either side
+            let slope_x = height(x + 1, y) - height(x + size - 1, y);
+            let slope_y = height(x, y + 1) - height(x, y + size - 1);
+            let facing = Vec3::new(-slope_x * depth, -slope_y * depth, 1.0).normalize();
+            let encoded = (facing * 0.5 + 0.5) * 255.0; // -1..1 stored as 0..255
+            normal.extend_from_slice(&[encoded.x as u8, encoded.y as u8, encoded.z as u8, 255]);
+        }
+    }
+
+    let image = |pixels: Vec<u8>, format: TextureFormat| {
+        let mut image = Image::new(
+            Extent3d {
+                width: WEAVE_PIXELS,
+                height: WEAVE_PIXELS,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            pixels,
+            format,
+            RenderAssetUsages::RENDER_WORLD,
+        );
+        // Repeat the square rather than stretching its edge pixels
+        image.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+            address_mode_u: ImageAddressMode::Repeat,
+            address_mode_v: ImageAddressMode::Repeat,
+            ..ImageSamplerDescriptor::linear()
+        });
+        image
+    };
+    // The normal map holds directions, not colours, so it isn't stored as sRGB
+    (
+        image(color, TextureFormat::Rgba8UnormSrgb),
+        image(normal, TextureFormat::Rgba8Unorm),
+    )
+}
+
+// The neon strip along the inside of the rails, just under the top. It glows brighter
+// than anything lit, so with bloom on (see GraphicsQuality) it gives off a haze.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Trim {
+    pub color: String, // Hex colour
+    pub glow: f32,     // How brightly it shines - 0.0 is no trim at all
+}
+
+impl Default for Trim {
+    fn default() -> Self {
+        Self {
+            color: "#FF5300".to_string(),
+            glow: 6.0,
+        }
+    }
+}
+
+impl TableConfig {
+    // The top of the wall along the dealer's far side (`side` 1.0) or the player's side
+    // (`side` -1.0), halfway along - where the dealer's bank sits and winnings go
+    pub fn rail_top(&self, table_size: Vec2, side: f32) -> Vec3 {
+        let z = (table_size.y + self.wall_thickness) / 2.0;
+        Vec3::new(0.0, self.wall_height, z * side)
+    }
+}
+
+// The table the game was built with, used if the file is missing or broken
+impl Default for TableConfig {
+    fn default() -> Self {
+        Self {
+            size: TableSize::Standard,
+            wall_height: 1.0,
+            wall_thickness: 0.2,
+            felt: Felt {
+                color: "#0B0B0B".to_string(),
+                roughness: 0.9,
+                restitution: 0.1,
+                friction: 0.8,
+                weave: default_weave(),
+            },
+            walls: Surface {
+                color: "#FF5300".to_string(),
+                metallic: 1.0,
+                roughness: 0.35,
+                restitution: 0.08,
+                friction: 0.5,
+            },
+            layout_lines: "#FF5300".to_string(),
+            trim: Trim::default(),
+        }
+    }
+}
+
+// The table file the game is using
+#[derive(Resource)]
+pub struct TableChoice {
+    pub config: Handle<TableConfig>,
+}
+
+impl FromWorld for TableChoice {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            config: world.resource::<AssetServer>().load("craps.table.ron"),
+        }
+    }
+}
+
+impl TableChoice {
+    // The loaded table, or the built-in one if the file couldn't be read
+    pub fn current(&self, configs: &Assets<TableConfig>) -> TableConfig {
+        configs.get(&self.config).cloned().unwrap_or_default()
+    }
+}
+
+fn wait_for_table_config(choice: Res<TableChoice>, mut loading: ResMut<LoadingAssets>) {
+    loading.add(&choice.config);
+}
+
+// Fit the printed layout to the table in the file, and to the game it deals
+fn apply_table_config(
+    choice: Res<TableChoice>,
+    configs: Res<Assets<TableConfig>>,
+    rules: Res<TableRules>,
+    registry: Res<BetRegistry>,
+    mut table_layout: ResMut<TableLayout>,
+) {
+    let size = choice.current(&configs).size.dimensions();
+    let sides = registry.len();
+    if table_layout.size != size
+        || table_layout.variant != rules.variant
+        || table_layout.side_bets != sides
+    {
+        *table_layout = TableLayout::new(size, rules.variant, sides);
+    }
+}
+
+// One of the four walls around the felt
+#[derive(Component, Clone, Copy)]
+pub struct Wall;
+
+// The far wall at +x. Both dice must bounce off it for a throw to count.
+#[derive(Component)]
+pub struct BackWall;
+
+// A printed bet zone on the felt, tagged with the spot it stands for
+#[derive(Component)]
+pub struct LayoutZone(pub BetSpot);
+
+// Everything printed on the felt (outlines and fills), so it can be printed again.
+// It remembers the palette it was printed in.
+#[derive(Component)]
+struct LayoutPrint(Palette);
+
+// The dealer's ON/OFF puck. When the round phase changes it hops from where it
+// is (`from`) to where it should be (`to`), flipping over on the way.
+#[derive(Component)]
+pub struct PointPuck {
+    from: Transform,
+    to: Transform,
+    progress: f32, // 0.0 = just left `from`, 1.0 = arrived at `to`
+}
+
+const PUCK_RADIUS: f32 = 0.11;
+const PUCK_HEIGHT: f32 = 0.04;
+const PUCK_MOVE_SECONDS: f32 = 0.6;
+const TRIM_HEIGHT: f32 = 0.04; // The neon strip round the rails
+const TRIM_DEPTH: f32 = 0.01; // How far it stands out from the wall
+const WEAVE_PIXELS: u32 = 64; // Pixels along each side of the felt's square of weave
+const WEAVE_THREADS: usize = 8; // Threads each way across the square
+const WEAVE_TILE: f32 = 0.25; // How much of the table one square covers
+
+// Where the puck should be for a given phase: on the point with the white "ON" side
+// up, or parked in the corner with the black "OFF" side up
+fn puck_transform(table_layout: &TableLayout, phase: RoundPhase) -> Transform {
+    let spot = table_layout.puck_position(phase.point());
+    let flip = if phase.point().is_some() {
+        Quat::IDENTITY // The white half is on top of the puck model
+    } else {
+        Quat::from_rotation_x(std::f32::consts::PI) // Upside down: black half on top
+    };
+    Transform::from_xyz(spot.x, 0.006 + PUCK_HEIGHT / 2.0, spot.y).with_rotation(flip)
+}
+
+// This function sets up the table - like arranging furniture in a room. It runs each
+// time the game moves to the table (see AppState in mod.rs).
+// The parameters are "resources" we can use to create things:
+fn setup_table(
+    mut commands: Commands, // The "commands" let us spawn entities (things in the world)
+    // 'mut' means we can modify it (mutable)
+    mut meshes: ResMut<Assets<Mesh>>, // Storage for 3D shapes (cube, plane, etc.)
+    // ResMut = Resource Mutable - we can add new meshes
+    mut materials: ResMut<Assets<StandardMaterial>>, // Storage for surface properties (color, shine)
+    mut images: ResMut<Assets<Image>>,               // Storage for pictures - the felt's weave
+    mut ambient: ResMut<AmbientLight>,               // Controls the general lighting in the scene
+    table_layout: Res<TableLayout>,                  // The table's size and bet zones
+    choice: Res<TableChoice>,                        // Which table file we're using
+    configs: Res<Assets<TableConfig>>,               // The loaded table files
+    settings: Res<Settings>,                         // The colour palette for the layout
+    registry: Res<BetRegistry>,                      // The names printed in the side bet boxes
+) {
+    let config = choice.current(&configs);
+
+    commands.spawn((
+        StateScoped(InGame),
+        DirectionalLight {
+            illuminance: 30000.0,
+            shadows_enabled: true,
+            color: Color::srgb(1.0, 0.92, 0.85),
+            ..default()
+        },
+        Transform {
+            translation: Vec3::new(-1.0, 5.0, -4.0),
+            rotation: Quat::from_rotation_x(-std::f32::consts::FRAC_PI_4),
+            ..default()
+        },
+    ));
+
+    // Set up ambient lighting - like turning on soft overhead lights
+    ambient.color = Color::WHITE; // White light (no color tint)
+    ambient.brightness = 0.7; // 70% brightness - not too harsh
+
+    // Define the craps table dimensions
+    let table_size_x = table_layout.size.x; // Table width (left-right)
+    let table_size_z = table_layout.size.y; // Table depth (front-back) - Vec2 calls it y
+
+    // Create the table surface mesh (3D shape)
+    let table_mesh = meshes.add(
+        // add() stores the mesh and returns a handle to it
+        Mesh::from(
+            Plane3d::default() // A flat plane facing upward
+                .mesh() // Convert to mesh data
+                .size(table_size_x, table_size_z), // Set the size
+        )
+        // The normal map needs to know which way is "across" the felt at each corner
+        .with_generated_tangents()
+        .unwrap(),
+    );
+
+    // Create the table material (how it looks) from the felt in the table file
+    let table_material = materials.add(config.felt.material(&mut images, table_layout.size));
+
+    // Create the table entity with visual and physics components
+    commands
+        .spawn((
+            StateScoped(InGame),
+            // First spawn with visual components
+            Mesh3d(table_mesh),             // The 3D shape to render
+            MeshMaterial3d(table_material), // How to render it (color, shine, etc.)
+            Transform::from_translation(Vec3::new(0.0, 0.0, 0.0)), // Position at origin
+        ))
+        // Chain .insert() calls to add physics components
+        .insert(RigidBody::Fixed) // Fixed = doesn't move (unlike Dynamic which falls)
+        .insert(Collider::cuboid(
+            // Invisible box for physics collisions
+            table_size_x / 2.0, // Half-width (cuboid uses half-extents)
+            0.05,               // Very thin (5cm thick)
+            table_size_z / 2.0, // Half-depth
+        ))
+        .insert(config.felt.physics()); // How bouncy and grippy the felt is
+
+    print_layout(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        &mut images,
+        &table_layout,
+        &config,
+        &registry,
+        settings.palette,
+    );
+
+    let wall_thickness = config.wall_thickness;
+    let wall_height = config.wall_height;
+    let half_x = table_size_x / 2.0;
+    let half_z = table_size_z / 2.0;
+    let long_wall = meshes.add(Cuboid::new(
+        wall_thickness,
+        wall_height,
+        table_size_z + wall_thickness * 2.0,
+    ));
+    let short_wall = meshes.add(Cuboid::new(
+        table_size_x + wall_thickness * 2.0,
+        wall_height,
+        wall_thickness,
+    ));
+
+    let wall_material = materials.add(config.walls.material());
+    // Every wall bounces the dice back the same way
+    let wall_physics = (config.walls.physics(), Wall);
+
+    // long sides
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Mesh3d(long_wall.clone()),
+            MeshMaterial3d(wall_material.clone()),
+            Transform::from_xyz(-half_x - wall_thickness / 2.0, wall_height / 2.0, 0.0),
+        ))
+        .insert(RigidBody::Fixed)
+        .insert(Collider::cuboid(
+            wall_thickness / 2.0,
+            wall_height / 2.0,
+            (table_size_z + wall_thickness * 2.0) / 2.0,
+        ))
+        .insert(wall_physics);
+
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Mesh3d(long_wall.clone()),
+            MeshMaterial3d(wall_material.clone()),
+            Transform::from_xyz(half_x + wall_thickness / 2.0, wall_height / 2.0, 0.0),
+        ))
+        .insert(RigidBody::Fixed)
+        .insert(Collider::cuboid(
+            wall_thickness / 2.0,
+            wall_height / 2.0,
+            (table_size_z + wall_thickness * 2.0) / 2.0,
+        ))
+        .insert(wall_physics)
+        // The far wall the shooter aims at. Rapier only reports collisions for colliders
+        // that ask for them, so this is the one wall that does.
+        .insert((BackWall, ActiveEvents::COLLISION_EVENTS));
+
+    // short sides
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Mesh3d(short_wall.clone()),
+            MeshMaterial3d(wall_material.clone()),
+            Transform::from_xyz(0.0, wall_height / 2.0, half_z + wall_thickness / 2.0),
+        ))
+        .insert(RigidBody::Fixed)
+        .insert(Collider::cuboid(
+            (table_size_x + wall_thickness * 2.0) / 2.0,
+            wall_height / 2.0,
+            wall_thickness / 2.0,
+        ))
+        .insert(wall_physics);
+
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Mesh3d(short_wall.clone()),
+            MeshMaterial3d(wall_material.clone()),
+            Transform::from_xyz(0.0, wall_height / 2.0, -half_z - wall_thickness / 2.0),
+        ))
+        .insert(RigidBody::Fixed)
+        .insert(Collider::cuboid(
+            (table_size_x + wall_thickness * 2.0) / 2.0,
+            wall_height / 2.0,
+            wall_thickness / 2.0,
+        ))
+        .insert(wall_physics);
+
+    // The neon trim: a thin strip on the inside face of each wall, with no collider,
+    // so the dice bounce off the wall as before
+    if config.trim.glow > 0.0 {
+        let color = Srgba::hex(&config.trim.color).unwrap_or(Srgba::WHITE);
+        let trim_material = materials.add(StandardMaterial {
+            base_color: color.into(),
+            emissive: LinearRgba::from(color) * config.trim.glow,
+            ..default()
+        });
+        let trim_y = wall_height - TRIM_HEIGHT;
+        let strips = [
+            (
+                Vec3::new(TRIM_DEPTH, TRIM_HEIGHT, table_size_z),
+                Vec3::new(-half_x + TRIM_DEPTH / 2.0, trim_y, 0.0),
+            ),
+            (
+                Vec3::new(TRIM_DEPTH, TRIM_HEIGHT, table_size_z),
+                Vec3::new(half_x - TRIM_DEPTH / 2.0, trim_y, 0.0),
+            ),
+            (
+                Vec3::new(table_size_x, TRIM_HEIGHT, TRIM_DEPTH),
+                Vec3::new(0.0, trim_y, half_z - TRIM_DEPTH / 2.0),
+            ),
+            (
+                Vec3::new(table_size_x, TRIM_HEIGHT, TRIM_DEPTH),
+                Vec3::new(0.0, trim_y, -half_z + TRIM_DEPTH / 2.0),
+            ),
+        ];
+        for (size, at) in strips {
+            commands.spawn((
+                StateScoped(InGame),
+                Mesh3d(meshes.add(Cuboid::from_size(size))),
+                MeshMaterial3d(trim_material.clone()),
+                Transform::from_translation(at),
+                NotShadowCaster,
+            ));
+        }
+    }
+
+    // The dealer's puck: two half-height discs glued together, white ("ON") on top
+    // and black ("OFF") underneath, so flipping it over switches what it says
+    let puck_half = meshes.add(Cylinder::new(PUCK_RADIUS, PUCK_HEIGHT / 2.0));
+    let puck_start = puck_transform(&table_layout, RoundPhase::ComeOut);
+    commands
+        .spawn((
+            StateScoped(InGame),
+            puck_start,
+            Visibility::default(),
+            PointPuck {
+                from: puck_start,
+                to: puck_start,
+                progress: 1.0, // Already where it belongs
+            },
+            Name::new("Point Puck"),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Mesh3d(puck_half.clone()),
+                MeshMaterial3d(materials.add(StandardMaterial {
+                    base_color: Color::WHITE,
+                    perceptual_roughness: 0.5,
+                    ..default()
+                })),
+                Transform::from_xyz(0.0, PUCK_HEIGHT / 4.0, 0.0),
+            ));
+            parent.spawn((
+                Mesh3d(puck_half),
+                MeshMaterial3d(materials.add(StandardMaterial {
+                    base_color: Color::BLACK,
+                    perceptual_roughness: 0.5,
+                    ..default()
+                })),
+                Transform::from_xyz(0.0, -PUCK_HEIGHT / 4.0, 0.0),
+            ));
+        });
+}
+
+// Print the layout on the felt: each bet zone is a thin outline (orange, on the
+// standard table) with a darker fill laid just inside it, floating a hair above the
+// table so it doesn't flicker against the felt. The lettering goes on top, in the
+// outlines' ink. It's all worked out from the zones, so a table file of another size,
+// a crapless table's extra boxes and the side bets' boxes all get their own markings.
+fn print_layout(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    images: &mut Assets<Image>,
+    table_layout: &TableLayout,
+    config: &TableConfig,
+    registry: &BetRegistry,
+    palette: Palette,
+) {
+    let zone_mesh = meshes.add(Plane3d::default().mesh().size(1.0, 1.0)); // Stretched per zone
+    let lines = palette
+        .layout_lines()
+        .unwrap_or(config.layout_lines.as_str());
+    let ink: Color = Srgba::hex(lines).unwrap_or(Srgba::WHITE).into();
+    let line_material = materials.add(StandardMaterial {
+        base_color: ink,
+        perceptual_roughness: 0.9, // Printed ink is matte
+        ..default()
+    });
+    let fill_materials: Vec<Handle<StandardMaterial>> = palette
+        .layout_fills()
+        .into_iter()
+        .map(|hex| {
+            materials.add(StandardMaterial {
+                base_color: Srgba::hex(hex).unwrap().into(),
+                perceptual_roughness: 0.9,
+                ..default()
+            })
+        })
+        .collect();
+    const LINE_WIDTH: f32 = 0.015;
+
+    for zone in &table_layout.zones {
+        let center = zone.center();
+        let size = zone.size();
+        let fill = match zone.spot {
+            BetSpot::PassLine | BetSpot::DontPass | BetSpot::Come | BetSpot::DontCome => 0,
+            BetSpot::Field => 1,
+            BetSpot::NumberBox(_) => 2,
+            BetSpot::NumberStrip(_) => 3,
+            BetSpot::AnySeven | BetSpot::Horn | BetSpot::CAndE | BetSpot::AnyCraps => 4,
+            // The side bets stand out from the pass line they're printed along
+            BetSpot::Hop(_, _) | BetSpot::Side(_) => 5,
+            // Sic bo borrows the craps colours: even money like the line bets, the
+            // totals like the field, and the dice bets like the props and hops
+            BetSpot::SicBo(bet) => match bet {
+                SicBoBet::Small | SicBoBet::Big => 0,
+                SicBoBet::Total(_) => 1,
+                SicBoBet::Single(_) => 2,
+                SicBoBet::Triple(_) | SicBoBet::AnyTriple | SicBoBet::Double(_) => 4,
+                SicBoBet::Combination(_, _) => 5,
+            },
+        };
+        commands.spawn((
+            StateScoped(InGame),
+            Mesh3d(zone_mesh.clone()),
+            MeshMaterial3d(line_material.clone()),
+            Transform::from_xyz(center.x, 0.003, center.y)
+                .with_scale(Vec3::new(size.x, 1.0, size.y)),
+            LayoutPrint(palette),
+        ));
+        commands.spawn((
+            StateScoped(InGame),
+            Mesh3d(zone_mesh.clone()),
+            MeshMaterial3d(fill_materials[fill].clone()),
+            Transform::from_xyz(center.x, 0.006, center.y).with_scale(Vec3::new(
+                size.x - LINE_WIDTH * 2.0,
+                1.0,
+                size.y - LINE_WIDTH * 2.0,
+            )),
+            LayoutZone(zone.spot),
+            LayoutPrint(palette),
+        ));
+
+        let marking = match zone.spot {
+            BetSpot::Side(index) => registry
+                .rule(BetKind::Side(index))
+                .map(|rule| rule.name(&Locale::default()).to_uppercase()),
+            spot => spot.marking(),
+        };
+        if let Some(text) = marking {
+            let (columns, rows, pixels) = lettering_pixels(&text);
+            let pixel = letter_pixel_size(columns, rows, size);
+            let print = lettering_image(columns, rows, pixels);
+            commands.spawn((
+                StateScoped(InGame),
+                Mesh3d(zone_mesh.clone()),
+                MeshMaterial3d(materials.add(StandardMaterial {
+                    base_color: ink,
+                    base_color_texture: Some(images.add(print)),
+                    alpha_mode: AlphaMode::Mask(0.5), // Only the ink is drawn
+                    perceptual_roughness: 0.9,
+                    ..default()
+                })),
+                // Turned round to read from the player's side of the table, at -z
+                Transform::from_xyz(center.x, 0.009, center.y)
+                    .with_rotation(Quat::from_rotation_y(std::f32::consts::PI))
+                    .with_scale(Vec3::new(columns as f32, 1.0, rows as f32) * pixel),
+                LayoutPrint(palette),
+            ));
+        }
+    }
+}
+
+// The biggest the lettering's pixels get, so a long pass line's letters don't tower
+const MAX_LETTER_PIXEL: f32 = 0.045;
+
+// How big a pixel of a spot's lettering is printed: as big as lets it fit across most
+// of the zone and half its depth, up to the biggest letters a layout has
+fn letter_pixel_size(columns: usize, rows: usize, zone: Vec2) -> f32 {
+    (zone.x * 0.85 / columns as f32)
+        .min(zone.y * 0.5 / rows as f32)
+        .min(MAX_LETTER_PIXEL)
+}
+
+// The lettering as a texture, its pixels kept square-edged however big it's printed
+fn lettering_image(columns: usize, rows: usize, pixels: Vec<u8>) -> Image {
+    let mut image = Image::new(
+        Extent3d {
+            width: columns as u32,
+            height: rows as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        pixels,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    image.sampler = ImageSampler::nearest(); // Crisp letters, not a blur
+    image
+}
+
+// System that reprints the layout when the table switches games - the number boxes
+// of a crapless table aren't the standard ones. A networked table only learns the
+// host's rules once it has sat down. It's reprinted in a new colour palette too.
+fn relayout_system(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+    rules: Res<TableRules>,
+    registry: Res<BetRegistry>,
+    mut table_layout: ResMut<TableLayout>,
+    choice: Res<TableChoice>,
+    configs: Res<Assets<TableConfig>>,
+    settings: Res<Settings>,
+    printed_q: Query<(Entity, &LayoutPrint)>,
+) {
+    let new_game = rules.is_changed() && table_layout.variant != rules.variant;
+    let new_palette = settings.is_changed()
+        && printed_q
+            .iter()
+            .any(|(_, print)| print.0 != settings.palette);
+    if !new_game && !new_palette {
+        return;
+    }
+    if new_game {
+        *table_layout = TableLayout::new(table_layout.size, rules.variant, table_layout.side_bets);
+    }
+    for (printed, _) in &printed_q {
+        commands.entity(printed).despawn();
+    }
+    let config = choice.current(&configs);
+    print_layout(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        &mut images,
+        &table_layout,
+        &config,
+        &registry,
+        settings.palette,
+    );
+}
+
+// System that moves the puck on to the point when it is set, and back OFF when the
+// round ends, with a little hop and flip on the way. Sic bo has no point, so a sic bo
+// table puts the puck away.
+fn puck_system(
+    time: Res<Time>,
+    phase: Res<RoundPhase>,
+    table_layout: Res<TableLayout>,
+    mut puck_q: Query<(&mut PointPuck, &mut Transform, &mut Visibility)>,
+) {
+    let Ok((mut puck, mut transform, mut visibility)) = puck_q.single_mut() else {
+        return;
+    };
+    visibility.set_if_neq(if table_layout.variant == Variant::SicBo {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    });
+
+    // A new phase (or a reprinted layout) gives the puck somewhere new to go
+    if phase.is_changed() || table_layout.is_changed() {
+        let target = puck_transform(&table_layout, *phase);
+        if target.translation != puck.to.translation || target.rotation != puck.to.rotation {
+            puck.from = *transform;
+            puck.to = target;
+            puck.progress = 0.0;
+        }
+    }
+    if puck.progress >= 1.0 {
+        return;
+    }
+
+    puck.progress = (puck.progress + time.delta_secs() / PUCK_MOVE_SECONDS).min(1.0);
+    // "Smoothstep" easing - starts slow, speeds up, then slows into place
+    let t = puck.progress * puck.progress * (3.0 - 2.0 * puck.progress);
+    let hop = (puck.progress * std::f32::consts::PI).sin() * 0.25; // Up and back down
+
+    transform.translation = puck.from.translation.lerp(puck.to.translation, t) + Vec3::Y * hop;
+    transform.rotation = puck.from.rotation.slerp(puck.to.rotation, t);
+}
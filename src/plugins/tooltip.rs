@@ -0,0 +1,173 @@
+// A tooltip beside the pointer whenever it's over a bet zone: the bet a click there
+// would make, what it pays, whether the next roll decides it, when it may go down and
+// the table's limits on it - greyed out with the reason when it can't be bet right now.
+// It follows the mouse, the gamepad pointer or a tap, since all three land in
+// `PlayerInput::pointer`.
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use super::camera::PlayerCamera;
+use super::controls::PlayerInput;
+use super::{AppState, InGame};
+use crate::betting::TableBets;
+use crate::layout::TableLayout;
+use crate::locale::Locale;
+use crate::payout::{Cents, format_money};
+use crate::registry::BetRegistry;
+use crate::rules::{RoundPhase, TableRules};
+
+pub struct TooltipPlugin;
+
+impl Plugin for TooltipPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(InGame), setup_tooltip)
+            .add_systems(Update, tooltip_system.run_if(in_state(AppState::Playing)));
+    }
+}
+
+#[derive(Component)]
+struct Tooltip;
+
+#[derive(Component)]
+struct TooltipText;
+
+const TOOLTIP_OFFSET: Vec2 = Vec2::new(18.0, 18.0); // Down and to the right of the pointer
+const TOOLTIP_WIDTH: f32 = 260.0;
+const OPEN_COLOR: Color = Color::WHITE;
+const CLOSED_COLOR: Color = Color::srgb(0.6, 0.6, 0.6); // Can't be bet right now
+
+fn setup_tooltip(mut commands: Commands) {
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Px(TOOLTIP_WIDTH),
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.02, 0.02, 0.02, 0.9)),
+            BorderRadius::all(Val::Px(4.0)),
+            GlobalZIndex(1), // Over the other readouts
+            Visibility::Hidden,
+            Tooltip,
+        ))
+        .with_children(|tooltip| {
+            tooltip.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 13.0,
+                    ..default()
+                },
+                TextColor(OPEN_COLOR),
+                TooltipText,
+            ));
+        });
+}
+
+// System that moves the tooltip to the pointer and fills it in for the zone under it
+fn tooltip_system(
+    input: Res<PlayerInput>,
+    phase: Res<RoundPhase>,
+    bets: Res<TableBets>,
+    rules: Res<TableRules>,
+    table_layout: Res<TableLayout>,
+    locale: Res<Locale>,
+    registry: Res<BetRegistry>, // Names and odds of the side bets too
+    ui_scale: Res<UiScale>,
+    window_q: Query<&Window, With<PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<PlayerCamera>>,
+    mut tooltip_q: Query<(&mut Node, &mut Visibility), With<Tooltip>>,
+    mut text_q: Query<(&mut Text, &mut TextColor), With<TooltipText>>,
+) {
+    let Ok((mut node, mut visibility)) = tooltip_q.single_mut() else {
+        return;
+    };
+    // Where the pointed-at spot on the felt is on screen, whichever device pointed
+    let hovered = input.pointer.and_then(|point| {
+        let spot = table_layout.spot_at(point)?;
+        let (camera, camera_transform) = camera_q.single().ok()?;
+        let on_screen = camera
+            .world_to_viewport(camera_transform, Vec3::new(point.x, 0.0, point.y))
+            .ok()?;
+        Some((spot, on_screen))
+    });
+    let Some((spot, on_screen)) = hovered else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+    visibility.set_if_neq(Visibility::Inherited);
+
+    // Flip to the pointer's left near the right-hand edge, so it stays on screen. The
+    // screen is measured in the interface's own pixels, which the UI scale makes bigger.
+    let on_screen = on_screen / ui_scale.0;
+    let window_width = window_q.single().map_or(f32::MAX, Window::width) / ui_scale.0;
+    let mut at = on_screen + TOOLTIP_OFFSET;
+    if at.x + TOOLTIP_WIDTH > window_width {
+        at.x = on_screen.x - TOOLTIP_OFFSET.x - TOOLTIP_WIDTH;
+    }
+    node.left = Val::Px(at.x);
+    node.top = Val::Px(at.y);
+
+    let Ok((mut text, mut color)) = text_q.single_mut() else {
+        return;
+    };
+    let kind = spot.bet_kind(*phase, &bets);
+    let decided = if kind.is_one_roll() {
+        "One-roll bet: the next roll decides it"
+    } else {
+        "Stays up until it wins or loses"
+    };
+    let odds = registry.payout_odds(kind, *phase, &rules, &locale);
+    let mut lines = vec![
+        registry.bet_name(kind, &locale),
+        locale.fill("Pays {odds}", &[("odds", &odds)]),
+        locale.tr(decided).to_string(),
+        locale.tr(kind.placement_window(&rules)).to_string(),
+    ];
+    if kind.has_table_limits(*phase) {
+        // An old session's rules can leave either end of the limits off
+        match (rules.min_bet, rules.max_bet) {
+            (0, Cents::MAX) => {}
+            (min, Cents::MAX) => {
+                lines.push(locale.fill("{money} minimum", &[("money", &format_money(min))]))
+            }
+            (min, max) => lines.push(locale.fill(
+                "Table limits {min} to {max}",
+                &[("min", &format_money(min)), ("max", &format_money(max))],
+            )),
+        }
+    }
+    // Odds bets say how much more the table's odds limit leaves room for
+    if let Some(room) = bets.odds_room(kind, *phase, &rules) {
+        let limit = rules.odds.describe(&locale);
+        lines.push(match room {
+            0 => locale.fill("Full odds are down ({limit})", &[("limit", &limit)]),
+            room => locale.fill(
+                "{limit}: up to {money} more",
+                &[("limit", &limit), ("money", &format_money(room))],
+            ),
+        });
+    }
+    // Every bet is in whole dollars anyway, so only mention steps bigger than $1
+    let step = kind.increment(*phase);
+    if rules.enforce_increments && step > 100 {
+        lines.push(locale.fill("In multiples of {money}", &[("money", &format_money(step))]));
+    }
+    // Money isn't the question here, only whether the bet is open at all
+    let open = registry
+        .check_open(kind, *phase)
+        .and_then(|()| bets.check_open(kind, *phase, &rules));
+    if let Err(error) = open {
+        lines.push(locale.fill("Not now: {reason}", &[("reason", &error.message(&locale))]));
+    }
+    let words = lines.join("\n");
+    if text.0 != words {
+        text.0 = words;
+    }
+    color.set_if_neq(TextColor(if open.is_ok() {
+        OPEN_COLOR
+    } else {
+        CLOSED_COLOR
+    }));
+}
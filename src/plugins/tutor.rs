@@ -0,0 +1,286 @@
+// The bet tutor, for players learning the game (T, or the settings screen, turns it
+// on). It lights up the spots a careful player would bet on right now in green, and
+// pointing at any spot explains the bet that a click there would make, with its house
+// edge. Bets that give the house a big edge glow red under the pointer and come with
+// a warning. What it says comes from src/tutor.rs.
+use bevy::color::prelude::*;
+use bevy::prelude::*;
+
+use super::controls::PlayerInput;
+use super::settings::Settings;
+use super::{AppState, InGame};
+use crate::betting::TableBets;
+use crate::layout::{BetSpot, TableLayout};
+use crate::locale::Locale;
+use crate::registry::BetRegistry;
+use crate::rules::{RoundPhase, TableRules};
+use crate::tutor::{HIGH_EDGE_PERCENT, phase_hint, recommended_spots};
+
+pub struct TutorPlugin;
+
+impl Plugin for TutorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(InGame), (setup_tutor_assets, setup_tutor_panel))
+            .add_systems(
+                Update,
+                (
+                    toggle_tutor_system,
+                    (
+                        tutor_highlight_system,
+                        tutor_panel_system,
+                        highlight_pulse_system,
+                    ),
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Playing)),
+            );
+    }
+}
+
+// The shapes and colours the highlights share
+#[derive(Resource)]
+struct TutorAssets {
+    plane: Handle<Mesh>,
+    recommended: Handle<StandardMaterial>, // Green: a good bet to make now
+    warning: Handle<StandardMaterial>,     // Red: the spot under the pointer is a bad bet
+}
+
+// A glow laid over one bet zone
+#[derive(Component)]
+struct TutorHighlight;
+
+#[derive(Component)]
+struct TutorPanel;
+
+#[derive(Component)]
+struct TutorText;
+
+const HIGHLIGHT_HEIGHT: f32 = 0.009; // Just above the printed zones
+const RECOMMENDED_COLOR: Color = Color::srgb(0.2, 0.9, 0.3);
+const WARNING_COLOR: Color = Color::srgb(1.0, 0.25, 0.2);
+const HIGHLIGHT_ALPHA: f32 = 0.35;
+const PULSE_SPEED: f32 = 2.0; // Pulses a second
+
+fn highlight_material(color: Color) -> StandardMaterial {
+    StandardMaterial {
+        base_color: color.with_alpha(HIGHLIGHT_ALPHA),
+        emissive: color.to_linear() * 0.5,
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    }
+}
+
+fn setup_tutor_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(TutorAssets {
+        plane: meshes.add(Plane3d::default().mesh().size(1.0, 1.0)), // Stretched per zone
+        recommended: materials.add(highlight_material(RECOMMENDED_COLOR)),
+        warning: materials.add(highlight_material(WARNING_COLOR)),
+    });
+}
+
+fn setup_tutor_panel(mut commands: Commands) {
+    // Across the bottom of the screen, above the power meter's row
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(90.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            Visibility::Hidden,
+            TutorPanel,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    Node {
+                        max_width: Val::Px(560.0),
+                        padding: UiRect::all(Val::Px(10.0)),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgba(0.05, 0.05, 0.05, 0.85)),
+                    BorderRadius::all(Val::Px(6.0)),
+                ))
+                .with_children(|panel| {
+                    panel.spawn((
+                        Text::new(""), // Filled in by tutor_panel_system
+                        TextFont {
+                            font_size: 15.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                        TutorText,
+                    ));
+                });
+        });
+}
+
+// System that turns the tutor on and off
+fn toggle_tutor_system(input: Res<PlayerInput>, mut settings: ResMut<Settings>) {
+    if input.toggle_tutor {
+        settings.tutor = !settings.tutor;
+    }
+}
+
+// The spot under the pointer, if it's on the layout
+fn hovered_spot(input: &PlayerInput, table_layout: &TableLayout) -> Option<BetSpot> {
+    input.pointer.and_then(|point| table_layout.spot_at(point))
+}
+
+// System that lights up the recommended spots, and the spot under the pointer when
+// it's a bad bet. Rebuilt whenever the round, the bets or the pointed-at spot change.
+fn tutor_highlight_system(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    input: Res<PlayerInput>,
+    phase: Res<RoundPhase>,
+    bets: Res<TableBets>,
+    rules: Res<TableRules>,
+    table_layout: Res<TableLayout>,
+    registry: Res<BetRegistry>,
+    assets: Res<TutorAssets>,
+    highlights_q: Query<Entity, With<TutorHighlight>>,
+    mut last_hovered: Local<Option<BetSpot>>,
+) {
+    let hovered = hovered_spot(&input, &table_layout);
+    let moved = hovered != *last_hovered;
+    *last_hovered = hovered;
+    if !moved && !settings.is_changed() && !phase.is_changed() && !bets.is_changed() {
+        return;
+    }
+
+    for highlight in &highlights_q {
+        commands.entity(highlight).despawn();
+    }
+    if !settings.tutor {
+        return;
+    }
+    let mut lit: Vec<(BetSpot, Handle<StandardMaterial>)> =
+        recommended_spots(*phase, &bets, rules.variant)
+            .into_iter()
+            .map(|spot| (spot, assets.recommended.clone()))
+            .collect();
+    if let Some(spot) = hovered {
+        let kind = spot.bet_kind(*phase, &bets);
+        if registry
+            .house_edge(kind, &rules)
+            .is_some_and(|edge| edge >= HIGH_EDGE_PERCENT)
+        {
+            lit.push((spot, assets.warning.clone()));
+        }
+    }
+    for (spot, material) in lit {
+        let Some(zone) = table_layout.zone(spot) else {
+            continue;
+        };
+        let center = zone.center();
+        let size = zone.size();
+        commands.spawn((
+            StateScoped(InGame),
+            Mesh3d(assets.plane.clone()),
+            MeshMaterial3d(material),
+            Transform::from_xyz(center.x, HIGHLIGHT_HEIGHT, center.y)
+                .with_scale(Vec3::new(size.x, 1.0, size.y)),
+            TutorHighlight,
+        ));
+    }
+}
+
+// System that gently pulses the highlights so they catch the eye
+fn highlight_pulse_system(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    assets: Res<TutorAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if !settings.tutor {
+        return;
+    }
+    let beat = (time.elapsed_secs() * PULSE_SPEED * std::f32::consts::TAU).sin();
+    let alpha = HIGHLIGHT_ALPHA * (0.7 + 0.3 * beat);
+    for (handle, color) in [
+        (&assets.recommended, RECOMMENDED_COLOR),
+        (&assets.warning, WARNING_COLOR),
+    ] {
+        if let Some(material) = materials.get_mut(handle) {
+            material.base_color = color.with_alpha(alpha);
+        }
+    }
+}
+
+// System that explains the bet under the pointer, or gives a tip for the round when
+// the pointer isn't on a bet
+fn tutor_panel_system(
+    settings: Res<Settings>,
+    input: Res<PlayerInput>,
+    phase: Res<RoundPhase>,
+    bets: Res<TableBets>,
+    rules: Res<TableRules>,
+    table_layout: Res<TableLayout>,
+    locale: Res<Locale>,
+    registry: Res<BetRegistry>, // What the side bets say about themselves
+    mut panel_q: Query<&mut Visibility, With<TutorPanel>>,
+    mut text_q: Query<(&mut Text, &mut TextColor), With<TutorText>>,
+) {
+    if let Ok(mut visibility) = panel_q.single_mut() {
+        visibility.set_if_neq(if settings.tutor {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+    if !settings.tutor {
+        return;
+    }
+    let Ok((mut text, mut color)) = text_q.single_mut() else {
+        return;
+    };
+
+    let (words, tint) = match hovered_spot(&input, &table_layout) {
+        Some(spot) => {
+            let kind = spot.bet_kind(*phase, &bets);
+            let name = registry.bet_name(kind, &locale);
+            // A side bet's rule may not know its own edge
+            let edge = registry.house_edge(kind, &rules);
+            let mut words = match edge {
+                Some(edge) => locale.fill(
+                    "{bet} - house edge {edge}%",
+                    &[("bet", &name), ("edge", &format!("{edge:.2}"))],
+                ),
+                None => locale.fill("{bet} - house edge unknown", &[("bet", &name)]),
+            };
+            words.push('\n');
+            words.push_str(&registry.explain(kind, &rules, &locale));
+            if let Some(edge) = edge.filter(|&edge| edge >= HIGH_EDGE_PERCENT) {
+                words.push('\n');
+                words.push_str(&locale.fill(
+                    "Careful: on average the house keeps {cents} cents of every dollar bet here.",
+                    &[("cents", &format!("{edge:.0}"))],
+                ));
+                (words, WARNING_COLOR)
+            } else {
+                (words, Color::WHITE)
+            }
+        }
+        None => (
+            locale.fill(
+                "Tutor: {hint}",
+                &[("hint", &phase_hint(*phase, &bets, &rules, &locale))],
+            ),
+            RECOMMENDED_COLOR,
+        ),
+    };
+    // Only touch the text when it changes, so the UI isn't laid out again every frame
+    if text.0 != words {
+        text.0 = words;
+    }
+    color.set_if_neq(TextColor(tint));
+}
@@ -0,0 +1,966 @@
+// Everything drawn flat on the screen: the power meter (with "No more bets" beside it
+// while the dice are out), the bankroll readout, the list of payouts and the big
+// result callout after each roll.
+use bevy::color::prelude::*; // Color tools - for painting our 3D objects
+use bevy::prelude::*;
+
+use super::betting::{
+    BetRejected, BetResolved, BettingWindow, RollResolved, SelectedChip, TakeDownRefused,
+};
+use super::dice::{
+    DiceSkin, DiceSkinLibrary, NoRoll, NoRollReason, PIP_CELLS, ThrowConfig, ThrowPower, ThrowRng,
+};
+use super::locale::Localized;
+use super::replay::{InstantReplay, ReplayRequested};
+use super::settings::{Settings, SettingsScreen};
+use super::sicbo::SicBoResolved;
+use super::{AppState, InGame, RollStep, redraw};
+use crate::betting::{BetResult, TableBets};
+use crate::dice_set::DiceSet;
+use crate::locale::Locale;
+use crate::payout::{Bankroll, format_money};
+use crate::registry::BetRegistry;
+use crate::rules::{RollOutcome, TableRules};
+use crate::sicbo::{is_triple, total};
+
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(InGame),
+            (
+                setup_ui,
+                redraw::<Bankroll>,
+                redraw::<ThrowPower>,
+                redraw::<ThrowConfig>,
+                redraw::<DiceSet>,
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                power_meter_system,
+                sweet_spot_system,
+                no_more_bets_system,
+                callout_system.in_set(RollStep::Announce),
+                callout_animation_system,
+                bankroll_ui_system,
+                seed_text_system,
+                payout_ui_system,
+                bet_warning_system,
+                skin_button_system,
+                dice_set_button_system,
+                settings_button_system,
+                replay_button_system,
+            )
+                .run_if(in_state(AppState::Playing)),
+        );
+    }
+}
+
+// A component with no data - just a "tag" to mark entities
+// Like putting a name tag on something without writing anything on it
+#[derive(Component)]
+struct PowerMeterFill; // Marks which UI element shows the power level
+
+#[derive(Component)]
+struct SweetSpotBand; // The stretch of the meter marked as the power that throws well
+
+// The big result callout in the middle of the screen ("SEVEN OUT", "POINT IS 6")
+#[derive(Component)]
+struct Callout {
+    age: f32, // Seconds since it was last shown
+}
+
+#[derive(Component)]
+struct CalloutText; // The big headline
+
+#[derive(Component)]
+struct CalloutSubtext; // The smaller line underneath with the money
+
+#[derive(Component)]
+struct CalloutDie; // The white face of one of the dice icons (two, or three at sic bo)
+
+// One of the nine pip spots on a dice icon, laid out in a 3 x 3 grid:
+// 0 1 2
+// 3 4 5
+// 6 7 8
+#[derive(Component)]
+struct CalloutPip {
+    die: usize,  // Which die, from 0
+    cell: usize, // 0-8
+}
+
+const CALLOUT_POP_SECONDS: f32 = 0.2; // Time to grow to full size
+const CALLOUT_HOLD_SECONDS: f32 = 2.0; // Time it stays fully visible
+const CALLOUT_FADE_SECONDS: f32 = 0.6; // Time to fade away
+
+#[derive(Component)]
+struct NoMoreBetsText; // Beside the power meter, from the start of a throw until it's paid
+
+#[derive(Component)]
+struct BankrollText; // The bankroll readout in the top-left corner
+
+#[derive(Component)]
+struct SeedText; // The session's seed, above the power meter
+
+// Why the table just turned a bet down, under the bankroll until it times out
+#[derive(Component)]
+struct BetWarningText {
+    age: f32,
+}
+
+const BET_WARNING_SECONDS: f32 = 2.5;
+
+#[derive(Component)]
+struct PayoutText; // The list of what the last roll paid, under the bankroll
+
+#[derive(Component)]
+struct SkinButton; // The button in the bottom-right corner that changes the dice skin
+
+#[derive(Component)]
+struct SkinButtonText; // The skin's name on that button
+
+#[derive(Component)]
+struct DiceSetButton; // Above the settings button: picks how the shooter sets the dice
+
+#[derive(Component)]
+struct DiceSetButtonText;
+
+#[derive(Component)]
+struct ReplayButton; // "Instant replay", top-right, shown once a roll has settled
+
+#[derive(Component)]
+struct OpenSettingsButton; // Above the skin button: pauses and opens the settings screen
+
+const METER_WIDTH: f32 = 200.0; // Width of the power meter in pixels
+const METER_HEIGHT: f32 = 20.0; // Height in pixels
+
+fn setup_ui(
+    mut commands: Commands,
+    throw_rng: Res<ThrowRng>,
+    locale: Res<Locale>,
+    settings: Res<Settings>,
+    rules: Res<TableRules>, // How many dice the callout shows
+) {
+    // Power Meter UI - shows how hard you're throwing
+    commands
+        .spawn((
+            StateScoped(InGame),
+            // Create the meter background (dark gray bar)
+            Node {
+                // Node is Bevy's UI building block
+                width: Val::Px(METER_WIDTH), // Val::Px = value in pixels
+                height: Val::Px(METER_HEIGHT),
+                position_type: PositionType::Absolute, // Position relative to screen edges
+                left: Val::Px(20.0),                   // 20 pixels from left edge
+                bottom: Val::Px(20.0),                 // 20 pixels from bottom edge
+                ..default()                            // Other properties use defaults
+            },
+            BackgroundColor(Color::srgb(0.1, 0.1, 0.1)), // Dark gray background
+        ))
+        .with_children(|parent| {
+            // Add child nodes inside this one
+            parent.spawn((
+                // The fill bar that grows, in the palette's colour (green, usually)
+                Node {
+                    width: Val::Px(0.0),           // Starts at 0 width (empty)
+                    height: Val::Px(METER_HEIGHT), // Same height as parent
+                    ..default()
+                },
+                BackgroundColor(Srgba::hex(settings.palette.meter_color()).unwrap().into()),
+                PowerMeterFill, // Tag so we can find and update it later
+            ));
+            // Over the fill, so it still shows once the fill has passed it. Placed by
+            // sweet_spot_system.
+            parent.spawn((
+                Node {
+                    position_type: PositionType::Absolute,
+                    height: Val::Px(METER_HEIGHT),
+                    border: UiRect::horizontal(Val::Px(1.0)),
+                    ..default()
+                },
+                BackgroundColor(Color::srgba(1.0, 1.0, 1.0, 0.15)),
+                BorderColor(Color::srgba(1.0, 0.8, 0.3, 0.8)), // Gold edges
+                Visibility::Hidden,
+                SweetSpotBand,
+            ));
+        });
+
+    // Lit the moment the throw button goes down
+    commands.spawn((
+        StateScoped(InGame),
+        Localized("NO MORE BETS"),
+        TextFont {
+            font_size: 18.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.8, 0.3)), // Gold
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(20.0 + METER_WIDTH + 12.0), // Just right of the meter
+            bottom: Val::Px(18.0),
+            ..default()
+        },
+        Visibility::Hidden,
+        NoMoreBetsText,
+    ));
+
+    // The session's seed, just above the power meter
+    commands.spawn((
+        StateScoped(InGame),
+        Text::new(seed_label(&throw_rng, &locale)),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.6, 0.6, 0.6)),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(20.0),
+            bottom: Val::Px(20.0 + METER_HEIGHT + 6.0), // Just above the meter
+            ..default()
+        },
+        SeedText,
+    ));
+
+    // Result callout - headline, the dice icons and a money line, centred near the top.
+    // Hidden until the first roll is resolved.
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(12.0),
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center, // Centre the children horizontally
+                row_gap: Val::Px(10.0),
+                ..default()
+            },
+            Visibility::Hidden,
+            Callout { age: 0.0 },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 64.0,
+                    ..default()
+                },
+                TextColor(Srgba::hex("#FF5300").unwrap().into()),
+                CalloutText,
+            ));
+            // A row with the dice icons side by side
+            parent
+                .spawn(Node {
+                    column_gap: Val::Px(12.0),
+                    ..default()
+                })
+                .with_children(|row| {
+                    for die in 0..usize::from(rules.variant.dice_count()) {
+                        row.spawn((
+                            Node {
+                                width: Val::Px(48.0),
+                                height: Val::Px(48.0),
+                                padding: UiRect::all(Val::Px(5.0)),
+                                // A 3 x 3 grid of equal cells, one per possible pip spot
+                                display: Display::Grid,
+                                grid_template_columns: RepeatedGridTrack::flex(3, 1.0),
+                                grid_template_rows: RepeatedGridTrack::flex(3, 1.0),
+                                ..default()
+                            },
+                            BackgroundColor(Color::WHITE),
+                            BorderRadius::all(Val::Px(8.0)),
+                            CalloutDie,
+                        ))
+                        .with_children(|face| {
+                            for cell in 0..9 {
+                                face.spawn(Node {
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                })
+                                .with_children(|spot| {
+                                    spot.spawn((
+                                        Node {
+                                            width: Val::Px(8.0),
+                                            height: Val::Px(8.0),
+                                            ..default()
+                                        },
+                                        BackgroundColor(Color::BLACK),
+                                        BorderRadius::MAX, // Fully rounded - a circle
+                                        Visibility::Hidden,
+                                        CalloutPip { die, cell },
+                                    ));
+                                });
+                            }
+                        });
+                    }
+                });
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 26.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                CalloutSubtext,
+            ));
+        });
+
+    // Dice skin button in the bottom-right corner - each click moves to the next skin
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Button, // Bevy keeps the Interaction component up to date for us
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(20.0),
+                bottom: Val::Px(20.0),
+                padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.1, 0.1, 0.1)),
+            BorderRadius::all(Val::Px(6.0)),
+            SkinButton,
+        ))
+        .with_children(|parent| {
+            // The skin's name is filled in once the skins file loads
+            parent.spawn((
+                Text::new(locale.tr("Dice")),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                SkinButtonText,
+            ));
+        });
+
+    // Instant replay button in the top-right corner, hidden until there's a roll to show
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Button,
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(20.0),
+                top: Val::Px(20.0),
+                padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.1, 0.1, 0.1)),
+            BorderRadius::all(Val::Px(6.0)),
+            Visibility::Hidden,
+            ReplayButton,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Localized("Instant replay (R)"),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+        });
+
+    // Settings button, just above the skin button
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Button,
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(20.0),
+                bottom: Val::Px(56.0),
+                padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.1, 0.1, 0.1)),
+            BorderRadius::all(Val::Px(6.0)),
+            OpenSettingsButton,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Localized("Settings"),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+        });
+
+    // Dice set button, above the settings button - each click picks up the dice
+    // another way round for the next throw
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Button,
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(20.0),
+                bottom: Val::Px(92.0),
+                padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.1, 0.1, 0.1)),
+            BorderRadius::all(Val::Px(6.0)),
+            DiceSetButton,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                DiceSetButtonText,
+            ));
+        });
+
+    // Bankroll readout and payout list, stacked in the top-left corner
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(20.0),
+                top: Val::Px(20.0),
+                flex_direction: FlexDirection::Column, // Children stack top to bottom
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(""), // Filled in by bankroll_ui_system on the first frame
+                TextFont {
+                    font_size: 22.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                BankrollText,
+            ));
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 0.6, 0.2)), // Orange, so it's noticed
+                Visibility::Hidden,
+                BetWarningText {
+                    age: BET_WARNING_SECONDS,
+                },
+            ));
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.8, 0.8, 0.8)),
+                PayoutText,
+            ));
+        });
+}
+
+// System that grows the bar as the throw charges up, and empties it on release. It
+// takes on a new colour palette picked on the settings screen too.
+fn power_meter_system(
+    power: Res<ThrowPower>,
+    settings: Res<Settings>,
+    mut fill_query: Query<(&mut Node, &mut BackgroundColor), With<PowerMeterFill>>,
+) {
+    if !power.is_changed() && !settings.is_changed() {
+        return;
+    }
+    if let Ok((mut fill_node, mut fill_color)) = fill_query.single_mut() {
+        let percent = power.current / power.max; // 0.0 to 1.0
+        fill_node.width = Val::Px(percent * METER_WIDTH); // Scale to meter width
+        let color = Srgba::hex(settings.palette.meter_color()).unwrap().into();
+        fill_color.set_if_neq(BackgroundColor(color));
+    }
+}
+
+// System that marks the meter's sweet spot, wherever the meter file puts it
+fn sweet_spot_system(
+    config: Res<ThrowConfig>,
+    mut band_q: Query<(&mut Node, &mut Visibility), With<SweetSpotBand>>,
+) {
+    if !config.is_changed() {
+        return;
+    }
+    let Ok((mut node, mut visibility)) = band_q.single_mut() else {
+        return;
+    };
+    let Some((low, high)) = config.sweet_spot else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    let (low, high) = (low.clamp(0.0, 1.0), high.clamp(0.0, 1.0));
+    node.left = Val::Px(low.min(high) * METER_WIDTH);
+    node.width = Val::Px((high - low).abs() * METER_WIDTH);
+    *visibility = Visibility::Inherited;
+}
+
+// System that shows "No more bets" while the table is closed to bets
+fn no_more_bets_system(
+    window: Res<BettingWindow>,
+    mut text_q: Query<&mut Visibility, With<NoMoreBetsText>>,
+) {
+    if !window.is_changed() {
+        return;
+    }
+    for mut visibility in &mut text_q {
+        *visibility = match *window {
+            // A watcher is told so on the seats panel instead
+            BettingWindow::Open | BettingWindow::Watching => Visibility::Hidden,
+            BettingWindow::NoMoreBets => Visibility::Inherited,
+        };
+    }
+}
+
+// System that keeps the bankroll readout in the corner up to date
+fn bankroll_ui_system(
+    bankroll: Res<Bankroll>,
+    bets: Res<TableBets>,
+    selected: Res<SelectedChip>,
+    locale: Res<Locale>,
+    mut text_q: Query<&mut Text, With<BankrollText>>,
+) {
+    // is_changed() is true only on frames where something modified the resource
+    if !bankroll.is_changed()
+        && !bets.is_changed()
+        && !selected.is_changed()
+        && !locale.is_changed()
+    {
+        return;
+    }
+    if let Ok(mut text) = text_q.single_mut() {
+        text.0 = locale.fill(
+            "Bankroll {bankroll}   On the table {at_risk}   Chip {chip} (scroll to change)",
+            &[
+                ("bankroll", &format_money(bankroll.balance)),
+                ("at_risk", &format_money(bets.total_at_risk())),
+                ("chip", &format_money(selected.value())),
+            ],
+        );
+    }
+}
+
+// System that lists what the last roll paid (or took) under the bankroll readout
+fn payout_ui_system(
+    mut payout_events: EventReader<BetResolved>,
+    locale: Res<Locale>,
+    registry: Res<BetRegistry>, // Side bets go by their own names
+    mut text_q: Query<&mut Text, With<PayoutText>>,
+) {
+    let lines: Vec<String> = payout_events
+        .read()
+        .filter_map(|payout| {
+            let line = |english: &str, money| {
+                locale.fill(
+                    english,
+                    &[
+                        ("bet", &registry.bet_name(payout.bet.kind, &locale)),
+                        ("money", &format_money(money)),
+                    ],
+                )
+            };
+            match payout.result {
+                BetResult::Win(profit) => Some(line("{bet} wins {money}", profit)),
+                BetResult::Lose => Some(line("{bet} loses {money}", payout.bet.amount)),
+                BetResult::Push => Some(line("{bet} pushes, {money} back", payout.returned)),
+                // Come bets moving to their number aren't a payout
+                BetResult::Travel(_) | BetResult::Stay => None,
+            }
+        })
+        .collect();
+    if lines.is_empty() {
+        return;
+    }
+    if let Ok(mut text) = text_q.single_mut() {
+        text.0 = lines.join("\n");
+    }
+}
+
+// System that says why a bet was turned down (or had to stay up), then clears it after
+// a moment
+fn bet_warning_system(
+    time: Res<Time>,
+    mut rejected_events: EventReader<BetRejected>,
+    mut refused_events: EventReader<TakeDownRefused>,
+    locale: Res<Locale>,
+    registry: Res<BetRegistry>,
+    mut text_q: Query<(&mut Text, &mut Visibility, &mut BetWarningText)>,
+) {
+    let Ok((mut text, mut visibility, mut warning)) = text_q.single_mut() else {
+        return;
+    };
+    // Only the latest refusal matters
+    let rejected = rejected_events.read().last().map(|rejected| {
+        locale.fill(
+            "Can't put {money} on {bet}: {reason}",
+            &[
+                ("money", &format_money(rejected.amount)),
+                ("bet", &registry.bet_name(rejected.kind, &locale)),
+                ("reason", &rejected.error.message(&locale)),
+            ],
+        )
+    });
+    let refused = refused_events.read().last().map(|refused| {
+        locale.fill(
+            "Can't take {bet} down: {reason}",
+            &[
+                ("bet", &registry.bet_name(refused.kind, &locale)),
+                ("reason", &refused.error.message(&locale)),
+            ],
+        )
+    });
+    if let Some(warning_text) = rejected.or(refused) {
+        text.0 = warning_text;
+        warning.age = 0.0;
+    }
+    warning.age += time.delta_secs();
+    visibility.set_if_neq(if warning.age < BET_WARNING_SECONDS {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    });
+}
+
+// The headline for a resolved roll, the way a stickman would call it
+fn callout_headline(resolved: &RollResolved, locale: &Locale) -> String {
+    let [first, second] = resolved.die_values;
+    // A pair on 4, 6, 8 or 10 is rolled "the hard way"
+    let hard = first == second && matches!(resolved.total, 4 | 6 | 8 | 10);
+    let number = |easy: &str, hard_way: &str, number: u8| {
+        locale.fill(if hard { hard_way } else { easy }, &[("number", &number)])
+    };
+    match resolved.outcome {
+        RollOutcome::Natural(11) => locale.tr("YO 11 – WINNER").to_string(),
+        RollOutcome::Natural(total) => locale.fill("{number} – WINNER", &[("number", &total)]),
+        RollOutcome::Craps(total) => locale.fill("CRAPS {number}", &[("number", &total)]),
+        RollOutcome::PointSet(point) => {
+            number("POINT IS {number}", "POINT IS HARD {number}", point)
+        }
+        RollOutcome::PointMade(point) => {
+            number("{number} – WINNER", "HARD {number} – WINNER", point)
+        }
+        RollOutcome::SevenOut => locale.tr("SEVEN OUT").to_string(),
+        RollOutcome::NoDecision(total) => number("{number}", "HARD {number}", total),
+    }
+}
+
+// The headline for a sic bo throw: whether it came up small or big, or a triple, which
+// is neither
+fn sic_bo_headline(resolved: &SicBoResolved, locale: &Locale) -> String {
+    let dice = resolved.die_values;
+    let number = total(dice);
+    if is_triple(dice) {
+        locale.fill("TRIPLE {number}", &[("number", &dice[0])])
+    } else if number <= 10 {
+        locale.fill("SMALL {number}", &[("number", &number)])
+    } else {
+        locale.fill("BIG {number}", &[("number", &number)])
+    }
+}
+
+// System that fills in and shows the callout whenever a roll is resolved, or a
+// throw turns out to be no roll
+fn callout_system(
+    mut resolved_events: EventReader<RollResolved>,
+    mut sic_bo_events: EventReader<SicBoResolved>,
+    mut payout_events: EventReader<BetResolved>,
+    mut no_roll_events: EventReader<NoRoll>,
+    locale: Res<Locale>,
+    mut callout_q: Query<(&mut Callout, &mut Visibility)>,
+    mut headline_q: Query<&mut Text, With<CalloutText>>,
+    mut subtext_q: Query<&mut Text, (With<CalloutSubtext>, Without<CalloutText>)>,
+    mut pip_q: Query<(&CalloutPip, &mut Visibility), Without<Callout>>,
+) {
+    // Only the newest roll matters if (somehow) two arrived in one frame
+    let rolled = resolved_events
+        .read()
+        .last()
+        .map(|resolved| {
+            let headline = callout_headline(resolved, &locale);
+            (headline, resolved.short_roll, resolved.die_values.to_vec())
+        })
+        .or_else(|| {
+            let resolved = sic_bo_events.read().last()?;
+            let headline = sic_bo_headline(resolved, &locale);
+            Some((headline, resolved.short_roll, resolved.die_values.to_vec()))
+        });
+    let (headline, subtext, die_values) = if let Some((headline, short_roll, die_values)) = rolled {
+        // Add up what this roll paid back against what it took
+        let (mut won, mut lost) = (0, 0);
+        for payout in payout_events.read() {
+            match payout.result {
+                BetResult::Win(profit) => won += profit,
+                BetResult::Lose => lost += payout.bet.amount,
+                BetResult::Push | BetResult::Stay | BetResult::Travel(_) => {}
+            }
+        }
+        let money_line = match won.cmp(&lost) {
+            std::cmp::Ordering::Greater => {
+                locale.fill("You win {money}", &[("money", &format_money(won - lost))])
+            }
+            std::cmp::Ordering::Less => {
+                locale.fill("You lose {money}", &[("money", &format_money(lost - won))])
+            }
+            std::cmp::Ordering::Equal => String::new(),
+        };
+        let mut subtext = money_line;
+        if short_roll {
+            // Tacked on under the money so the shooter learns to throw harder
+            if !subtext.is_empty() {
+                subtext.push('\n');
+            }
+            subtext.push_str(locale.tr("Short roll - hit the back wall next time"));
+        }
+        (headline, subtext, die_values)
+    } else if let Some(no_roll) = no_roll_events.read().last() {
+        // Nothing was decided - blank dice and the reason underneath
+        let reason = match no_roll.reason {
+            NoRollReason::CockedDie(die) => {
+                locale.fill("Die {die} is cocked - rolling it again", &[("die", &die)])
+            }
+            NoRollReason::ShortRoll => locale
+                .tr("Short roll - both dice must hit the back wall")
+                .to_string(),
+            NoRollReason::OffTable(die) => locale.fill(
+                "Die {die} left the table - the dice go back to the shooter",
+                &[("die", &die)],
+            ),
+        };
+        (locale.tr("NO ROLL").to_string(), reason, Vec::new())
+    } else {
+        payout_events.clear();
+        return;
+    };
+
+    if let Ok(mut text) = headline_q.single_mut() {
+        text.0 = headline;
+    }
+    if let Ok(mut text) = subtext_q.single_mut() {
+        text.0 = subtext;
+    }
+    for (pip, mut visibility) in &mut pip_q {
+        // A value of 0 (or none at all) shows a blank die
+        let value = die_values.get(pip.die).copied().unwrap_or(0);
+        let cells = match usize::from(value) {
+            value @ 1..=6 => PIP_CELLS[value - 1],
+            _ => &[],
+        };
+        *visibility = if cells.contains(&pip.cell) {
+            Visibility::Inherited // Shown whenever its parent is shown
+        } else {
+            Visibility::Hidden
+        };
+    }
+    if let Ok((mut callout, mut visibility)) = callout_q.single_mut() {
+        callout.age = 0.0;
+        *visibility = Visibility::Inherited;
+    }
+}
+
+// System that pops the callout in, holds it, then fades it away
+fn callout_animation_system(
+    time: Res<Time>,
+    mut callout_q: Query<(&mut Callout, &mut Transform, &mut Visibility)>,
+    mut text_q: Query<
+        (&mut TextColor, Has<CalloutText>),
+        Or<(With<CalloutText>, With<CalloutSubtext>)>,
+    >,
+    mut die_q: Query<&mut BackgroundColor, (With<CalloutDie>, Without<CalloutPip>)>,
+    mut pip_q: Query<&mut BackgroundColor, (With<CalloutPip>, Without<CalloutDie>)>,
+) {
+    let Ok((mut callout, mut transform, mut visibility)) = callout_q.single_mut() else {
+        return;
+    };
+    if *visibility == Visibility::Hidden {
+        return;
+    }
+
+    callout.age += time.delta_secs();
+    let fade_start = CALLOUT_POP_SECONDS + CALLOUT_HOLD_SECONDS;
+    if callout.age >= fade_start + CALLOUT_FADE_SECONDS {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+
+    // Grow from 60% to full size, then shrink the opacity to nothing at the end
+    let pop = (callout.age / CALLOUT_POP_SECONDS).min(1.0);
+    transform.scale = Vec3::splat(0.6 + 0.4 * pop);
+    let alpha = 1.0 - ((callout.age - fade_start) / CALLOUT_FADE_SECONDS).clamp(0.0, 1.0);
+
+    for (mut color, is_headline) in &mut text_q {
+        let base: Color = if is_headline {
+            Srgba::hex("#FF5300").unwrap().into()
+        } else {
+            Color::WHITE
+        };
+        color.0 = base.with_alpha(alpha);
+    }
+    for mut color in &mut die_q {
+        color.0 = Color::WHITE.with_alpha(alpha);
+    }
+    for mut color in &mut pip_q {
+        color.0 = Color::BLACK.with_alpha(alpha);
+    }
+}
+
+fn seed_label(throw_rng: &ThrowRng, locale: &Locale) -> String {
+    locale.fill(
+        "Seed {seed} (run with --seed {seed} to replay these throws)",
+        &[("seed", &throw_rng.seed())],
+    )
+}
+
+// System that keeps the seed readout up to date, for when the debug console sets a new one
+fn seed_text_system(
+    throw_rng: Res<ThrowRng>,
+    locale: Res<Locale>,
+    mut text_q: Query<&mut Text, With<SeedText>>,
+) {
+    if !throw_rng.is_changed() && !locale.is_changed() {
+        return;
+    }
+    if let Ok(mut text) = text_q.single_mut() {
+        text.0 = seed_label(&throw_rng, &locale);
+    }
+}
+
+// System that switches skins when the skin button is clicked, and keeps its label
+// showing the skin the dice are wearing
+fn skin_button_system(
+    mut skin: ResMut<DiceSkin>,
+    libraries: Res<Assets<DiceSkinLibrary>>,
+    locale: Res<Locale>,
+    mut button_q: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<SkinButton>),
+    >,
+    mut text_q: Query<&mut Text, With<SkinButtonText>>,
+) {
+    for (interaction, mut color) in &mut button_q {
+        // Light up under the mouse so it's clear the button can be clicked
+        color.0 = match interaction {
+            Interaction::Pressed => Color::srgb(0.3, 0.3, 0.3),
+            Interaction::Hovered => Color::srgb(0.2, 0.2, 0.2),
+            Interaction::None => Color::srgb(0.1, 0.1, 0.1),
+        };
+        if *interaction == Interaction::Pressed {
+            skin.next(&libraries);
+        }
+    }
+
+    if !skin.is_changed() && !libraries.is_changed() && !locale.is_changed() {
+        return;
+    }
+    // The skins' names come from their file, so they stay as they're written
+    if let (Some(style), Ok(mut text)) = (skin.current(&libraries), text_q.single_mut()) {
+        text.0 = locale.fill("Dice: {skin} (click to change)", &[("skin", &style.name)]);
+    }
+}
+
+// System that picks the next dice set when its button is clicked, and names the set
+// (and the totals it's aiming for) on the button
+fn dice_set_button_system(
+    mut dice_set: ResMut<DiceSet>,
+    locale: Res<Locale>,
+    mut button_q: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<DiceSetButton>),
+    >,
+    mut text_q: Query<&mut Text, With<DiceSetButtonText>>,
+) {
+    for (interaction, mut color) in &mut button_q {
+        color.0 = match interaction {
+            Interaction::Pressed => Color::srgb(0.3, 0.3, 0.3),
+            Interaction::Hovered => Color::srgb(0.2, 0.2, 0.2),
+            Interaction::None => Color::srgb(0.1, 0.1, 0.1),
+        };
+        if *interaction == Interaction::Pressed {
+            *dice_set = dice_set.next();
+        }
+    }
+
+    if !dice_set.is_changed() && !locale.is_changed() {
+        return;
+    }
+    if let Ok(mut text) = text_q.single_mut() {
+        let set = locale.tr(dice_set.label());
+        text.0 = match dice_set.primary_totals() {
+            Some(totals) => locale.fill(
+                "Dice set: {set}, for {totals}",
+                &[
+                    ("set", &set),
+                    ("totals", &totals.map(|total| total.to_string()).join(", ")),
+                ],
+            ),
+            None => locale.fill("Dice set: {set}", &[("set", &set)]),
+        };
+    }
+}
+
+// System that pauses the game and opens the settings screen when the settings button
+// is clicked
+fn settings_button_system(
+    mut button_q: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<OpenSettingsButton>),
+    >,
+    mut screen: ResMut<SettingsScreen>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for (interaction, mut color) in &mut button_q {
+        color.0 = match interaction {
+            Interaction::Pressed => Color::srgb(0.3, 0.3, 0.3),
+            Interaction::Hovered => Color::srgb(0.2, 0.2, 0.2),
+            Interaction::None => Color::srgb(0.1, 0.1, 0.1),
+        };
+        if *interaction == Interaction::Pressed {
+            screen.open = true;
+            next_state.set(AppState::Paused);
+        }
+    }
+}
+
+// System that shows the replay button when there's a settled roll to replay, and asks
+// for the replay when it's clicked
+fn replay_button_system(
+    replay: Res<InstantReplay>,
+    mut requests: EventWriter<ReplayRequested>,
+    mut button_q: Query<
+        (Ref<Interaction>, &mut BackgroundColor, &mut Visibility),
+        With<ReplayButton>,
+    >,
+) {
+    let Ok((interaction, mut color, mut visibility)) = button_q.single_mut() else {
+        return;
+    };
+    let available = replay.is_ready() && !replay.is_playing();
+    visibility.set_if_neq(if available {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    });
+    let hover_color = match *interaction {
+        Interaction::Pressed => Color::srgb(0.3, 0.3, 0.3),
+        Interaction::Hovered => Color::srgb(0.2, 0.2, 0.2),
+        Interaction::None => Color::srgb(0.1, 0.1, 0.1),
+    };
+    color.set_if_neq(BackgroundColor(hover_color));
+    // Only on the click itself - holding the button down shouldn't replay again and again
+    if available && interaction.is_changed() && *interaction == Interaction::Pressed {
+        requests.write(ReplayRequested);
+    }
+}
@@ -0,0 +1,172 @@
+// Taking back a mis-click. Every chip put down since the last throw is remembered, most
+// recent last, and Ctrl+Z (or the Undo button) takes the latest back off the layout with
+// its money, commission and all. One press undoes one placement - a click on the layout,
+// a press, or one spot of the same bets or a preset. Once the dice go out it's too late:
+// it's no more bets as soon as the throw button goes down, the list is cleared with each
+// throw, and a bet taken down with a right-click drops out of it too.
+use bevy::prelude::*;
+
+use super::betting::{BetPlaced, BetTakenDown, BetUndone, BettingWindow, TakeDownRefused};
+use super::controls::PlayerInput;
+use super::dice::{DiceSettled, ThrowDice};
+use super::hotseat::Hotseat;
+use super::locale::Localized;
+use super::session::SessionPlayback;
+use super::{AppState, InGame, RollStep};
+use crate::betting::{Bet, BetError, TableBets};
+use crate::payout::{Bankroll, format_money};
+use crate::rules::TableRules;
+
+pub struct UndoPlugin;
+
+impl Plugin for UndoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlacedThisRoll>()
+            .add_systems(OnEnter(InGame), setup_undo_button)
+            .add_systems(
+                Update,
+                (
+                    // Once the roll is judged, so a bet made as the dice stop is cleared
+                    // with the rest of that roll's bets
+                    history_system.after(RollStep::Judge),
+                    undo_system,
+                )
+                    .run_if(in_state(AppState::Playing)),
+            );
+    }
+}
+
+// The chips put down since the last throw, in the order they went down
+#[derive(Resource, Debug, Default)]
+pub struct PlacedThisRoll {
+    pub bets: Vec<Bet>,
+    seat: Option<Entity>, // Whose they are, at a hotseat table
+}
+
+#[derive(Component)]
+struct UndoButton; // Above the same bets button, shown while there's something to undo
+
+const BUTTON_COLOR: Color = Color::srgb(0.1, 0.1, 0.1);
+
+fn setup_undo_button(mut commands: Commands, mut placed: ResMut<PlacedThisRoll>) {
+    placed.bets.clear(); // A new table starts with nothing to take back
+    commands
+        .spawn((
+            StateScoped(InGame),
+            Button,
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(20.0),
+                bottom: Val::Px(164.0),
+                padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                ..default()
+            },
+            BackgroundColor(BUTTON_COLOR),
+            BorderRadius::all(Val::Px(6.0)),
+            Visibility::Hidden,
+            UndoButton,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Localized("Undo (Ctrl+Z)"),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+        });
+}
+
+// System that keeps the list of chips put down since the last throw
+fn history_system(
+    mut placed_events: EventReader<BetPlaced>,
+    mut taken_events: EventReader<BetTakenDown>,
+    mut throw_events: EventReader<ThrowDice>,
+    mut dice_events: EventReader<DiceSettled>, // A networked table's other players never throw
+    mut placed: ResMut<PlacedThisRoll>,
+    hotseat: Option<Res<Hotseat>>,
+) {
+    let seat = hotseat.and_then(|hotseat| hotseat.in_control());
+    if placed.seat != seat {
+        // The next player's bets are on the layout now
+        placed.bets.clear();
+        placed.seat = seat;
+    }
+    for bet in placed_events.read() {
+        placed.bets.push(Bet::new(bet.kind, bet.amount));
+    }
+    for taken in taken_events.read() {
+        placed.bets.retain(|bet| bet.kind != taken.kind);
+    }
+    if throw_events.read().count() + dice_events.read().count() > 0 {
+        placed.bets.clear();
+    }
+}
+
+// System that takes the latest chips back off the layout when the button (or Ctrl+Z) is
+// pressed
+fn undo_system(
+    input: Res<PlayerInput>,
+    rules: Res<TableRules>,
+    window: Res<BettingWindow>,
+    mut placed: ResMut<PlacedThisRoll>,
+    mut bets: ResMut<TableBets>,
+    mut bankroll: ResMut<Bankroll>,
+    mut button_q: Query<
+        (Ref<Interaction>, &mut BackgroundColor, &mut Visibility),
+        With<UndoButton>,
+    >,
+    mut undone_events: EventWriter<BetUndone>,
+    mut refused_events: EventWriter<TakeDownRefused>,
+    playback: Option<Res<SessionPlayback>>, // A replayed session undoes its own bets
+) {
+    let Ok((interaction, mut color, mut visibility)) = button_q.single_mut() else {
+        return;
+    };
+    let offered = !placed.bets.is_empty() && playback.is_none();
+    visibility.set_if_neq(if offered {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    });
+    if interaction.is_changed() {
+        color.0 = match *interaction {
+            Interaction::Pressed => Color::srgb(0.3, 0.3, 0.3),
+            Interaction::Hovered => Color::srgb(0.2, 0.2, 0.2),
+            Interaction::None => BUTTON_COLOR,
+        };
+    }
+    let clicked = interaction.is_changed() && *interaction == Interaction::Pressed;
+    if !offered || !(clicked || input.undo_bet) {
+        return;
+    }
+    let Some(&bet) = placed.bets.last() else {
+        return;
+    };
+
+    let undone = window
+        .check()
+        .and_then(|()| bets.undo_placement(bet, &rules, &mut bankroll));
+    match undone {
+        Ok(()) => {
+            placed.bets.pop();
+            info!("Took {} back off {:?}", format_money(bet.amount), bet.kind);
+            undone_events.write(BetUndone {
+                kind: bet.kind,
+                amount: bet.amount,
+            });
+        }
+        Err(BetError::NotOnLayout) => {
+            // Gone some other way (the host's word on a networked table) - forget it
+            placed.bets.pop();
+        }
+        Err(error) => {
+            info!("Can't undo {:?}: {error}", bet.kind);
+            refused_events.write(TakeDownRefused {
+                kind: bet.kind,
+                error,
+            });
+        }
+    }
+}
@@ -0,0 +1,201 @@
+// VR, with `--features vr`, through OpenXR (the bevy_mod_openxr crates). The player
+// stands at the shooter's end of the table with a controller in their right hand:
+// pointing it at the layout aims (a laser shows where), the trigger puts a chip down
+// there, and the grip holds the dice - squeeze it, swing your arm and let go, and the
+// dice leave your hand as fast as it was moving.
+//
+// Everything here only turns the headset's world into a `TrackedHand` (see
+// controls.rs). From there a VR throw is an ordinary `ThrowDice` with a hand velocity,
+// so the dice, bets, replays and networked tables don't know a headset is involved.
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy_mod_xr::session::XrTrackingRoot;
+use bevy_xr_utils::tracking_utils::{TrackingUtilitiesPlugin, XrTrackedRightGrip};
+use bevy_xr_utils::xr_utils_actions::{
+    ActionType, ActiveSet, XRUtilsAction, XRUtilsActionSet, XRUtilsActionState,
+    XRUtilsActionsPlugin, XRUtilsBinding,
+};
+
+use super::controls::{PlayerInput, TrackedHand, read_input_system};
+use super::room::FLOOR_Y;
+use super::{AppState, InGame};
+use crate::layout::TableLayout;
+
+pub struct VrPlugin;
+
+impl Plugin for VrPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((TrackingUtilitiesPlugin, XRUtilsActionsPlugin))
+            .add_systems(Startup, setup_hand_buttons)
+            .add_systems(OnEnter(InGame), setup_hand)
+            // Read just before the rest of the input, so it's this frame's hand
+            .add_systems(PreUpdate, track_hand_system.before(read_input_system))
+            .add_systems(
+                Update,
+                (stand_at_table_system, laser_system).run_if(in_state(AppState::Playing)),
+            );
+    }
+}
+
+// Which of the controller's buttons an OpenXR action is
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum HandButton {
+    Grip,
+    Trigger,
+}
+
+const VR_SCALE: f32 = 2.75; // Table units to a metre: the felt stands at a real table's 80cm
+const VELOCITY_WINDOW: f32 = 0.08; // Seconds of hand movement averaged for a throw's speed
+
+// The buttons, bound for the common controllers. OpenXR picks whichever binding suits
+// the controller in the player's hand.
+fn setup_hand_buttons(mut commands: Commands) {
+    let set = commands
+        .spawn((
+            XRUtilsActionSet {
+                name: "craps".into(),
+                pretty_name: "Craps".into(),
+                priority: u32::MIN,
+            },
+            ActiveSet,
+        ))
+        .id();
+    let buttons = [
+        (
+            HandButton::Grip,
+            "grip",
+            "Hold the dice",
+            [
+                ("oculus/touch_controller", "squeeze/value"),
+                ("valve/index_controller", "squeeze/value"),
+                ("htc/vive_controller", "squeeze/click"),
+            ],
+        ),
+        (
+            HandButton::Trigger,
+            "bet",
+            "Place a bet",
+            [
+                ("oculus/touch_controller", "trigger/value"),
+                ("valve/index_controller", "trigger/click"),
+                ("htc/vive_controller", "trigger/click"),
+            ],
+        ),
+    ];
+    for (button, name, localized_name, bindings) in buttons {
+        let action = commands
+            .spawn((
+                XRUtilsAction {
+                    action_name: name.into(),
+                    localized_name: localized_name.into(),
+                    action_type: ActionType::Bool,
+                },
+                button,
+            ))
+            .id();
+        for (profile, input) in bindings {
+            let binding = commands
+                .spawn(XRUtilsBinding {
+                    profile: format!("/interaction_profiles/{profile}").into(),
+                    binding: format!("/user/hand/right/input/{input}").into(),
+                })
+                .id();
+            commands.entity(action).add_child(binding);
+        }
+        commands.entity(set).add_child(action);
+    }
+}
+
+// A small dark box in the player's hand, following the controller
+fn setup_hand(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn((
+        StateScoped(InGame),
+        Mesh3d(meshes.add(Cuboid::new(0.04, 0.04, 0.12))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: Color::srgb(0.1, 0.1, 0.1),
+            perceptual_roughness: 0.6,
+            ..default()
+        })),
+        Transform::default(),
+        XrTrackedRightGrip,
+    ));
+}
+
+// System that stands the player at the shooter's end of the table (-x), facing the
+// back wall, with the headset's metres scaled up to the table's units
+fn stand_at_table_system(
+    table_layout: Res<TableLayout>,
+    mut root_q: Query<&mut Transform, With<XrTrackingRoot>>,
+) {
+    let place = Transform::from_xyz(-table_layout.size.x / 2.0 - 0.8, FLOOR_Y, 0.0)
+        .with_rotation(Quat::from_rotation_y(-std::f32::consts::FRAC_PI_2)) // Forward is +x
+        .with_scale(Vec3::splat(VR_SCALE));
+    for mut transform in &mut root_q {
+        if *transform != place {
+            *transform = place;
+        }
+    }
+}
+
+// System that reads the controller into `TrackedHand`: where it is, how fast it's
+// been moving lately, and which buttons are held
+fn track_hand_system(
+    time: Res<Time>,
+    grip_q: Query<&GlobalTransform, With<XrTrackedRightGrip>>,
+    buttons_q: Query<(&XRUtilsActionState, &HandButton)>,
+    mut history: Local<VecDeque<(f32, Vec3)>>, // Where the hand was, and when
+    mut hand: ResMut<TrackedHand>,
+) {
+    let Ok(grip) = grip_q.single() else {
+        // Off the table, or no headset
+        history.clear();
+        *hand = TrackedHand::default();
+        return;
+    };
+    let pose = grip.compute_transform();
+
+    // The speed is measured over the last few frames rather than one, so a single
+    // jittery reading from the tracking doesn't fling the dice
+    let now = time.elapsed_secs();
+    history.push_back((now, pose.translation));
+    while history
+        .front()
+        .is_some_and(|&(at, _)| now - at > VELOCITY_WINDOW)
+    {
+        history.pop_front();
+    }
+    let velocity = match (history.front(), history.back()) {
+        (Some(&(start, from)), Some(&(end, to))) if end > start => (to - from) / (end - start),
+        _ => Vec3::ZERO,
+    };
+
+    let held = |which: HandButton| {
+        buttons_q.iter().any(|(state, &button)| {
+            button == which
+                && matches!(state, XRUtilsActionState::Bool(state) if state.current_state)
+        })
+    };
+    *hand = TrackedHand {
+        pose: Some(pose),
+        velocity,
+        grip: held(HandButton::Grip),
+        trigger: held(HandButton::Trigger),
+    };
+}
+
+// System that draws a laser from the controller to the spot it's pointing at
+fn laser_system(input: Res<PlayerInput>, hand: Res<TrackedHand>, mut gizmos: Gizmos) {
+    let (Some(pose), Some(point)) = (hand.pose, input.pointer) else {
+        return;
+    };
+    gizmos.line(
+        pose.translation,
+        Vec3::new(point.x, 0.01, point.y),
+        Color::srgba(1.0, 0.33, 0.0, 0.8), // Papaya orange, like the throw's path
+    );
+}
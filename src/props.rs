@@ -0,0 +1,213 @@
+// The one-roll props that stand on their own - the field, the horn, C&E and the hops -
+// written as `BetRule`s, the same way a registered bet is. Each is decided by the very
+// next roll and doesn't lean on any other bet, so nothing about it needs the engine.
+// They keep their `BetKind` variants, which are what session logs, presets and the
+// network carry, and `BetKind::prop` hands back the rule behind one: its placement,
+// resolution, payout, name and explanation all come from here.
+use crate::betting::BetResult;
+use crate::locale::Locale;
+use crate::payout::Cents;
+use crate::registry::{BetRule, SideRoll};
+use crate::rules::RoundPhase;
+
+// Wins on 2, 3, 4, 9, 10, 11 or 12, paying extra on the 2 and 12 as the table says
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldBet {
+    pub two_pays: u64,    // Times the bet on a 2...
+    pub twelve_pays: u64, // ...and on a 12
+}
+
+// Four equal bets: 2 and 12 pay 30:1, 3 and 11 pay 15:1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HornBet;
+
+// Two equal bets: any craps at 7:1 and eleven at 15:1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CAndEBet;
+
+// Exactly these two dice, the lower one first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HopBet {
+    pub low: u8,
+    pub high: u8,
+}
+
+// The next roll's total, the one a prop is decided by - None until there's been one
+fn next_total(rolls: &[SideRoll]) -> Option<u8> {
+    rolls.first().map(|roll| roll.dice[0] + roll.dice[1])
+}
+
+impl BetRule for FieldBet {
+    fn name(&self, locale: &Locale) -> String {
+        locale.tr("Field").to_string()
+    }
+
+    fn payout_odds(&self, locale: &Locale) -> String {
+        locale.fill(
+            "1:1, {two}:1 on the 2, {twelve}:1 on the 12",
+            &[("two", &self.two_pays), ("twelve", &self.twelve_pays)],
+        )
+    }
+
+    fn explain(&self, locale: &Locale) -> String {
+        locale
+            .tr(
+                "One roll: wins on 2, 3, 4, 9, 10, 11 or 12 (extra on the 2 and 12) \
+                 and loses on 5, 6, 7 or 8. Looks like lots of numbers, but the losing \
+                 ones come up more.",
+            )
+            .to_string()
+    }
+
+    // The field is decided on every single roll - it never stays up
+    fn resolve(&self, amount: Cents, rolls: &[SideRoll]) -> BetResult {
+        match next_total(rolls) {
+            None => BetResult::Stay,
+            Some(2) => BetResult::Win(amount * self.two_pays),
+            Some(12) => BetResult::Win(amount * self.twelve_pays),
+            Some(3 | 4 | 9 | 10 | 11) => BetResult::Win(amount),
+            Some(_) => BetResult::Lose, // 5, 6, 7 and 8
+        }
+    }
+}
+
+impl BetRule for HornBet {
+    fn name(&self, locale: &Locale) -> String {
+        locale.tr("Horn").to_string()
+    }
+
+    fn payout_odds(&self, locale: &Locale) -> String {
+        locale
+            .tr("30:1 on 2 or 12, 15:1 on 3 or 11, on a quarter of the bet")
+            .to_string()
+    }
+
+    fn explain(&self, locale: &Locale) -> String {
+        locale
+            .tr(
+                "One roll, split four ways across the 2, 3, 11 and 12. One part can win \
+                 while the other three lose.",
+            )
+            .to_string()
+    }
+
+    // The three quarters that missed are lost against the one that won
+    fn resolve(&self, amount: Cents, rolls: &[SideRoll]) -> BetResult {
+        match next_total(rolls) {
+            None => BetResult::Stay,
+            Some(2 | 12) => BetResult::Win(amount * (30 - 3) / 4),
+            Some(3 | 11) => BetResult::Win(amount * (15 - 3) / 4),
+            Some(_) => BetResult::Lose,
+        }
+    }
+}
+
+impl BetRule for CAndEBet {
+    fn name(&self, locale: &Locale) -> String {
+        locale.tr("C & E").to_string()
+    }
+
+    fn payout_odds(&self, locale: &Locale) -> String {
+        locale
+            .tr("7:1 on craps, 15:1 on 11, on half the bet")
+            .to_string()
+    }
+
+    fn explain(&self, locale: &Locale) -> String {
+        locale
+            .tr("One roll, split between any craps (2, 3 or 12) and the 11.")
+            .to_string()
+    }
+
+    // The half that missed is lost against the half that won
+    fn resolve(&self, amount: Cents, rolls: &[SideRoll]) -> BetResult {
+        match next_total(rolls) {
+            None => BetResult::Stay,
+            Some(2 | 3 | 12) => BetResult::Win(amount * (7 - 1) / 2),
+            Some(11) => BetResult::Win(amount * (15 - 1) / 2),
+            Some(_) => BetResult::Lose,
+        }
+    }
+}
+
+impl BetRule for HopBet {
+    fn name(&self, locale: &Locale) -> String {
+        locale.fill(
+            "Hop {low}-{high}",
+            &[("low", &self.low), ("high", &self.high)],
+        )
+    }
+
+    fn payout_odds(&self, _locale: &Locale) -> String {
+        if self.low == self.high {
+            "30:1".to_string()
+        } else {
+            "15:1".to_string()
+        }
+    }
+
+    fn explain(&self, locale: &Locale) -> String {
+        locale
+            .tr(
+                "One roll: the next throw has to show exactly these two dice. Pays 30 \
+                 to 1 for a pair, 15 to 1 otherwise.",
+            )
+            .to_string()
+    }
+
+    // Only a pair of real faces can be hopped, the lower one named first
+    fn can_place(&self, _phase: RoundPhase) -> bool {
+        (1..=6).contains(&self.low) && (self.low..=6).contains(&self.high)
+    }
+
+    // Hops need the individual dice, not just the total. A pair ("hard hop") pays
+    // 30:1, any other combination 15:1.
+    fn resolve(&self, amount: Cents, rolls: &[SideRoll]) -> BetResult {
+        let Some(roll) = rolls.first() else {
+            return BetResult::Stay;
+        };
+        let rolled = (
+            roll.dice[0].min(roll.dice[1]),
+            roll.dice[0].max(roll.dice[1]),
+        );
+        if rolled != (self.low, self.high) {
+            BetResult::Lose
+        } else if self.low == self.high {
+            BetResult::Win(amount * 30)
+        } else {
+            BetResult::Win(amount * 15)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rolled(dice: [u8; 2]) -> [SideRoll; 1] {
+        [SideRoll { dice, point: None }]
+    }
+
+    #[test]
+    fn a_prop_waits_for_its_roll() {
+        let field = FieldBet {
+            two_pays: 2,
+            twelve_pays: 3,
+        };
+        assert_eq!(field.resolve(500, &[]), BetResult::Stay);
+        assert_eq!(HornBet.resolve(400, &[]), BetResult::Stay);
+        assert_eq!(field.resolve(500, &rolled([6, 6])), BetResult::Win(1500));
+        // $1 on the eleven at 15:1, less the $1 on craps
+        assert_eq!(CAndEBet.resolve(200, &rolled([5, 6])), BetResult::Win(1400));
+    }
+
+    #[test]
+    fn only_real_faces_can_be_hopped() {
+        let hop = |low, high| HopBet { low, high }.can_place(RoundPhase::ComeOut);
+        assert!(hop(2, 5));
+        assert!(hop(6, 6));
+        assert!(!hop(5, 2)); // The lower die comes first
+        assert!(!hop(0, 3));
+        assert!(!hop(3, 7));
+    }
+}
@@ -0,0 +1,175 @@
+// Bets that aren't built into the game. Anything new is a `BetRule`: an object that
+// says when the bet may go down, how the rolls since then decide it and what it pays.
+// The `BetRegistry` resource holds them, and each one takes a side bet box on the
+// layout, where a bet on it is `BetKind::Side(n)` for the rule in box n.
+//
+// The bets every craps table has are still `BetKind`s, since they're saved in session
+// logs and presets and sent over the network as those values. The one-roll props that
+// stand alone - the field, horn, C&E and hops - are rules underneath (see props.rs),
+// handed back by `BetKind::prop`. The rest are decided by `BetKind::resolve`'s match:
+// they lean on each other in ways a rule on its own can't - odds sit behind a line
+// bet, a come bet travels to its number, buy and lay bets pay commission, and the
+// round itself is run from the pass line. So a rule can add a bet, but not change how
+// one of those works.
+//
+// A plugin adds a bet by registering its rule at startup, before the table is dealt:
+//
+//   fn add_my_bet(mut registry: ResMut<BetRegistry>) {
+//       registry.register(MyBet);
+//   }
+//
+// The side bet scripts (see side_bets.rs) are rules too.
+use bevy::prelude::*;
+
+use crate::betting::{Bet, BetError, BetKind, BetResult};
+use crate::locale::Locale;
+use crate::payout::Cents;
+use crate::rules::{RoundPhase, TableRules};
+use crate::tutor;
+
+// As many as fit in the row of boxes along the front of the pass line
+pub const MAX_REGISTERED_BETS: usize = 6;
+
+// What the tutor says about a registered bet that doesn't explain itself
+pub const SIDE_BET_EXPLAINED: &str =
+    "A side bet with rules and a payout of its own. Nobody has worked out its house edge.";
+
+// One roll, as a registered bet sees it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SideRoll {
+    pub dice: [u8; 2],
+    pub point: Option<u8>, // The point that was on when it was thrown
+}
+
+// A bet the engine doesn't know about itself
+pub trait BetRule: Send + Sync + 'static {
+    // What the bet is called, in the player's language
+    fn name(&self, locale: &Locale) -> String;
+
+    // What a win pays, like the odds printed on a layout
+    fn payout_odds(&self, locale: &Locale) -> String;
+
+    // How the bet works, in a sentence or two for the tutor
+    fn explain(&self, locale: &Locale) -> String {
+        locale.tr(SIDE_BET_EXPLAINED).to_string()
+    }
+
+    // The house edge in percent, if anyone has worked it out
+    fn house_edge(&self) -> Option<f64> {
+        None
+    }
+
+    // May the bet go down at this point in the round? Any time, unless the rule says
+    fn can_place(&self, phase: RoundPhase) -> bool {
+        let _ = phase;
+        true
+    }
+
+    // Decide a bet of `amount` against every roll since it went down, oldest first.
+    // `BetResult::Stay` leaves it up for the next roll.
+    fn resolve(&self, amount: Cents, rolls: &[SideRoll]) -> BetResult;
+}
+
+// Every registered bet, in the order of their boxes on the layout
+#[derive(Resource, Default)]
+pub struct BetRegistry {
+    rules: Vec<Box<dyn BetRule>>,
+}
+
+impl BetRegistry {
+    // Add a bet, handing back the kind a bet on it is - or None once every box is taken
+    pub fn register(&mut self, rule: impl BetRule) -> Option<BetKind> {
+        if self.rules.len() == MAX_REGISTERED_BETS {
+            return None;
+        }
+        self.rules.push(Box::new(rule));
+        Some(BetKind::Side(self.rules.len() as u8 - 1))
+    }
+
+    // How many boxes the layout needs
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    // The rule behind a bet on the layout, if it's a registered one
+    pub fn rule(&self, kind: BetKind) -> Option<&dyn BetRule> {
+        match kind {
+            BetKind::Side(index) => self.rules.get(usize::from(index)).map(|rule| &**rule),
+            _ => None,
+        }
+    }
+
+    // Check the rule lets the bet go down right now. Any built-in bet passes - the
+    // table's own checks (`TableBets::check_open`) cover those.
+    pub fn check_open(&self, kind: BetKind, phase: RoundPhase) -> Result<(), BetError> {
+        if !matches!(kind, BetKind::Side(_)) {
+            return Ok(());
+        }
+        match self.rule(kind) {
+            None => Err(BetError::NotOffered),
+            Some(rule) if !rule.can_place(phase) => Err(BetError::WrongPhase),
+            Some(_) => Ok(()),
+        }
+    }
+
+    // The bets in a pattern that their rules let go down right now, for putting a
+    // remembered pattern back on the layout
+    pub fn open_part(&self, pattern: &[Bet], phase: RoundPhase) -> Vec<Bet> {
+        pattern
+            .iter()
+            .filter(|bet| self.check_open(bet.kind, phase).is_ok())
+            .copied()
+            .collect()
+    }
+
+    // Decide a registered bet. One with no rule behind it (another player's, or from
+    // a script that has since been removed) comes back untouched rather than sitting
+    // on the layout for ever.
+    pub fn resolve(&self, kind: BetKind, amount: Cents, rolls: &[SideRoll]) -> BetResult {
+        match self.rule(kind) {
+            Some(rule) => rule.resolve(amount, rolls),
+            None => BetResult::Push,
+        }
+    }
+
+    // What's said about any bet, from its rule if it has one and from the built-in
+    // tables otherwise
+    pub fn bet_name(&self, kind: BetKind, locale: &Locale) -> String {
+        match self.rule(kind) {
+            Some(rule) => rule.name(locale),
+            None => tutor::bet_name(kind, locale),
+        }
+    }
+
+    pub fn payout_odds(
+        &self,
+        kind: BetKind,
+        phase: RoundPhase,
+        rules: &TableRules,
+        locale: &Locale,
+    ) -> String {
+        match self.rule(kind) {
+            Some(rule) => rule.payout_odds(locale),
+            None => kind.payout_odds(phase, rules, locale),
+        }
+    }
+
+    pub fn explain(&self, kind: BetKind, rules: &TableRules, locale: &Locale) -> String {
+        match self.rule(kind) {
+            Some(rule) => rule.explain(locale),
+            None => tutor::explain(kind, rules, locale),
+        }
+    }
+
+    // None when nobody knows
+    pub fn house_edge(&self, kind: BetKind, rules: &TableRules) -> Option<f64> {
+        match self.rule(kind) {
+            Some(rule) => rule.house_edge(),
+            None => Some(tutor::house_edge(kind, rules)),
+        }
+    }
+}
@@ -0,0 +1,57 @@
+// Loading game data from RON files. RON ("Rusty Object Notation") looks like Rust
+// struct syntax, so a data file reads just like the struct it fills in:
+//
+//     (name: "Classic", body: "#F4F1EA", pips: "#000000")
+//
+// `RonLoader` works for any asset type that serde can deserialize. Each asset type
+// registers it with its own file extension, like "skins.ron".
+use std::marker::PhantomData;
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::prelude::*;
+use serde::de::DeserializeOwned;
+
+// Everything that can go wrong reading one of our data files
+#[derive(Debug, thiserror::Error)]
+pub enum RonLoaderError {
+    #[error("could not read the file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse the file: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+pub struct RonLoader<T> {
+    extensions: &'static [&'static str],
+    _asset: PhantomData<fn() -> T>, // Remembers the asset type without storing one
+}
+
+impl<T> RonLoader<T> {
+    pub fn new(extensions: &'static [&'static str]) -> Self {
+        Self {
+            extensions,
+            _asset: PhantomData,
+        }
+    }
+}
+
+impl<T: Asset + DeserializeOwned> AssetLoader for RonLoader<T> {
+    type Asset = T;
+    type Settings = ();
+    type Error = RonLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<T, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
+    }
+}
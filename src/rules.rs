@@ -0,0 +1,526 @@
+// The craps rules engine - given where we are in a round and what was just rolled,
+// it decides what happened and what comes next. There is no rendering or physics in
+// here, only the rules of the game, so the rest of the app can trust it blindly.
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::locale::Locale;
+use crate::payout::Cents;
+
+// Where the table is in the current round. Stored as a resource so every system
+// can check "is the point on?" without tracking it themselves.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum RoundPhase {
+    #[default]
+    ComeOut, // No point yet - the next roll is a come-out roll
+    PointEstablished(u8), // A point (4, 5, 6, 8, 9 or 10, or any of them in crapless) is on
+    RoundOver,            // A decision was just made; the next roll starts a new come-out
+}
+
+impl RoundPhase {
+    // The current point, if there is one
+    pub fn point(self) -> Option<u8> {
+        match self {
+            RoundPhase::PointEstablished(point) => Some(point),
+            RoundPhase::ComeOut | RoundPhase::RoundOver => None,
+        }
+    }
+
+    // True when the next roll is a come-out roll
+    pub fn is_come_out(self) -> bool {
+        self.point().is_none()
+    }
+}
+
+// The house rules that vary from casino to casino. The table reads them from a file in
+// assets/rules/ (see the rules plugin), so a different casino is just a different file.
+#[derive(Asset, Resource, TypePath, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)] // Rules files only need to list what differs from the defaults
+pub struct TableRules {
+    pub variant: Variant,            // Standard craps, crapless, or sic bo
+    pub odds: OddsLimit,             // How much free odds may be taken behind a line bet
+    pub vig: VigPolicy,              // When the commission on buy and lay bets is collected
+    pub commission_percent: u64,     // The commission ("vig") on buy and lay bets
+    pub field_two_pays: u64,         // The field pays this many times the bet on a 2...
+    pub field_twelve_pays: u64,      // ...and this many times on a 12
+    pub barred_number: u8,           // The craps number that ties don't bets (2 or 12)
+    pub come_odds_working: bool,     // Come odds stay up on the come-out instead of being off
+    pub buy_working: bool,           // Buy bets stay up on the come-out too
+    pub roll_mode: RollMode,         // Whether physics or a random draw decides the dice
+    pub short_roll: ShortRollPolicy, // What happens when a die doesn't reach the back wall
+    // The least and most that may sit on one spot (odds and the center props aside).
+    // Unlike the rules above, these are off when left out, so a session recorded
+    // before the table had limits replays with the bets it was played with.
+    #[serde(default)]
+    pub min_bet: Cents,
+    #[serde(default = "no_maximum")]
+    pub max_bet: Cents,
+    #[serde(default)]
+    pub enforce_increments: bool, // Odds, buy and lay bets must pay out in whole dollars
+}
+
+fn no_maximum() -> Cents {
+    Cents::MAX
+}
+
+impl Default for TableRules {
+    fn default() -> Self {
+        Self {
+            variant: Variant::default(),
+            odds: OddsLimit::default(),
+            vig: VigPolicy::default(),
+            commission_percent: 5, // The standard 5% vig
+            field_two_pays: 2,     // Double on the 2
+            field_twelve_pays: 3,  // Triple on the 12 (some casinos only double it)
+            barred_number: 12,     // "Bar 12" - a few old casinos bar the 2 instead
+            come_odds_working: false,
+            buy_working: false,
+            roll_mode: RollMode::default(),
+            short_roll: ShortRollPolicy::default(),
+            min_bet: 500,     // $5
+            max_bet: 500_000, // $5,000
+            enforce_increments: true,
+        }
+    }
+}
+
+// Which game the table deals. The rest of the rules engine asks the variant what a
+// number does on the come-out and which numbers can be points, rather than knowing the
+// numbers itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Variant {
+    #[default]
+    Standard, // 7 and 11 win on the come-out, 2, 3 and 12 are craps
+    // "Crapless craps": only a 7 wins on the come-out and nothing loses, because 2, 3,
+    // 11 and 12 become points like any other number. There are no don't bets - with
+    // no craps to win on, the don't side would have the edge over the house.
+    Crapless,
+    // Not craps at all: three dice, and every bet is decided by the next throw (see
+    // sicbo.rs). There's no come-out and no point, so none of the craps bets are taken.
+    SicBo,
+}
+
+// What a number thrown on a come-out roll does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComeOut {
+    Natural, // The pass line wins
+    Craps,   // The pass line loses
+    Point,   // The number becomes the point
+}
+
+impl Variant {
+    pub fn come_out(self, total: u8) -> ComeOut {
+        match (self, total) {
+            (_, 7) | (Variant::Standard, 11) => ComeOut::Natural,
+            (Variant::Standard, 2 | 3 | 12) => ComeOut::Craps,
+            _ => ComeOut::Point,
+        }
+    }
+
+    // Every number that can be a point, in the order their boxes run along the table
+    pub fn point_numbers(self) -> &'static [u8] {
+        match self {
+            Variant::Standard => &[4, 5, 6, 8, 9, 10],
+            Variant::Crapless => &[2, 3, 4, 5, 6, 8, 9, 10, 11, 12],
+            Variant::SicBo => &[],
+        }
+    }
+
+    pub fn is_point(self, number: u8) -> bool {
+        self.point_numbers().contains(&number)
+    }
+
+    pub fn has_dont_bets(self) -> bool {
+        self == Variant::Standard
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Variant::Standard => "Craps",
+            Variant::Crapless => "Crapless craps",
+            Variant::SicBo => "Sic bo",
+        }
+    }
+
+    // How many dice are thrown at once
+    pub fn dice_count(self) -> u8 {
+        match self {
+            Variant::Standard | Variant::Crapless => 2,
+            Variant::SicBo => 3,
+        }
+    }
+}
+
+// Buy and lay bets pay true odds, and the house makes its money with a commission
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum VigPolicy {
+    UpFront, // Charged the moment the bet is made, win or lose
+    #[default]
+    OnWin, // Only taken out of the winnings, so a losing bet costs no commission
+}
+
+// Who decides what the dice show
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum RollMode {
+    #[default]
+    Physics, // Whatever face ends up on top is the roll
+    // The result is drawn from a fair random number generator the moment the dice are
+    // thrown, and the dice are gently turned to show it as they come to rest. Every
+    // total then has exactly its true chance, however the physics behaves.
+    RngAuthoritative,
+}
+
+// Casinos require both dice to hit the back wall, so nobody can gently set them down
+// on the number they want. A throw that falls short is a "short roll".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ShortRollPolicy {
+    #[default]
+    Warn, // The roll counts, but the shooter is told to throw harder
+    ReRoll,        // The roll doesn't count and the dice go back to the shooter
+    ResolveAnyway, // The roll counts and nobody says a word
+}
+
+// The most free odds allowed, as a multiple of the line bet, for each pair of points.
+// The classic "3-4-5x" table allows 3x on 4/10, 4x on 5/9 and 5x on 6/8 - which
+// means a maximum odds bet always wins exactly 6x the line bet. A crapless table keeps
+// that going with 1x on 2/12 and 2x on 3/11.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OddsLimit {
+    pub two_twelve: u64, // Only points at a crapless table
+    pub three_eleven: u64,
+    pub four_ten: u64,
+    pub five_nine: u64,
+    pub six_eight: u64,
+}
+
+impl Default for OddsLimit {
+    fn default() -> Self {
+        Self {
+            two_twelve: 1,
+            three_eleven: 2,
+            four_ten: 3,
+            five_nine: 4,
+            six_eight: 5,
+        }
+    }
+}
+
+impl OddsLimit {
+    // The same multiple on every point, like a "10x odds" table
+    pub fn flat(multiple: u64) -> Self {
+        Self {
+            two_twelve: multiple,
+            three_eleven: multiple,
+            four_ten: multiple,
+            five_nine: multiple,
+            six_eight: multiple,
+        }
+    }
+
+    // The preset these limits match, if they're one of the usual ones
+    pub fn preset(self) -> Option<OddsPreset> {
+        OddsPreset::ALL
+            .into_iter()
+            .find(|preset| preset.limit() == self)
+    }
+
+    // How a casino would put it on the sign by the table
+    pub fn describe(self, locale: &Locale) -> String {
+        match self.preset() {
+            Some(preset) => locale.fill("{preset} odds", &[("preset", &preset.name())]),
+            None => locale.fill(
+                "{four}x/{five}x/{six}x odds on 4-10/5-9/6-8",
+                &[
+                    ("four", &self.four_ten),
+                    ("five", &self.five_nine),
+                    ("six", &self.six_eight),
+                ],
+            ),
+        }
+    }
+
+    // The odds multiple allowed on a given point
+    pub fn multiple(self, point: u8) -> u64 {
+        match point {
+            2 | 12 => self.two_twelve,
+            3 | 11 => self.three_eleven,
+            4 | 10 => self.four_ten,
+            5 | 9 => self.five_nine,
+            6 | 8 => self.six_eight,
+            _ => 0, // Not a point number - no odds allowed
+        }
+    }
+}
+
+// The odds schemes tables usually deal, picked with `--odds` when the game starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OddsPreset {
+    Double,        // 2x on every point
+    ThreeFourFive, // 3x on 4/10, 4x on 5/9, 5x on 6/8
+    Ten,           // 10x on every point
+    Hundred,       // 100x - a few casinos really do offer it
+}
+
+impl OddsPreset {
+    pub const ALL: [OddsPreset; 4] = [
+        OddsPreset::Double,
+        OddsPreset::ThreeFourFive,
+        OddsPreset::Ten,
+        OddsPreset::Hundred,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            OddsPreset::Double => "2x",
+            OddsPreset::ThreeFourFive => "3-4-5x",
+            OddsPreset::Ten => "10x",
+            OddsPreset::Hundred => "100x",
+        }
+    }
+
+    pub fn limit(self) -> OddsLimit {
+        match self {
+            OddsPreset::Double => OddsLimit::flat(2),
+            OddsPreset::ThreeFourFive => OddsLimit::default(),
+            OddsPreset::Ten => OddsLimit::flat(10),
+            OddsPreset::Hundred => OddsLimit::flat(100),
+        }
+    }
+
+    // Read a preset's name, as typed after `--odds` ("345x" and "3-4-5" work too)
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase();
+        let bare = name.trim_end_matches('x').replace('-', "");
+        match bare.as_str() {
+            "2" => Some(OddsPreset::Double),
+            "345" => Some(OddsPreset::ThreeFourFive),
+            "10" => Some(OddsPreset::Ten),
+            "100" => Some(OddsPreset::Hundred),
+            _ => None,
+        }
+    }
+}
+
+// The true odds of rolling a number before a 7, as (pays, for) - "2 for 1" etc.
+// Free odds are the only bet in the casino paid at these exact odds. Whether the
+// number can be a point at this table is up to the `Variant`.
+pub fn true_odds(point: u8) -> Option<(u64, u64)> {
+    match point {
+        2 | 12 => Some((6, 1)), // 1 way to roll it vs 6 ways to roll a 7
+        3 | 11 => Some((3, 1)), // 2 ways vs 6
+        4 | 10 => Some((2, 1)), // 3 ways vs 6
+        5 | 9 => Some((3, 2)),  // 4 ways vs 6
+        6 | 8 => Some((6, 5)),  // 5 ways vs 6
+        _ => None,
+    }
+}
+
+// What a single roll meant for the round
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum RollOutcome {
+    Natural(u8),    // 7 or 11 on the come-out (only 7 in crapless) - the pass line wins
+    Craps(u8),      // 2, 3 or 12 on the come-out - the pass line loses
+    PointSet(u8),   // Any other number on the come-out becomes the point
+    PointMade(u8),  // The point was rolled again before a 7 - the pass line wins
+    SevenOut,       // A 7 arrived before the point - the pass line loses
+    NoDecision(u8), // Anything else while the point is on
+}
+
+// Everything a bet needs to know about one roll
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Roll {
+    pub die_values: [u8; 2],
+    pub previous: RoundPhase, // The phase the roll was thrown in
+    pub outcome: RollOutcome,
+}
+
+impl Roll {
+    pub fn total(&self) -> u8 {
+        self.die_values[0] + self.die_values[1]
+    }
+
+    // Was this a come-out roll (no point on when it was thrown)?
+    pub fn is_come_out(&self) -> bool {
+        self.previous.is_come_out()
+    }
+}
+
+// Apply one roll to the round. Returns what the roll meant and the phase to move to.
+pub fn resolve_roll(phase: RoundPhase, total: u8, variant: Variant) -> (RollOutcome, RoundPhase) {
+    match phase.point() {
+        // Come-out roll (RoundOver behaves exactly like a fresh come-out)
+        None => match variant.come_out(total) {
+            ComeOut::Natural => (RollOutcome::Natural(total), RoundPhase::RoundOver),
+            ComeOut::Craps => (RollOutcome::Craps(total), RoundPhase::RoundOver),
+            ComeOut::Point => (
+                RollOutcome::PointSet(total),
+                RoundPhase::PointEstablished(total),
+            ),
+        },
+        // Point is on - only the point or a 7 matter
+        Some(point) if total == point => (RollOutcome::PointMade(point), RoundPhase::RoundOver),
+        Some(_) if total == 7 => (RollOutcome::SevenOut, RoundPhase::RoundOver),
+        Some(_) => (RollOutcome::NoDecision(total), phase),
+    }
+}
+
+// How many points in a row the shooter holding the dice has made. A seven-out passes
+// the dice to the next shooter, so the streak starts again from nothing.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ShooterStreak {
+    pub points_made: u32,
+}
+
+// How worked up the table is about the shooter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Heat {
+    Cold,   // No points made yet
+    Warm,   // One point made
+    Hot,    // Two in a row - a "hot shooter"
+    OnFire, // Three or more
+}
+
+impl ShooterStreak {
+    // The streak after a roll: one longer for a point made, back to zero on a
+    // seven-out, and unchanged by anything else
+    pub fn after(self, outcome: RollOutcome) -> Self {
+        match outcome {
+            RollOutcome::PointMade(_) => Self {
+                points_made: self.points_made + 1,
+            },
+            RollOutcome::SevenOut => Self::default(),
+            _ => self,
+        }
+    }
+
+    pub fn heat(self) -> Heat {
+        match self.points_made {
+            0 => Heat::Cold,
+            1 => Heat::Warm,
+            2 => Heat::Hot,
+            _ => Heat::OnFire,
+        }
+    }
+}
+
+// Who has the dice at a table with several seats. The dice go round the table
+// clockwise, one seat along after every seven-out. A player who is offered the dice
+// may turn them down before their first throw, and they go on to the next seat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShooterRotation {
+    pub seats: usize,
+    pub shooter: usize,   // The seat holding the dice
+    pub has_thrown: bool, // The shooter has rolled at least once this hand
+}
+
+impl ShooterRotation {
+    pub fn new(seats: usize) -> Self {
+        Self {
+            seats: seats.max(1),
+            shooter: 0,
+            has_thrown: false,
+        }
+    }
+
+    // The seat clockwise from this one, wrapping round
+    pub fn next_seat(self, seat: usize) -> usize {
+        (seat + 1) % self.seats
+    }
+
+    // Who has the dice after a roll: the next seat after a seven-out, otherwise the
+    // same shooter, who can no longer decline them
+    pub fn after(self, outcome: RollOutcome) -> Self {
+        match outcome {
+            RollOutcome::SevenOut => self.passed(),
+            _ => Self {
+                has_thrown: true,
+                ..self
+            },
+        }
+    }
+
+    // A shooter can only turn the dice down before they've thrown, and only if there's
+    // someone else to take them
+    pub fn may_decline(self) -> bool {
+        !self.has_thrown && self.seats > 1
+    }
+
+    // The shooter turned the dice down
+    pub fn declined(self) -> Self {
+        if self.may_decline() {
+            self.passed()
+        } else {
+            self
+        }
+    }
+
+    fn passed(self) -> Self {
+        Self {
+            shooter: self.next_seat(self.shooter),
+            has_thrown: false,
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn standard_come_out() {
+        let come_out = |total| resolve_roll(RoundPhase::ComeOut, total, Variant::Standard);
+        for total in [7, 11] {
+            assert_eq!(
+                come_out(total),
+                (RollOutcome::Natural(total), RoundPhase::RoundOver)
+            );
+        }
+        for total in [2, 3, 12] {
+            assert_eq!(
+                come_out(total),
+                (RollOutcome::Craps(total), RoundPhase::RoundOver)
+            );
+        }
+        for total in [4, 5, 6, 8, 9, 10] {
+            assert_eq!(
+                come_out(total),
+                (
+                    RollOutcome::PointSet(total),
+                    RoundPhase::PointEstablished(total)
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn crapless_come_out_makes_every_number_but_seven_a_point() {
+        let come_out = |total| resolve_roll(RoundPhase::RoundOver, total, Variant::Crapless);
+        assert_eq!(
+            come_out(7),
+            (RollOutcome::Natural(7), RoundPhase::RoundOver)
+        );
+        for total in [2, 3, 4, 5, 6, 8, 9, 10, 11, 12] {
+            assert_eq!(
+                come_out(total),
+                (
+                    RollOutcome::PointSet(total),
+                    RoundPhase::PointEstablished(total)
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn point_is_made_or_sevened_out() {
+        let phase = RoundPhase::PointEstablished(11);
+        let on_point = |total| resolve_roll(phase, total, Variant::Crapless);
+        assert_eq!(
+            on_point(11),
+            (RollOutcome::PointMade(11), RoundPhase::RoundOver)
+        );
+        assert_eq!(on_point(7), (RollOutcome::SevenOut, RoundPhase::RoundOver));
+        assert_eq!(on_point(2), (RollOutcome::NoDecision(2), phase));
+    }
+}
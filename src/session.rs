@@ -0,0 +1,193 @@
+// A whole session written down: every bet placed and every roll thrown, in order,
+// along with where the dice went on each throw. Replaying the log re-runs the money
+// through the same rules and bets code the live game uses, so a replayed session
+// always ends with exactly the same bankroll - there's no physics to disagree with.
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::betting::{Bet, BetError, BetKind, BetResult, TableBets};
+use crate::payout::{Bankroll, Cents, returned_to_player};
+use crate::rules::{Roll, RollOutcome, RoundPhase, TableRules, resolve_roll};
+
+// Everything needed to replay a session from the start
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionLog {
+    pub seed: u64,                 // The ThrowRng seed the session was played with
+    pub starting_balance: Cents,   // What the player sat down with
+    pub rules: TableRules,         // The house rules it was played under
+    pub events: Vec<SessionEvent>, // What happened, in order
+}
+
+// One thing that happened at the table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SessionEvent {
+    BetPlaced { kind: BetKind, amount: Cents },
+    BetTakenDown { kind: BetKind }, // With any odds behind it
+    BetUndone { kind: BetKind, amount: Cents }, // Chips put down since the last roll taken back
+    Rolled(RecordedRoll),
+}
+
+// A throw that settled: what it showed and how the dice got there
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedRoll {
+    pub die_values: [u8; 2],
+    pub short_roll: bool,
+    pub frames: Vec<RecordedFrame>,
+}
+
+// Both dice at one moment of a throw
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    pub time: f32,                       // Seconds since the dice left the hand
+    pub dice: Vec<([f32; 3], [f32; 4])>, // Position and rotation of die #1, die #2
+}
+
+impl RecordedRoll {
+    pub fn duration(&self) -> f32 {
+        self.frames.last().map_or(0.0, |frame| frame.time)
+    }
+
+    // Where each die was `time` seconds into the throw, blending between the two
+    // recorded frames either side of it
+    pub fn poses_at(&self, time: f32) -> Vec<(Vec3, Quat)> {
+        let after = self.frames.partition_point(|frame| frame.time < time);
+        let (Some(start), Some(end)) = (
+            self.frames.get(after.saturating_sub(1)),
+            self.frames
+                .get(after.min(self.frames.len().saturating_sub(1))),
+        ) else {
+            return Vec::new();
+        };
+        let span = end.time - start.time;
+        let t = if span > 0.0 {
+            ((time - start.time) / span).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        start
+            .dice
+            .iter()
+            .zip(&end.dice)
+            .map(|(&(from_at, from_turn), &(to_at, to_turn))| {
+                (
+                    Vec3::from(from_at).lerp(Vec3::from(to_at), t),
+                    Quat::from_array(from_turn).slerp(Quat::from_array(to_turn), t),
+                )
+            })
+            .collect()
+    }
+}
+
+// The money side of the table at some point in a session
+#[derive(Debug, Clone)]
+pub struct TableState {
+    pub phase: RoundPhase,
+    pub bets: TableBets,
+    pub bankroll: Bankroll,
+}
+
+impl SessionLog {
+    pub fn new(seed: u64, starting_balance: Cents, rules: TableRules) -> Self {
+        Self {
+            seed,
+            starting_balance,
+            rules,
+            events: Vec::new(),
+        }
+    }
+
+    // The table as the session began: no point, no bets, the starting bankroll
+    pub fn opening_state(&self) -> TableState {
+        TableState {
+            phase: RoundPhase::default(),
+            bets: TableBets::default(),
+            bankroll: Bankroll {
+                balance: self.starting_balance,
+            },
+        }
+    }
+
+    // The table just before event number `index` happened
+    pub fn state_before(&self, index: usize) -> TableState {
+        let mut state = self.opening_state();
+        for event in self.events.iter().take(index) {
+            // A bet that was accepted live is accepted again here, so ignoring the
+            // result is safe
+            let _ = state.apply(event, &self.rules);
+        }
+        state
+    }
+
+    // Where each roll sits in the event list
+    pub fn roll_indices(&self) -> Vec<usize> {
+        self.events
+            .iter()
+            .enumerate()
+            .filter(|(_, event)| matches!(event, SessionEvent::Rolled(_)))
+            .map(|(index, _)| index)
+            .collect()
+    }
+}
+
+impl TableState {
+    // Put a bet down, the same way a click on the layout does
+    pub fn place_bet(
+        &mut self,
+        kind: BetKind,
+        amount: Cents,
+        rules: &TableRules,
+    ) -> Result<Cents, BetError> {
+        self.bets
+            .buy_in(kind, amount, self.phase, rules, &mut self.bankroll)
+    }
+
+    // Take a bet back down, the same way a right-click on the layout does
+    pub fn take_down(&mut self, kind: BetKind) -> Result<Vec<Bet>, BetError> {
+        self.bets.take_down(kind, self.phase, &mut self.bankroll)
+    }
+
+    // Take back chips put down since the last roll, the same way Ctrl+Z does
+    pub fn undo_bet(&mut self, bet: Bet, rules: &TableRules) -> Result<(), BetError> {
+        self.bets.undo_placement(bet, rules, &mut self.bankroll)
+    }
+
+    // Judge a roll and settle every bet against it, the same way the live round and
+    // payout systems do. Hands back what the roll meant and what each bet did.
+    pub fn roll(
+        &mut self,
+        die_values: [u8; 2],
+        rules: &TableRules,
+    ) -> (RollOutcome, Vec<(Bet, BetResult)>) {
+        let previous = self.phase;
+        let (outcome, next) = resolve_roll(previous, die_values[0] + die_values[1], rules.variant);
+        self.phase = next;
+        let roll = Roll {
+            die_values,
+            previous,
+            outcome,
+        };
+        let decisions = self.bets.resolve_roll(&roll, rules);
+        for (bet, result) in &decisions {
+            self.bankroll.credit(returned_to_player(bet, *result));
+        }
+        (outcome, decisions)
+    }
+
+    fn apply(&mut self, event: &SessionEvent, rules: &TableRules) -> Result<(), BetError> {
+        match event {
+            SessionEvent::BetPlaced { kind, amount } => {
+                self.place_bet(*kind, *amount, rules)?;
+            }
+            SessionEvent::BetTakenDown { kind } => {
+                self.take_down(*kind)?;
+            }
+            SessionEvent::BetUndone { kind, amount } => {
+                self.undo_bet(Bet::new(*kind, *amount), rules)?;
+            }
+            SessionEvent::Rolled(roll) => {
+                self.roll(roll.die_values, rules);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,194 @@
+// Sic bo, the old Chinese game of three dice. There are no points and no rounds: every
+// bet on the layout is decided by the very next throw. The bets are on the total of the
+// three dice (small, big or an exact total), on a pair or a triple showing, on two
+// numbers showing together, and on single numbers. The sic bo plugin pays the bets
+// when the dice settle.
+use serde::{Deserialize, Serialize};
+
+use crate::betting::BetResult;
+use crate::locale::Locale;
+use crate::payout::Cents;
+
+// The three dice, in DiceId order
+pub type SicBoDice = [u8; 3];
+
+// Every bet on a sic bo layout. The odds are the usual Macau ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SicBoBet {
+    Small,               // A total of 4 to 10, but not a triple. Pays 1:1
+    Big,                 // A total of 11 to 17, but not a triple. Pays 1:1
+    Total(u8),           // Exactly this total, 4 to 17. Pays from 6:1 to 50:1
+    Triple(u8),          // All three dice showing this number. Pays 150:1
+    AnyTriple,           // All three dice the same, whatever the number. Pays 24:1
+    Double(u8),          // At least two dice showing this number. Pays 8:1
+    Combination(u8, u8), // Two different numbers both showing (lower first). Pays 5:1
+    Single(u8),          // This number on one, two or three dice. Pays 1:1, 2:1 or 3:1
+}
+
+// What an exact total pays. The middle totals come up most, so they pay least.
+pub fn total_pays(total: u8) -> Option<u64> {
+    match total {
+        4 | 17 => Some(50),
+        5 | 16 => Some(18),
+        6 | 15 => Some(14),
+        7 | 14 => Some(12),
+        8 | 13 => Some(8),
+        9..=12 => Some(6),
+        _ => None, // 3 and 18 can only be thrown as triples, which have bets of their own
+    }
+}
+
+pub fn is_triple(dice: SicBoDice) -> bool {
+    dice[0] == dice[1] && dice[1] == dice[2]
+}
+
+pub fn total(dice: SicBoDice) -> u8 {
+    dice.iter().sum()
+}
+
+impl SicBoBet {
+    // Every spot on the layout, in the order they're printed
+    pub fn all() -> Vec<SicBoBet> {
+        let mut bets = vec![SicBoBet::Small, SicBoBet::Big, SicBoBet::AnyTriple];
+        bets.extend((4..=17).map(SicBoBet::Total));
+        bets.extend((1..=6).map(SicBoBet::Triple));
+        bets.extend((1..=6).map(SicBoBet::Double));
+        for low in 1..=6 {
+            bets.extend((low + 1..=6).map(|high| SicBoBet::Combination(low, high)));
+        }
+        bets.extend((1..=6).map(SicBoBet::Single));
+        bets
+    }
+
+    // Is this a bet the layout really has? (No Total(3), no Combination(2, 2).)
+    pub fn is_valid(self) -> bool {
+        let face = |number: u8| (1..=6).contains(&number);
+        match self {
+            SicBoBet::Small | SicBoBet::Big | SicBoBet::AnyTriple => true,
+            SicBoBet::Total(number) => total_pays(number).is_some(),
+            SicBoBet::Triple(number) | SicBoBet::Double(number) | SicBoBet::Single(number) => {
+                face(number)
+            }
+            SicBoBet::Combination(low, high) => face(low) && face(high) && low < high,
+        }
+    }
+
+    // Decide the bet against a throw of the three dice
+    pub fn resolve(self, amount: Cents, dice: SicBoDice) -> BetResult {
+        let showing = |number: u8| dice.iter().filter(|&&die| die == number).count() as u64;
+        let total = total(dice);
+        let triple = is_triple(dice);
+        let wins = |pays: u64| BetResult::Win(amount * pays);
+        match self {
+            // A triple loses small and big - that's where the house makes its money
+            SicBoBet::Small if !triple && (4..=10).contains(&total) => wins(1),
+            SicBoBet::Big if !triple && (11..=17).contains(&total) => wins(1),
+            SicBoBet::Total(number) if number == total => {
+                total_pays(number).map_or(BetResult::Push, wins)
+            }
+            SicBoBet::Triple(number) if showing(number) == 3 => wins(150),
+            SicBoBet::AnyTriple if triple => wins(24),
+            SicBoBet::Double(number) if showing(number) >= 2 => wins(8),
+            SicBoBet::Combination(low, high) if showing(low) > 0 && showing(high) > 0 => wins(5),
+            // Paid once for each die showing the number
+            SicBoBet::Single(number) if showing(number) > 0 => wins(showing(number)),
+            _ => BetResult::Lose,
+        }
+    }
+
+    // What a win pays, as the odds printed on a layout
+    pub fn payout_odds(self, locale: &Locale) -> String {
+        match self {
+            SicBoBet::Small | SicBoBet::Big => locale.tr("1:1, loses on any triple").to_string(),
+            SicBoBet::Total(number) => match total_pays(number) {
+                Some(pays) => format!("{pays}:1"),
+                None => "-".to_string(),
+            },
+            SicBoBet::Triple(_) => "150:1".to_string(),
+            SicBoBet::AnyTriple => "24:1".to_string(),
+            SicBoBet::Double(_) => "8:1".to_string(),
+            SicBoBet::Combination(_, _) => "5:1".to_string(),
+            SicBoBet::Single(_) => locale
+                .tr("1:1 on one die, 2:1 on two, 3:1 on three")
+                .to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BET: Cents = 1_000;
+
+    #[test]
+    fn small_and_big_lose_on_a_triple() {
+        assert_eq!(SicBoBet::Small.resolve(BET, [1, 2, 4]), BetResult::Win(BET));
+        assert_eq!(SicBoBet::Small.resolve(BET, [2, 2, 2]), BetResult::Lose);
+        assert_eq!(SicBoBet::Small.resolve(BET, [6, 3, 2]), BetResult::Lose);
+        assert_eq!(SicBoBet::Big.resolve(BET, [6, 3, 2]), BetResult::Win(BET));
+        assert_eq!(SicBoBet::Big.resolve(BET, [5, 5, 5]), BetResult::Lose);
+    }
+
+    #[test]
+    fn totals_triples_and_doubles_pay_their_odds() {
+        assert_eq!(
+            SicBoBet::Total(4).resolve(BET, [1, 1, 2]),
+            BetResult::Win(BET * 50)
+        );
+        assert_eq!(
+            SicBoBet::Total(10).resolve(BET, [3, 3, 4]),
+            BetResult::Win(BET * 6)
+        );
+        assert_eq!(SicBoBet::Total(10).resolve(BET, [3, 3, 5]), BetResult::Lose);
+        assert_eq!(
+            SicBoBet::Triple(3).resolve(BET, [3, 3, 3]),
+            BetResult::Win(BET * 150)
+        );
+        assert_eq!(SicBoBet::Triple(3).resolve(BET, [3, 3, 4]), BetResult::Lose);
+        assert_eq!(
+            SicBoBet::AnyTriple.resolve(BET, [6, 6, 6]),
+            BetResult::Win(BET * 24)
+        );
+        // A triple counts as a double of its number too
+        assert_eq!(
+            SicBoBet::Double(6).resolve(BET, [6, 6, 6]),
+            BetResult::Win(BET * 8)
+        );
+        assert_eq!(SicBoBet::Double(6).resolve(BET, [6, 1, 2]), BetResult::Lose);
+    }
+
+    #[test]
+    fn combinations_and_singles_count_the_dice_showing() {
+        assert_eq!(
+            SicBoBet::Combination(2, 5).resolve(BET, [5, 1, 2]),
+            BetResult::Win(BET * 5)
+        );
+        assert_eq!(
+            SicBoBet::Combination(2, 5).resolve(BET, [2, 2, 1]),
+            BetResult::Lose
+        );
+        assert_eq!(
+            SicBoBet::Single(4).resolve(BET, [4, 1, 2]),
+            BetResult::Win(BET)
+        );
+        assert_eq!(
+            SicBoBet::Single(4).resolve(BET, [4, 4, 2]),
+            BetResult::Win(BET * 2)
+        );
+        assert_eq!(
+            SicBoBet::Single(4).resolve(BET, [4, 4, 4]),
+            BetResult::Win(BET * 3)
+        );
+        assert_eq!(SicBoBet::Single(4).resolve(BET, [1, 2, 3]), BetResult::Lose);
+    }
+
+    #[test]
+    fn every_spot_on_the_layout_is_a_real_bet() {
+        let all = SicBoBet::all();
+        assert!(all.iter().all(|bet| bet.is_valid()));
+        assert_eq!(all.len(), 3 + 14 + 6 + 6 + 15 + 6);
+        assert!(!SicBoBet::Total(3).is_valid());
+        assert!(!SicBoBet::Combination(2, 2).is_valid());
+    }
+}
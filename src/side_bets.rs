@@ -0,0 +1,173 @@
+// Side bets written by players rather than by us. Each one is a short script (in the
+// rhai language) kept in a `side_bets` folder next to the save files, so a regional
+// bet - a fire bet, an all-tall-small, whatever the local casino prints - can be added
+// without touching the game. A script has these functions:
+//
+//   fn name() { "Fire bet" }                        // What the layout calls it
+//   fn pays() { "Up to 999 to 1 for six points" }   // What it pays, in a few words
+//   fn can_place(point) { point == () }             // Optional: when it may go down
+//   fn decide(rolls) { ... }                        // What the rolls so far make of it
+//
+// `rolls` holds every roll since the bet went down, oldest first, each a map of
+// `dice` (the two dice), `total` and `point` (the point that was on when it was
+// thrown, or `()` on a come-out); `can_place` gets the point that's on now the same
+// way. `decide` answers `()` while the bet is still up, `false` once it loses, or a
+// number n when it wins n to 1 (0 hands the bet back).
+//
+// Each script that loads is a `BetRule` in the `BetRegistry` (see registry.rs).
+// Scripts are only read in a build with `--features side-bets`.
+#[cfg(feature = "side-bets")]
+pub use script::{ScriptedBet, load_script};
+
+#[cfg(feature = "side-bets")]
+mod script {
+    use std::sync::Arc;
+
+    use bevy::log::warn;
+    use rhai::{AST, Array, Dynamic, Engine, INT, ImmutableString, Map, Scope};
+
+    use crate::betting::BetResult;
+    use crate::locale::Locale;
+    use crate::payout::Cents;
+    use crate::registry::{BetRule, SideRoll};
+    use crate::rules::RoundPhase;
+
+    // Enough for any sensible bet over a long hand; a script stuck in a loop is cut
+    // off here instead of freezing the game
+    const MAX_OPERATIONS: u64 = 200_000;
+
+    // A side bet read from a script
+    pub struct ScriptedBet {
+        name: String,
+        pays: String,
+        engine: Arc<Engine>,
+        ast: AST,
+        has_can_place: bool, // Whether the script says when it may go down
+    }
+
+    // Read a side bet from the text of its script. The error says what was wrong with
+    // the script, for the log.
+    pub fn load_script(source: &str) -> Result<ScriptedBet, String> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        let ast = engine.compile(source).map_err(|error| error.to_string())?;
+        let name = call_text(&engine, &ast, "name")?;
+        // Saying what it pays is optional
+        let pays = call_text(&engine, &ast, "pays").unwrap_or_default();
+        let defines = |wanted: &str| ast.iter_functions().any(|function| function.name == wanted);
+        if !defines("decide") {
+            return Err("it has no decide(rolls) function".to_string());
+        }
+        let has_can_place = defines("can_place");
+        Ok(ScriptedBet {
+            name,
+            pays,
+            engine: Arc::new(engine),
+            ast,
+            has_can_place,
+        })
+    }
+
+    impl BetRule for ScriptedBet {
+        // Scripts aren't translated - the name is whatever the script says
+        fn name(&self, _locale: &Locale) -> String {
+            self.name.clone()
+        }
+
+        fn payout_odds(&self, _locale: &Locale) -> String {
+            if self.pays.is_empty() {
+                "-".to_string()
+            } else {
+                self.pays.clone()
+            }
+        }
+
+        fn can_place(&self, phase: RoundPhase) -> bool {
+            if !self.has_can_place {
+                return true;
+            }
+            let point = point_value(phase.point());
+            let answer =
+                self.engine
+                    .call_fn::<bool>(&mut Scope::new(), &self.ast, "can_place", (point,));
+            answer.unwrap_or_else(|error| {
+                warn!("The {} side bet's can_place went wrong: {error}", self.name);
+                false
+            })
+        }
+
+        fn resolve(&self, amount: Cents, rolls: &[SideRoll]) -> BetResult {
+            let rolls: Array = rolls.iter().map(roll_map).collect();
+            let answer =
+                self.engine
+                    .call_fn::<Dynamic>(&mut Scope::new(), &self.ast, "decide", (rolls,));
+            match answer {
+                Ok(answer) => decision(answer, amount, &self.name),
+                Err(error) => {
+                    // A broken bet shouldn't take the player's money
+                    warn!(
+                        "The {} side bet went wrong, so it's handed back: {error}",
+                        self.name
+                    );
+                    BetResult::Push
+                }
+            }
+        }
+    }
+
+    fn call_text(engine: &Engine, ast: &AST, function: &str) -> Result<String, String> {
+        engine
+            .call_fn::<ImmutableString>(&mut Scope::new(), ast, function, ())
+            .map(|text| text.to_string())
+            .map_err(|error| format!("{function}(): {error}"))
+    }
+
+    // A point as a script reads it: the number, or () when there isn't one
+    fn point_value(point: Option<u8>) -> Dynamic {
+        point.map_or(Dynamic::UNIT, |point| Dynamic::from_int(INT::from(point)))
+    }
+
+    // A roll the way a script reads it: #{ dice: [3, 4], total: 7, point: 6 }
+    fn roll_map(roll: &SideRoll) -> Dynamic {
+        let mut map = Map::new();
+        let dice = roll
+            .dice
+            .iter()
+            .map(|&die| Dynamic::from_int(INT::from(die)));
+        map.insert("dice".into(), Dynamic::from_array(dice.collect()));
+        let total = roll.dice[0] + roll.dice[1];
+        map.insert("total".into(), Dynamic::from_int(INT::from(total)));
+        map.insert("point".into(), point_value(roll.point));
+        Dynamic::from_map(map)
+    }
+
+    // What decide() answered: () stays up, false loses, a number n wins n to 1 and 0
+    // hands the bet back. So does anything else, with a warning - including a win too
+    // big to count.
+    fn decision(answer: Dynamic, amount: Cents, name: &str) -> BetResult {
+        if answer.is_unit() {
+            return BetResult::Stay;
+        }
+        if let Ok(won) = answer.as_bool() {
+            // true is as good as even money
+            return if won {
+                BetResult::Win(amount)
+            } else {
+                BetResult::Lose
+            };
+        }
+        let won = answer
+            .as_int()
+            .ok()
+            .and_then(|to_one| u64::try_from(to_one).ok())
+            .and_then(|to_one| amount.checked_mul(to_one));
+        match won {
+            Some(0) => BetResult::Push,
+            Some(profit) => BetResult::Win(profit),
+            None => {
+                warn!("The {name} side bet's answer makes no sense, so it's handed back");
+                BetResult::Push
+            }
+        }
+    }
+}
@@ -0,0 +1,160 @@
+// Statistics for the session being played: how the dice have come up, how each
+// shooter's hand went, and how the player's money has done since they sat down. The
+// stats plugin feeds it each judged roll and draws the panel.
+use bevy::prelude::*;
+
+use crate::payout::Cents;
+use crate::rules::RollOutcome;
+
+#[derive(Resource, Debug, Clone, Default)]
+pub struct SessionStats {
+    pub rolls: u32,
+    // How often each total has come up, indexed by the total (0 and 1 stay empty)
+    pub totals: [u32; 13],
+    pub points_made: u32,
+    pub seven_outs: u32,
+    pub hands: Vec<u32>,   // How many rolls each finished shooter threw, in order
+    pub current_hand: u32, // Rolls thrown by the shooter who has the dice now
+    pub hand_points: Vec<u32>, // Points each finished shooter made, in the same order
+    pub current_hand_points: u32, // Points made by the shooter who has the dice now
+    pub starting_balance: Cents, // What the player sat down with
+    pub peak_holding: Cents, // The most the player has held at once this session
+}
+
+impl SessionStats {
+    pub fn new(starting_balance: Cents) -> Self {
+        Self {
+            starting_balance,
+            peak_holding: starting_balance,
+            ..Self::default()
+        }
+    }
+
+    // Count one judged roll. A seven-out passes the dice on, so it ends the hand.
+    pub fn record(&mut self, total: u8, outcome: RollOutcome) {
+        self.rolls += 1;
+        if let Some(count) = self.totals.get_mut(usize::from(total)) {
+            *count += 1;
+        }
+        self.current_hand += 1;
+        match outcome {
+            RollOutcome::PointMade(_) => {
+                self.points_made += 1;
+                self.current_hand_points += 1;
+            }
+            RollOutcome::SevenOut => {
+                self.seven_outs += 1;
+                self.hands.push(self.current_hand);
+                self.current_hand = 0;
+                self.hand_points.push(self.current_hand_points);
+                self.current_hand_points = 0;
+            }
+            _ => {}
+        }
+    }
+
+    // The most rolls any one shooter has thrown, counting the hand still going
+    pub fn longest_hand(&self) -> u32 {
+        self.hands
+            .iter()
+            .copied()
+            .chain([self.current_hand])
+            .max()
+            .unwrap_or(0)
+    }
+
+    // The most points any one shooter has made, counting the hand still going
+    pub fn most_points_in_hand(&self) -> u32 {
+        self.hand_points
+            .iter()
+            .copied()
+            .chain([self.current_hand_points])
+            .max()
+            .unwrap_or(0)
+    }
+
+    // Keep track of the most the player has held, given what they have now (bankroll
+    // plus any chips still on the layout)
+    pub fn note_holding(&mut self, holding: Cents) {
+        self.peak_holding = self.peak_holding.max(holding);
+    }
+
+    // Average rolls per finished hand, if a shooter has sevened out yet
+    pub fn average_hand(&self) -> Option<f32> {
+        if self.hands.is_empty() {
+            return None;
+        }
+        Some(self.hands.iter().sum::<u32>() as f32 / self.hands.len() as f32)
+    }
+
+    // The share of all rolls so far that came up `total` (0.0 before the first roll)
+    pub fn frequency(&self, total: u8) -> f32 {
+        let count = self.totals.get(usize::from(total)).copied().unwrap_or(0);
+        count as f32 / self.rolls.max(1) as f32
+    }
+
+    // Up or down since sitting down, given what the player has now (bankroll plus any
+    // chips still on the layout). Positive is a win.
+    pub fn net(&self, holding: Cents) -> i64 {
+        holding as i64 - self.starting_balance as i64
+    }
+}
+
+// How often fair dice should show `total`: the number of ways two dice can make it
+// out of all 36 (a 7 can be made six ways, so it comes up 1 time in 6)
+pub fn chance_of_total(total: u8) -> f32 {
+    if !(2..=12).contains(&total) {
+        return 0.0;
+    }
+    let ways = 6 - (7 - i32::from(total)).abs();
+    ways as f32 / 36.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_seven_out_ends_the_hand() {
+        let mut stats = SessionStats::new(100_000);
+        stats.record(6, RollOutcome::PointSet(6));
+        stats.record(8, RollOutcome::NoDecision(8));
+        stats.record(6, RollOutcome::PointMade(6));
+        stats.record(4, RollOutcome::PointSet(4));
+        stats.record(7, RollOutcome::SevenOut);
+        stats.record(11, RollOutcome::Natural(11));
+
+        assert_eq!(stats.rolls, 6);
+        assert_eq!(stats.totals[6], 2);
+        assert_eq!(stats.totals[7], 1);
+        assert_eq!(stats.points_made, 1);
+        assert_eq!(stats.seven_outs, 1);
+        assert_eq!(stats.hands, vec![5]);
+        assert_eq!(stats.hand_points, vec![1]);
+        assert_eq!(stats.current_hand, 1);
+        assert_eq!(stats.longest_hand(), 5);
+        assert_eq!(stats.most_points_in_hand(), 1);
+        assert_eq!(stats.average_hand(), Some(5.0));
+        assert_eq!(stats.frequency(6), 2.0 / 6.0);
+    }
+
+    #[test]
+    fn money_is_counted_from_the_starting_stack() {
+        let mut stats = SessionStats::new(100_000);
+        assert_eq!(stats.average_hand(), None);
+        assert_eq!(stats.frequency(7), 0.0);
+        stats.note_holding(150_000);
+        stats.note_holding(90_000);
+        assert_eq!(stats.peak_holding, 150_000);
+        assert_eq!(stats.net(90_000), -10_000);
+    }
+
+    #[test]
+    fn fair_dice_chances_add_up_to_one() {
+        assert_eq!(chance_of_total(7), 6.0 / 36.0);
+        assert_eq!(chance_of_total(2), 1.0 / 36.0);
+        assert_eq!(chance_of_total(13), 0.0);
+        let sum: f32 = (2..=12).map(chance_of_total).sum();
+        assert!((sum - 1.0).abs() < 1e-6);
+    }
+}
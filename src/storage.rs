@@ -0,0 +1,88 @@
+// Somewhere to keep small bits of text between sessions - settings, saved games,
+// stats. On the desktop each key is a file in the operating system's usual place for
+// app data (see `backend::save_dir`); in the browser there's no file system, so each
+// key is an entry in the page's local storage.
+// Callers decide what the text is (usually RON); this module only stores it.
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum StorageError {
+    #[error("couldn't write the save file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("the browser won't let us save (local storage is unavailable or full)")]
+    Browser,
+}
+
+// Read back whatever was last saved under `key`, if anything
+pub fn load(key: &str) -> Option<String> {
+    backend::load(key)
+}
+
+// Save `contents` under `key`, replacing what was there
+pub fn save(key: &str, contents: &str) -> Result<(), StorageError> {
+    backend::save(key, contents)
+}
+
+// A folder beside the save files where the player can leave files of their own for
+// the game to read (side bet scripts, say). There's no such thing in the browser.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn folder(name: &str) -> std::path::PathBuf {
+    backend::save_dir().join(name)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod backend {
+    use std::path::PathBuf;
+
+    use directories::ProjectDirs;
+
+    use super::StorageError;
+
+    // ~/.local/share/bevy_craps on Linux, %APPDATA%\bevy_craps\data on Windows and
+    // ~/Library/Application Support/bevy_craps on macOS. A system with no home folder
+    // falls back to a `saves/` folder next to wherever the game was started.
+    pub fn save_dir() -> PathBuf {
+        ProjectDirs::from("", "", "bevy_craps").map_or_else(
+            || PathBuf::from("saves"),
+            |dirs| dirs.data_dir().to_path_buf(),
+        )
+    }
+
+    fn path(key: &str) -> PathBuf {
+        save_dir().join(format!("{key}.ron"))
+    }
+
+    pub fn load(key: &str) -> Option<String> {
+        std::fs::read_to_string(path(key)).ok() // A missing file just means nothing saved yet
+    }
+
+    pub fn save(key: &str, contents: &str) -> Result<(), StorageError> {
+        std::fs::create_dir_all(save_dir())?;
+        std::fs::write(path(key), contents)?;
+        Ok(())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod backend {
+    use super::StorageError;
+
+    const KEY_PREFIX: &str = "bevy_craps.";
+
+    fn local_storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok()? // None in private windows that block it
+    }
+
+    pub fn load(key: &str) -> Option<String> {
+        local_storage()?
+            .get_item(&format!("{KEY_PREFIX}{key}"))
+            .ok()?
+    }
+
+    pub fn save(key: &str, contents: &str) -> Result<(), StorageError> {
+        local_storage()
+            .ok_or(StorageError::Browser)?
+            .set_item(&format!("{KEY_PREFIX}{key}"), contents)
+            .map_err(|_| StorageError::Browser)
+    }
+}
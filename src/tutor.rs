@@ -0,0 +1,329 @@
+// What the tutor knows about each bet: its name, what it does in a sentence, and its
+// house edge - the share of every dollar bet the house keeps in the long run. Also
+// which spots a careful player would bet right now. The tutor plugin draws the hints.
+use crate::betting::{BetKind, BetResult, TableBets};
+use crate::layout::BetSpot;
+use crate::locale::Locale;
+use crate::payout::format_money;
+use crate::registry::SIDE_BET_EXPLAINED;
+use crate::rules::{Roll, RoundPhase, TableRules, Variant, VigPolicy, resolve_roll, true_odds};
+use crate::sicbo::SicBoBet;
+
+// Bets with a house edge at or above this many percent come with a warning
+pub const HIGH_EDGE_PERCENT: f64 = 5.0;
+
+// The well-known edges of the line bets, which take a whole round to work out
+const PASS_EDGE_PERCENT: f64 = 1.41;
+const DONT_PASS_EDGE_PERCENT: f64 = 1.36;
+// Turning the 2, 3 and 12 from losers into points costs the pass line its 11 on the
+// come-out, and the new points are hard to make
+const CRAPLESS_PASS_EDGE_PERCENT: f64 = 5.38;
+
+// What the bet is called, in the player's language
+pub fn bet_name(kind: BetKind, locale: &Locale) -> String {
+    let on = |english: &str, number: u8| locale.fill(english, &[("number", &number)]);
+    match kind {
+        BetKind::PassLine => locale.tr("Pass line").to_string(),
+        BetKind::DontPass => locale.tr("Don't pass").to_string(),
+        BetKind::Come => locale.tr("Come").to_string(),
+        BetKind::DontCome => locale.tr("Don't come").to_string(),
+        BetKind::ComePoint(number) => on("Come bet on the {number}", number),
+        BetKind::DontComePoint(number) => on("Don't come bet on the {number}", number),
+        BetKind::PassOdds => locale.tr("Pass line odds").to_string(),
+        BetKind::DontPassOdds => locale.tr("Don't pass odds").to_string(),
+        BetKind::ComeOdds(number) => on("Odds on the come {number}", number),
+        BetKind::DontComeOdds(number) => on("Odds on the don't come {number}", number),
+        BetKind::Buy(number) => on("Buy the {number}", number),
+        BetKind::Lay(number) => on("Lay the {number}", number),
+        BetKind::AnySeven => locale.tr("Any seven").to_string(),
+        BetKind::AnyCraps => locale.tr("Any craps").to_string(),
+        // The props written as rules name themselves, whatever the table pays
+        BetKind::Field | BetKind::Horn | BetKind::CAndE | BetKind::Hop(_, _) => kind
+            .prop(&TableRules::default())
+            .map(|prop| prop.name(locale))
+            .unwrap_or_default(),
+        BetKind::SicBo(bet) => sic_bo_name(bet, locale),
+        // Its rule has its real name (BetRegistry::bet_name); counted from 1 here
+        BetKind::Side(index) => on("Side bet {number}", index + 1),
+    }
+}
+
+fn sic_bo_name(bet: SicBoBet, locale: &Locale) -> String {
+    let on = |english: &str, number: u8| locale.fill(english, &[("number", &number)]);
+    match bet {
+        SicBoBet::Small => locale.tr("Small").to_string(),
+        SicBoBet::Big => locale.tr("Big").to_string(),
+        SicBoBet::Total(number) => on("Total of {number}", number),
+        SicBoBet::Triple(number) => on("Triple {number}s", number),
+        SicBoBet::AnyTriple => locale.tr("Any triple").to_string(),
+        SicBoBet::Double(number) => on("Double {number}s", number),
+        SicBoBet::Combination(low, high) => {
+            locale.fill("{low} and {high}", &[("low", &low), ("high", &high)])
+        }
+        SicBoBet::Single(number) => on("Single {number}", number),
+    }
+}
+
+// How the bet works, in a sentence or two for someone new to the game. The props
+// written as rules explain themselves.
+pub fn explain(kind: BetKind, rules: &TableRules, locale: &Locale) -> String {
+    match kind.prop(rules) {
+        Some(prop) => prop.explain(locale),
+        None => locale.tr(explain_in_english(kind, rules)).to_string(),
+    }
+}
+
+fn explain_in_english(kind: BetKind, rules: &TableRules) -> &'static str {
+    // Where this table's house rules change how a bet plays
+    let crapless = rules.variant == Variant::Crapless;
+    match kind {
+        BetKind::PassLine if crapless => {
+            return "Bet with the shooter. Only a 7 wins on the come-out and nothing \
+                    loses: every other number, even 2, 3, 11 and 12, becomes the point, and \
+                    the bet wins if it's rolled again before a 7.";
+        }
+        BetKind::Come if crapless => {
+            return "A pass line bet made while a point is on. A 7 on the next roll \
+                    wins, and any other number becomes its point.";
+        }
+        BetKind::DontPass if rules.barred_number == 2 => {
+            return "Bet against the shooter: the pass line turned round. Wins on 3 or 12 \
+                    on the come-out (2 is a tie), and wins if a 7 comes before the point.";
+        }
+        BetKind::Buy(_) if rules.buy_working => {
+            return "Bets the number comes before a 7, paid at true odds less a commission. \
+                    This table works it on the come-out too.";
+        }
+        _ => {}
+    }
+    match kind {
+        BetKind::PassLine => {
+            "Bet with the shooter. Wins on a 7 or 11 on the come-out, loses on 2, 3 or 12. \
+             Any other number becomes the point, and the bet wins if it's rolled again before a 7."
+        }
+        BetKind::DontPass => {
+            "Bet against the shooter: the pass line turned round. Wins on 2 or 3 on the \
+             come-out (12 is a tie), and wins if a 7 comes before the point."
+        }
+        BetKind::Come => {
+            "A pass line bet made while a point is on. The next roll is its own come-out: \
+             7 or 11 wins, 2, 3 or 12 loses, and any other number becomes its point."
+        }
+        BetKind::DontCome => {
+            "A don't pass bet made while a point is on, with the next roll as its come-out."
+        }
+        BetKind::ComePoint(_) => {
+            "A come bet that has moved to its number. It wins if the number comes before a 7."
+        }
+        BetKind::DontComePoint(_) => {
+            "A don't come bet behind its number. It wins if a 7 comes before the number."
+        }
+        BetKind::PassOdds | BetKind::ComeOdds(_) => {
+            "Extra money behind a line bet, paid at the true odds of its number. \
+             The house has no edge on it at all - the best bet on the table."
+        }
+        BetKind::DontPassOdds | BetKind::DontComeOdds(_) => {
+            "Extra money laid behind a don't bet, paid at the true odds against its number. \
+             No house edge."
+        }
+        BetKind::Buy(_) => {
+            "Bets the number comes before a 7, paid at true odds less a commission. \
+             Off on the come-out."
+        }
+        BetKind::Lay(_) => "Bets a 7 comes before the number, paid at true odds less a commission.",
+        BetKind::AnySeven => "One roll: pays 4 to 1 if the next roll is a 7.",
+        BetKind::AnyCraps => "One roll: pays 7 to 1 if the next roll is a 2, 3 or 12.",
+        // Explained by their rules (see explain)
+        BetKind::Field | BetKind::Horn | BetKind::CAndE | BetKind::Hop(_, _) => "",
+        BetKind::SicBo(bet) => explain_sic_bo(bet),
+        BetKind::Side(_) => SIDE_BET_EXPLAINED, // Its rule may say more
+    }
+}
+
+fn explain_sic_bo(bet: SicBoBet) -> &'static str {
+    match bet {
+        SicBoBet::Small => {
+            "The three dice add up to 4 to 10. Pays even money, but loses on any triple - \
+             that's the house's edge."
+        }
+        SicBoBet::Big => {
+            "The three dice add up to 11 to 17. Pays even money, but loses on any triple - \
+             that's the house's edge."
+        }
+        SicBoBet::Total(_) => {
+            "The three dice add up to exactly this number. The rarer the total, the more \
+             it pays."
+        }
+        SicBoBet::Triple(_) => "All three dice show this number. Pays 150 to 1.",
+        SicBoBet::AnyTriple => {
+            "All three dice show the same number, whichever it is. Pays 24 to 1."
+        }
+        SicBoBet::Double(_) => "At least two of the dice show this number. Pays 8 to 1.",
+        SicBoBet::Combination(_, _) => "Both of these numbers show on the dice. Pays 5 to 1.",
+        SicBoBet::Single(_) => {
+            "This number shows on at least one die. Pays 1 to 1 for each die showing it, so \
+             up to 3 to 1."
+        }
+    }
+}
+
+// The house edge in percent: how much of each dollar bet the house keeps on average
+pub fn house_edge(kind: BetKind, rules: &TableRules) -> f64 {
+    match kind {
+        BetKind::PassLine | BetKind::Come | BetKind::ComePoint(_) => match rules.variant {
+            Variant::Crapless => CRAPLESS_PASS_EDGE_PERCENT,
+            Variant::Standard | Variant::SicBo => PASS_EDGE_PERCENT,
+        },
+        BetKind::DontPass | BetKind::DontCome | BetKind::DontComePoint(_) => DONT_PASS_EDGE_PERCENT,
+        BetKind::PassOdds
+        | BetKind::DontPassOdds
+        | BetKind::ComeOdds(_)
+        | BetKind::DontComeOdds(_) => 0.0,
+        // True odds, so the commission is the house's whole edge
+        BetKind::Buy(number) | BetKind::Lay(number) => {
+            let Some((pays, per)) = true_odds(number) else {
+                return 0.0;
+            };
+            let ways = f64::from(6 - 7u8.abs_diff(number));
+            let commission = rules.commission_percent as f64 / 100.0;
+            // Buy bets pay commission on the bet, lay bets on what they'd win
+            let (win_chance, charged_on) = match kind {
+                BetKind::Buy(_) => (ways / (ways + 6.0), 1.0),
+                _ => (6.0 / (ways + 6.0), per as f64 / pays as f64),
+            };
+            let vig = commission * charged_on;
+            let edge = match rules.vig {
+                VigPolicy::OnWin => win_chance * vig,
+                VigPolicy::UpFront => vig / (1.0 + vig), // Out of the bet and the vig paid
+            };
+            edge * 100.0
+        }
+        BetKind::SicBo(bet) => sic_bo_edge(bet),
+        // A registered bet could do anything - its rule says, if anyone knows
+        BetKind::Side(_) => 0.0,
+        // The one-roll bets can be worked out exactly over all 36 ways the dice land
+        _ => one_roll_edge(kind, rules),
+    }
+}
+
+// The same for a sic bo bet, over all 216 ways three dice land
+fn sic_bo_edge(bet: SicBoBet) -> f64 {
+    const BET: u64 = 100;
+    let mut net = 0.0;
+    for first in 1..=6 {
+        for second in 1..=6 {
+            for third in 1..=6 {
+                net += match bet.resolve(BET, [first, second, third]) {
+                    BetResult::Win(profit) => profit as f64,
+                    BetResult::Lose => -(BET as f64),
+                    _ => 0.0,
+                };
+            }
+        }
+    }
+    -net / 216.0 / BET as f64 * 100.0
+}
+
+fn one_roll_edge(kind: BetKind, rules: &TableRules) -> f64 {
+    const BET: u64 = 10_000; // Big enough that a split bet's quarters are whole cents
+    let mut net = 0.0;
+    for first in 1..=6 {
+        for second in 1..=6 {
+            let (outcome, _) = resolve_roll(RoundPhase::ComeOut, first + second, rules.variant);
+            let roll = Roll {
+                die_values: [first, second],
+                previous: RoundPhase::ComeOut,
+                outcome,
+            };
+            net += match kind.resolve(BET, &roll, rules) {
+                BetResult::Win(profit) => profit as f64,
+                BetResult::Lose => -(BET as f64),
+                _ => 0.0,
+            };
+        }
+    }
+    -net / 36.0 / BET as f64 * 100.0
+}
+
+// The spots a careful player would put money on right now: a line bet to start with,
+// then odds behind each line and come bet already down
+pub fn recommended_spots(phase: RoundPhase, bets: &TableBets, variant: Variant) -> Vec<BetSpot> {
+    if variant == Variant::SicBo {
+        // The two bets with the smallest house edge by far (under 3%)
+        return vec![
+            BetSpot::SicBo(SicBoBet::Small),
+            BetSpot::SicBo(SicBoBet::Big),
+        ];
+    }
+    if phase.is_come_out() {
+        return match variant {
+            Variant::Crapless => vec![BetSpot::PassLine], // The only line there is
+            Variant::Standard | Variant::SicBo => vec![BetSpot::PassLine, BetSpot::DontPass],
+        };
+    }
+    let mut spots = Vec::new();
+    if bets.amount_on(BetKind::PassLine) > 0 {
+        spots.push(BetSpot::PassLine); // The pass line takes odds once the point is on
+    }
+    if bets.amount_on(BetKind::DontPass) > 0 {
+        spots.push(BetSpot::DontPass);
+    }
+    spots.push(BetSpot::Come);
+    for bet in &bets.bets {
+        match bet.kind {
+            BetKind::ComePoint(number) => spots.push(BetSpot::NumberBox(number)),
+            BetKind::DontComePoint(number) => spots.push(BetSpot::NumberStrip(number)),
+            _ => {}
+        }
+    }
+    spots
+}
+
+// A line of advice for the round as it stands
+pub fn phase_hint(
+    phase: RoundPhase,
+    bets: &TableBets,
+    rules: &TableRules,
+    locale: &Locale,
+) -> String {
+    match phase.point() {
+        _ if rules.variant == Variant::SicBo => locale
+            .tr(
+                "Sic bo: small and big have a house edge under 3%. Most of the rest are over \
+                 10%, however much they pay.",
+            )
+            .to_string(),
+        None if rules.variant == Variant::Crapless => locale
+            .tr(
+                "Coming out: a crapless pass line has a house edge over 5%, so keep it small \
+                 and back it with odds once the point is on.",
+            )
+            .to_string(),
+        None => locale
+            .tr(
+                "Coming out: start with the pass line (or don't pass) - both have a house \
+                 edge under 1.5%.",
+            )
+            .to_string(),
+        Some(point) => match bets.odds_room(BetKind::PassOdds, phase, rules) {
+            Some(0) => locale.fill(
+                "The point is {point}: your pass line has full odds. A come bet works like a \
+                 fresh pass line bet, odds and all.",
+                &[("point", &point)],
+            ),
+            Some(room) => locale.fill(
+                "The point is {point}: back your pass line with up to {money} more odds (this \
+                 table allows {odds}) - the house has no edge on them.",
+                &[
+                    ("point", &point),
+                    ("money", &format_money(room)),
+                    ("odds", &rules.odds.describe(locale)),
+                ],
+            ),
+            None => locale.fill(
+                "The point is {point}: a come bet works like a fresh pass line bet, odds and all.",
+                &[("point", &point)],
+            ),
+        },
+    }
+}